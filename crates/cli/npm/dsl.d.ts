@@ -158,6 +158,18 @@ interface Grammar<
     previous: Rule[],
   ) => RuleOrLiteral[];
 
+  /**
+   * A list of visible rules, other than the first rule, from which parsing
+   * can begin. These allow parsing fragments of a document, such as a single
+   * expression.
+   *
+   * @param $ grammar rules
+   */
+  start_rules?: (
+    $: GrammarSymbols<RuleName | BaseGrammarRuleName>,
+    previous: Rule[],
+  ) => RuleOrLiteral[];
+
   /**
    * The name of a token that will match keywords for the purpose of the
    * keyword extraction optimization.
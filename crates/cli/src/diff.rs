@@ -0,0 +1,162 @@
+use std::{
+    fs,
+    io::{self, Write},
+    path::Path,
+};
+
+use anstyle::AnsiColor;
+use anyhow::{anyhow, Context, Result};
+use tree_sitter::{
+    diff::{self, DiffOptions, Edit, EditScript},
+    Language, Node, Parser,
+};
+
+use crate::test::paint;
+
+/// The maximum number of characters of a leaf node's text that are shown in
+/// an update.
+const MAX_TEXT_LENGTH: usize = 40;
+
+pub struct DiffFileOptions {
+    pub min_height: usize,
+    pub named_only: bool,
+    pub color: bool,
+}
+
+/// Parse two files with the given language and print the structural edits
+/// that turn the first into the second.
+///
+/// Returns whether the files differ structurally.
+pub fn diff_files_at_paths(
+    language: &Language,
+    old_path: &Path,
+    new_path: &Path,
+    opts: &DiffFileOptions,
+) -> Result<bool> {
+    let old_source = fs::read(old_path)
+        .with_context(|| format!("Error reading source file {}", old_path.display()))?;
+    let new_source = fs::read(new_path)
+        .with_context(|| format!("Error reading source file {}", new_path.display()))?;
+
+    let mut parser = Parser::new();
+    parser.set_language(language)?;
+    let old_tree = parser
+        .parse(&old_source, None)
+        .ok_or_else(|| anyhow!("Failed to parse {}", old_path.display()))?;
+    let new_tree = parser
+        .parse(&new_source, None)
+        .ok_or_else(|| anyhow!("Failed to parse {}", new_path.display()))?;
+
+    let script = diff::diff(
+        old_tree.root_node(),
+        &old_source,
+        new_tree.root_node(),
+        &new_source,
+        Some(DiffOptions::new().min_height(opts.min_height)),
+    );
+
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+    write_edit_script(&mut stdout, &script, &old_source, &new_source, opts)?;
+    Ok(!script.is_empty())
+}
+
+/// Write one line for each edit in `script`, skipping the edits of anonymous
+/// nodes if `opts.named_only` is set.
+pub fn write_edit_script(
+    out: &mut impl Write,
+    script: &EditScript,
+    old_source: &[u8],
+    new_source: &[u8],
+    opts: &DiffFileOptions,
+) -> Result<()> {
+    let color = |color: AnsiColor| opts.color.then_some(color);
+    for edit in script.edits() {
+        match *edit {
+            Edit::Insert {
+                node,
+                parent,
+                index,
+            } => {
+                if opts.named_only && !node.is_named() {
+                    continue;
+                }
+                writeln!(
+                    out,
+                    "{} {} into {} at {index}",
+                    paint(color(AnsiColor::Green), "insert"),
+                    describe_node(node),
+                    describe_node(parent),
+                )?;
+            }
+            Edit::Delete { node } => {
+                if opts.named_only && !node.is_named() {
+                    continue;
+                }
+                writeln!(
+                    out,
+                    "{} {}",
+                    paint(color(AnsiColor::Red), "delete"),
+                    describe_node(node),
+                )?;
+            }
+            Edit::Update { old, new } => {
+                if opts.named_only && !new.is_named() {
+                    continue;
+                }
+                writeln!(
+                    out,
+                    "{} {} {} -> {} {}",
+                    paint(color(AnsiColor::Yellow), "update"),
+                    describe_node(old),
+                    node_text(old, old_source),
+                    describe_node(new),
+                    node_text(new, new_source),
+                )?;
+            }
+            Edit::Move {
+                old,
+                new,
+                parent,
+                index,
+            } => {
+                if opts.named_only && !new.is_named() {
+                    continue;
+                }
+                writeln!(
+                    out,
+                    "{} {} to {} into {} at {index}",
+                    paint(color(AnsiColor::Blue), "move  "),
+                    describe_node(old),
+                    describe_node(new),
+                    describe_node(parent),
+                )?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn describe_node(node: Node) -> String {
+    let start = node.start_position();
+    let end = node.end_position();
+    let kind = if node.is_named() {
+        node.kind().to_string()
+    } else {
+        format!("{:?}", node.kind())
+    };
+    format!(
+        "({kind} [{}, {}] - [{}, {}])",
+        start.row, start.column, end.row, end.column
+    )
+}
+
+fn node_text(node: Node, source: &[u8]) -> String {
+    let text = String::from_utf8_lossy(&source[node.byte_range()]);
+    let mut chars = text.chars();
+    let mut result = chars.by_ref().take(MAX_TEXT_LENGTH).collect::<String>();
+    if chars.next().is_some() {
+        result.push_str("...");
+    }
+    format!("{result:?}")
+}
@@ -0,0 +1,263 @@
+use std::{
+    cmp::Reverse,
+    collections::HashMap,
+    fs,
+    hash::{DefaultHasher, Hash, Hasher},
+    io::{self, Write},
+    path::PathBuf,
+};
+
+use anstyle::AnsiColor;
+use anyhow::{anyhow, Context, Result};
+use tree_sitter::{Language, Node, Parser, Range, StructuralHashOptions, Tree};
+
+use crate::test::paint;
+
+pub struct DuplicatesOptions {
+    /// The minimum number of compared nodes in a reported subtree.
+    pub min_size: usize,
+    /// Compare only the structure of subtrees, and not the text of their
+    /// leaves.
+    pub ignore_text: bool,
+    /// Compare extra nodes, such as comments, instead of skipping them.
+    pub include_extras: bool,
+    /// The kinds of named nodes that are skipped when comparing subtrees.
+    pub ignored_kinds: Vec<String>,
+    /// Color the output with ANSI escape codes.
+    pub color: bool,
+}
+
+/// A set of structurally identical subtrees.
+#[derive(Debug, PartialEq, Eq)]
+pub struct DuplicateGroup {
+    pub kind: &'static str,
+    /// The number of nodes that were compared in each subtree, which excludes
+    /// the skipped extras and ignored kinds.
+    pub size: usize,
+    /// The indices of the sources that contain the subtrees, along with their
+    /// ranges.
+    pub locations: Vec<(usize, Range)>,
+}
+
+/// Parse the given files with the given language and print the groups of
+/// structurally identical subtrees in them.
+///
+/// Returns the number of groups.
+pub fn find_duplicates_at_paths(
+    language: &Language,
+    paths: &[PathBuf],
+    opts: &DuplicatesOptions,
+) -> Result<usize> {
+    let sources = paths
+        .iter()
+        .map(|path| {
+            fs::read(path).with_context(|| format!("Error reading source file {}", path.display()))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let groups = find_duplicates(language, &sources, opts)?;
+
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+    write_duplicates(&mut stdout, &groups, paths, opts)?;
+    Ok(groups.len())
+}
+
+/// Find the groups of structurally identical named subtrees in the given
+/// sources that have at least `opts.min_size` compared nodes.
+///
+/// Subtrees that are contained in a larger reported subtree are not reported
+/// on their own. The groups are ordered by decreasing size.
+pub fn find_duplicates(
+    language: &Language,
+    sources: &[Vec<u8>],
+    opts: &DuplicatesOptions,
+) -> Result<Vec<DuplicateGroup>> {
+    let ignored_kinds = opts
+        .ignored_kinds
+        .iter()
+        .map(|kind| match language.id_for_node_kind(kind, true) {
+            0 => Err(anyhow!("Unknown node kind {kind}")),
+            id => Ok(id),
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let hash_options = StructuralHashOptions::new()
+        .include_text(!opts.ignore_text)
+        .ignore_extras(!opts.include_extras)
+        .ignored_kinds(&ignored_kinds);
+
+    let mut parser = Parser::new();
+    parser.set_language(language)?;
+    let trees = sources
+        .iter()
+        .enumerate()
+        .map(|(i, source)| {
+            parser
+                .parse(source, None)
+                .ok_or_else(|| anyhow!("Failed to parse source {i}"))
+        })
+        .collect::<Result<Vec<Tree>>>()?;
+
+    let mut candidates = HashMap::<u64, Vec<(usize, Node, usize)>>::new();
+    for (i, tree) in trees.iter().enumerate() {
+        for info in index_subtrees(tree, &sources[i], &hash_options) {
+            if info.node.is_named() && info.size >= opts.min_size {
+                let entry = candidates.entry(info.hash).or_default();
+                entry.push((i, info.node, info.size));
+            }
+        }
+    }
+
+    // Split the subtrees that hash equally into groups of subtrees that are
+    // actually identical, in case of hash collisions.
+    let mut groups = Vec::new();
+    for nodes in candidates.into_values() {
+        let mut classes = Vec::<Vec<(usize, Node, usize)>>::new();
+        for (i, node, size) in nodes {
+            let class = classes.iter_mut().find(|class| {
+                let (j, other, _) = class[0];
+                node.structurally_eq(&sources[i], &other, &sources[j], &hash_options)
+            });
+            match class {
+                Some(class) => class.push((i, node, size)),
+                None => classes.push(vec![(i, node, size)]),
+            }
+        }
+        groups.extend(classes.into_iter().filter(|nodes| nodes.len() > 1));
+    }
+    groups.sort_by_key(|nodes| {
+        let (i, node, size) = nodes[0];
+        (Reverse(size), i, node.start_byte())
+    });
+
+    // Skip the groups whose subtrees are all part of larger duplicates.
+    let mut reported = Vec::<(usize, Range)>::new();
+    let mut result = Vec::new();
+    for nodes in groups {
+        let is_covered = nodes.iter().all(|(i, node, _)| {
+            reported.iter().any(|(j, range)| {
+                i == j && range.start_byte <= node.start_byte() && node.end_byte() <= range.end_byte
+            })
+        });
+        if is_covered {
+            continue;
+        }
+        let locations = nodes
+            .iter()
+            .map(|(i, node, _)| (*i, node.range()))
+            .collect::<Vec<_>>();
+        reported.extend_from_slice(&locations);
+        let (_, node, size) = nodes[0];
+        result.push(DuplicateGroup {
+            kind: node.kind(),
+            size,
+            locations,
+        });
+    }
+    Ok(result)
+}
+
+/// A node that is compared when looking for duplicates.
+struct SubtreeInfo<'t> {
+    node: Node<'t>,
+    /// The id of the field in which this node appears in its parent.
+    field_id: u16,
+    children: Vec<usize>,
+    /// The number of compared nodes in this node's subtree, including itself.
+    size: usize,
+    /// A hash of this node's subtree, which is equal for subtrees that are
+    /// [structurally equal](Node::structurally_eq).
+    hash: u64,
+}
+
+/// Get the nodes of a tree that are not skipped by the given options, in
+/// pre-order, with the size and hash of each of their subtrees.
+fn index_subtrees<'t>(
+    tree: &'t Tree,
+    source: &[u8],
+    options: &StructuralHashOptions,
+) -> Vec<SubtreeInfo<'t>> {
+    let mut nodes = Vec::<SubtreeInfo>::new();
+    let mut ancestors = Vec::<usize>::new();
+    let mut cursor = tree.walk();
+    'outer: loop {
+        let node = cursor.node();
+        if !options.is_ignored(node) {
+            let index = nodes.len();
+            if let Some(&parent) = ancestors.last() {
+                nodes[parent].children.push(index);
+            }
+            nodes.push(SubtreeInfo {
+                node,
+                field_id: cursor.field_id().map_or(0, u16::from),
+                children: Vec::new(),
+                size: 1,
+                hash: 0,
+            });
+            if cursor.goto_first_child() {
+                ancestors.push(index);
+                continue;
+            }
+        }
+        while !cursor.goto_next_sibling() {
+            if ancestors.pop().is_none() || !cursor.goto_parent() {
+                break 'outer;
+            }
+        }
+    }
+
+    // Children always come after their parents in pre-order.
+    for index in (0..nodes.len()).rev() {
+        let info = &nodes[index];
+        let mut hasher = DefaultHasher::new();
+        info.node.kind_id().hash(&mut hasher);
+        if options.include_text && info.node.child_count() == 0 {
+            source
+                .get(info.node.byte_range())
+                .unwrap_or_default()
+                .hash(&mut hasher);
+        }
+        let mut size = 1;
+        for &child in &info.children {
+            let child = &nodes[child];
+            child.field_id.hash(&mut hasher);
+            child.hash.hash(&mut hasher);
+            size += child.size;
+        }
+        let info = &mut nodes[index];
+        info.size = size;
+        info.hash = hasher.finish();
+    }
+    nodes
+}
+
+/// Write each group of duplicates, followed by one line for each location.
+pub fn write_duplicates(
+    out: &mut impl Write,
+    groups: &[DuplicateGroup],
+    paths: &[PathBuf],
+    opts: &DuplicatesOptions,
+) -> Result<()> {
+    let color = |color: AnsiColor| opts.color.then_some(color);
+    for group in groups {
+        writeln!(
+            out,
+            "{} ({}, {} nodes) in {} places",
+            paint(color(AnsiColor::Yellow), "duplicate"),
+            group.kind,
+            group.size,
+            group.locations.len(),
+        )?;
+        for (i, range) in &group.locations {
+            writeln!(
+                out,
+                "  {} [{}, {}] - [{}, {}]",
+                paths[*i].display(),
+                range.start_point.row,
+                range.start_point.column,
+                range.end_point.row,
+                range.end_point.column,
+            )?;
+        }
+    }
+    Ok(())
+}
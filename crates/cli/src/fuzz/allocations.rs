@@ -7,8 +7,15 @@ use std::{
     },
 };
 
+/// The environment variable that disables the recording of allocations, so
+/// that a process can install its own allocator for the library.
+pub const SKIP_RECORDING_VAR: &str = "TREE_SITTER_SKIP_ALLOCATION_RECORDING";
+
 #[ctor::ctor]
 unsafe fn initialize_allocation_recording() {
+    if std::env::var_os(SKIP_RECORDING_VAR).is_some() {
+        return;
+    }
     tree_sitter::set_allocator(
         Some(ts_record_malloc),
         Some(ts_record_calloc),
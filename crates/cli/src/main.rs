@@ -12,8 +12,10 @@ use dialoguer::{theme::ColorfulTheme, Confirm, FuzzySelect, Input, MultiSelect};
 use heck::ToUpperCamelCase;
 use regex::Regex;
 use semver::Version as SemverVersion;
-use tree_sitter::{ffi, Parser, Point};
+use tree_sitter::{ffi, Parser, ParserPool, Point};
 use tree_sitter_cli::{
+    diff::{self, DiffFileOptions},
+    duplicates::{self, DuplicatesOptions},
     fuzz::{
         fuzz_language_corpus, FuzzOptions, EDIT_COUNT, ITERATION_COUNT, LOG_ENABLED,
         LOG_GRAPH_ENABLED, START_SEED,
@@ -24,6 +26,7 @@ use tree_sitter_cli::{
     logger,
     parse::{self, ParseDebugType, ParseFileOptions, ParseOutput, ParseTheme},
     playground, query,
+    select::{self, SelectOptions},
     tags::{self, TagsOptions},
     test::{self, TestOptions, TestStats},
     test_highlight, test_tags, util, version, wasm,
@@ -59,6 +62,12 @@ enum Commands {
     Fuzz(Fuzz),
     /// Search files using a syntax tree query
     Query(Query),
+    /// Show the structural differences between two files
+    Diff(Diff),
+    /// Find structurally identical subtrees in a set of files
+    Duplicates(Duplicates),
+    /// Search files for nodes that match a selector
+    Select(Select),
     /// Highlight a file
     Highlight(Highlight),
     /// Generate a list of tags
@@ -133,6 +142,9 @@ struct Generate {
         default_value = "node"
     )]
     pub js_runtime: Option<String>,
+    /// Also generate `node_types.rs`, a module of typed Rust wrappers for the grammar's nodes
+    #[arg(long)]
+    pub rust_types: bool,
 }
 
 #[derive(Args)]
@@ -156,6 +168,9 @@ struct Build {
     /// Compile a parser in debug mode
     #[arg(long, short = '0')]
     pub debug: bool,
+    /// Also compile the grammar's queries into precompiled query files next to the library
+    #[arg(long, conflicts_with = "wasm")]
+    pub precompile_queries: bool,
 }
 
 #[derive(Args)]
@@ -195,7 +210,18 @@ struct Parse {
     /// Output the parse data in a pretty-printed CST format
     #[arg(long = "cst", short = 'c')]
     pub output_cst: bool,
-    /// Show parsing statistic
+    /// Output syntax errors as diagnostics, with the tokens that were expected
+    #[arg(long = "diagnostics")]
+    pub output_diagnostics: bool,
+    /// Print the node kinds that could appear at a one-based position, in the
+    /// format "row:column"
+    #[arg(long, value_name = "ROW:COL")]
+    pub expected_at: Option<String>,
+    /// Start parsing from the given rule, which must be listed in the
+    /// grammar's `start_rules`
+    #[arg(long, value_name = "RULE")]
+    pub start_rule: Option<String>,
+    /// Show parsing statistics, including the size and memory usage of the trees
     #[arg(long, short)]
     pub stat: bool,
     /// Interrupt the parsing process by timeout (µs)
@@ -237,6 +263,10 @@ struct Parse {
     /// Omit ranges in the output
     #[arg(long)]
     pub no_ranges: bool,
+    /// Parse multiple files in parallel on the given number of threads, or on
+    /// one thread per available CPU if 0
+    #[arg(long)]
+    pub jobs: Option<usize>,
 }
 
 #[derive(ValueEnum, Clone)]
@@ -388,6 +418,89 @@ struct Query {
     pub test_number: Option<u32>,
 }
 
+#[derive(Args)]
+struct Diff {
+    /// The old version of the file
+    #[arg(index = 1, required = true)]
+    pub old_path: PathBuf,
+    /// The new version of the file
+    #[arg(index = 2, required = true)]
+    pub new_path: PathBuf,
+    /// The path to the tree-sitter grammar directory
+    #[arg(long, short = 'p')]
+    pub grammar_path: Option<PathBuf>,
+    /// Select a language by the scope instead of a file extension
+    #[arg(long)]
+    pub scope: Option<String>,
+    /// The minimum height of subtrees that are matched as a whole
+    #[arg(long, default_value_t = 2)]
+    pub min_height: usize,
+    /// Only show edits of named nodes
+    #[arg(long)]
+    pub named_only: bool,
+    /// The path to an alternative config.json file
+    #[arg(long)]
+    pub config_path: Option<PathBuf>,
+}
+
+#[derive(Args)]
+#[command(alias = "dup")]
+struct Duplicates {
+    /// The path to a file with paths to source file(s)
+    #[arg(long = "paths")]
+    pub paths_file: Option<PathBuf>,
+    /// The source file(s) to search
+    #[arg(index = 1, num_args = 1..)]
+    pub paths: Option<Vec<PathBuf>>,
+    /// The path to the tree-sitter grammar directory
+    #[arg(long, short = 'p')]
+    pub grammar_path: Option<PathBuf>,
+    /// Select a language by the scope instead of a file extension
+    #[arg(long)]
+    pub scope: Option<String>,
+    /// The minimum number of compared nodes in a reported subtree
+    #[arg(long, default_value_t = 20)]
+    pub min_size: usize,
+    /// Compare only the structure of subtrees, ignoring the text of their leaves
+    #[arg(long)]
+    pub ignore_text: bool,
+    /// Compare extra nodes, such as comments, instead of skipping them
+    #[arg(long)]
+    pub include_extras: bool,
+    /// Skip nodes of the given kinds when comparing subtrees
+    #[arg(long, num_args = 1..)]
+    pub ignore_kinds: Vec<String>,
+    /// The path to an alternative config.json file
+    #[arg(long)]
+    pub config_path: Option<PathBuf>,
+}
+
+#[derive(Args)]
+#[command(alias = "sel")]
+struct Select {
+    /// The selector to match, such as `function_definition > name:identifier`
+    #[arg(index = 1)]
+    pub selector: String,
+    /// The path to a file with paths to source file(s)
+    #[arg(long = "paths")]
+    pub paths_file: Option<PathBuf>,
+    /// The source file(s) to search
+    #[arg(index = 2, num_args = 1..)]
+    pub paths: Option<Vec<PathBuf>>,
+    /// The path to the tree-sitter grammar directory
+    #[arg(long, short = 'p')]
+    pub grammar_path: Option<PathBuf>,
+    /// Select a language by the scope instead of a file extension
+    #[arg(long)]
+    pub scope: Option<String>,
+    /// Only print the number of matching nodes in each file
+    #[arg(long, short)]
+    pub count: bool,
+    /// The path to an alternative config.json file
+    #[arg(long)]
+    pub config_path: Option<PathBuf>,
+}
+
 #[derive(Args)]
 #[command(alias = "hi")]
 struct Highlight {
@@ -479,6 +592,9 @@ struct DumpLanguages {
     /// The path to an alternative config.json file
     #[arg(long)]
     pub config_path: Option<PathBuf>,
+    /// Print the fingerprint of each language, compiling its parser if needed
+    #[arg(long)]
+    pub fingerprint: bool,
 }
 
 #[derive(Args)]
@@ -807,6 +923,7 @@ impl Generate {
             abi_version,
             self.report_states_for_rule.as_deref(),
             self.js_runtime.as_deref(),
+            self.rust_types,
         ) {
             if self.json {
                 eprintln!("{}", serde_json::to_string_pretty(&err)?);
@@ -880,9 +997,13 @@ impl Build {
             let config = Config::load(None)?;
             let loader_config = config.get()?;
             loader.find_all_languages(&loader_config).unwrap();
-            loader
-                .compile_parser_at_path(&grammar_path, output_path, flags)
+            let language = loader
+                .compile_parser_at_path(&grammar_path, output_path.clone(), flags)
                 .unwrap();
+
+            if self.precompile_queries {
+                query::precompile_queries(&language, &grammar_path.join("queries"), &output_path)?;
+            }
         }
         Ok(())
     }
@@ -898,6 +1019,8 @@ impl Parse {
             ParseOutput::Xml
         } else if self.output_cst {
             ParseOutput::Cst
+        } else if self.output_diagnostics {
+            ParseOutput::Diagnostics
         } else if self.quiet || self.json {
             ParseOutput::Quiet
         } else {
@@ -959,6 +1082,13 @@ impl Parse {
                 .collect::<Vec<&str>>(),
             output,
             print_time: time,
+            tree_stats: should_track_stats,
+            expected_at: self
+                .expected_at
+                .as_deref()
+                .map(parse::parse_position)
+                .transpose()?,
+            start_rule: self.start_rule.as_deref(),
             timeout,
             stats: &mut stats,
             debug,
@@ -981,6 +1111,9 @@ impl Parse {
                     stats.cumulative_stats.total_bytes += bytes;
                     stats.cumulative_stats.total_duration += duration;
                 }
+                if let Some(tree_stats) = parse_result.tree_stats {
+                    stats.cumulative_stats.add_tree_stats(&tree_stats);
+                }
             }
 
             has_error |= !parse_result.successful;
@@ -1000,20 +1133,55 @@ impl Parse {
                     .max()
                     .unwrap_or(0);
 
-                for path in &paths {
-                    let path = Path::new(&path);
-                    let language =
-                        loader.select_language(path, current_dir, self.scope.as_deref())?;
-
-                    parse::parse_file_at_path(
-                        &mut parser,
-                        &language,
-                        path,
-                        &path.display().to_string(),
-                        max_path_length,
-                        &mut options,
-                    )?;
-                    update_stats(options.stats);
+                // Parse multiple files in parallel if `--jobs` was passed, unless the
+                // parser needs to log its progress, or to load languages into its wasm
+                // store.
+                let pool = self.jobs.map(ParserPool::new).filter(|pool| {
+                    paths.len() > 1
+                        && pool.thread_count() > 1
+                        && debug == ParseDebugType::Quiet
+                        && !self.debug_graph
+                        && !self.wasm
+                });
+                if let Some(pool) = pool {
+                    for paths in paths.chunks(pool.thread_count() * 8) {
+                        let files = paths
+                            .iter()
+                            .map(|path| {
+                                let language = loader.select_language(
+                                    path,
+                                    current_dir,
+                                    self.scope.as_deref(),
+                                )?;
+                                Ok((path.clone(), language))
+                            })
+                            .collect::<Result<Vec<_>>>()?;
+
+                        parse::parse_files_at_paths(
+                            &pool,
+                            &mut parser,
+                            &files,
+                            max_path_length,
+                            &mut options,
+                            &mut update_stats,
+                        )?;
+                    }
+                } else {
+                    for path in &paths {
+                        let path = Path::new(&path);
+                        let language =
+                            loader.select_language(path, current_dir, self.scope.as_deref())?;
+
+                        parse::parse_file_at_path(
+                            &mut parser,
+                            &language,
+                            path,
+                            &path.display().to_string(),
+                            max_path_length,
+                            &mut options,
+                        )?;
+                        update_stats(options.stats);
+                    }
                 }
             }
 
@@ -1364,6 +1532,85 @@ impl Query {
     }
 }
 
+impl Diff {
+    fn run(self, mut loader: loader::Loader, current_dir: &Path) -> Result<()> {
+        let config = Config::load(self.config_path)?;
+        let loader_config = config.get()?;
+        loader.find_all_languages(&loader_config)?;
+        let language =
+            loader.select_language(&self.old_path, current_dir, self.scope.as_deref())?;
+
+        let options = DiffFileOptions {
+            min_height: self.min_height,
+            named_only: self.named_only,
+            color: env::var("NO_COLOR").map_or(true, |v| v != "1"),
+        };
+        if diff::diff_files_at_paths(&language, &self.old_path, &self.new_path, &options)? {
+            return Err(anyhow!(""));
+        }
+        Ok(())
+    }
+}
+
+impl Duplicates {
+    fn run(self, mut loader: loader::Loader, current_dir: &Path) -> Result<()> {
+        let config = Config::load(self.config_path)?;
+        let loader_config = config.get()?;
+        loader.find_all_languages(&loader_config)?;
+
+        let cancellation_flag = util::cancel_on_signal();
+        let CliInput::Paths(paths) = get_input(
+            self.paths_file.as_deref(),
+            self.paths,
+            None,
+            &cancellation_flag,
+        )?
+        else {
+            return Err(anyhow!("The duplicates command requires source file paths"));
+        };
+        let language = loader.select_language(&paths[0], current_dir, self.scope.as_deref())?;
+
+        let options = DuplicatesOptions {
+            min_size: self.min_size,
+            ignore_text: self.ignore_text,
+            include_extras: self.include_extras,
+            ignored_kinds: self.ignore_kinds,
+            color: env::var("NO_COLOR").map_or(true, |v| v != "1"),
+        };
+        if duplicates::find_duplicates_at_paths(&language, &paths, &options)? > 0 {
+            return Err(anyhow!(""));
+        }
+        Ok(())
+    }
+}
+
+impl Select {
+    fn run(self, mut loader: loader::Loader, current_dir: &Path) -> Result<()> {
+        let config = Config::load(self.config_path)?;
+        let loader_config = config.get()?;
+        loader.find_all_languages(&loader_config)?;
+
+        let cancellation_flag = util::cancel_on_signal();
+        let CliInput::Paths(paths) = get_input(
+            self.paths_file.as_deref(),
+            self.paths,
+            None,
+            &cancellation_flag,
+        )?
+        else {
+            return Err(anyhow!("The select command requires source file paths"));
+        };
+        let language = loader.select_language(&paths[0], current_dir, self.scope.as_deref())?;
+
+        let options = SelectOptions {
+            count: self.count,
+            color: env::var("NO_COLOR").map_or(true, |v| v != "1"),
+        };
+        select::select_at_paths(&language, &self.selector, &paths, &options)?;
+        Ok(())
+    }
+}
+
 impl Highlight {
     fn run(self, mut loader: loader::Loader, current_dir: &Path) -> Result<()> {
         let config = Config::load(self.config_path)?;
@@ -1665,10 +1912,22 @@ impl DumpLanguages {
         let loader_config = config.get()?;
         loader.find_all_languages(&loader_config)?;
         for (configuration, language_path) in loader.get_all_language_configurations() {
+            let fingerprint = if self.fingerprint {
+                let fingerprint = loader
+                    .language_for_configuration(configuration)
+                    .map_or_else(
+                        |e| format!("unavailable ({e})"),
+                        |language| format!("{:016x}", language.fingerprint()),
+                    );
+                format!("fingerprint: {fingerprint}\n")
+            } else {
+                String::new()
+            };
             println!(
                 concat!(
                     "scope: {}\n",
                     "parser: {:?}\n",
+                    "{}",
                     "highlights: {:?}\n",
                     "file_types: {:?}\n",
                     "content_regex: {:?}\n",
@@ -1676,6 +1935,7 @@ impl DumpLanguages {
                 ),
                 configuration.scope.as_ref().unwrap_or(&String::new()),
                 language_path,
+                fingerprint,
                 configuration.highlights_filenames,
                 configuration.file_types,
                 configuration.content_regex,
@@ -1753,6 +2013,9 @@ fn run() -> Result<()> {
         | Commands::Version(Version { grammar_path, .. })
         | Commands::Fuzz(Fuzz { grammar_path, .. })
         | Commands::Query(Query { grammar_path, .. })
+        | Commands::Diff(Diff { grammar_path, .. })
+        | Commands::Duplicates(Duplicates { grammar_path, .. })
+        | Commands::Select(Select { grammar_path, .. })
         | Commands::Highlight(Highlight { grammar_path, .. })
         | Commands::Tags(Tags { grammar_path, .. })
         | Commands::Playground(Playground { grammar_path, .. }) => grammar_path,
@@ -1777,6 +2040,11 @@ fn run() -> Result<()> {
         Commands::Version(version_options) => version_options.run(current_dir)?,
         Commands::Fuzz(fuzz_options) => fuzz_options.run(loader, &current_dir)?,
         Commands::Query(query_options) => query_options.run(loader, &current_dir)?,
+        Commands::Diff(diff_options) => diff_options.run(loader, &current_dir)?,
+        Commands::Duplicates(duplicates_options) => {
+            duplicates_options.run(loader, &current_dir)?;
+        }
+        Commands::Select(select_options) => select_options.run(loader, &current_dir)?,
         Commands::Highlight(highlight_options) => highlight_options.run(loader, &current_dir)?,
         Commands::Tags(tags_options) => tags_options.run(loader, &current_dir)?,
         Commands::Playground(playground_options) => playground_options.run(&current_dir)?,
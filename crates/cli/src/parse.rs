@@ -1,6 +1,7 @@
 use std::{
     fmt, fs,
     io::{self, StdoutLock, Write},
+    mem,
     path::{Path, PathBuf},
     sync::atomic::{AtomicUsize, Ordering},
     time::{Duration, Instant},
@@ -11,8 +12,9 @@ use anyhow::{anyhow, Context, Result};
 use clap::ValueEnum;
 use serde::{Deserialize, Serialize};
 use tree_sitter::{
-    ffi, InputEdit, Language, LogType, ParseOptions, ParseState, Parser, Point, Range, Tree,
-    TreeCursor,
+    ffi, InputEdit, Language, LanguageRef, LineIndex, LogType, Node, ParseOptions, ParseState,
+    Parser, ParserPool, Point, Range, SyntaxDiagnostic, SyntaxDiagnosticKind, Tree, TreeCursor,
+    TreeStats,
 };
 
 use super::util;
@@ -24,6 +26,30 @@ pub struct Stats {
     pub total_parses: usize,
     pub total_bytes: usize,
     pub total_duration: Duration,
+    pub total_nodes: usize,
+    pub total_errors: usize,
+    pub max_depth: usize,
+    pub total_memory: usize,
+    pub incremental_parses: usize,
+    pub total_shared_nodes: usize,
+    pub total_shared_memory: usize,
+}
+
+impl Stats {
+    /// Add the statistics of a parsed tree to the totals.
+    pub fn add_tree_stats(&mut self, tree_stats: &ParseTreeStats) {
+        self.total_nodes += tree_stats.node_count;
+        self.total_errors += tree_stats.error_count;
+        self.max_depth = self.max_depth.max(tree_stats.max_depth);
+        self.total_memory += tree_stats.memory_usage;
+        if let (Some(shared_nodes), Some(shared_memory)) =
+            (tree_stats.shared_node_count, tree_stats.shared_memory_usage)
+        {
+            self.incremental_parses += 1;
+            self.total_shared_nodes += shared_nodes;
+            self.total_shared_memory += shared_memory;
+        }
+    }
 }
 
 impl fmt::Display for Stats {
@@ -51,7 +77,29 @@ impl fmt::Display for Stats {
             self.total_parses,
             self.successful_parses,
             self.total_parses - self.successful_parses,
-        )
+        )?;
+        if self.total_nodes > 0 {
+            write!(
+                f,
+                "Total nodes: {}; error nodes: {}; max depth: {}; tree memory: {} bytes; average size: {:.2} bytes/node",
+                self.total_nodes,
+                self.total_errors,
+                self.max_depth,
+                self.total_memory,
+                self.total_memory as f64 / self.total_nodes as f64,
+            )?;
+            if self.incremental_parses > 0 {
+                write!(
+                    f,
+                    "; reused nodes: {} ({:.2}%); reused memory: {} bytes",
+                    self.total_shared_nodes,
+                    (self.total_shared_nodes as f64 / self.total_nodes as f64) * 100.0,
+                    self.total_shared_memory,
+                )?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
     }
 }
 
@@ -186,6 +234,7 @@ pub enum ParseOutput {
     Xml,
     Cst,
     Dot,
+    Diagnostics,
 }
 
 /// A position in a multi-line text document, in terms of rows and columns.
@@ -208,6 +257,34 @@ impl From<Point> for ParsePoint {
     }
 }
 
+/// A summary of the size and shape of a parsed tree.
+///
+/// This serves as a serializable wrapper for `TreeStats`. The shared counts
+/// are only present if the tree was created by reparsing after edits.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct ParseTreeStats {
+    pub node_count: usize,
+    pub error_count: usize,
+    pub max_depth: usize,
+    pub memory_usage: usize,
+    pub shared_node_count: Option<usize>,
+    pub shared_memory_usage: Option<usize>,
+}
+
+impl ParseTreeStats {
+    #[must_use]
+    pub fn new(stats: TreeStats, is_incremental: bool) -> Self {
+        Self {
+            node_count: stats.node_count,
+            error_count: stats.error_count,
+            max_depth: stats.max_depth,
+            memory_usage: stats.memory_usage,
+            shared_node_count: is_incremental.then_some(stats.shared_node_count),
+            shared_memory_usage: is_incremental.then_some(stats.shared_memory_usage),
+        }
+    }
+}
+
 #[derive(Serialize, Default, Debug, Clone)]
 pub struct ParseSummary {
     pub file: PathBuf,
@@ -216,6 +293,7 @@ pub struct ParseSummary {
     pub end: Option<ParsePoint>,
     pub duration: Option<Duration>,
     pub bytes: Option<usize>,
+    pub tree_stats: Option<ParseTreeStats>,
 }
 
 impl ParseSummary {
@@ -248,6 +326,12 @@ pub struct ParseFileOptions<'a> {
     pub output: ParseOutput,
     pub stats: &'a mut ParseStats,
     pub print_time: bool,
+    pub tree_stats: bool,
+    /// The position at which to print the node kinds that could appear.
+    pub expected_at: Option<Point>,
+    /// The name of the rule from which to start parsing, instead of the
+    /// grammar's first rule.
+    pub start_rule: Option<&'a str>,
     pub timeout: u64,
     pub debug: ParseDebugType,
     pub debug_graph: bool,
@@ -275,7 +359,8 @@ pub fn parse_file_at_path(
 ) -> Result<()> {
     let mut _log_session = None;
     parser.set_language(language)?;
-    let mut source_code = fs::read(path).with_context(|| format!("Error reading {name:?}"))?;
+    set_start_rule(parser, language, opts.start_rule)?;
+    let source_code = fs::read(path).with_context(|| format!("Error reading {name:?}"))?;
 
     // Render an HTML graph if `--debug-graph` was passed
     if opts.debug_graph {
@@ -324,7 +409,100 @@ pub fn parse_file_at_path(
     }
 
     let parse_time = Instant::now();
+    let tree = parse_source_code(
+        parser,
+        &source_code,
+        opts.encoding,
+        opts.timeout,
+        opts.cancellation_flag,
+    );
+    let parsed = ParsedSource {
+        tree,
+        source_code,
+        duration: parse_time.elapsed(),
+    };
 
+    report_parsed_source(parser, parsed, path, name, max_path_length, opts)
+}
+
+/// Parse the files at the given paths on the threads of a [`ParserPool`], and
+/// then report the results in the order of the paths.
+pub fn parse_files_at_paths(
+    pool: &ParserPool,
+    parser: &mut Parser,
+    files: &[(PathBuf, Language)],
+    max_path_length: usize,
+    opts: &mut ParseFileOptions,
+    mut on_parsed: impl FnMut(&mut ParseStats),
+) -> Result<()> {
+    let sources = files
+        .iter()
+        .map(|(path, _)| {
+            fs::read(path)
+                .with_context(|| format!("Error reading {:?}", path.display().to_string()))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let encoding = opts.encoding;
+    let timeout = opts.timeout;
+    let cancellation_flag = opts.cancellation_flag;
+    let start_rule = opts.start_rule;
+    let mut results = files.iter().map(|_| None).collect::<Vec<_>>();
+    let mut languages = Vec::<&Language>::new();
+    for (_, language) in files {
+        if languages.contains(&language) {
+            continue;
+        }
+        languages.push(language);
+
+        let indices = (0..files.len())
+            .filter(|i| files[*i].1 == *language)
+            .collect::<Vec<_>>();
+        parser.set_language(language)?;
+        let start_symbol = set_start_rule(parser, language, start_rule)?;
+        let language_results = pool.map(language, &indices, |parser, _, i| {
+            parser.set_start_symbol(start_symbol).unwrap();
+            let parse_time = Instant::now();
+            let tree =
+                parse_source_code(parser, &sources[*i], encoding, timeout, cancellation_flag);
+            (tree, parse_time.elapsed())
+        })?;
+        for (i, result) in indices.into_iter().zip(language_results) {
+            results[i] = Some(result);
+        }
+    }
+
+    for (((path, language), source_code), result) in files.iter().zip(sources).zip(results) {
+        let (tree, duration) = result.unwrap();
+        parser.set_language(language)?;
+        set_start_rule(parser, language, start_rule)?;
+        let parsed = ParsedSource {
+            tree,
+            source_code,
+            duration,
+        };
+        let name = path.display().to_string();
+        report_parsed_source(parser, parsed, path, &name, max_path_length, opts)?;
+        on_parsed(opts.stats);
+    }
+
+    Ok(())
+}
+
+/// The result of parsing a single source file.
+struct ParsedSource {
+    tree: Option<Tree>,
+    source_code: Vec<u8>,
+    duration: Duration,
+}
+
+fn parse_source_code(
+    parser: &mut Parser,
+    source_code: &[u8],
+    encoding: Option<u32>,
+    timeout: u64,
+    cancellation_flag: Option<&AtomicUsize>,
+) -> Option<Tree> {
     #[inline(always)]
     fn is_utf16_le_bom(bom_bytes: &[u8]) -> bool {
         bom_bytes == [0xFF, 0xFE]
@@ -335,7 +513,7 @@ pub fn parse_file_at_path(
         bom_bytes == [0xFE, 0xFF]
     }
 
-    let encoding = match opts.encoding {
+    let encoding = match encoding {
         None if source_code.len() >= 2 => {
             if is_utf16_le_bom(&source_code[0..2]) {
                 Some(ffi::TSInputEncodingUTF16LE)
@@ -345,7 +523,7 @@ pub fn parse_file_at_path(
                 None
             }
         }
-        _ => opts.encoding,
+        _ => encoding,
     };
 
     // If the `--cancel` flag was passed, then cancel the parse
@@ -355,13 +533,13 @@ pub fn parse_file_at_path(
     // after the specified number of microseconds.
     let start_time = Instant::now();
     let progress_callback = &mut |_: &ParseState| {
-        if let Some(cancellation_flag) = opts.cancellation_flag {
+        if let Some(cancellation_flag) = cancellation_flag {
             if cancellation_flag.load(Ordering::SeqCst) != 0 {
                 return true;
             }
         }
 
-        if opts.timeout > 0 && start_time.elapsed().as_micros() > opts.timeout as u128 {
+        if timeout > 0 && start_time.elapsed().as_micros() > timeout as u128 {
             return true;
         }
 
@@ -370,7 +548,7 @@ pub fn parse_file_at_path(
 
     let parse_opts = ParseOptions::new().progress_callback(progress_callback);
 
-    let tree = match encoding {
+    match encoding {
         Some(encoding) if encoding == ffi::TSInputEncodingUTF16LE => {
             let source_code_utf16 = source_code
                 .chunks_exact(2)
@@ -416,8 +594,22 @@ pub fn parse_file_at_path(
             None,
             Some(parse_opts),
         ),
-    };
-    let parse_duration = parse_time.elapsed();
+    }
+}
+
+fn report_parsed_source(
+    parser: &mut Parser,
+    parsed: ParsedSource,
+    path: &Path,
+    name: &str,
+    max_path_length: usize,
+    opts: &mut ParseFileOptions,
+) -> Result<()> {
+    let ParsedSource {
+        tree,
+        mut source_code,
+        duration: parse_duration,
+    } = parsed;
 
     let stdout = io::stdout();
     let mut stdout = stdout.lock();
@@ -428,10 +620,12 @@ pub fn parse_file_at_path(
         }
 
         let edit_time = Instant::now();
+        let mut previous_tree = None;
         for (i, edit) in opts.edits.iter().enumerate() {
             let edit = parse_edit_flag(&source_code, edit)?;
             perform_edit(&mut tree, &mut source_code, &edit)?;
-            tree = parser.parse(&source_code, Some(&tree)).unwrap();
+            let new_tree = parser.parse(&source_code, Some(&tree)).unwrap();
+            previous_tree = Some(mem::replace(&mut tree, new_tree));
 
             if opts.debug_graph {
                 println!("AFTER {i}:\n{}", String::from_utf8_lossy(&source_code));
@@ -644,6 +838,27 @@ pub fn parse_file_at_path(
             util::print_tree_graph(&tree, "log.html", opts.open_log).unwrap();
         }
 
+        if opts.output == ParseOutput::Diagnostics {
+            for diagnostic in tree.diagnostics() {
+                let start = diagnostic.offending_range.start_point;
+                writeln!(
+                    &mut stdout,
+                    "{name}:{}:{}: {}",
+                    start.row + 1,
+                    start.column + 1,
+                    diagnostic_message(&diagnostic, &source_code),
+                )?;
+            }
+        }
+
+        if let Some(position) = opts.expected_at {
+            writeln!(
+                &mut stdout,
+                "{}",
+                expected_at_message(&tree, &source_code, name, position)
+            )?;
+        }
+
         let mut first_error = None;
         let mut earliest_node_with_error = None;
         'outer: loop {
@@ -739,6 +954,10 @@ pub fn parse_file_at_path(
             end: Some(tree.root_node().end_position().into()),
             duration: Some(parse_duration),
             bytes: Some(source_code.len()),
+            tree_stats: opts.tree_stats.then(|| {
+                let stats = tree.stats(previous_tree.as_ref());
+                ParseTreeStats::new(stats, previous_tree.is_some())
+            }),
         });
 
         return Ok(());
@@ -746,8 +965,7 @@ pub fn parse_file_at_path(
     parser.stop_printing_dot_graphs();
 
     if opts.print_time {
-        let duration = parse_time.elapsed();
-        let duration_ms = duration.as_micros() as f64 / 1e3;
+        let duration_ms = parse_duration.as_micros() as f64 / 1e3;
         writeln!(
             &mut stdout,
             "{:width$}\tParse: {duration_ms:>7.2} ms\t(timed out)",
@@ -763,11 +981,100 @@ pub fn parse_file_at_path(
         end: None,
         duration: None,
         bytes: Some(source_code.len()),
+        tree_stats: None,
     });
 
     Ok(())
 }
 
+/// The maximum number of expected tokens that are listed in a diagnostic.
+const MAX_EXPECTED_KINDS: usize = 8;
+
+fn diagnostic_message(diagnostic: &SyntaxDiagnostic, source_code: &[u8]) -> String {
+    let node = diagnostic.node;
+    let language = node.language();
+    let offending_text = source_code
+        .get(diagnostic.offending_range.start_byte..diagnostic.offending_range.end_byte)
+        .map(|text| render_node_text(&String::from_utf8_lossy(text)))
+        .unwrap_or_default();
+    let mut message = match diagnostic.kind {
+        SyntaxDiagnosticKind::UnexpectedToken => format!("unexpected `{offending_text}`"),
+        SyntaxDiagnosticKind::MissingNode => format!("missing {}", render_kind(node)),
+        SyntaxDiagnosticKind::Unterminated if node.is_missing() => {
+            let construct = node.parent().map_or(node.kind(), |parent| parent.kind());
+            format!("unterminated {construct} starting at `{offending_text}`")
+        }
+        SyntaxDiagnosticKind::Unterminated => {
+            format!("unexpected end of input in construct starting at `{offending_text}`")
+        }
+    };
+
+    let expected = expected_kind_names(&language, &diagnostic.expected_symbols);
+    if !expected.is_empty() {
+        message += "; expected ";
+        if expected.len() > 1 {
+            message += "one of ";
+        }
+        message += &expected[..expected.len().min(MAX_EXPECTED_KINDS)].join(", ");
+        let remaining = expected.len().saturating_sub(MAX_EXPECTED_KINDS);
+        if remaining > 0 {
+            message = format!("{message}, and {remaining} more");
+        }
+    }
+    message
+}
+
+/// Describe the node kinds that could appear at the given zero-based position.
+///
+/// The message has the format `name:row:column: expected kinds`. Like the
+/// diagnostics, the row and column are printed one-based.
+#[must_use]
+pub fn expected_at_message(tree: &Tree, source_code: &[u8], name: &str, position: Point) -> String {
+    let offset = LineIndex::new(source_code).point_to_byte(position);
+    let expected = expected_kind_names(&tree.language(), &tree.expected_symbols_at(offset));
+    format!(
+        "{name}:{}:{}: expected {}",
+        position.row + 1,
+        position.column + 1,
+        if expected.is_empty() {
+            "nothing".to_string()
+        } else {
+            expected.join(", ")
+        },
+    )
+}
+
+/// Get the names of the visible symbols, without duplicates. Anonymous
+/// symbols are quoted.
+fn expected_kind_names(language: &LanguageRef, symbols: &[u16]) -> Vec<String> {
+    let mut expected = Vec::new();
+    for &symbol in symbols {
+        if !language.node_kind_is_visible(symbol) {
+            continue;
+        }
+        let Some(name) = language.node_kind_for_id(symbol) else {
+            continue;
+        };
+        let name = if language.node_kind_is_named(symbol) {
+            name.to_string()
+        } else {
+            format!("{name:?}")
+        };
+        if !expected.contains(&name) {
+            expected.push(name);
+        }
+    }
+    expected
+}
+
+fn render_kind(node: Node) -> String {
+    if node.is_named() {
+        node.kind().to_string()
+    } else {
+        format!("{:?}", node.kind())
+    }
+}
+
 const fn escape_invisible(c: char) -> Option<&'static str> {
     Some(match c {
         '\n' => "\\n",
@@ -1003,23 +1310,14 @@ fn cst_render_node(
 }
 
 pub fn perform_edit(tree: &mut Tree, input: &mut Vec<u8>, edit: &Edit) -> Result<InputEdit> {
-    let start_byte = edit.position;
-    let old_end_byte = edit.position + edit.deleted_length;
-    let new_end_byte = edit.position + edit.inserted_text.len();
-    let start_position = position_for_offset(input, start_byte)?;
-    let old_end_position = position_for_offset(input, old_end_byte)?;
-    input.splice(start_byte..old_end_byte, edit.inserted_text.iter().copied());
-    let new_end_position = position_for_offset(input, new_end_byte)?;
-    let edit = InputEdit {
-        start_byte,
-        old_end_byte,
-        new_end_byte,
-        start_position,
-        old_end_position,
-        new_end_position,
-    };
-    tree.edit(&edit);
-    Ok(edit)
+    let range = edit.position..edit.position + edit.deleted_length;
+    if range.end > input.len() {
+        return Err(anyhow!("Failed to address an offset: {}", range.end));
+    }
+    let input_edit = InputEdit::from_replacement(input, range.clone(), &edit.inserted_text);
+    input.splice(range, edit.inserted_text.iter().copied());
+    tree.edit(&input_edit);
+    Ok(input_edit)
 }
 
 fn parse_edit_flag(source_code: &[u8], flag: &str) -> Result<Edit> {
@@ -1063,6 +1361,47 @@ fn parse_edit_flag(source_code: &[u8], flag: &str) -> Result<Edit> {
     })
 }
 
+/// Make the parser start parsing from the rule with the given name, which must
+/// be one of the grammar's start rules, and return that rule's symbol.
+pub fn set_start_rule(
+    parser: &mut Parser,
+    language: &Language,
+    start_rule: Option<&str>,
+) -> Result<u16> {
+    let Some(name) = start_rule else {
+        return Ok(0);
+    };
+    let symbol = language.id_for_node_kind(name, true);
+    if symbol == 0 {
+        return Err(anyhow!("The grammar has no rule named `{name}`"));
+    }
+    parser
+        .set_start_symbol(symbol)
+        .with_context(|| format!("`{name}` is not one of the grammar's start rules"))?;
+    Ok(symbol)
+}
+
+/// Parse a one-based position in the format `row:column`, as it is printed in
+/// diagnostics, into a zero-based [`Point`].
+pub fn parse_position(text: &str) -> Result<Point> {
+    let error = || {
+        anyhow!("Positions must match the pattern '<ROW>:<COLUMN>', starting at 1:1, got '{text}'")
+    };
+    let (row, column) = text.split_once(':').ok_or_else(error)?;
+    let parse_one_based = |value: &str| {
+        value
+            .trim()
+            .parse::<usize>()
+            .ok()
+            .and_then(|value| value.checked_sub(1))
+            .ok_or_else(error)
+    };
+    Ok(Point {
+        row: parse_one_based(row)?,
+        column: parse_one_based(column)?,
+    })
+}
+
 pub fn offset_for_position(input: &[u8], position: Point) -> Result<usize> {
     let mut row = 0;
     let mut offset = 0;
@@ -1095,16 +1434,5 @@ pub fn position_for_offset(input: &[u8], offset: usize) -> Result<Point> {
     if offset > input.len() {
         return Err(anyhow!("Failed to address an offset: {offset}"));
     }
-    let mut result = Point { row: 0, column: 0 };
-    let mut last = 0;
-    for pos in memchr::memchr_iter(b'\n', &input[..offset]) {
-        result.row += 1;
-        last = pos;
-    }
-    result.column = if result.row > 0 {
-        offset - last - 1
-    } else {
-        offset
-    };
-    Ok(result)
+    Ok(Point::from_byte_offset(input, offset))
 }
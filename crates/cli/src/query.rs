@@ -2,14 +2,14 @@ use std::{
     fs,
     io::{self, Write},
     ops::Range,
-    path::Path,
+    path::{Path, PathBuf},
     time::Instant,
 };
 
 use anstyle::AnsiColor;
 use anyhow::{Context, Result};
 use streaming_iterator::StreamingIterator;
-use tree_sitter::{Language, Parser, Point, Query, QueryCursor};
+use tree_sitter::{Language, LineIndex, Parser, Point, Query, QueryCursor};
 
 use crate::{
     query_testing::{self, to_utf8_point},
@@ -33,9 +33,7 @@ pub fn query_file_at_path(
     let stdout = io::stdout();
     let mut stdout = stdout.lock();
 
-    let query_source = fs::read_to_string(query_path)
-        .with_context(|| format!("Error reading query file {}", query_path.display()))?;
-    let query = Query::new(language, &query_source).with_context(|| "Query compilation failed")?;
+    let query = load_query(language, query_path)?;
 
     let mut query_cursor = QueryCursor::new();
     if let Some(range) = byte_range {
@@ -57,6 +55,7 @@ pub fn query_file_at_path(
     let source_code =
         fs::read(path).with_context(|| format!("Error reading source file {}", path.display()))?;
     let tree = parser.parse(&source_code, None).unwrap();
+    let line_index = LineIndex::new(&source_code);
 
     let start = Instant::now();
     if ordered_captures {
@@ -77,8 +76,8 @@ pub fn query_file_at_path(
             }
             results.push(query_testing::CaptureInfo {
                 name: (*capture_name).to_string(),
-                start: to_utf8_point(capture.node.start_position(), source_code.as_slice()),
-                end: to_utf8_point(capture.node.end_position(), source_code.as_slice()),
+                start: to_utf8_point(capture.node.start_position(), &source_code, &line_index),
+                end: to_utf8_point(capture.node.end_position(), &source_code, &line_index),
             });
         }
     } else {
@@ -108,8 +107,8 @@ pub fn query_file_at_path(
                 }
                 results.push(query_testing::CaptureInfo {
                     name: (*capture_name).to_string(),
-                    start: to_utf8_point(capture.node.start_position(), source_code.as_slice()),
-                    end: to_utf8_point(capture.node.end_position(), source_code.as_slice()),
+                    start: to_utf8_point(capture.node.start_position(), &source_code, &line_index),
+                    end: to_utf8_point(capture.node.end_position(), &source_code, &line_index),
                 });
             }
         }
@@ -146,3 +145,61 @@ pub fn query_file_at_path(
 
     Ok(())
 }
+
+/// Load the query at `query_path`, which is either a query's source code, or a
+/// precompiled query with the `.tsq` extension, as written by
+/// [`precompile_queries`].
+pub fn load_query(language: &Language, query_path: &Path) -> Result<Query> {
+    if query_path.extension().is_some_and(|ext| ext == "tsq") {
+        let bytes = fs::read(query_path)
+            .with_context(|| format!("Error reading query file {}", query_path.display()))?;
+        return Query::deserialize(language, &bytes)
+            .with_context(|| format!("Failed to load query {}", query_path.display()));
+    }
+
+    let query_source = fs::read_to_string(query_path)
+        .with_context(|| format!("Error reading query file {}", query_path.display()))?;
+    Query::new(language, &query_source).with_context(|| "Query compilation failed")
+}
+
+/// Compile every query file in `queries_dir` into a precompiled query.
+///
+/// The precompiled queries are written next to the parser library at
+/// `library_path`, named after the library and the query file, e.g.
+/// `javascript.highlights.tsq`.
+///
+/// Returns the paths of the precompiled query files.
+pub fn precompile_queries(
+    language: &Language,
+    queries_dir: &Path,
+    library_path: &Path,
+) -> Result<Vec<PathBuf>> {
+    let mut query_paths = match fs::read_dir(queries_dir) {
+        Ok(entries) => entries
+            .map(|entry| Ok(entry?.path()))
+            .collect::<io::Result<Vec<_>>>()
+            .with_context(|| format!("Error reading directory {}", queries_dir.display()))?,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => {
+            return Err(error)
+                .with_context(|| format!("Error reading directory {}", queries_dir.display()))
+        }
+    };
+    query_paths.retain(|path| path.extension().is_some_and(|ext| ext == "scm"));
+    query_paths.sort();
+
+    let mut result = Vec::with_capacity(query_paths.len());
+    for query_path in query_paths {
+        let query_source = fs::read_to_string(&query_path)
+            .with_context(|| format!("Error reading query file {}", query_path.display()))?;
+        let query = Query::new(language, &query_source)
+            .with_context(|| format!("Failed to compile query {}", query_path.display()))?;
+
+        let query_name = query_path.file_stem().unwrap().to_string_lossy();
+        let output_path = library_path.with_extension(format!("{query_name}.tsq"));
+        fs::write(&output_path, query.serialize())
+            .with_context(|| format!("Error writing query file {}", output_path.display()))?;
+        result.push(output_path);
+    }
+    Ok(result)
+}
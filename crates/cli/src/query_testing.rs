@@ -1,9 +1,9 @@
 use std::{fs, path::Path, sync::LazyLock};
 
 use anyhow::{anyhow, Result};
-use bstr::{BStr, ByteSlice};
+use bstr::ByteSlice;
 use regex::Regex;
-use tree_sitter::{Language, Parser, Point};
+use tree_sitter::{Language, LineIndex, Parser, Point};
 
 static CAPTURE_NAME_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new("[\\w_\\-.]+").unwrap());
 
@@ -26,26 +26,15 @@ impl Utf8Point {
     }
 }
 
+/// Convert a point, whose column is measured in bytes, into one whose column
+/// is measured in grapheme clusters, using an index of the lines of `source`.
 #[must_use]
-pub fn to_utf8_point(point: Point, source: &[u8]) -> Utf8Point {
-    if point.column == 0 {
-        return Utf8Point::new(point.row, 0);
-    }
-
-    let bstr = BStr::new(source);
-    let line = bstr.lines_with_terminator().nth(point.row).unwrap();
-    let mut utf8_column = 0;
-
-    for (_, grapheme_end, _) in line.grapheme_indices() {
-        utf8_column += 1;
-        if grapheme_end >= point.column {
-            break;
-        }
-    }
-
+pub fn to_utf8_point(point: Point, source: &[u8], line_index: &LineIndex) -> Utf8Point {
+    let line_start = line_index.line_start(point.row).unwrap_or(source.len());
+    let line_end = (line_start + point.column).min(source.len());
     Utf8Point {
         row: point.row,
-        column: utf8_column,
+        column: source[line_start..line_end].graphemes().count(),
     }
 }
 
@@ -97,6 +86,7 @@ pub fn parse_position_comments(
     parser.set_included_ranges(&[]).unwrap();
     parser.set_language(language).unwrap();
     let tree = parser.parse(source, None).unwrap();
+    let line_index = LineIndex::new(source);
 
     // Walk the tree, finding comment nodes that contain assertions.
     let mut ascending = false;
@@ -160,7 +150,7 @@ pub fn parse_position_comments(
                         {
                             assertion_ranges.push((node.start_position(), node.end_position()));
                             result.push(Assertion {
-                                position: to_utf8_point(position, source),
+                                position: to_utf8_point(position, source, &line_index),
                                 length: arrow_count,
                                 negative,
                                 expected_capture_name: mat.as_str().to_string(),
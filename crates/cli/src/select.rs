@@ -0,0 +1,92 @@
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use anstyle::AnsiColor;
+use anyhow::{anyhow, Context, Result};
+use tree_sitter::{Language, Parser, Selector};
+
+use crate::test::paint;
+
+pub struct SelectOptions {
+    /// Print only the number of matching nodes in each file.
+    pub count: bool,
+    pub color: bool,
+}
+
+/// Parse the given files with the given language and print the nodes in them
+/// that match the selector.
+///
+/// Returns the total number of matching nodes.
+pub fn select_at_paths(
+    language: &Language,
+    selector: &str,
+    paths: &[PathBuf],
+    opts: &SelectOptions,
+) -> Result<usize> {
+    let selector = Selector::new(language, selector)?;
+    let mut parser = Parser::new();
+    parser.set_language(language)?;
+
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+    let mut total = 0;
+    for path in paths {
+        let source = fs::read(path)
+            .with_context(|| format!("Error reading source file {}", path.display()))?;
+        total += write_selection(&mut stdout, &mut parser, &selector, path, &source, opts)?;
+    }
+    Ok(total)
+}
+
+/// Parse `source` and write the path, followed by one line for each node
+/// that matches the selector, with its kind, range and, if it fits on one
+/// line, its text.
+///
+/// Returns the number of matching nodes.
+pub fn write_selection(
+    out: &mut impl Write,
+    parser: &mut Parser,
+    selector: &Selector,
+    path: &Path,
+    source: &[u8],
+    opts: &SelectOptions,
+) -> Result<usize> {
+    let tree = parser
+        .parse(source, None)
+        .ok_or_else(|| anyhow!("Failed to parse {}", path.display()))?;
+
+    if opts.count {
+        let count = tree.select(selector).count();
+        writeln!(out, "{}: {count}", path.display())?;
+        return Ok(count);
+    }
+
+    writeln!(out, "{}", path.display())?;
+    let mut count = 0;
+    for node in tree.select(selector) {
+        count += 1;
+        let start = node.start_position();
+        let end = node.end_position();
+        write!(
+            out,
+            "  {} [{}, {}] - [{}, {}]",
+            paint(opts.color.then_some(AnsiColor::Blue), node.kind()),
+            start.row,
+            start.column,
+            end.row,
+            end.column,
+        )?;
+        if start.row == end.row {
+            write!(
+                out,
+                " `{}`",
+                String::from_utf8_lossy(&source[node.byte_range()])
+            )?;
+        }
+        writeln!(out)?;
+    }
+    Ok(count)
+}
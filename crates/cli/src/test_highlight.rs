@@ -2,7 +2,7 @@ use std::{fs, path::Path};
 
 use anstyle::AnsiColor;
 use anyhow::{anyhow, Result};
-use tree_sitter::Point;
+use tree_sitter::LineIndex;
 use tree_sitter_highlight::{Highlight, HighlightConfiguration, HighlightEvent, Highlighter};
 use tree_sitter_loader::{Config, Loader};
 
@@ -228,14 +228,10 @@ pub fn get_highlight_positions(
     highlight_config: &HighlightConfiguration,
     source: &[u8],
 ) -> Result<Vec<(Utf8Point, Utf8Point, Highlight)>> {
-    let mut row = 0;
-    let mut column = 0;
-    let mut byte_offset = 0;
-    let mut was_newline = false;
     let mut result = Vec::new();
     let mut highlight_stack = Vec::new();
     let source = String::from_utf8_lossy(source);
-    let mut char_indices = source.char_indices();
+    let line_index = LineIndex::new(source.as_bytes());
     for event in highlighter.highlight(highlight_config, source.as_bytes(), None, |string| {
         loader.highlight_config_for_injection_string(string)
     })? {
@@ -245,28 +241,17 @@ pub fn get_highlight_positions(
                 highlight_stack.pop();
             }
             HighlightEvent::Source { start, end } => {
-                let mut start_position = Point::new(row, column);
-                while byte_offset < end {
-                    if byte_offset <= start {
-                        start_position = Point::new(row, column);
-                    }
-                    if let Some((i, c)) = char_indices.next() {
-                        if was_newline {
-                            row += 1;
-                            column = 0;
-                        } else {
-                            column += i - byte_offset;
-                        }
-                        was_newline = c == '\n';
-                        byte_offset = i;
-                    } else {
-                        break;
-                    }
-                }
                 if let Some(highlight) = highlight_stack.last() {
-                    let utf8_start_position = to_utf8_point(start_position, source.as_bytes());
-                    let utf8_end_position =
-                        to_utf8_point(Point::new(row, column), source.as_bytes());
+                    let utf8_start_position = to_utf8_point(
+                        line_index.byte_to_point(start),
+                        source.as_bytes(),
+                        &line_index,
+                    );
+                    let utf8_end_position = to_utf8_point(
+                        line_index.byte_to_point(end),
+                        source.as_bytes(),
+                        &line_index,
+                    );
                     result.push((utf8_start_position, utf8_end_position, *highlight));
                 }
             }
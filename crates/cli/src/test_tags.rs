@@ -2,6 +2,7 @@ use std::{fs, path::Path};
 
 use anstyle::AnsiColor;
 use anyhow::{anyhow, Result};
+use tree_sitter::LineIndex;
 use tree_sitter_loader::{Config, Loader};
 use tree_sitter_tags::{TagsConfiguration, TagsContext};
 
@@ -209,6 +210,7 @@ pub fn get_tag_positions(
     source: &[u8],
 ) -> Result<Vec<(Utf8Point, Utf8Point, String)>> {
     let (tags_iter, _has_error) = tags_context.generate_tags(tags_config, source, None)?;
+    let line_index = LineIndex::new(source);
     let tag_positions = tags_iter
         .filter_map(std::result::Result::ok)
         .map(|tag| {
@@ -219,8 +221,8 @@ pub fn get_tag_positions(
                 format!("reference.{tag_postfix}")
             };
             (
-                to_utf8_point(tag.span.start, source),
-                to_utf8_point(tag.span.end, source),
+                to_utf8_point(tag.span.start, source, &line_index),
+                to_utf8_point(tag.span.end, source, &line_index),
                 tag_name,
             )
         })
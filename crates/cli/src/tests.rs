@@ -1,14 +1,19 @@
+mod allocator_test;
 mod async_context_test;
 mod corpus_test;
 mod detect_language;
+mod duplicates_test;
 mod helpers;
 mod highlight_test;
 mod language_test;
+mod line_index_test;
 mod node_test;
 mod parser_hang_test;
 mod parser_test;
 mod pathological_test;
 mod query_test;
+mod rust_types_test;
+mod select_test;
 mod tags_test;
 mod test_highlight_test;
 mod test_tags_test;
@@ -0,0 +1,125 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    env,
+    process::Command,
+};
+
+use streaming_iterator::StreamingIterator;
+use tree_sitter::{
+    install_allocator, AllocationStats, AllocatorError, Parser, Query, QueryCursor,
+    TrackingAllocator,
+};
+
+use super::helpers::fixtures::get_language;
+use crate::fuzz::allocations::SKIP_RECORDING_VAR;
+
+#[test]
+fn test_install_allocator_after_set_allocator() {
+    static ALLOCATOR: System = System;
+
+    // The tests record their allocations with an allocator that is set with
+    // `set_allocator` before they start.
+    assert_eq!(
+        install_allocator(&ALLOCATOR),
+        Err(AllocatorError::AlreadyInstalled)
+    );
+}
+
+#[test]
+fn test_tracking_allocator_totals() {
+    static ALLOCATOR: TrackingAllocator = TrackingAllocator::new();
+
+    let layout = Layout::from_size_align(100, 8).unwrap();
+    unsafe {
+        let ptr = ALLOCATOR.alloc(layout);
+        let ptr = ALLOCATOR.realloc(ptr, layout, 200);
+        assert_eq!(
+            ALLOCATOR.totals(),
+            AllocationStats {
+                allocations: 2,
+                allocated_bytes: 300,
+                deallocations: 1,
+                deallocated_bytes: 100,
+            }
+        );
+        assert_eq!(ALLOCATOR.totals().retained_bytes(), 200);
+
+        ALLOCATOR.dealloc(ptr, Layout::from_size_align(200, 8).unwrap());
+        assert_eq!(ALLOCATOR.totals().retained_bytes(), 0);
+    }
+
+    // Nothing is attributed to trees and query cursors unless the tracking
+    // allocator is installed for the core library.
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+    let tree = parser.parse("a;", None).unwrap();
+    assert_eq!(tree.allocation_stats(), None);
+    assert_eq!(QueryCursor::new().allocation_stats(), None);
+}
+
+#[test]
+fn test_tracking_allocator_attributes_allocations() {
+    // The tracking allocator can only be installed before the library has
+    // allocated any memory, so the test reruns itself in a new process, in
+    // which allocations are not recorded.
+    if env::var_os(SKIP_RECORDING_VAR).is_none() {
+        let status = Command::new(env::current_exe().unwrap())
+            .args([
+                "--exact",
+                "tests::allocator_test::test_tracking_allocator_attributes_allocations",
+            ])
+            .env(SKIP_RECORDING_VAR, "1")
+            .status()
+            .unwrap();
+        assert!(status.success());
+        return;
+    }
+
+    static ALLOCATOR: TrackingAllocator = TrackingAllocator::new();
+    ALLOCATOR.install().unwrap();
+    assert_eq!(ALLOCATOR.install(), Err(AllocatorError::AlreadyInstalled));
+
+    let language = get_language("javascript");
+    let mut parser = Parser::new();
+    parser.set_language(&language).unwrap();
+    let small_tree = parser.parse("a;", None).unwrap();
+    let large_tree = parser.parse("a(b, c);\n".repeat(100), None).unwrap();
+    let small_stats = small_tree.allocation_stats().unwrap();
+    let large_stats = large_tree.allocation_stats().unwrap();
+    assert!(small_stats.allocated_bytes > 0);
+    assert!(large_stats.allocated_bytes > small_stats.allocated_bytes);
+    assert!(large_stats.allocations > small_stats.allocations);
+    assert!(large_tree.clone().allocation_stats().is_none());
+
+    let query = Query::new(&language, "(call_expression (arguments (identifier) @arg))").unwrap();
+    let mut cursor = QueryCursor::new();
+    assert_eq!(cursor.allocation_stats(), None);
+    let source = "a(b, c);\n".repeat(100);
+    let mut matches = cursor.matches(&query, large_tree.root_node(), source.as_bytes());
+    let mut match_count = 0;
+    while matches.next().is_some() {
+        match_count += 1;
+    }
+    assert_eq!(match_count, 200);
+    let cursor_stats = cursor.allocation_stats().unwrap();
+    assert!(cursor_stats.allocations > 0);
+
+    // Each cursor has its own totals.
+    let mut other_cursor = QueryCursor::new();
+    let mut captures = other_cursor.captures(&query, large_tree.root_node(), source.as_bytes());
+    while captures.next().is_some() {}
+    assert!(other_cursor.allocation_stats().unwrap().allocations > 0);
+    assert_eq!(cursor.allocation_stats(), Some(cursor_stats));
+
+    // The totals of each object are part of the totals of the allocator, and
+    // are unaffected by the allocations of the others.
+    assert_eq!(small_tree.allocation_stats(), Some(small_stats));
+    assert_eq!(large_tree.allocation_stats(), Some(large_stats));
+    let totals = ALLOCATOR.totals();
+    assert!(
+        totals.allocated_bytes
+            >= small_stats.allocated_bytes
+                + large_stats.allocated_bytes
+                + cursor_stats.allocated_bytes
+    );
+}
@@ -1,5 +1,6 @@
 use std::{
     future::Future,
+    num::NonZeroUsize,
     pin::{pin, Pin},
     ptr,
     task::{self, Context, Poll, RawWaker, RawWakerVTable, Waker},
@@ -194,6 +195,58 @@ fn test_node_and_cursor_ref_in_fut_with_inner_spawns() {
     assert_eq!(ret, 2);
 }
 
+#[test]
+fn test_parse_async() {
+    let source = "const x = [1, 2, 3].map((y) => y * 2);\n".repeat(200);
+
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+    let expected_sexp = parser.parse(&source, None).unwrap().root_node().to_sexp();
+
+    let ((sexp, mut parser, source), pended) = tokio_like_spawn(async move {
+        let tree = parser.parse_async(&source, None).await.unwrap();
+        (tree.root_node().to_sexp(), parser, source)
+    })
+    .join();
+
+    // Each poll resumes the paused parse, so the parse finishes after the
+    // executor has been yielded to a number of times.
+    assert_eq!(sexp, expected_sexp);
+    assert!(pended > 0);
+
+    let ((), pended_with_short_interval) = tokio_like_spawn(async move {
+        parser
+            .parse_async(&source, None)
+            .yield_interval(NonZeroUsize::MIN)
+            .await
+            .unwrap();
+    })
+    .join();
+    assert!(pended_with_short_interval > pended);
+}
+
+#[test]
+fn test_parse_async_cancelled_by_drop() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+
+    let waker = noop_waker();
+    let mut cx = Context::from_waker(&waker);
+    let source = "const x = 1;\n".repeat(1000);
+    {
+        let mut future = parser.parse_async(&source, None);
+        assert!(Pin::new(&mut future).poll(&mut cx).is_pending());
+    }
+
+    // Dropping the future resets the parser, so the next parse starts over
+    // with the new text.
+    let tree = parser.parse("let y = 2;", None).unwrap();
+    assert_eq!(
+        tree.root_node().to_sexp(),
+        "(program (lexical_declaration (variable_declarator name: (identifier) value: (number))))"
+    );
+}
+
 fn tokio_like_spawn<T>(future: T) -> JoinHandle<(T::Output, usize)>
 where
     T: Future + Send + 'static,
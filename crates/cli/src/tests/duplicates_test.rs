@@ -0,0 +1,98 @@
+use std::path::PathBuf;
+
+use indoc::indoc;
+use tree_sitter::{Parser, Point};
+
+use super::helpers::fixtures::get_language;
+use crate::duplicates::{find_duplicates, write_duplicates, DuplicatesOptions};
+
+#[test]
+fn test_find_duplicates() {
+    let language = get_language("javascript");
+    let sources = [
+        indoc! {"
+            function add(a, b) {
+              // Add the numbers.
+              const sum = a + b;
+              return sum;
+            }
+
+            function other() {}
+        "},
+        indoc! {"
+            const x = 1;
+            function add(a, b) {
+              const sum = a + b;
+              return sum;
+            }
+
+            function plus(x, y) {
+              const total = x + y;
+              return total;
+            }
+        "},
+    ]
+    .map(|source| source.as_bytes().to_vec());
+
+    let mut options = DuplicatesOptions {
+        min_size: 10,
+        ignore_text: false,
+        include_extras: false,
+        ignored_kinds: Vec::new(),
+        color: false,
+    };
+    let groups = find_duplicates(&language, &sources, &options).unwrap();
+    assert_eq!(groups.len(), 1);
+    assert_eq!(groups[0].kind, "function_declaration");
+    assert_eq!(
+        groups[0]
+            .locations
+            .iter()
+            .map(|(i, range)| (*i, range.start_point, range.end_point))
+            .collect::<Vec<_>>(),
+        &[
+            (0, Point::new(0, 0), Point::new(4, 1)),
+            (1, Point::new(1, 0), Point::new(4, 1)),
+        ]
+    );
+
+    // The size only counts the compared nodes, so the comment in the first
+    // copy isn't included, and doesn't help it reach the minimum size.
+    let mut parser = Parser::new();
+    parser.set_language(&language).unwrap();
+    let tree = parser.parse(&sources[1], None).unwrap();
+    let function = tree.root_node().named_child(1).unwrap();
+    assert_eq!(groups[0].size, function.descendant_count());
+    options.min_size = groups[0].size + 1;
+    assert_eq!(find_duplicates(&language, &sources, &options).unwrap(), &[]);
+    options.min_size = 10;
+
+    let mut output = Vec::new();
+    let paths = [PathBuf::from("a.js"), PathBuf::from("b.js")];
+    write_duplicates(&mut output, &groups, &paths, &options).unwrap();
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        format!(
+            "duplicate (function_declaration, {} nodes) in 2 places\n  a.js [0, 0] - [4, 1]\n  b.js [1, 0] - [4, 1]\n",
+            groups[0].size
+        )
+    );
+
+    // Comments are compared when extras are included.
+    options.include_extras = true;
+    let groups = find_duplicates(&language, &sources, &options).unwrap();
+    assert_eq!(groups.len(), 1);
+    assert_eq!(groups[0].kind, "lexical_declaration");
+    assert_eq!(groups[0].locations.len(), 2);
+
+    // Renamed identifiers are ignored when only the structure is compared.
+    options.include_extras = false;
+    options.ignore_text = true;
+    let groups = find_duplicates(&language, &sources, &options).unwrap();
+    assert_eq!(groups.len(), 1);
+    assert_eq!(groups[0].kind, "function_declaration");
+    assert_eq!(groups[0].locations.len(), 3);
+
+    options.ignored_kinds = vec!["nonexistent".to_string()];
+    assert!(find_duplicates(&language, &sources, &options).is_err());
+}
@@ -9,6 +9,9 @@ use std::{
 
 #[ctor::ctor]
 unsafe fn initialize_allocation_recording() {
+    if std::env::var_os(crate::fuzz::allocations::SKIP_RECORDING_VAR).is_some() {
+        return;
+    }
     tree_sitter::set_allocator(
         Some(ts_record_malloc),
         Some(ts_record_calloc),
@@ -1,6 +1,11 @@
+use std::fs;
+
 use tree_sitter::{self, Parser};
 
-use super::helpers::fixtures::get_language;
+use super::{
+    generate_parser,
+    helpers::fixtures::{fixtures_dir, get_language, get_test_language},
+};
 
 #[test]
 fn test_lookahead_iterator() {
@@ -197,3 +202,77 @@ fn test_supertypes() {
         }
     }
 }
+
+#[test]
+fn test_language_fingerprint() {
+    // Older parsers compute the fingerprint from their tables.
+    let rust = get_language("rust");
+    let javascript = get_language("javascript");
+    assert_eq!(rust.abi_version(), 15);
+    assert_eq!(rust.fingerprint(), get_language("rust").fingerprint());
+    assert_ne!(rust.fingerprint(), javascript.fingerprint());
+
+    // Newer parsers embed a fingerprint. The parsers are renamed, so that they
+    // don't replace the fixture parsers.
+    for name in ["json", "javascript", "python"] {
+        let src_dir = fixtures_dir().join("grammars").join(name).join("src");
+        let grammar_json = fs::read_to_string(src_dir.join("grammar.json")).unwrap();
+        let (parser_name, parser_code) = generate_parser(&grammar_json).unwrap();
+        let test_name = format!("{parser_name}_fingerprint");
+        let parser_code = parser_code.replace(
+            &format!("tree_sitter_{parser_name}(void)"),
+            &format!("tree_sitter_{test_name}(void)"),
+        );
+        let language = get_test_language(&test_name, &parser_code, Some(&src_dir));
+        assert_eq!(language.abi_version(), tree_sitter::LANGUAGE_VERSION);
+        assert!(parser_code.contains(&format!(
+            ".fingerprint = 0x{:016x}ULL,",
+            language.fingerprint()
+        )));
+    }
+
+    // Returns the embedded fingerprint of a grammar with a single rule, and
+    // the one that is computed for an older copy of the same parser.
+    let fingerprints = |name: &str, rule: &str| {
+        let grammar = format!(r#"{{"name": "{name}", "rules": {{"source_file": {rule}}}}}"#);
+        let (parser_name, parser_code) = generate_parser(&grammar).unwrap();
+        let language = get_test_language(&parser_name, &parser_code, None);
+
+        let older_name = format!("{parser_name}_abi_15");
+        let older_parser_code = parser_code
+            .lines()
+            .filter(|line| !line.contains(".fingerprint = "))
+            .collect::<Vec<_>>()
+            .join("\n")
+            .replace("#define LANGUAGE_VERSION 16", "#define LANGUAGE_VERSION 15")
+            .replace(
+                &format!("tree_sitter_{parser_name}(void)"),
+                &format!("tree_sitter_{older_name}(void)"),
+            );
+        let older_language = get_test_language(&older_name, &older_parser_code, None);
+        assert_eq!(older_language.abi_version(), 15);
+        (language.fingerprint(), older_language.fingerprint())
+    };
+    let word = r#"{"type": "PATTERN", "value": "[a-z]+"}"#;
+    let words = r#"{"type": "REPEAT", "content": {"type": "PATTERN", "value": "[a-z]+"}}"#;
+    let number = r#"{"type": "PATTERN", "value": "[0-9]+"}"#;
+    let (word_fingerprint, older_word_fingerprint) = fingerprints("test_fingerprint_word", word);
+
+    // The fingerprint does not depend on the language's name.
+    assert_eq!(
+        fingerprints("test_fingerprint_word_2", word),
+        (word_fingerprint, older_word_fingerprint)
+    );
+
+    // It changes with the parse tables.
+    let (words_fingerprint, older_words_fingerprint) =
+        fingerprints("test_fingerprint_words", words);
+    assert_ne!(words_fingerprint, word_fingerprint);
+    assert_ne!(older_words_fingerprint, older_word_fingerprint);
+
+    // Only the embedded fingerprint changes with the lex tables.
+    let (number_fingerprint, older_number_fingerprint) =
+        fingerprints("test_fingerprint_number", number);
+    assert_ne!(number_fingerprint, word_fingerprint);
+    assert_eq!(older_number_fingerprint, older_word_fingerprint);
+}
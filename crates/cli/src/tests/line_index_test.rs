@@ -0,0 +1,149 @@
+use tree_sitter::{LineIndex, LspPosition, LspRange, Parser, Point, PositionEncoding};
+
+use super::helpers::fixtures::get_language;
+
+fn position(line: usize, character: usize) -> LspPosition {
+    LspPosition { line, character }
+}
+
+#[test]
+fn test_line_index_points() {
+    let text = b"ab\n\ncd\n";
+    let index = LineIndex::new(text);
+    assert_eq!(index.line_count(), 4);
+    assert_eq!(index.line_start(2), Some(4));
+    assert_eq!(index.line_start(4), None);
+
+    for offset in 0..=text.len() {
+        let point = index.byte_to_point(offset);
+        assert_eq!(point, Point::from_byte_offset(text, offset));
+        assert_eq!(index.point_to_byte(point), offset);
+    }
+
+    // Points past the end of their line or of the text are clamped.
+    assert_eq!(index.point_to_byte(Point::new(0, 10)), 2);
+    assert_eq!(index.point_to_byte(Point::new(10, 0)), text.len());
+    assert_eq!(index.byte_to_point(100), Point::new(3, 0));
+}
+
+#[test]
+fn test_line_index_positions() {
+    // 'é' is two bytes in UTF8 and one code unit in UTF16, and '😀' is four
+    // bytes in UTF8 and two code units in UTF16.
+    let text = "aé😀b\n😀c".as_bytes();
+    let index = LineIndex::new(text);
+
+    let b = 1 + 2 + 4;
+    assert_eq!(
+        index.byte_to_position(b, PositionEncoding::Utf8),
+        position(0, 7)
+    );
+    assert_eq!(
+        index.byte_to_position(b, PositionEncoding::Utf16),
+        position(0, 4)
+    );
+    assert_eq!(
+        index.byte_to_position(b, PositionEncoding::Utf32),
+        position(0, 3)
+    );
+
+    let c = text.len() - 1;
+    assert_eq!(
+        index.byte_to_position(c, PositionEncoding::Utf16),
+        position(1, 2)
+    );
+    assert_eq!(
+        index.byte_to_position(c, PositionEncoding::Utf32),
+        position(1, 1)
+    );
+
+    for encoding in [
+        PositionEncoding::Utf8,
+        PositionEncoding::Utf16,
+        PositionEncoding::Utf32,
+    ] {
+        for (offset, _) in String::from_utf8_lossy(text).char_indices() {
+            let position = index.byte_to_position(offset, encoding);
+            assert_eq!(index.position_to_byte(position, encoding), offset);
+            assert_eq!(
+                index.position_to_point(position, encoding),
+                index.byte_to_point(offset)
+            );
+        }
+    }
+
+    // Positions within a character are moved to its start, and positions past
+    // the end of a line are clamped.
+    assert_eq!(
+        index.byte_to_position(4, PositionEncoding::Utf16),
+        position(0, 2)
+    );
+    assert_eq!(
+        index.position_to_byte(position(0, 3), PositionEncoding::Utf16),
+        3
+    );
+    assert_eq!(
+        index.position_to_byte(position(0, 10), PositionEncoding::Utf16),
+        b + 1
+    );
+    assert_eq!(
+        index.position_to_byte(position(5, 0), PositionEncoding::Utf16),
+        text.len()
+    );
+
+    // Invalid UTF8 is counted as one code unit per byte.
+    let index = LineIndex::new(b"\xffa\xe2\x82b");
+    assert_eq!(
+        index.byte_to_position(1, PositionEncoding::Utf16),
+        position(0, 1)
+    );
+    assert_eq!(
+        index.byte_to_position(4, PositionEncoding::Utf32),
+        position(0, 4)
+    );
+}
+
+#[test]
+fn test_node_lsp_range() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+    let source = "let s = \"😀\";\nlet t = s;";
+    let tree = parser.parse(source, None).unwrap();
+    let index = LineIndex::new(source.as_bytes());
+
+    let string = tree
+        .root_node()
+        .named_descendant_for_byte_range(9, 9)
+        .unwrap()
+        .parent()
+        .unwrap();
+    assert_eq!(string.kind(), "string");
+    assert_eq!(
+        string.lsp_range(&index, PositionEncoding::Utf16),
+        LspRange {
+            start: position(0, 8),
+            end: position(0, 12),
+        }
+    );
+    assert_eq!(
+        string.range().lsp_range(&index, PositionEncoding::Utf32),
+        LspRange {
+            start: position(0, 8),
+            end: position(0, 11),
+        }
+    );
+
+    let declaration = tree.root_node().child(1).unwrap();
+    assert_eq!(
+        declaration.start_lsp_position(&index, PositionEncoding::Utf16),
+        position(1, 0)
+    );
+    assert_eq!(
+        declaration.end_lsp_position(&index, PositionEncoding::Utf16),
+        position(1, 10)
+    );
+    assert_eq!(
+        index.point_to_position(declaration.end_position(), PositionEncoding::Utf16),
+        position(1, 10)
+    );
+}
@@ -1,4 +1,4 @@
-use tree_sitter::{Node, Parser, Point, Tree};
+use tree_sitter::{Node, Parser, Point, StructuralHashOptions, Tree};
 use tree_sitter_generate::load_grammar_file;
 
 use super::{
@@ -1133,6 +1133,62 @@ private:
     );
 }
 
+#[test]
+fn test_node_structural_hash() {
+    let language = get_language("javascript");
+    let source = "f(a, b);\nf(a, b);\nf(c, d);\nf(a, /* x */ b);\ng = f(a, b);\n";
+    let mut parser = Parser::new();
+    parser.set_language(&language).unwrap();
+    let tree = parser.parse(source, None).unwrap();
+    let root = tree.root_node();
+    let statements = (0..5)
+        .map(|i| root.named_child(i).unwrap())
+        .collect::<Vec<_>>();
+    let assigned_call = statements[4]
+        .named_child(0)
+        .unwrap()
+        .child_by_field_name("right")
+        .unwrap();
+
+    // Nodes are only identical when they hash equally.
+    let hash = |node: Node, options: &StructuralHashOptions| {
+        let hash = node.structural_hash(source.as_bytes(), options);
+        for other in &statements {
+            assert_eq!(
+                node.structurally_eq(source.as_bytes(), other, source.as_bytes(), options),
+                hash == other.structural_hash(source.as_bytes(), options),
+                "{node} {other}"
+            );
+        }
+        hash
+    };
+
+    let options = StructuralHashOptions::new().include_text(true);
+    assert_eq!(hash(statements[0], &options), hash(statements[1], &options));
+    assert_ne!(hash(statements[0], &options), hash(statements[2], &options));
+    assert_ne!(hash(statements[0], &options), hash(statements[3], &options));
+    assert_eq!(
+        hash(statements[0].named_child(0).unwrap(), &options),
+        hash(assigned_call, &options)
+    );
+    assert_ne!(hash(statements[0], &options), hash(root, &options));
+
+    // Without text, only the structure is compared.
+    let options = StructuralHashOptions::new();
+    assert_eq!(hash(statements[0], &options), hash(statements[2], &options));
+
+    // Comments can be skipped as extras, or by their kind.
+    let options = StructuralHashOptions::new()
+        .include_text(true)
+        .ignore_extras(true);
+    assert_eq!(hash(statements[0], &options), hash(statements[3], &options));
+    let comment = [language.id_for_node_kind("comment", true)];
+    let options = StructuralHashOptions::new()
+        .include_text(true)
+        .ignored_kinds(&comment);
+    assert_eq!(hash(statements[0], &options), hash(statements[3], &options));
+}
+
 fn get_all_nodes(tree: &Tree) -> Vec<Node> {
     let mut result = Vec::new();
     let mut visited_children = false;
@@ -1,10 +1,20 @@
 use std::{
-    sync::atomic::{AtomicUsize, Ordering},
-    thread, time,
+    fmt::Write,
+    io::{self, Cursor, Read, Seek, SeekFrom},
+    mem,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        LazyLock, Mutex,
+    },
+    thread,
+    time::{self, Duration},
 };
 
+use ropey::Rope;
 use tree_sitter::{
-    Decode, IncludedRangesError, InputEdit, LogType, ParseOptions, ParseState, Parser, Point, Range,
+    ChunkedText, Decode, IncludedRangesError, InputEdit, InputEncoding, LogEvent, LogType,
+    ParseBatchOptions, ParseOptions, ParseState, Parser, ParserPool, Point, Range,
+    StartSymbolError, Tree,
 };
 use tree_sitter_proc_macro::retry;
 
@@ -86,6 +96,175 @@ fn test_parsing_with_logging() {
     assert!(row_starts_from_0);
 }
 
+#[test]
+fn test_log_event_fields() {
+    let event = LogEvent::parse(LogType::Parse, "reduce sym:struct_item, child_count:3");
+    assert_eq!(event.action, "reduce");
+    assert_eq!(event.symbol, Some("struct_item"));
+    assert_eq!(event.state, None);
+    assert_eq!(event.position, None);
+
+    let event = LogEvent::parse(
+        LogType::Parse,
+        "process version:0, version_count:1, state:12, row:3, col:4",
+    );
+    assert_eq!(event.action, "process");
+    assert_eq!(event.state, Some(12));
+    assert_eq!(event.position, Some(Point::new(3, 4)));
+
+    let event = LogEvent::parse(LogType::Lex, "lex_internal state:7, row:0, column:9");
+    assert_eq!(event.state, Some(7));
+    assert_eq!(event.position, Some(Point::new(0, 9)));
+
+    // Symbol names can contain the separator between fields.
+    let event = LogEvent::parse(LogType::Parse, "lexed_lookahead sym:,, size:1");
+    assert_eq!(event.symbol, Some(","));
+    let event = LogEvent::parse(LogType::Parse, "reuse_node symbol:a, b");
+    assert_eq!(event.symbol, Some("a, b"));
+
+    let event = LogEvent::parse(LogType::Lex, "consume character:'a'");
+    assert_eq!(event.action, "consume");
+    assert_eq!(event.symbol, None);
+    assert_eq!(event.message, "consume character:'a'");
+}
+
+#[test]
+fn test_parsing_with_log_logger() {
+    // The target, level, state and symbol of each record.
+    type Record = (String, log::Level, Option<u64>, Option<String>);
+    struct Recorder(Mutex<Vec<Record>>);
+
+    impl log::Log for Recorder {
+        fn enabled(&self, metadata: &log::Metadata) -> bool {
+            metadata.target().starts_with("tree_sitter::")
+        }
+
+        fn log(&self, record: &log::Record) {
+            if self.enabled(record.metadata()) {
+                let key_values = record.key_values();
+                let state = key_values.get("state".into()).and_then(|v| v.to_u64());
+                let symbol = key_values.get("symbol".into()).map(|v| v.to_string());
+                self.0.lock().unwrap().push((
+                    record.target().to_string(),
+                    record.level(),
+                    state,
+                    symbol,
+                ));
+            }
+        }
+
+        fn flush(&self) {}
+    }
+
+    static RECORDER: Recorder = Recorder(Mutex::new(Vec::new()));
+    log::set_logger(&RECORDER).unwrap();
+    log::set_max_level(log::LevelFilter::Trace);
+
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("rust")).unwrap();
+    parser.set_logger(Some(tree_sitter::log_logger()));
+    parser.parse("struct Stuff {}", None).unwrap();
+    log::set_max_level(log::LevelFilter::Off);
+
+    let records = mem::take(&mut *RECORDER.0.lock().unwrap());
+    assert!(records.contains(&(
+        "tree_sitter::parse".to_string(),
+        log::Level::Debug,
+        None,
+        Some("struct_item".to_string())
+    )));
+    assert!(records
+        .iter()
+        .any(|(target, _, state, _)| target == "tree_sitter::parse" && state.is_some()));
+    assert!(records
+        .iter()
+        .any(|(target, level, _, _)| target == "tree_sitter::lex" && *level == log::Level::Trace));
+}
+
+#[test]
+fn test_parsing_with_tracing_logger() {
+    // The target, level, state and symbol of each event.
+    type Record = (String, tracing::Level, Option<u64>, Option<String>);
+
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<Record>>);
+
+    #[derive(Default)]
+    struct Fields {
+        state: Option<u64>,
+        symbol: Option<String>,
+    }
+
+    impl tracing::field::Visit for Fields {
+        fn record_u64(&mut self, field: &tracing::field::Field, value: u64) {
+            if field.name() == "state" {
+                self.state = Some(value);
+            }
+        }
+
+        fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
+            if field.name() == "symbol" {
+                self.symbol = Some(value.to_string());
+            }
+        }
+
+        fn record_debug(&mut self, _: &tracing::field::Field, _: &dyn std::fmt::Debug) {}
+    }
+
+    impl tracing::Subscriber for &'static Recorder {
+        fn enabled(&self, metadata: &tracing::Metadata) -> bool {
+            metadata.target().starts_with("tree_sitter::")
+        }
+
+        fn event(&self, event: &tracing::Event) {
+            let mut fields = Fields::default();
+            event.record(&mut fields);
+            let metadata = event.metadata();
+            self.0.lock().unwrap().push((
+                metadata.target().to_string(),
+                *metadata.level(),
+                fields.state,
+                fields.symbol,
+            ));
+        }
+
+        fn new_span(&self, _: &tracing::span::Attributes) -> tracing::span::Id {
+            tracing::span::Id::from_u64(1)
+        }
+
+        fn record(&self, _: &tracing::span::Id, _: &tracing::span::Record) {}
+
+        fn record_follows_from(&self, _: &tracing::span::Id, _: &tracing::span::Id) {}
+
+        fn enter(&self, _: &tracing::span::Id) {}
+
+        fn exit(&self, _: &tracing::span::Id) {}
+    }
+
+    static RECORDER: LazyLock<Recorder> = LazyLock::new(Recorder::default);
+
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("rust")).unwrap();
+    parser.set_logger(Some(tree_sitter::tracing_logger()));
+    tracing::subscriber::with_default(&*RECORDER, || {
+        parser.parse("struct Stuff {}", None).unwrap();
+    });
+
+    let records = mem::take(&mut *RECORDER.0.lock().unwrap());
+    assert!(records.contains(&(
+        "tree_sitter::parse".to_string(),
+        tracing::Level::DEBUG,
+        None,
+        Some("struct_item".to_string())
+    )));
+    assert!(records
+        .iter()
+        .any(|(target, _, state, _)| target == "tree_sitter::parse" && state.is_some()));
+    assert!(records.iter().any(
+        |(target, level, _, _)| target == "tree_sitter::lex" && *level == tracing::Level::TRACE
+    ));
+}
+
 #[test]
 #[cfg(unix)]
 fn test_parsing_with_debug_graph_enabled() {
@@ -262,6 +441,266 @@ fn test_parsing_with_callback_returning_owned_strings() {
     );
 }
 
+#[test]
+fn test_parsing_from_seekable_reader() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+
+    // Make the text large enough that not all of its chunks are cached.
+    let mut source = String::new();
+    for i in 0..40_000 {
+        writeln!(source, "const value{i} = \"€{i}\" + f(x, y);").unwrap();
+    }
+    assert!(source.len() > 1024 * 1024);
+
+    let expected = parser.parse(&source, None).unwrap();
+
+    let mut input = b"header".to_vec();
+    input.extend_from_slice(source.as_bytes());
+    let mut reader = Cursor::new(input);
+    reader.seek(SeekFrom::Start(6)).unwrap();
+    let tree = parser
+        .parse_reader(reader, InputEncoding::UTF8, None, None)
+        .unwrap()
+        .unwrap();
+
+    assert!(!tree.root_node().has_error());
+    assert_eq!(tree.root_node().end_byte(), source.len());
+    assert_eq!(tree.root_node().to_sexp(), expected.root_node().to_sexp());
+
+    // Reparse after an edit, reusing the old tree.
+    let mut tree = tree;
+    let position = source[source.len() / 2..].find('\n').unwrap() + source.len() / 2 + 1;
+    let mut source = source.into_bytes();
+    perform_edit(
+        &mut tree,
+        &mut source,
+        &Edit {
+            position,
+            deleted_length: 0,
+            inserted_text: b"let edited = 1;\n".to_vec(),
+        },
+    )
+    .unwrap();
+    let tree = parser
+        .parse_reader(Cursor::new(&source), InputEncoding::UTF8, Some(&tree), None)
+        .unwrap()
+        .unwrap();
+    let expected = parser.parse(&source, None).unwrap();
+    assert_eq!(tree.root_node().to_sexp(), expected.root_node().to_sexp());
+}
+
+#[test]
+fn test_parsing_utf16_from_seekable_reader() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("rust")).unwrap();
+
+    let source = "pub fn foo() { println!(\"€50 ∑\"); }\n".repeat(4000);
+    let utf16 = source.encode_utf16().collect::<Vec<_>>();
+    let expected = parser.parse_utf16_le(&utf16, None).unwrap();
+
+    let le_bytes = utf16
+        .iter()
+        .flat_map(|c| c.to_le_bytes())
+        .collect::<Vec<_>>();
+    let tree = parser
+        .parse_reader(Cursor::new(le_bytes), InputEncoding::UTF16_LE, None, None)
+        .unwrap()
+        .unwrap();
+    assert!(!tree.root_node().has_error());
+    assert_eq!(tree.root_node().end_byte(), utf16.len() * 2);
+    assert_eq!(tree.root_node().to_sexp(), expected.root_node().to_sexp());
+
+    let be_bytes = utf16
+        .iter()
+        .flat_map(|c| c.to_be_bytes())
+        .collect::<Vec<_>>();
+    let tree = parser
+        .parse_reader(Cursor::new(be_bytes), InputEncoding::UTF16_BE, None, None)
+        .unwrap()
+        .unwrap();
+    assert_eq!(tree.root_node().to_sexp(), expected.root_node().to_sexp());
+}
+
+#[test]
+fn test_parsing_from_buffered_reader() {
+    // A non-seekable reader that returns a few bytes at a time, so that
+    // multi-byte characters are split across reads.
+    struct TrickleReader<'a>(&'a [u8]);
+
+    impl Read for TrickleReader<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let len = buf.len().min(self.0.len()).min(7);
+            buf[..len].copy_from_slice(&self.0[..len]);
+            self.0 = &self.0[len..];
+            Ok(len)
+        }
+    }
+
+    struct Cp1252Decoder;
+
+    impl Decode for Cp1252Decoder {
+        fn decode(bytes: &[u8]) -> (i32, u32) {
+            bytes.first().map_or((0, 0), |byte| (i32::from(*byte), 1))
+        }
+    }
+
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("rust")).unwrap();
+
+    let source = "fn main() { let s = \"€50 ∑ é\"; }\n".repeat(5000);
+    let expected = parser.parse(&source, None).unwrap();
+    let tree = parser
+        .parse_buffered_reader(
+            TrickleReader(source.as_bytes()),
+            1024,
+            InputEncoding::UTF8,
+            None,
+            None,
+        )
+        .unwrap()
+        .unwrap();
+    assert!(!tree.root_node().has_error());
+    assert_eq!(tree.root_node().to_sexp(), expected.root_node().to_sexp());
+
+    let cp1252_source = "fn main() { let s = \"é\"; }\n".repeat(5000);
+    let cp1252_bytes = encoding_rs::WINDOWS_1252.encode(&cp1252_source).0;
+    let tree = parser
+        .parse_buffered_reader(
+            TrickleReader(&cp1252_bytes),
+            1024,
+            InputEncoding::custom::<Cp1252Decoder>(),
+            None,
+            None,
+        )
+        .unwrap()
+        .unwrap();
+    assert!(!tree.root_node().has_error());
+    assert_eq!(tree.root_node().end_byte(), cp1252_bytes.len());
+}
+
+#[test]
+fn test_parsing_from_reader_that_fails() {
+    struct FailingReader(usize);
+
+    impl Read for FailingReader {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.0 == 0 {
+                return Err(io::Error::new(io::ErrorKind::BrokenPipe, "connection lost"));
+            }
+            let len = buf.len().min(self.0);
+            buf[..len].fill(b' ');
+            self.0 -= len;
+            Ok(len)
+        }
+    }
+
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("rust")).unwrap();
+
+    let error = parser
+        .parse_buffered_reader(FailingReader(100), 1024, InputEncoding::UTF8, None, None)
+        .unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::BrokenPipe);
+
+    // The parser can still be used after a failed read.
+    let tree = parser.parse("fn main() {}", None).unwrap();
+    assert_eq!(
+        tree.root_node().to_sexp(),
+        "(source_file (function_item name: (identifier) parameters: (parameters) body: (block)))"
+    );
+}
+
+#[test]
+fn test_parsing_chunked_text() {
+    // A buffer whose chunks split multi-byte characters.
+    struct Chunks(Vec<Vec<u8>>);
+
+    impl ChunkedText for Chunks {
+        fn chunk_at_byte(&self, byte_offset: usize) -> (&[u8], usize) {
+            let mut start = 0;
+            for chunk in &self.0 {
+                if byte_offset < start + chunk.len() {
+                    return (chunk, start);
+                }
+                start += chunk.len();
+            }
+            (&[], start)
+        }
+    }
+
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("rust")).unwrap();
+
+    let source = "fn main() { let s = \"€50 ∑ é 😀\"; }\n".repeat(20);
+    let expected = parser.parse(&source, None).unwrap();
+
+    let chunks = Chunks(source.as_bytes().chunks(3).map(<[u8]>::to_vec).collect());
+    let tree = parser.parse_chunked_text(&chunks, None, None).unwrap();
+    assert!(!tree.root_node().has_error());
+    assert_eq!(tree.root_node().to_sexp(), expected.root_node().to_sexp());
+
+    let chunk_text = chunks
+        .chunks_in_range(13..25)
+        .flatten()
+        .copied()
+        .collect::<Vec<_>>();
+    assert_eq!(chunk_text, &source.as_bytes()[13..25]);
+}
+
+#[test]
+fn test_parsing_rope() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+
+    let mut source = String::new();
+    for i in 0..2000 {
+        writeln!(source, "const value{i} = \"€{i}\";").unwrap();
+    }
+    let mut rope = Rope::from_str(&source);
+    let mut tree = parser.parse_chunked_text(&rope, None, None).unwrap();
+    assert!(rope.chunks().count() > 1);
+    assert!(!tree.root_node().has_error());
+    assert_eq!(
+        tree.root_node().to_sexp(),
+        parser.parse(&source, None).unwrap().root_node().to_sexp()
+    );
+
+    // Edit the rope and reparse it incrementally.
+    let start_byte = rope.line_to_byte(1000);
+    let inserted_text = "let edited = 1;\n";
+    rope.insert(rope.byte_to_char(start_byte), inserted_text);
+    tree.edit(&InputEdit {
+        start_byte,
+        old_end_byte: start_byte,
+        new_end_byte: start_byte + inserted_text.len(),
+        start_position: Point::new(1000, 0),
+        old_end_position: Point::new(1000, 0),
+        new_end_position: Point::new(1001, 0),
+    });
+    let tree = parser.parse_chunked_text(&rope, Some(&tree), None).unwrap();
+    let node = tree
+        .root_node()
+        .descendant_for_byte_range(start_byte, start_byte)
+        .unwrap();
+    assert_eq!(node.kind(), "let");
+    assert_eq!(
+        tree.root_node().to_sexp(),
+        parser
+            .parse(rope.to_string(), None)
+            .unwrap()
+            .root_node()
+            .to_sexp()
+    );
+
+    let slice = rope.byte_slice(start_byte..);
+    let tree = parser.parse_chunked_text(&slice, None, None).unwrap();
+    assert_eq!(
+        tree.root_node().child(0).unwrap().kind(),
+        "lexical_declaration"
+    );
+}
+
 #[test]
 fn test_parsing_text_with_byte_order_mark() {
     let mut parser = Parser::new();
@@ -747,6 +1186,88 @@ fn test_parsing_cancelled_by_another_thread() {
 
 // Timeouts
 
+#[test]
+fn test_parser_pool() {
+    let language = get_language("javascript");
+    let sources = (0..50)
+        .map(|i| "let x = a + b;\n".repeat(i) + &format!("function f{i}() {{ return {i}; }}"))
+        .collect::<Vec<_>>();
+
+    let pool = ParserPool::new(4);
+    assert_eq!(pool.thread_count(), 4);
+    let trees = pool.parse(&language, &sources, None).unwrap();
+    assert_eq!(trees.len(), sources.len());
+
+    let mut parser = Parser::new();
+    parser.set_language(&language).unwrap();
+    for (source, tree) in sources.iter().zip(&trees) {
+        let expected = parser.parse(source, None).unwrap();
+        assert_eq!(
+            tree.as_ref().unwrap().root_node().to_sexp(),
+            expected.root_node().to_sexp()
+        );
+    }
+
+    // The pool's parsers can be used for a different language.
+    let trees = pool
+        .parse(&get_language("rust"), &["fn main() {}"], None)
+        .unwrap();
+    assert_eq!(trees[0].as_ref().unwrap().root_node().kind(), "source_file");
+
+    // Inputs can be handled with custom parsing logic.
+    let lengths = pool
+        .map(&language, &sources, |parser, index, source| {
+            let utf16 = source.encode_utf16().collect::<Vec<_>>();
+            let tree = parser.parse_utf16_le(&utf16, None).unwrap();
+            (index, tree.root_node().end_byte())
+        })
+        .unwrap();
+    for (i, (index, length)) in lengths.into_iter().enumerate() {
+        assert_eq!(index, i);
+        assert_eq!(length, sources[i].len() * 2);
+    }
+}
+
+#[test]
+fn test_parser_pool_with_progress_callback_and_timeout() {
+    let language = get_language("javascript");
+    let sources = (0..8)
+        .map(|i| format!("[{}]", "0,".repeat(if i % 2 == 0 { 10 } else { 100_000 })))
+        .collect::<Vec<_>>();
+    let pool = ParserPool::new(3);
+
+    // Cancel the parse of every input with an odd index.
+    let progress_callback = |index: usize, _: &ParseState| index % 2 == 1;
+    let trees = pool
+        .parse(
+            &language,
+            &sources,
+            Some(ParseBatchOptions::new().progress_callback(&progress_callback)),
+        )
+        .unwrap();
+    for (i, tree) in trees.iter().enumerate() {
+        assert_eq!(tree.is_some(), i % 2 == 0);
+    }
+
+    // A cancelled parse does not affect the next input that is parsed with
+    // the same parser.
+    let trees = pool.parse(&language, &sources, None).unwrap();
+    assert!(trees.iter().all(Option::is_some));
+
+    let trees = pool
+        .parse(
+            &language,
+            &sources,
+            Some(ParseBatchOptions::new().timeout(Duration::from_micros(1))),
+        )
+        .unwrap();
+    for (i, tree) in trees.iter().enumerate() {
+        if i % 2 == 1 {
+            assert!(tree.is_none());
+        }
+    }
+}
+
 #[test]
 #[retry(10)]
 fn test_parsing_with_a_timeout() {
@@ -1574,6 +2095,117 @@ fn test_parsing_with_included_ranges_and_missing_tokens() {
     assert_eq!(root.child(3).unwrap().start_byte(), 4);
 }
 
+#[test]
+fn test_parsing_from_an_alternate_start_rule() {
+    let (parser_name, parser_code) = generate_parser(
+        r#"
+        {
+            "name": "test_start_rules",
+            "rules": {
+                "program": {
+                    "type": "REPEAT",
+                    "content": { "type": "SYMBOL", "name": "statement" }
+                },
+                "statement": {
+                    "type": "SEQ",
+                    "members": [
+                        { "type": "SYMBOL", "name": "expression" },
+                        { "type": "STRING", "value": ";" }
+                    ]
+                },
+                "expression": {
+                    "type": "CHOICE",
+                    "members": [
+                        { "type": "SYMBOL", "name": "sum" },
+                        { "type": "SYMBOL", "name": "identifier" }
+                    ]
+                },
+                "sum": {
+                    "type": "PREC_LEFT",
+                    "value": 1,
+                    "content": {
+                        "type": "SEQ",
+                        "members": [
+                            { "type": "SYMBOL", "name": "expression" },
+                            { "type": "STRING", "value": "+" },
+                            { "type": "SYMBOL", "name": "expression" }
+                        ]
+                    }
+                },
+                "identifier": { "type": "PATTERN", "value": "[a-z]+" }
+            },
+            "extras": [ { "type": "PATTERN", "value": "\\s" } ],
+            "start_rules": ["statement", "expression"]
+        }
+        "#,
+    )
+    .unwrap();
+
+    let language = get_test_language(&parser_name, &parser_code, None);
+    let mut parser = Parser::new();
+    parser.set_language(&language).unwrap();
+    assert_eq!(parser.start_symbol(), 0);
+
+    let expression = language.id_for_node_kind("expression", true);
+    parser.set_start_symbol(expression).unwrap();
+    assert_eq!(parser.start_symbol(), expression);
+    let tree = parser.parse("a + b", None).unwrap();
+    assert_eq!(
+        tree.root_node().to_sexp(),
+        "(expression (sum (expression (identifier)) (expression (identifier))))"
+    );
+
+    // The expected symbols are computed from the fragment's start state, and
+    // the start state is kept when the tree is copied or serialized.
+    assert_ne!(tree.start_state(), 1);
+    let names = |symbols: Vec<u16>| {
+        symbols
+            .into_iter()
+            .map(|symbol| language.node_kind_for_id(symbol).unwrap())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(names(tree.expected_symbols_at(1)), ["+"]);
+    assert_eq!(tree.clone().start_state(), tree.start_state());
+    let deserialized = Tree::deserialize(&language, &tree.serialize()).unwrap();
+    assert_eq!(deserialized.start_state(), tree.start_state());
+    assert_eq!(
+        names(deserialized.expected_symbols_at(1)),
+        names(tree.expected_symbols_at(1))
+    );
+
+    // Input that only matches the document's rule is a syntax error.
+    let tree = parser.parse("a + b;", None).unwrap();
+    assert_eq!(tree.root_node().kind(), "expression");
+    assert!(tree.root_node().has_error());
+
+    let statement = language.id_for_node_kind("statement", true);
+    parser.set_start_symbol(statement).unwrap();
+    let tree = parser.parse("a;", None).unwrap();
+    assert_eq!(
+        tree.root_node().to_sexp(),
+        "(statement (expression (identifier)))"
+    );
+
+    // Rules that aren't start rules are rejected.
+    let sum = language.id_for_node_kind("sum", true);
+    assert_eq!(parser.set_start_symbol(sum), Err(StartSymbolError(sum)));
+    let identifier = language.id_for_node_kind("identifier", true);
+    assert_eq!(
+        parser.set_start_symbol(identifier),
+        Err(StartSymbolError(identifier))
+    );
+    assert_eq!(parser.start_symbol(), statement);
+
+    // Setting the language restores the document's rule.
+    parser.set_language(&language).unwrap();
+    assert_eq!(parser.start_symbol(), 0);
+    let tree = parser.parse("a; b + c;", None).unwrap();
+    assert_eq!(
+        tree.root_node().to_sexp(),
+        "(program (statement (expression (identifier))) (statement (expression (sum (expression (identifier)) (expression (identifier))))))"
+    );
+}
+
 #[test]
 fn test_grammars_that_can_hang_on_eof() {
     let (parser_name, parser_code) = generate_parser(
@@ -3,7 +3,7 @@ use proc_macro2::Span;
 use quote::quote;
 use syn::{
     parse::{Parse, ParseStream},
-    parse_macro_input, Error, Expr, Ident, ItemFn, LitInt, Token,
+    parse_macro_input, Error, Expr, Ident, ItemFn, LitInt, LitStr, Token,
 };
 
 #[proc_macro_attribute]
@@ -133,3 +133,24 @@ pub fn test_with_seed(args: TokenStream, input: TokenStream) -> TokenStream {
         }
     })
 }
+
+/// Expand to the typed node wrappers generated for a `node-types.json` string.
+///
+/// This is the code that `tree-sitter generate --rust-types` writes, so that
+/// tests can compile it and use it on real syntax trees.
+#[proc_macro]
+pub fn rust_types(input: TokenStream) -> TokenStream {
+    let node_types = parse_macro_input!(input as LitStr);
+    let code = match tree_sitter_generate::generate_rust_types(&node_types.value()) {
+        Ok(code) => code,
+        Err(e) => return Error::new(node_types.span(), e).to_compile_error().into(),
+    };
+    code.parse().unwrap_or_else(|e| {
+        Error::new(
+            node_types.span(),
+            format!("Generated code is not valid Rust -- {e}"),
+        )
+        .to_compile_error()
+        .into()
+    })
+}
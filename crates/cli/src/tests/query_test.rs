@@ -1,18 +1,19 @@
-use std::{env, fmt::Write, sync::LazyLock};
+use std::{env, fmt::Write, fs, sync::LazyLock};
 
 use indoc::indoc;
 use rand::{prelude::StdRng, SeedableRng};
 use streaming_iterator::StreamingIterator;
 use tree_sitter::{
-    CaptureQuantifier, InputEdit, Language, Node, Parser, Point, Query, QueryCursor,
-    QueryCursorOptions, QueryError, QueryErrorKind, QueryPredicate, QueryPredicateArg,
-    QueryProperty, Range,
+    CaptureQuantifier, ChunkedText, InputEdit, Language, Node, Parser, PatternPredicateArg, Point,
+    PredicateArg, Query, QueryBuilder, QueryCursor, QueryCursorOptions, QueryError, QueryErrorKind,
+    QueryPattern, QueryPredicate, QueryPredicateArg, QueryProperty, Range, SnapshotError,
+    UnregisteredPredicates,
 };
 use unindent::Unindent;
 
 use super::helpers::{
     allocations,
-    fixtures::{get_language, get_test_language},
+    fixtures::{get_language, get_language_queries_path, get_test_language},
     query_helpers::{assert_query_matches, Match, Pattern},
 };
 use crate::tests::{
@@ -3850,6 +3851,69 @@ fn test_query_text_callback_returns_chunks() {
     });
 }
 
+#[test]
+fn test_query_text_predicates_with_chunked_text() {
+    allocations::record(|| {
+        let language = get_language("javascript");
+        let query = Query::new(
+            &language,
+            r#"
+            ((assignment_expression
+               left: (identifier) @left
+               right: (identifier) @right)
+             (#eq? @left @right))
+            ((identifier) @known
+             (#any-of? @known "transform" "AnotherThing"))
+            ((identifier) @other
+             (#not-eq? @other "SOMETHING"))
+            "#,
+        )
+        .unwrap();
+
+        let source = "SOMETHING = SOMETHING; transform = AnotherThing; x = property;";
+
+        // Store the source code in chunks of 4 bytes, so that every node's
+        // text is split across chunk boundaries.
+        let source_chunks = Chunks(source.as_bytes().chunks(4).collect());
+
+        let mut parser = Parser::new();
+        parser.set_language(&language).unwrap();
+        let tree = parser
+            .parse_chunked_text(&source_chunks, None, None)
+            .unwrap();
+        let mut cursor = QueryCursor::new();
+        let matches = cursor.matches(&query, tree.root_node(), source_chunks.text_provider());
+
+        assert_eq!(
+            collect_matches(matches, &query, source),
+            &[
+                (0, vec![("left", "SOMETHING"), ("right", "SOMETHING")]),
+                (1, vec![("known", "transform")]),
+                (2, vec![("other", "transform")]),
+                (1, vec![("known", "AnotherThing")]),
+                (2, vec![("other", "AnotherThing")]),
+                (2, vec![("other", "x")]),
+                (2, vec![("other", "property")]),
+            ]
+        );
+    });
+}
+
+struct Chunks<'a>(Vec<&'a [u8]>);
+
+impl ChunkedText for Chunks<'_> {
+    fn chunk_at_byte(&self, byte_offset: usize) -> (&[u8], usize) {
+        let mut start = 0;
+        for chunk in &self.0 {
+            if byte_offset < start + chunk.len() {
+                return (chunk, start);
+            }
+            start += chunk.len();
+        }
+        (&[], start)
+    }
+}
+
 #[test]
 fn test_query_start_end_byte_for_pattern() {
     let language = get_language("javascript");
@@ -5644,3 +5708,596 @@ fn test_query_with_predicate_causing_oob_access() {
      (#set! injection.language \"regex\"))";
     Query::new(&language, query).unwrap();
 }
+
+#[test]
+fn test_query_snapshot_round_trip() {
+    let language = get_language("javascript");
+    let source = indoc! {r"
+        // A comment.
+        class A extends B {
+            get c() { return this.d(/e+/); }
+        }
+        const f = (g, h) => html`<i>${g}</i>`;
+    "};
+    let mut parser = Parser::new();
+    parser.set_language(&language).unwrap();
+    let tree = parser.parse(source, None).unwrap();
+
+    for query_name in ["highlights", "injections", "locals", "tags"] {
+        let query_path = get_language_queries_path("javascript").join(format!("{query_name}.scm"));
+        let query = Query::new(&language, &fs::read_to_string(query_path).unwrap()).unwrap();
+        let bytes = query.serialize();
+        let restored = Query::deserialize(&language, &bytes).unwrap();
+
+        assert_eq!(restored.serialize(), bytes);
+        assert_eq!(restored.capture_names(), query.capture_names());
+        assert_eq!(restored.pattern_count(), query.pattern_count());
+        for i in 0..query.pattern_count() {
+            assert_eq!(restored.property_settings(i), query.property_settings(i));
+            assert_eq!(restored.general_predicates(i), query.general_predicates(i));
+            assert_eq!(
+                restored.start_byte_for_pattern(i),
+                query.start_byte_for_pattern(i)
+            );
+            assert_eq!(
+                restored.start_row_for_pattern(i),
+                query.start_row_for_pattern(i)
+            );
+        }
+        assert_ne!(restored.start_row_for_pattern(query.pattern_count() - 1), 0);
+
+        let mut cursor = QueryCursor::new();
+        let expected = collect_captures(
+            cursor.captures(&query, tree.root_node(), source.as_bytes()),
+            &query,
+            source,
+        );
+        let mut cursor = QueryCursor::new();
+        let actual = collect_captures(
+            cursor.captures(&restored, tree.root_node(), source.as_bytes()),
+            &restored,
+            source,
+        );
+        assert!(!expected.is_empty());
+        assert_eq!(actual, expected, "query {query_name}");
+    }
+}
+
+#[test]
+fn test_query_snapshot_loaded_from_precompiled_file() {
+    let language = get_language("javascript");
+    let queries_dir = get_language_queries_path("javascript");
+    let output_dir = tempfile::tempdir().unwrap();
+    let library_path = output_dir.path().join("javascript.so");
+
+    let paths = crate::query::precompile_queries(&language, &queries_dir, &library_path).unwrap();
+    let highlights_path = output_dir.path().join("javascript.highlights.tsq");
+    assert!(paths.contains(&highlights_path));
+
+    let query = crate::query::load_query(&language, &queries_dir.join("highlights.scm")).unwrap();
+    let restored = crate::query::load_query(&language, &highlights_path).unwrap();
+    assert_eq!(restored.serialize(), query.serialize());
+    assert_eq!(restored.pattern_count(), query.pattern_count());
+
+    fs::write(&highlights_path, b"not a snapshot").unwrap();
+    assert!(crate::query::load_query(&language, &highlights_path).is_err());
+}
+
+#[test]
+fn test_query_snapshot_rejects_invalid_input() {
+    let language = get_language("javascript");
+    let query = Query::new(
+        &language,
+        r#"((identifier) @variable (#match? @variable "^[a-z]")) (call_expression function: (_) @function)"#,
+    )
+    .unwrap();
+    let bytes = query.serialize();
+
+    assert_eq!(
+        Query::deserialize(&get_language("rust"), &bytes).err(),
+        Some(SnapshotError::Language)
+    );
+    assert_eq!(
+        Query::deserialize(&language, b"not a snapshot").err(),
+        Some(SnapshotError::Format)
+    );
+
+    let mut wrong_version = bytes.clone();
+    wrong_version[4] = 0xff;
+    assert_eq!(
+        Query::deserialize(&language, &wrong_version).err(),
+        Some(SnapshotError::Version)
+    );
+
+    let mut trailing_data = bytes.clone();
+    trailing_data.push(0);
+    assert_eq!(
+        Query::deserialize(&language, &trailing_data).err(),
+        Some(SnapshotError::Format)
+    );
+
+    allocations::record(|| {
+        for len in 0..bytes.len() {
+            assert!(Query::deserialize(&language, &bytes[..len]).is_err());
+        }
+    });
+
+    // Corrupting the query data must be detected or produce a usable query.
+    let mut parser = Parser::new();
+    parser.set_language(&language).unwrap();
+    let source = "a(b.c, function d() { e; });";
+    let tree = parser.parse(source, None).unwrap();
+    for i in 0..bytes.len() {
+        let mut corrupted = bytes.clone();
+        corrupted[i] ^= 0xff;
+        if let Ok(query) = Query::deserialize(&language, &corrupted) {
+            let mut cursor = QueryCursor::new();
+            let mut matches = cursor.matches(&query, tree.root_node(), source.as_bytes());
+            while matches.next().is_some() {}
+        }
+    }
+}
+
+#[test]
+fn test_query_custom_predicates() {
+    let language = get_language("javascript");
+    let mut query = Query::new(
+        &language,
+        r#"
+        ((identifier) @name
+          (#any-prefix? @name "get" "set"))
+        ((comment) @doc
+          (#word-count? @doc "4"))
+        "#,
+    )
+    .unwrap();
+    query
+        .register_predicate("any-prefix?", |args, text| {
+            let [PredicateArg::Capture(nodes), prefixes @ ..] = args else {
+                return false;
+            };
+            nodes.iter().all(|node| {
+                let text = text.text(*node).to_vec();
+                prefixes.iter().any(|prefix| {
+                    matches!(prefix, PredicateArg::String(prefix) if text.starts_with(prefix.as_bytes()))
+                })
+            })
+        })
+        .register_predicate("word-count?", |args, text| {
+            let [PredicateArg::Capture([node]), PredicateArg::String(count)] = args else {
+                return false;
+            };
+            text.text_str(*node)
+                .is_some_and(|text| text.split_whitespace().count().to_string() == *count)
+        });
+
+    let source = indoc! {"
+        // get a value
+        // set the value here
+        getA(setB, c, get);
+    "};
+    let mut parser = Parser::new();
+    parser.set_language(&language).unwrap();
+    let tree = parser.parse(source, None).unwrap();
+
+    let mut cursor = QueryCursor::new();
+    let matches = cursor.matches(&query, tree.root_node(), source.as_bytes());
+    assert_eq!(
+        collect_matches(matches, &query, source),
+        &[
+            (1, vec![("doc", "// get a value")]),
+            (0, vec![("name", "getA")]),
+            (0, vec![("name", "setB")]),
+            (0, vec![("name", "get")]),
+        ],
+    );
+
+    let mut cursor = QueryCursor::new();
+    let captures = cursor.captures(&query, tree.root_node(), source.as_bytes());
+    assert_eq!(
+        collect_captures(captures, &query, source),
+        &[
+            ("doc", "// get a value"),
+            ("name", "getA"),
+            ("name", "setB"),
+            ("name", "get"),
+        ],
+    );
+
+    // Registering a predicate again replaces its handler.
+    query.register_predicate("any-prefix?", |_, _| false);
+    let mut cursor = QueryCursor::new();
+    let matches = cursor.matches(&query, tree.root_node(), source.as_bytes());
+    assert_eq!(
+        collect_matches(matches, &query, source),
+        &[(1, vec![("doc", "// get a value")])],
+    );
+}
+
+#[test]
+fn test_query_check_predicates() {
+    let language = get_language("javascript");
+    let mut query = Query::new(
+        &language,
+        r#"
+        ((identifier) @a (#eq? @a "b"))
+
+        ((identifier) @c
+          (#lua-match? @c "^d")
+          (#is-global? @c))
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        query.check_predicates(&[]),
+        Err(QueryError {
+            kind: QueryErrorKind::Predicate,
+            row: 3,
+            column: 0,
+            offset: 0,
+            message: "Unknown predicate #lua-match?".to_string(),
+        })
+    );
+    assert_eq!(
+        query.check_predicates(&["lua-match?", "is-global?"]),
+        Ok(())
+    );
+
+    query.register_predicate("lua-match?", |_, _| true);
+    assert_eq!(
+        query.check_predicates(&[]).unwrap_err().message,
+        "Unknown predicate #is-global?"
+    );
+    assert_eq!(query.check_predicates(&["is-global?"]), Ok(()));
+}
+
+#[test]
+fn test_query_unregistered_predicates() {
+    let language = get_language("javascript");
+    let mut query = Query::new(
+        &language,
+        r#"
+        ((identifier) @a (#eq? @a "b"))
+        ((identifier) @c (#lua-match? @c "^d"))
+        ((identifier) @e (#is-global? @e))
+        "#,
+    )
+    .unwrap();
+
+    let source = "b; d; f;";
+    let mut parser = Parser::new();
+    parser.set_language(&language).unwrap();
+    let tree = parser.parse(source, None).unwrap();
+
+    // By default, unregistered predicates are left to the caller.
+    assert_eq!(
+        query.unregistered_predicates(),
+        UnregisteredPredicates::Ignore
+    );
+    let mut cursor = QueryCursor::new();
+    let matches = cursor.matches(&query, tree.root_node(), source.as_bytes());
+    assert_eq!(
+        collect_matches(matches, &query, source),
+        &[
+            (0, vec![("a", "b")]),
+            (1, vec![("c", "b")]),
+            (2, vec![("e", "b")]),
+            (1, vec![("c", "d")]),
+            (2, vec![("e", "d")]),
+            (1, vec![("c", "f")]),
+            (2, vec![("e", "f")]),
+        ],
+    );
+
+    // When they are rejected, only the patterns whose predicates are all
+    // built in or registered can match.
+    query
+        .set_unregistered_predicates(UnregisteredPredicates::Reject)
+        .register_predicate("lua-match?", |args, text| {
+            let [PredicateArg::Capture([node]), PredicateArg::String(prefix)] = args else {
+                return false;
+            };
+            let prefix = prefix.trim_start_matches('^').as_bytes();
+            text.text(*node).starts_with(prefix)
+        });
+    let mut cursor = QueryCursor::new();
+    let matches = cursor.matches(&query, tree.root_node(), source.as_bytes());
+    assert_eq!(
+        collect_matches(matches, &query, source),
+        &[(0, vec![("a", "b")]), (1, vec![("c", "d")])],
+    );
+    let mut cursor = QueryCursor::new();
+    let captures = cursor.captures(&query, tree.root_node(), source.as_bytes());
+    assert_eq!(
+        collect_captures(captures, &query, source),
+        &[("a", "b"), ("c", "d")],
+    );
+
+    query.register_predicate("is-global?", |_, _| true);
+    let mut cursor = QueryCursor::new();
+    let captures = cursor.captures(&query, tree.root_node(), source.as_bytes());
+    assert_eq!(
+        collect_captures(captures, &query, source),
+        &[("a", "b"), ("e", "b"), ("c", "d"), ("e", "d"), ("e", "f")],
+    );
+}
+
+#[test]
+fn test_query_builder() {
+    let language = get_language("javascript");
+    let mut builder = QueryBuilder::new(&language);
+    builder
+        .pattern(
+            QueryPattern::node("call_expression")
+                .field(
+                    "function",
+                    QueryPattern::alternation([
+                        QueryPattern::node("identifier").capture("function"),
+                        QueryPattern::node("member_expression")
+                            .field("property", QueryPattern::node("property_identifier"))
+                            .capture("method"),
+                    ]),
+                )
+                .field(
+                    "arguments",
+                    QueryPattern::node("arguments")
+                        .child(QueryPattern::anonymous("("))
+                        .anchor()
+                        .child(QueryPattern::named_wildcard().capture("first"))
+                        .child(QueryPattern::node("string").zero_or_more()),
+                ),
+        )
+        .unwrap()
+        .pattern(
+            QueryPattern::node("function_declaration")
+                .negated_field("parameters")
+                .field("name", QueryPattern::node("identifier").capture("name"))
+                .predicate(
+                    "match?",
+                    [
+                        PatternPredicateArg::capture("name"),
+                        PatternPredicateArg::string("^get\"\\w+"),
+                    ],
+                ),
+        )
+        .unwrap();
+
+    assert_eq!(
+        builder.to_string(),
+        concat!(
+            "(call_expression function: [(identifier) @function (member_expression property: ",
+            "(property_identifier)) @method] arguments: (arguments \"(\" . (_) @first (string)*))\n",
+            "((function_declaration !parameters name: (identifier) @name) ",
+            "(#match? @name \"^get\\\"\\\\w+\"))\n",
+        )
+    );
+    assert_eq!(builder.patterns().len(), 2);
+
+    let query = builder.build().unwrap();
+    assert_eq!(
+        query.capture_names(),
+        &["function", "method", "first", "name"]
+    );
+    assert_eq!(
+        query.capture_quantifiers(0),
+        &[
+            CaptureQuantifier::ZeroOrOne,
+            CaptureQuantifier::ZeroOrOne,
+            CaptureQuantifier::One,
+            CaptureQuantifier::Zero,
+        ]
+    );
+    assert_query_matches(
+        &language,
+        &query,
+        "a(b, 'c'); d.e(f); function g() {}",
+        &[
+            (0, vec![("function", "a"), ("first", "b")]),
+            (0, vec![("method", "d.e"), ("first", "f")]),
+        ],
+    );
+}
+
+#[test]
+fn test_query_builder_errors() {
+    let language = get_language("javascript");
+    let mut builder = QueryBuilder::new(&language);
+    builder.pattern(QueryPattern::node("identifier")).unwrap();
+
+    assert_eq!(
+        builder
+            .pattern(QueryPattern::node("program").child(QueryPattern::node("non_existent")))
+            .unwrap_err(),
+        QueryError {
+            kind: QueryErrorKind::NodeType,
+            row: 1,
+            column: 10,
+            offset: 23,
+            message: "\"non_existent\"".to_string(),
+        }
+    );
+    assert_eq!(
+        builder
+            .pattern(QueryPattern::node("program").child(QueryPattern::anonymous("non_existent")))
+            .unwrap_err(),
+        Query::new(&language, "(identifier)\n(program \"non_existent\")").unwrap_err(),
+    );
+    assert_eq!(
+        builder
+            .pattern(QueryPattern::node("program").field("nope", QueryPattern::wildcard()))
+            .unwrap_err(),
+        QueryError {
+            kind: QueryErrorKind::Field,
+            row: 1,
+            column: 9,
+            offset: 22,
+            message: "\"nope\"".to_string(),
+        }
+    );
+    assert_eq!(
+        builder
+            .pattern(
+                QueryPattern::node("identifier")
+                    .capture("a")
+                    .predicate("eq?", [PatternPredicateArg::capture("b")])
+            )
+            .unwrap_err()
+            .kind,
+        QueryErrorKind::Capture
+    );
+    assert_eq!(
+        builder
+            .pattern(QueryPattern::node("identifier").capture("a b"))
+            .unwrap_err()
+            .kind,
+        QueryErrorKind::Syntax
+    );
+    assert_eq!(
+        builder
+            .pattern(QueryPattern::node("identifier").predicate("eq", []))
+            .unwrap_err()
+            .kind,
+        QueryErrorKind::Predicate
+    );
+
+    // Failed patterns are not added.
+    assert_eq!(builder.to_string(), "(identifier)\n");
+    assert_eq!(builder.build().unwrap().pattern_count(), 1);
+}
+
+#[test]
+fn test_query_builder_round_trip() {
+    let language = get_language("javascript");
+    let query_source = r#"
+        ; A comment.
+        ((identifier) @constant
+         (#match? @constant "^[A-Z\\]\"]"))
+
+        (pair
+          key: [(property_identifier) (string)] @key
+          . value: (arrow_function)
+          (#set! role method))
+
+        (function_declaration !name) @anonymous
+        ( (comment)+ . (function_declaration) )
+        (ERROR _ @e)
+        (MISSING ";")
+        "#;
+    let query = Query::new(&language, query_source).unwrap();
+
+    let builder = QueryBuilder::from_query(&query, query_source).unwrap();
+    let source = builder.to_string();
+    assert_eq!(
+        source,
+        concat!(
+            "((identifier) @constant (#match? @constant \"^[A-Z\\\\]\\\"]\"))\n",
+            "((pair key: [(property_identifier) (string)] @key . value: (arrow_function)) ",
+            "(#set! \"role\" \"method\"))\n",
+            "(function_declaration !name) @anonymous\n",
+            "((comment)+ . (function_declaration))\n",
+            "(ERROR _ @e)\n",
+            "(MISSING \";\")\n",
+        )
+    );
+
+    let canonical = builder.build().unwrap();
+    let reparsed = QueryBuilder::from_query(&canonical, &source).unwrap();
+    assert_eq!(reparsed.patterns(), builder.patterns());
+    assert_eq!(reparsed.to_string(), source);
+    for i in 0..query.pattern_count() {
+        assert_eq!(canonical.general_predicates(i), query.general_predicates(i));
+        assert_eq!(canonical.property_settings(i), query.property_settings(i));
+    }
+
+    // The bundled queries produce the same captures in their canonical form.
+    let source = "class A { get b() { return c(/d/, `${e}`); } }";
+    let mut parser = Parser::new();
+    parser.set_language(&language).unwrap();
+    let tree = parser.parse(source, None).unwrap();
+    for query_name in ["highlights", "injections", "locals", "tags"] {
+        let query_path = get_language_queries_path("javascript").join(format!("{query_name}.scm"));
+        let query_source = fs::read_to_string(query_path).unwrap();
+        let query = Query::new(&language, &query_source).unwrap();
+        let canonical = QueryBuilder::from_query(&query, &query_source)
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(canonical.pattern_count(), query.pattern_count());
+
+        let mut cursor = QueryCursor::new();
+        let expected = collect_captures(
+            cursor.captures(&query, tree.root_node(), source.as_bytes()),
+            &query,
+            source,
+        );
+        let mut cursor = QueryCursor::new();
+        let actual = collect_captures(
+            cursor.captures(&canonical, tree.root_node(), source.as_bytes()),
+            &canonical,
+            source,
+        );
+        assert_eq!(actual, expected);
+    }
+
+    // A restored query can be round-tripped with its source, and a different
+    // source is rejected.
+    let restored = Query::deserialize(&language, &query.serialize()).unwrap();
+    let builder = QueryBuilder::from_query(&restored, query_source).unwrap();
+    assert_eq!(builder.patterns(), reparsed.patterns());
+    assert!(QueryBuilder::from_query(&query, &reparsed.to_string()).is_none());
+    assert!(QueryBuilder::from_query(&query, "(identifier)").is_none());
+
+    // So is a source whose patterns start at the same positions, but compile
+    // to different patterns.
+    let query = Query::new(&language, "(identifier) @a").unwrap();
+    assert!(QueryBuilder::from_query(&query, "(identifier) @a").is_some());
+    assert!(QueryBuilder::from_query(&query, "(identifier) @b").is_none());
+    assert!(QueryBuilder::from_query(&query, "(number) @a").is_none());
+    assert!(QueryBuilder::from_query(&query, "(identifier)+ @a").is_none());
+}
+
+#[test]
+fn test_query_is_equivalent() {
+    let language = get_language("javascript");
+    let query = Query::new(
+        &language,
+        r#"((identifier) @a (#eq? @a "b")) (call_expression function: (_) @c !arguments)"#,
+    )
+    .unwrap();
+
+    // The formatting of the source, and the order in which captures and
+    // strings first appear, don't matter.
+    let formatted = Query::new(
+        &language,
+        indoc! {r#"
+            ; A comment.
+            (
+              (identifier) @a
+              (#eq? @a "b")
+            )
+
+            (call_expression
+              !arguments
+              function: (_) @c)
+        "#},
+    )
+    .unwrap();
+    assert!(query.is_equivalent(&formatted));
+    assert!(formatted.is_equivalent(&query));
+    let restored = Query::deserialize(&language, &query.serialize()).unwrap();
+    assert!(query.is_equivalent(&restored));
+
+    for other in [
+        r#"((identifier) @a (#eq? @a "c")) (call_expression function: (_) @c !arguments)"#,
+        r#"((identifier) @a (#eq? @a "b")) (call_expression function: (_) @d !arguments)"#,
+        r#"((identifier) @a (#eq? @a "b")) (call_expression function: (_) @c)"#,
+        r#"((identifier) @a (#eq? @a "b")) (call_expression function: (_)* @c !arguments)"#,
+        r#"((identifier) @a (#eq? @a "b"))"#,
+        r#"(call_expression function: (_) @c !arguments) ((identifier) @a (#eq? @a "b"))"#,
+    ] {
+        let other = Query::new(&language, other).unwrap();
+        assert!(!query.is_equivalent(&other));
+        assert!(!other.is_equivalent(&query));
+    }
+}
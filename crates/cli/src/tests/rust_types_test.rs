@@ -0,0 +1,131 @@
+use tree_sitter::{Node, Parser};
+
+use super::helpers::fixtures::get_language;
+
+// The node types of the JSON grammar, which cover fields, unnamed children
+// and supertypes.
+#[allow(dead_code)]
+mod json {
+    tree_sitter_proc_macro::rust_types!(
+        r#"[
+            {
+                "type": "_value",
+                "named": true,
+                "subtypes": [
+                    {"type": "array", "named": true},
+                    {"type": "false", "named": true},
+                    {"type": "null", "named": true},
+                    {"type": "number", "named": true},
+                    {"type": "object", "named": true},
+                    {"type": "string", "named": true},
+                    {"type": "true", "named": true}
+                ]
+            },
+            {
+                "type": "array",
+                "named": true,
+                "fields": {},
+                "children": {"multiple": true, "required": false, "types": [{"type": "_value", "named": true}]}
+            },
+            {
+                "type": "document",
+                "named": true,
+                "root": true,
+                "fields": {},
+                "children": {"multiple": true, "required": false, "types": [{"type": "_value", "named": true}]}
+            },
+            {
+                "type": "object",
+                "named": true,
+                "fields": {},
+                "children": {"multiple": true, "required": false, "types": [{"type": "pair", "named": true}]}
+            },
+            {
+                "type": "pair",
+                "named": true,
+                "fields": {
+                    "key": {"multiple": false, "required": true, "types": [{"type": "string", "named": true}]},
+                    "value": {"multiple": false, "required": true, "types": [{"type": "_value", "named": true}]}
+                }
+            },
+            {
+                "type": "string",
+                "named": true,
+                "fields": {},
+                "children": {
+                    "multiple": true,
+                    "required": false,
+                    "types": [{"type": "escape_sequence", "named": true}, {"type": "string_content", "named": true}]
+                }
+            },
+            {"type": "comment", "named": true},
+            {"type": "escape_sequence", "named": true},
+            {"type": "false", "named": true},
+            {"type": "null", "named": true},
+            {"type": "number", "named": true},
+            {"type": "string_content", "named": true},
+            {"type": "true", "named": true},
+            {"type": ",", "named": false},
+            {"type": ":", "named": false},
+            {"type": "[", "named": false},
+            {"type": "]", "named": false},
+            {"type": "{", "named": false},
+            {"type": "}", "named": false}
+        ]"#
+    );
+}
+
+#[test]
+fn test_generated_rust_types() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("json")).unwrap();
+    let source = r#"{"a": [1, null], "b\n": true}"#;
+    let tree = parser.parse(source, None).unwrap();
+    let text = |node: Node| node.utf8_text(source.as_bytes()).unwrap();
+
+    let document = json::Document::try_from(tree.root_node()).unwrap();
+    let mut cursor = tree.walk();
+    let values = document.children(&mut cursor).collect::<Vec<_>>();
+    let [json::Value::Object(object)] = values[..] else {
+        panic!("Expected one object, found {values:?}");
+    };
+
+    let pairs = object.children(&mut cursor).collect::<Vec<_>>();
+    assert_eq!(pairs.len(), 2);
+    let keys = pairs
+        .iter()
+        .map(|pair| text(pair.key().unwrap().node()))
+        .collect::<Vec<_>>();
+    assert_eq!(keys, [r#""a""#, r#""b\n""#]);
+
+    let Some(json::Value::Array(array)) = pairs[0].value() else {
+        panic!("Expected an array, found {:?}", pairs[0].value());
+    };
+    let elements = array.children(&mut cursor).collect::<Vec<_>>();
+    assert!(matches!(
+        elements[..],
+        [json::Value::Number(_), json::Value::Null(_)]
+    ));
+    assert!(matches!(pairs[1].value(), Some(json::Value::True(_))));
+
+    let key = pairs[1].key().unwrap();
+    let key_children = key.children(&mut cursor).collect::<Vec<_>>();
+    assert!(matches!(
+        key_children[..],
+        [
+            json::StringChild::StringContent(_),
+            json::StringChild::EscapeSequence(_)
+        ]
+    ));
+
+    // Converting a node of another kind fails, and the wrappers convert back
+    // into the nodes that they wrap.
+    let error = json::Pair::try_from(array.node()).unwrap_err();
+    assert_eq!(error.expected, "pair");
+    assert_eq!(error.to_string(), "Expected a `pair` node, found `array`");
+    assert_eq!(Node::from(pairs[0]), pairs[0].node());
+    assert_eq!(
+        json::Value::from_node(array.node()),
+        Ok(json::Value::Array(array))
+    );
+}
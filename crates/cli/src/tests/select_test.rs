@@ -0,0 +1,128 @@
+use std::path::Path;
+
+use indoc::indoc;
+use tree_sitter::{Parser, Selector, SelectorErrorKind};
+
+use super::helpers::fixtures::get_language;
+use crate::select::{write_selection, SelectOptions};
+
+const SOURCE: &str = indoc! {r#"
+    function add(a, b) { return foo("x", a); }
+    const f = function named() {};
+    call("a", 1);
+"#};
+
+#[test]
+fn test_selector_matches() {
+    let language = get_language("javascript");
+    let mut parser = Parser::new();
+    parser.set_language(&language).unwrap();
+    let tree = parser.parse(SOURCE, None).unwrap();
+
+    let select = |selector: &str| {
+        let selector = Selector::new(&language, selector).unwrap();
+        tree.select(&selector)
+            .map(|node| node.utf8_text(SOURCE.as_bytes()).unwrap())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(select("function_declaration > name:identifier"), &["add"]);
+    assert_eq!(select("name:identifier"), &["add", "f", "named"]);
+    assert_eq!(select("function_expression name:*"), &["named"]);
+    assert_eq!(
+        select("call_expression:has(string)"),
+        &[r#"foo("x", a)"#, r#"call("a", 1)"#]
+    );
+    assert!(select("call_expression:has(> string)").is_empty());
+    assert_eq!(
+        select("call_expression:not(:has(number))"),
+        &[r#"foo("x", a)"#]
+    );
+    assert_eq!(select("arguments > :nth-child(2)"), &["a", "1"]);
+    assert_eq!(select("arguments > :first-child"), &[r#""x""#, r#""a""#]);
+    assert_eq!(select("arguments > *:last-child"), &["a", "1"]);
+    assert_eq!(
+        select("formal_parameters > identifier + identifier"),
+        &["b"]
+    );
+    assert_eq!(select("formal_parameters identifier ~ *"), &["b"]);
+    assert_eq!(select("string, number"), &[r#""x""#, r#""a""#, "1"]);
+    assert_eq!(select(r#"arguments > ",""#), &[",", ","]);
+
+    // The search is limited to the subtree of the node that it starts from.
+    let selector = Selector::new(&language, "function_declaration identifier").unwrap();
+    let function = tree.root_node().child(0).unwrap();
+    let body = function.child_by_field_name("body").unwrap();
+    assert_eq!(function.select(&selector).count(), 5);
+    assert_eq!(body.select(&selector).count(), 0);
+}
+
+#[test]
+fn test_selector_errors() {
+    let language = get_language("javascript");
+
+    let error = Selector::new(&language, "program > nonexistent").unwrap_err();
+    assert_eq!(error.kind, SelectorErrorKind::NodeType);
+    assert_eq!(error.offset, 10);
+    assert_eq!(error.message, "nonexistent");
+
+    let error = Selector::new(&language, "call_expression bogus:identifier").unwrap_err();
+    assert_eq!(error.kind, SelectorErrorKind::Field);
+    assert_eq!(error.offset, 16);
+
+    let error = Selector::new(&language, "call_expression:first").unwrap_err();
+    assert_eq!(error.kind, SelectorErrorKind::Syntax);
+    assert_eq!(error.offset, 15);
+
+    for selector in [
+        "program >",
+        ":nth-child(0)",
+        "arguments:has(string",
+        "program, ",
+    ] {
+        let error = Selector::new(&language, selector).unwrap_err();
+        assert_eq!(error.kind, SelectorErrorKind::Syntax, "{selector}");
+    }
+}
+
+#[test]
+fn test_write_selection() {
+    let language = get_language("javascript");
+    let mut parser = Parser::new();
+    parser.set_language(&language).unwrap();
+    let selector = Selector::new(&language, "call_expression > identifier").unwrap();
+
+    let mut options = SelectOptions {
+        count: false,
+        color: false,
+    };
+    let mut output = Vec::new();
+    let path = Path::new("a.js");
+    let count = write_selection(
+        &mut output,
+        &mut parser,
+        &selector,
+        path,
+        SOURCE.as_bytes(),
+        &options,
+    )
+    .unwrap();
+    assert_eq!(count, 2);
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "a.js\n  identifier [0, 28] - [0, 31] `foo`\n  identifier [2, 0] - [2, 4] `call`\n"
+    );
+
+    options.count = true;
+    let mut output = Vec::new();
+    write_selection(
+        &mut output,
+        &mut parser,
+        &selector,
+        path,
+        SOURCE.as_bytes(),
+        &options,
+    )
+    .unwrap();
+    assert_eq!(String::from_utf8(output).unwrap(), "a.js: 2\n");
+}
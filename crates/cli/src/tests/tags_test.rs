@@ -205,6 +205,38 @@ fn test_tags_columns_measured_in_utf16_code_units() {
     assert_eq!(tag.utf16_column_range, 9..18);
 }
 
+#[test]
+fn test_tags_columns_count_invalid_utf8_as_replacement_characters() {
+    let language = get_language("python");
+    let tags_config = TagsConfiguration::new(language, PYTHON_TAG_QUERY, "").unwrap();
+    let mut tag_context = TagsContext::new();
+
+    // The truncated sequence in the string counts as one replacement character,
+    // like it does in `String::from_utf8_lossy`, rather than one per byte.
+    let source = b"x = \"\xe2\x9d\"; a(); bb()";
+
+    let tags = tag_context
+        .generate_tags(&tags_config, source, None)
+        .unwrap()
+        .0
+        .map(|tag| tag.unwrap())
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        tags.iter()
+            .map(|tag| (
+                substr(source, &tag.name_range),
+                tag.span.clone(),
+                tag.utf16_column_range.clone()
+            ))
+            .collect::<Vec<_>>(),
+        &[
+            ("a", Point::new(0, 10)..Point::new(0, 11), 9..10),
+            ("bb", Point::new(0, 15)..Point::new(0, 17), 14..16),
+        ]
+    );
+}
+
 #[test]
 fn test_tags_ruby() {
     let language = get_language("ruby");
@@ -1,11 +1,15 @@
-use std::str;
+use std::{fs, str};
 
-use tree_sitter::{InputEdit, Parser, Point, Range, Tree};
+use tree_sitter::{
+    diff::{self, Edit as DiffEdit},
+    InjectionConfig, InputEdit, LayeredTree, Node, NodeHandle, Parser, Point, Range, RewriteError,
+    Rewriter, SnapshotError, SyntaxDiagnosticKind, Tree, VisitAction, Visitor,
+};
 
-use super::helpers::fixtures::get_language;
+use super::helpers::fixtures::{get_language, get_language_queries_path};
 use crate::{
     fuzz::edits::Edit,
-    parse::perform_edit,
+    parse::{expected_at_message, parse_position, perform_edit},
     tests::{helpers::fixtures::get_test_fixture_language, invert_edit},
 };
 
@@ -381,6 +385,197 @@ fn test_tree_cursor() {
     assert_eq!(copy.node().kind(), "struct_item");
 }
 
+#[test]
+fn test_tree_traversal_iterators() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+    let tree = parser.parse("a(b, c);", None).unwrap();
+
+    fn kinds<'a>(nodes: impl Iterator<Item = Node<'a>>) -> Vec<&'static str> {
+        nodes.map(|node| node.kind()).collect()
+    }
+
+    assert_eq!(
+        kinds(tree.preorder()),
+        &[
+            "program",
+            "expression_statement",
+            "call_expression",
+            "identifier",
+            "arguments",
+            "(",
+            "identifier",
+            ",",
+            "identifier",
+            ")",
+            ";",
+        ]
+    );
+    assert_eq!(
+        kinds(tree.postorder()),
+        &[
+            "identifier",
+            "(",
+            "identifier",
+            ",",
+            "identifier",
+            ")",
+            "arguments",
+            "call_expression",
+            ";",
+            "expression_statement",
+            "program",
+        ]
+    );
+    assert_eq!(
+        kinds(tree.breadth_first()),
+        &[
+            "program",
+            "expression_statement",
+            "call_expression",
+            ";",
+            "identifier",
+            "arguments",
+            "(",
+            "identifier",
+            ",",
+            "identifier",
+            ")",
+        ]
+    );
+    assert_eq!(
+        kinds(tree.leaves()),
+        &["identifier", "(", "identifier", ",", "identifier", ")", ";"]
+    );
+
+    // Skipping a subtree continues with the node's next sibling.
+    let mut preorder = tree.preorder();
+    let mut visited = Vec::new();
+    while let Some(node) = preorder.next() {
+        visited.push(node.kind());
+        if node.kind() == "arguments" {
+            assert_eq!(preorder.cursor().field_name(), Some("arguments"));
+            preorder.skip_subtree();
+        }
+    }
+    assert_eq!(
+        visited,
+        &[
+            "program",
+            "expression_statement",
+            "call_expression",
+            "identifier",
+            "arguments",
+            ";",
+        ]
+    );
+
+    // The iterators only visit the subtree of the node they start from.
+    let call = tree.root_node().child(0).unwrap().child(0).unwrap();
+    let arguments = call.child_by_field_name("arguments").unwrap();
+    assert_eq!(
+        kinds(arguments.preorder()),
+        &["arguments", "(", "identifier", ",", "identifier", ")"]
+    );
+    assert_eq!(kinds(arguments.postorder()).last(), Some(&"arguments"));
+    assert_eq!(
+        kinds(arguments.breadth_first()),
+        &["arguments", "(", "identifier", ",", "identifier", ")"]
+    );
+    assert_eq!(
+        kinds(call.leaves()),
+        &["identifier", "(", "identifier", ",", "identifier", ")"]
+    );
+    let leaf = arguments.child(1).unwrap();
+    assert_eq!(kinds(leaf.leaves()), &["identifier"]);
+    assert_eq!(kinds(leaf.breadth_first()), &["identifier"]);
+}
+
+#[test]
+fn test_tree_visitor() {
+    struct Collector<'a> {
+        arguments_id: u16,
+        events: Vec<String>,
+        stop_at: Option<&'a str>,
+    }
+
+    impl<'tree> Visitor<'tree> for Collector<'_> {
+        fn enter(&mut self, kind_id: u16, node: Node<'tree>) -> VisitAction {
+            self.events.push(format!("enter {}", node.kind()));
+            if Some(node.kind()) == self.stop_at {
+                VisitAction::Stop
+            } else if kind_id == self.arguments_id {
+                VisitAction::SkipChildren
+            } else {
+                VisitAction::Continue
+            }
+        }
+
+        fn leave(&mut self, _: u16, node: Node<'tree>) {
+            self.events.push(format!("leave {}", node.kind()));
+        }
+    }
+
+    let language = get_language("javascript");
+    let mut parser = Parser::new();
+    parser.set_language(&language).unwrap();
+    let tree = parser.parse("a(b);", None).unwrap();
+
+    let mut collector = Collector {
+        arguments_id: language.id_for_node_kind("arguments", true),
+        events: Vec::new(),
+        stop_at: None,
+    };
+    assert!(tree.visit(&mut collector));
+    assert_eq!(
+        collector.events,
+        &[
+            "enter program",
+            "enter expression_statement",
+            "enter call_expression",
+            "enter identifier",
+            "leave identifier",
+            "enter arguments",
+            "leave arguments",
+            "leave call_expression",
+            "enter ;",
+            "leave ;",
+            "leave expression_statement",
+            "leave program",
+        ]
+    );
+
+    collector.events.clear();
+    collector.stop_at = Some("identifier");
+    assert!(!tree.visit(&mut collector));
+    assert_eq!(
+        collector.events,
+        &[
+            "enter program",
+            "enter expression_statement",
+            "enter call_expression",
+            "enter identifier",
+        ]
+    );
+
+    // Visiting a node doesn't leave its subtree.
+    collector.events.clear();
+    collector.stop_at = None;
+    let call = tree.root_node().child(0).unwrap().child(0).unwrap();
+    assert!(call.visit(&mut collector));
+    assert_eq!(
+        collector.events,
+        &[
+            "enter call_expression",
+            "enter identifier",
+            "leave identifier",
+            "enter arguments",
+            "leave arguments",
+            "leave call_expression",
+        ]
+    );
+}
+
 #[test]
 fn test_tree_cursor_previous_sibling_with_aliases() {
     let mut parser = Parser::new();
@@ -741,6 +936,511 @@ fn test_consistency_with_mid_codepoint_edit() {
     assert_eq!(tree3.root_node().to_sexp(), tree.root_node().to_sexp());
 }
 
+#[test]
+fn test_input_edit_from_diff() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+
+    let old_source = "function a() {\n  b(1);\n  c(2);\n}\n";
+    let new_source = "function a() {\n  b(1, 'ü');\n  d();\n  c(2);\n}\n";
+
+    let edit = InputEdit::from_diff(old_source.as_bytes(), new_source.as_bytes());
+    assert_eq!(
+        edit,
+        InputEdit {
+            start_byte: 20,
+            old_end_byte: 20,
+            new_end_byte: 33,
+            start_position: Point::new(1, 5),
+            old_end_position: Point::new(1, 5),
+            new_end_position: Point::new(2, 4),
+        }
+    );
+
+    let mut tree = parser.parse(old_source, None).unwrap();
+    tree.edit(&edit);
+    let tree = parser.parse(new_source, Some(&tree)).unwrap();
+    assert_eq!(
+        tree.root_node().to_sexp(),
+        parser
+            .parse(new_source, None)
+            .unwrap()
+            .root_node()
+            .to_sexp()
+    );
+
+    let edit = InputEdit::from_diff(new_source.as_bytes(), new_source.as_bytes());
+    assert_eq!(edit.start_byte, new_source.len());
+    assert_eq!(edit.old_end_byte, edit.new_end_byte);
+    assert_eq!(edit.start_position, Point::new(5, 0));
+}
+
+#[test]
+fn test_input_edit_from_replacements() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+
+    let source = "let a = 1;\nlet b = 2;\nlet c = 3;\n";
+    let mut tree = parser.parse(source, None).unwrap();
+
+    // The replacements are given out of order.
+    let replacements = [
+        (30..31, "[\n  3\n]"),
+        (4..5, "first"),
+        (11..22, ""),
+        (11..11, "// inserted\n"),
+    ];
+    let edits = InputEdit::from_replacements(source.as_bytes(), &replacements);
+    assert_eq!(edits.len(), 4);
+    assert_eq!(
+        edits[0],
+        InputEdit {
+            start_byte: 30,
+            old_end_byte: 31,
+            new_end_byte: 37,
+            start_position: Point::new(2, 8),
+            old_end_position: Point::new(2, 9),
+            new_end_position: Point::new(4, 1),
+        }
+    );
+    assert_eq!(edits[3].start_position, Point::new(0, 4));
+    assert_eq!(edits[3].new_end_position, Point::new(0, 9));
+
+    let mut new_source = source.as_bytes().to_vec();
+    for edit in &edits {
+        let (range, text) = replacements
+            .iter()
+            .find(|(range, text)| {
+                range.start == edit.start_byte
+                    && range.end == edit.old_end_byte
+                    && range.start + text.len() == edit.new_end_byte
+            })
+            .unwrap();
+        new_source.splice(range.clone(), text.bytes());
+        tree.edit(edit);
+    }
+    assert_eq!(
+        str::from_utf8(&new_source).unwrap(),
+        "let first = 1;\n// inserted\nlet c = [\n  3\n];\n"
+    );
+
+    let tree = parser.parse(&new_source, Some(&tree)).unwrap();
+    assert_eq!(
+        tree.root_node().to_sexp(),
+        parser
+            .parse(&new_source, None)
+            .unwrap()
+            .root_node()
+            .to_sexp()
+    );
+    assert_eq!(
+        Point::from_byte_offset(&new_source, new_source.len()),
+        tree.root_node().end_position()
+    );
+}
+
+#[test]
+fn test_diff_identical_trees() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+    let old_source = "function a(b) { return b + 1; }";
+    let new_source = "\n\nfunction a(b) {\n  return b + 1;\n}\n";
+    let old_tree = parser.parse(old_source, None).unwrap();
+    let new_tree = parser.parse(new_source, None).unwrap();
+
+    let script = diff::diff(
+        old_tree.root_node(),
+        old_source.as_bytes(),
+        new_tree.root_node(),
+        new_source.as_bytes(),
+        None,
+    );
+    assert!(script.is_empty());
+    assert_eq!(
+        script.matches().len(),
+        old_tree.root_node().descendant_count()
+    );
+}
+
+#[test]
+fn test_diff_update_and_insert() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+    let old_source = "let x = foo(1);\nconsole.log(x);\n";
+    let new_source = "let x = bar(1);\nconsole.log(x, 2);\n";
+    let old_tree = parser.parse(old_source, None).unwrap();
+    let new_tree = parser.parse(new_source, None).unwrap();
+
+    let script = diff::diff(
+        old_tree.root_node(),
+        old_source.as_bytes(),
+        new_tree.root_node(),
+        new_source.as_bytes(),
+        None,
+    );
+    let edits = script
+        .edits()
+        .iter()
+        .map(|edit| match *edit {
+            DiffEdit::Insert {
+                node,
+                parent,
+                index,
+            } => format!("insert {} into {} at {index}", node.kind(), parent.kind()),
+            DiffEdit::Delete { node } => format!("delete {}", node.kind()),
+            DiffEdit::Update { old, new } => format!(
+                "update {} to {}",
+                &old_source[old.byte_range()],
+                &new_source[new.byte_range()]
+            ),
+            DiffEdit::Move { new, .. } => format!("move {}", new.kind()),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        edits,
+        [
+            "update foo to bar",
+            "insert , into arguments at 2",
+            "insert number into arguments at 3",
+        ]
+    );
+}
+
+#[test]
+fn test_diff_move_and_delete() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+    let old_source = "function a() { return 1; }\nfunction b() { return 2; }\nc();\n";
+    let new_source = "function b() { return 2; }\nfunction a() { return 1; }\n";
+    let old_tree = parser.parse(old_source, None).unwrap();
+    let new_tree = parser.parse(new_source, None).unwrap();
+
+    let script = diff::diff(
+        old_tree.root_node(),
+        old_source.as_bytes(),
+        new_tree.root_node(),
+        new_source.as_bytes(),
+        None,
+    );
+    let [DiffEdit::Move {
+        old,
+        new,
+        parent,
+        index,
+    }, DiffEdit::Delete { node }] = script.edits()
+    else {
+        panic!("unexpected edits {:?}", script.edits());
+    };
+    assert_eq!(old.start_position().row, 0);
+    assert_eq!(new.start_position().row, 1);
+    assert_eq!(&new_source[new.byte_range()], "function a() { return 1; }");
+    assert_eq!(parent.kind(), "program");
+    assert_eq!(*index, 1);
+    assert_eq!(&old_source[node.byte_range()], "c();");
+}
+
+#[test]
+fn test_diff_repeated_statements() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+    let old_source = "if (a) { x++; return; }\n".repeat(2000);
+    let new_source = "if (b) { x++; return; }\n".repeat(2000);
+    let old_tree = parser.parse(&old_source, None).unwrap();
+    let new_tree = parser.parse(&new_source, None).unwrap();
+
+    // The many identical blocks are matched in order, without comparing each
+    // of them to all of the others.
+    let script = diff::diff(
+        old_tree.root_node(),
+        old_source.as_bytes(),
+        new_tree.root_node(),
+        new_source.as_bytes(),
+        None,
+    );
+    assert_eq!(script.edits().len(), 2000);
+    for (i, edit) in script.edits().iter().enumerate() {
+        let DiffEdit::Update { old, new } = edit else {
+            panic!("unexpected edit {edit:?}");
+        };
+        assert_eq!(old.start_position(), Point::new(i, 4));
+        assert_eq!(&old_source[old.byte_range()], "a");
+        assert_eq!(new.start_position(), Point::new(i, 4));
+        assert_eq!(&new_source[new.byte_range()], "b");
+    }
+    assert!(script
+        .matches()
+        .iter()
+        .all(|(old, new)| old.start_position() == new.start_position()));
+}
+
+#[test]
+fn test_diff_deeply_nested_trees() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("json")).unwrap();
+    let depth = 10_000;
+    let old_source = format!("{}1{}", "[".repeat(depth), "]".repeat(depth));
+    let new_source = format!("{}2{}", "[".repeat(depth), "]".repeat(depth));
+    let old_tree = parser.parse(&old_source, None).unwrap();
+    let new_tree = parser.parse(&new_source, None).unwrap();
+
+    // Every array on the path to the changed number is recovered, without
+    // recursing once per level of nesting.
+    let script = diff::diff(
+        old_tree.root_node(),
+        old_source.as_bytes(),
+        new_tree.root_node(),
+        new_source.as_bytes(),
+        None,
+    );
+    assert_eq!(script.edits().len(), 1);
+    let DiffEdit::Update { old, new } = &script.edits()[0] else {
+        panic!("unexpected edit {:?}", script.edits()[0]);
+    };
+    assert_eq!(&old_source[old.byte_range()], "1");
+    assert_eq!(&new_source[new.byte_range()], "2");
+}
+
+#[test]
+fn test_rewriter() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+    let source = "let x = foo(1, 2);\n// keep this\nconsole.log(x);\n";
+    let mut tree = parser.parse(source, None).unwrap();
+
+    let root = tree.root_node();
+    let declaration = root.child(0).unwrap();
+    let call = root.named_child(2).unwrap();
+    let callee = declaration.named_descendant_for_byte_range(8, 11).unwrap();
+    let arguments = declaration.named_descendant_for_byte_range(11, 17).unwrap();
+    assert_eq!(callee.kind(), "identifier");
+    assert_eq!(arguments.kind(), "arguments");
+
+    let mut rewriter = Rewriter::new(source.as_bytes());
+    rewriter
+        .replace(callee, "bar")
+        .delete(arguments.named_child(1).unwrap())
+        .insert_after(arguments.named_child(0).unwrap(), " + 1")
+        .insert_before(call, "x++;\n")
+        .insert_after(call, " // done");
+    let rewrite = rewriter.rewrite().unwrap();
+    assert_eq!(
+        str::from_utf8(&rewrite.source).unwrap(),
+        "let x = bar(1 + 1, );\n// keep this\nx++;\nconsole.log(x); // done\n"
+    );
+
+    rewrite.edit_tree(&mut tree);
+    let new_tree = parser.parse(&rewrite.source, Some(&tree)).unwrap();
+    let fresh_tree = parser.parse(&rewrite.source, None).unwrap();
+    assert_eq!(
+        new_tree.root_node().to_sexp(),
+        fresh_tree.root_node().to_sexp()
+    );
+}
+
+#[test]
+fn test_rewriter_with_overlapping_changes() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+    let source = "foo(bar);";
+    let tree = parser.parse(source, None).unwrap();
+    let call = tree.root_node().child(0).unwrap().child(0).unwrap();
+    let argument = call.named_descendant_for_byte_range(4, 7).unwrap();
+
+    let mut rewriter = Rewriter::new(source.as_bytes());
+    rewriter.replace(call, "baz()").delete(argument);
+    assert_eq!(
+        rewriter.rewrite(),
+        Err(RewriteError {
+            range: 0..8,
+            other_range: 4..7,
+        })
+    );
+
+    let mut rewriter = Rewriter::new(source.as_bytes());
+    rewriter
+        .replace(argument, "qux")
+        .insert_before(argument, "a, ")
+        .insert_after(argument, ", b");
+    let rewrite = rewriter.rewrite().unwrap();
+    assert_eq!(rewrite.source, b"foo(a, qux, b);");
+    assert_eq!(rewrite.edits.len(), 3);
+}
+
+#[test]
+fn test_tree_diagnostics() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+
+    let tree = parser.parse("let x = 1;\n", None).unwrap();
+    assert!(tree.diagnostics().is_empty());
+
+    let source = "let x = 1 2;\nlet y = [1, 2;\n";
+    let tree = parser.parse(source, None).unwrap();
+    let diagnostics = tree.diagnostics();
+    assert_eq!(diagnostics.len(), 2);
+
+    let unexpected = &diagnostics[0];
+    assert_eq!(unexpected.kind, SyntaxDiagnosticKind::UnexpectedToken);
+    assert!(unexpected.node.is_error());
+    assert_eq!(unexpected.range, unexpected.node.range());
+    assert_eq!(
+        &source[unexpected.offending_range.start_byte..unexpected.offending_range.end_byte],
+        "2"
+    );
+    assert!(unexpected.expected_kinds().contains(&";"));
+    assert!(!unexpected.expected_kinds().contains(&"number"));
+
+    let missing = &diagnostics[1];
+    assert_eq!(missing.kind, SyntaxDiagnosticKind::MissingNode);
+    assert!(missing.node.is_missing());
+    assert_eq!(missing.offending_range, missing.range);
+    assert_eq!(missing.range.start_byte, source.rfind(';').unwrap());
+    assert!(missing.expected_kinds().contains(&"]"));
+
+    let source = "let x = 1; )\n";
+    let tree = parser.parse(source, None).unwrap();
+    let [diagnostic] = tree.diagnostics().try_into().unwrap();
+    assert_eq!(diagnostic.kind, SyntaxDiagnosticKind::UnexpectedToken);
+    assert_eq!(diagnostic.offending_range.start_byte, 11);
+}
+
+#[test]
+fn test_tree_diagnostics_for_unterminated_constructs() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+
+    let source = "function f() { return 1;\n";
+    let tree = parser.parse(source, None).unwrap();
+    let [diagnostic] = tree.diagnostics().try_into().unwrap();
+    assert_eq!(diagnostic.kind, SyntaxDiagnosticKind::Unterminated);
+    assert!(diagnostic.node.is_missing());
+    assert_eq!(
+        &source[diagnostic.offending_range.start_byte..diagnostic.offending_range.end_byte],
+        "{"
+    );
+    assert_eq!(diagnostic.expected_kinds(), ["}"]);
+
+    let source = "let s = \"abc\n";
+    let tree = parser.parse(source, None).unwrap();
+    let [diagnostic] = tree.diagnostics().try_into().unwrap();
+    assert_eq!(diagnostic.kind, SyntaxDiagnosticKind::Unterminated);
+    assert!(diagnostic.node.is_error());
+    assert_eq!(
+        &source[diagnostic.offending_range.start_byte..diagnostic.offending_range.end_byte],
+        "let"
+    );
+    assert!(diagnostic.expected_kinds().contains(&"\""));
+}
+
+#[test]
+fn test_layered_tree() {
+    let html = layered_tree_config("html");
+    let javascript = layered_tree_config("javascript");
+    let resolver = |name: &str| match name {
+        "html" => Some(&html),
+        "javascript" => Some(&javascript),
+        _ => None,
+    };
+
+    let source = "<div><script>let a = html`<b>${x}</b>`;</script></div>";
+    let mut parser = Parser::new();
+    let tree = LayeredTree::parse(&mut parser, source.as_bytes(), &html, None, resolver).unwrap();
+
+    let layers = tree
+        .layers()
+        .iter()
+        .map(|layer| (layer.config().name(), layer.depth(), layer.parent()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        layers,
+        [
+            ("html", 0, None),
+            ("javascript", 1, Some(0)),
+            ("html", 2, Some(1))
+        ]
+    );
+
+    // The two fragments of the template string are parsed as one document.
+    let template_ranges = tree.layers()[2].included_ranges();
+    assert_eq!(
+        template_ranges
+            .iter()
+            .map(|range| &source[range.start_byte..range.end_byte])
+            .collect::<Vec<_>>(),
+        ["<b>", "</b>"]
+    );
+    assert_eq!(
+        tree.layers()[2].tree().root_node().to_sexp(),
+        "(document (element (start_tag (tag_name)) (end_tag (tag_name))))"
+    );
+
+    let (layer, node) = tree.node_at(source.find("let").unwrap());
+    assert_eq!(layer.config().name(), "javascript");
+    assert_eq!(node.kind(), "let");
+
+    let (layer, node) = tree.node_at(source.find('b').unwrap());
+    assert_eq!(layer.config().name(), "html");
+    assert_eq!(layer.depth(), 2);
+    assert_eq!(node.kind(), "tag_name");
+
+    let (layer, node) = tree.node_at(1);
+    assert_eq!(layer.depth(), 0);
+    assert_eq!(node.kind(), "tag_name");
+
+    // Positions within the injected text that are excluded from a layer
+    // belong to the layer that contains them.
+    let (layer, node) = tree.node_at(source.find('x').unwrap());
+    assert_eq!(layer.config().name(), "javascript");
+    assert_eq!(node.kind(), "identifier");
+}
+
+#[test]
+fn test_layered_tree_reparse_after_edit() {
+    let html = layered_tree_config("html");
+    let javascript = layered_tree_config("javascript");
+    let resolver = |name: &str| match name {
+        "html" => Some(&html),
+        "javascript" => Some(&javascript),
+        _ => None,
+    };
+
+    let mut source = b"<p>one</p><script>let a = 1;</script><script>let b = 2;</script>".to_vec();
+    let mut parser = Parser::new();
+    let mut tree = LayeredTree::parse(&mut parser, &source, &html, None, resolver).unwrap();
+    assert_eq!(tree.layers().len(), 3);
+
+    let position = source.iter().position(|c| *c == b'1').unwrap();
+    let input_edit = InputEdit::from_replacement(&source, position..position + 1, b"[1]");
+    source.splice(position..=position, b"[1]".iter().copied());
+    tree.edit(&input_edit);
+
+    let new_tree = LayeredTree::parse(&mut parser, &source, &html, Some(&tree), resolver).unwrap();
+    assert_eq!(new_tree.layers().len(), 3);
+    assert_eq!(
+        parser.included_ranges(),
+        tree.root_layer().included_ranges()
+    );
+
+    // Only the edited script's tree has changed, and it was reparsed
+    // incrementally, as the changed ranges are limited to the edit.
+    let changed_ranges = tree.layers()[1]
+        .tree()
+        .changed_ranges(new_tree.layers()[1].tree())
+        .collect::<Vec<_>>();
+    assert_eq!(changed_ranges.len(), 1);
+    assert_eq!(changed_ranges[0].start_byte, position);
+    assert!(tree.layers()[2]
+        .tree()
+        .changed_ranges(new_tree.layers()[2].tree())
+        .next()
+        .is_none());
+
+    let (layer, node) = new_tree.node_at(position + 1);
+    assert_eq!(layer.config().name(), "javascript");
+    assert_eq!(node.kind(), "number");
+    assert_eq!(node.parent().unwrap().kind(), "array");
+}
+
 #[test]
 fn test_tree_cursor_on_aliased_root_with_extra_child() {
     let source = r"
@@ -768,6 +1468,391 @@ fn main() {
     assert_eq!(cursor.node().kind(), "block_comment");
 }
 
+#[test]
+fn test_tree_snapshot_round_trip() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("python")).unwrap();
+
+    let mut source_code = br#"
+def foo(a, b):
+    if a:
+        return b + "a string"
+    return [x for x in a]
+"#
+    .to_vec();
+
+    let tree = parser.parse(&source_code, None).unwrap();
+    let bytes = tree.serialize();
+    let mut restored = Tree::deserialize(&get_language("python"), &bytes).unwrap();
+
+    assert_eq!(restored.root_node().to_sexp(), tree.root_node().to_sexp());
+    assert_eq!(restored.root_node().range(), tree.root_node().range());
+    assert_eq!(restored.serialize(), bytes);
+    assert_eq!(tree.changed_ranges(&restored).count(), 0);
+
+    // The restored tree can be used for incremental parsing.
+    let edit = Edit {
+        position: index_of(&source_code, "b + "),
+        deleted_length: 1,
+        inserted_text: b"(a or b)".to_vec(),
+    };
+    perform_edit(&mut restored, &mut source_code, &edit).unwrap();
+    let new_tree = parser.parse(&source_code, Some(&restored)).unwrap();
+    let fresh_tree = parser.parse(&source_code, None).unwrap();
+    assert_eq!(
+        new_tree.root_node().to_sexp(),
+        fresh_tree.root_node().to_sexp()
+    );
+    assert!(!new_tree.root_node().has_error());
+}
+
+#[test]
+fn test_tree_snapshot_with_errors_and_included_ranges() {
+    let source_code = "<div><% if (x ++ { %>hello</div>";
+    let js_ranges = [Range {
+        start_byte: 7,
+        end_byte: 19,
+        start_point: Point::new(0, 7),
+        end_point: Point::new(0, 19),
+    }];
+
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+    parser.set_included_ranges(&js_ranges).unwrap();
+    let tree = parser.parse(source_code, None).unwrap();
+    assert!(tree.root_node().has_error());
+
+    let restored = Tree::deserialize(&get_language("javascript"), &tree.serialize()).unwrap();
+    assert_eq!(restored.included_ranges(), js_ranges);
+    assert_eq!(restored.root_node().to_sexp(), tree.root_node().to_sexp());
+    assert!(restored.root_node().has_error());
+}
+
+#[test]
+fn test_tree_snapshot_rejects_invalid_input() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+    let tree = parser.parse("let x = [1, 2, 3];", None).unwrap();
+    let bytes = tree.serialize();
+
+    assert_eq!(
+        Tree::deserialize(&get_language("rust"), &bytes).err(),
+        Some(SnapshotError::Language)
+    );
+    assert_eq!(
+        Tree::deserialize(&get_language("javascript"), &bytes[..bytes.len() - 1]).err(),
+        Some(SnapshotError::Format)
+    );
+    assert_eq!(
+        Tree::deserialize(&get_language("javascript"), b"not a snapshot").err(),
+        Some(SnapshotError::Format)
+    );
+
+    let mut wrong_version = bytes.clone();
+    wrong_version[4] = 0xff;
+    assert_eq!(
+        Tree::deserialize(&get_language("javascript"), &wrong_version).err(),
+        Some(SnapshotError::Version)
+    );
+
+    // Snapshots from before the start state was recorded have version 1.
+    let mut version_1 = bytes.clone();
+    version_1[4..8].copy_from_slice(&1u32.to_le_bytes());
+    assert_eq!(
+        Tree::deserialize(&get_language("javascript"), &version_1).err(),
+        Some(SnapshotError::Version)
+    );
+
+    let mut trailing_data = bytes.clone();
+    trailing_data.push(0);
+    assert_eq!(
+        Tree::deserialize(&get_language("javascript"), &trailing_data).err(),
+        Some(SnapshotError::Format)
+    );
+
+    // Corrupting the tree data must be detected or produce a usable tree.
+    for i in 0..bytes.len() {
+        let mut corrupted = bytes.clone();
+        corrupted[i] ^= 0xff;
+        if let Ok(tree) = Tree::deserialize(&get_language("javascript"), &corrupted) {
+            assert!(!tree.root_node().to_sexp().is_empty());
+        }
+    }
+}
+
+#[test]
+fn test_node_handle_resolve() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+    let tree = parser.parse("a(b, c); d(e);", None).unwrap();
+
+    let mut handles = Vec::new();
+    let mut cursor = tree.walk();
+    loop {
+        handles.push((cursor.node().id(), NodeHandle::new(cursor.node())));
+        if cursor.goto_first_child() {
+            continue;
+        }
+        while !cursor.goto_next_sibling() {
+            if !cursor.goto_parent() {
+                break;
+            }
+        }
+        if cursor.depth() == 0 {
+            break;
+        }
+    }
+
+    for (id, handle) in handles {
+        assert!(handle.belongs_to(&tree));
+        assert_eq!(handle.resolve(&tree).unwrap().id(), id);
+    }
+
+    // Handles can be moved to other threads and resolved against a copy of
+    // the tree.
+    let node = tree.root_node().descendant_for_byte_range(11, 12).unwrap();
+    let handle = NodeHandle::from(node);
+    let tree_copy = tree.clone();
+    assert!(!handle.belongs_to(&tree_copy));
+    let kind = std::thread::spawn(move || handle.resolve(&tree_copy).unwrap().kind().to_string())
+        .join()
+        .unwrap();
+    assert_eq!(kind, "identifier");
+}
+
+#[test]
+fn test_node_handle_edit() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+
+    let mut source_code = b"a(b, c);\nd(e);\nf(g);".to_vec();
+    let mut tree = parser.parse(&source_code, None).unwrap();
+    let root = tree.root_node();
+    let before = NodeHandle::new(root.child(0).unwrap());
+    let edited = NodeHandle::new(root.child(1).unwrap());
+    let after = NodeHandle::new(root.named_descendant_for_byte_range(17, 18).unwrap());
+    let mut handles = [before, edited, after];
+
+    let edit = Edit {
+        position: index_of(&source_code, "e"),
+        deleted_length: 1,
+        inserted_text: b"x,\ny".to_vec(),
+    };
+    let input_edit = perform_edit(&mut tree, &mut source_code, &edit).unwrap();
+    for handle in &mut handles {
+        handle.edit(&input_edit);
+    }
+    let [before, edited, after] = handles;
+
+    assert!(!before.is_invalidated());
+    assert!(edited.is_invalidated());
+    assert!(!after.is_invalidated());
+    assert_eq!(after.range().start_point, Point::new(3, 2));
+
+    // The handles resolve against the edited tree.
+    assert_eq!(
+        before.resolve(&tree).unwrap().kind(),
+        "expression_statement"
+    );
+    assert_eq!(edited.resolve(&tree), None);
+    let node = after.resolve(&tree).unwrap();
+    assert_eq!(node.utf8_text(&source_code).unwrap(), "g");
+
+    // They also resolve against the new tree that results from reparsing.
+    let new_tree = parser.parse(&source_code, Some(&tree)).unwrap();
+    assert!(!after.belongs_to(&new_tree));
+    let node = after.resolve(&new_tree).unwrap();
+    assert_eq!(node.kind(), "identifier");
+    assert_eq!(node.utf8_text(&source_code).unwrap(), "g");
+    assert_eq!(before.resolve(&new_tree).unwrap().byte_range(), 0..8);
+    assert_eq!(edited.resolve(&new_tree), None);
+}
+
+#[test]
+fn test_node_handle_without_edit() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+
+    let mut source_code = b"a; b;".to_vec();
+    let mut tree = parser.parse(&source_code, None).unwrap();
+    let handle = NodeHandle::new(tree.root_node().child(1).unwrap());
+
+    // A handle that was not updated along with its tree no longer resolves.
+    let edit = Edit {
+        position: 0,
+        deleted_length: 0,
+        inserted_text: b"c; ".to_vec(),
+    };
+    perform_edit(&mut tree, &mut source_code, &edit).unwrap();
+    assert_eq!(handle.resolve(&tree), None);
+}
+
+#[test]
+fn test_node_handle_after_tree_is_dropped() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+
+    // A new tree may be allocated at the address of a dropped one, but it
+    // still is not treated as the tree that the handle belongs to.
+    let tree = parser.parse("a; b;", None).unwrap();
+    let handle = NodeHandle::new(tree.root_node().child(1).unwrap());
+    drop(tree);
+    for _ in 0..8 {
+        let tree = parser.parse("a; b;", None).unwrap();
+        assert!(!handle.belongs_to(&tree));
+    }
+}
+
+#[test]
+fn test_tree_stats() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+
+    let mut source_code =
+        b"function a() { return [1, 2, 3]; }\nfunction b() { c(d, e); }\n".to_vec();
+    let mut tree = parser.parse(&source_code, None).unwrap();
+    let stats = tree.stats(None);
+    assert_eq!(stats.node_count, tree.root_node().descendant_count());
+    assert_eq!(stats.error_count, 0);
+    assert_eq!(stats.max_depth, 6);
+    assert_eq!(stats.memory_usage, tree.memory_usage());
+    assert!(stats.memory_usage > 0);
+    assert!(stats.bytes_per_node() > 0.0);
+    assert_eq!(stats.shared_node_count, 0);
+    assert_eq!(stats.shared_memory_usage, 0);
+
+    // A tree shares all of its memory with a copy of itself.
+    let copy = tree.clone();
+    let stats = tree.stats(Some(&copy));
+    assert_eq!(stats.shared_node_count, stats.node_count);
+    assert!(stats.shared_memory_usage > 0);
+    assert!(stats.shared_memory_usage < stats.memory_usage);
+
+    // After an incremental parse, the unchanged function is shared with the
+    // old tree.
+    let edit = Edit {
+        position: index_of(&source_code, "c(d"),
+        deleted_length: 1,
+        inserted_text: b"fn".to_vec(),
+    };
+    perform_edit(&mut tree, &mut source_code, &edit).unwrap();
+    let new_tree = parser.parse(&source_code, Some(&tree)).unwrap();
+    let stats = new_tree.stats(Some(&tree));
+    let first_function = new_tree.root_node().child(0).unwrap();
+    assert!(stats.shared_node_count >= first_function.descendant_count());
+    assert!(stats.shared_node_count < stats.node_count);
+    assert!(stats.shared_memory_usage > 0);
+    assert!(stats.shared_memory_usage < stats.memory_usage);
+    assert_eq!(stats.error_count, 0);
+}
+
+#[test]
+fn test_tree_expected_symbols_at() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+    let expected_kinds_at = |tree: &Tree, offset: usize| {
+        let language = tree.language();
+        tree.expected_symbols_at(offset)
+            .into_iter()
+            .map(|symbol| language.node_kind_for_id(symbol).unwrap())
+            .collect::<Vec<_>>()
+    };
+
+    let mut source_code = b"let xyz = a.b; /* c */ f(1)".to_vec();
+    let mut tree = parser.parse(&source_code, None).unwrap();
+
+    // After the member access operator, only a property name can follow,
+    // even though the `.` token was lexed before `a` was reduced to an
+    // expression.
+    let after_dot = expected_kinds_at(&tree, index_of(&source_code, "b;"));
+    assert!(after_dot.contains(&"identifier"));
+    assert!(after_dot.contains(&"private_property_identifier"));
+    assert!(!after_dot.contains(&"("));
+    assert!(!after_dot.contains(&"expression"));
+
+    let after_equals = expected_kinds_at(&tree, index_of(&source_code, "a."));
+    assert!(after_equals.contains(&"expression"));
+    assert!(after_equals.contains(&"number"));
+    assert!(!after_equals.contains(&";"));
+
+    // The start of the file and the position after a statement expect
+    // statements, and extras are skipped.
+    let at_start = expected_kinds_at(&tree, 0);
+    assert!(at_start.contains(&"statement"));
+    assert!(at_start.contains(&"lexical_declaration"));
+    assert_eq!(
+        expected_kinds_at(&tree, index_of(&source_code, " /*")),
+        expected_kinds_at(&tree, index_of(&source_code, "f(")),
+    );
+
+    // Inside of a token, the symbols that could replace it are expected.
+    assert_eq!(
+        expected_kinds_at(&tree, index_of(&source_code, "yz")),
+        expected_kinds_at(&tree, index_of(&source_code, "xyz")),
+    );
+
+    // Tokens that were changed by an edit do not advance the state.
+    let edit = Edit {
+        position: index_of(&source_code, "b;"),
+        deleted_length: 1,
+        inserted_text: b"bcd".to_vec(),
+    };
+    perform_edit(&mut tree, &mut source_code, &edit).unwrap();
+    assert_eq!(
+        expected_kinds_at(&tree, index_of(&source_code, ";")),
+        after_dot
+    );
+    assert_eq!(
+        expected_kinds_at(&tree, index_of(&source_code, "a.")),
+        after_equals
+    );
+
+    // Tokens inside of an error do not advance the state.
+    let source_code = b"let xyz = ) a";
+    let tree = parser.parse(source_code, None).unwrap();
+    assert_eq!(
+        tree.root_node().to_sexp(),
+        "(program (lexical_declaration (variable_declarator name: (identifier) (ERROR) value: (identifier))))"
+    );
+    assert_eq!(
+        expected_kinds_at(&tree, index_of(source_code, " a")),
+        after_equals
+    );
+}
+
+#[test]
+fn test_parse_expected_at_message() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+    let source_code = b"x;\na.b";
+    let tree = parser.parse(source_code, None).unwrap();
+
+    // Positions are one-based, like the positions of diagnostics.
+    let message = |position: &str| {
+        let position = parse_position(position).unwrap();
+        expected_at_message(&tree, source_code, "test.js", position)
+    };
+    let after_a = message("2:2");
+    assert!(after_a.starts_with("test.js:2:2: expected "));
+    assert!(after_a.contains(r#""(""#));
+    assert!(!after_a.contains("private_property_identifier"));
+    let after_dot = message("2:3");
+    assert!(after_dot.contains("private_property_identifier"));
+    assert!(!after_dot.contains(r#""(""#));
+
+    // The first row is not offset by a byte.
+    let at_start = message("1:1");
+    assert!(at_start.starts_with("test.js:1:1: expected "));
+    assert!(at_start.contains("lexical_declaration"));
+    let after_x = message("1:2");
+    assert!(after_x.contains(r#"";""#));
+    assert!(!after_x.contains("lexical_declaration"));
+
+    assert!(parse_position("0:1").is_err());
+    assert!(parse_position("1:0").is_err());
+    assert!(parse_position("1").is_err());
+}
+
 fn index_of(text: &[u8], substring: &str) -> usize {
     str::from_utf8(text).unwrap().find(substring).unwrap()
 }
@@ -795,3 +1880,15 @@ fn get_changed_ranges(
     *tree = new_tree;
     result
 }
+
+fn layered_tree_config(language_name: &str) -> InjectionConfig {
+    let injections_query =
+        fs::read_to_string(get_language_queries_path(language_name).join("injections.scm"))
+            .unwrap();
+    InjectionConfig::new(
+        get_language(language_name),
+        language_name,
+        &injections_query,
+    )
+    .unwrap()
+}
@@ -1,5 +1,7 @@
 #![doc = include_str!("../README.md")]
 
+pub mod diff;
+pub mod duplicates;
 pub mod fuzz;
 pub mod highlight;
 pub mod init;
@@ -9,6 +11,7 @@ pub mod parse;
 pub mod playground;
 pub mod query;
 pub mod query_testing;
+pub mod select;
 pub mod tags;
 pub mod test;
 pub mod test_highlight;
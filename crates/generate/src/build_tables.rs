@@ -17,6 +17,7 @@ use self::{
     build_lex_table::build_lex_table,
     build_parse_table::{build_parse_table, ParseStateInfo},
     coincident_tokens::CoincidentTokenIndex,
+    item::start_production,
     item_set_builder::ParseItemSetBuilder,
     minimize_parse_table::minimize_parse_table,
     token_conflicts::TokenConflictMap,
@@ -44,6 +45,11 @@ pub fn build_tables(
     inlines: &InlinedProductionMap,
     report_symbol_name: Option<&str>,
 ) -> BuildTableResult<Tables> {
+    let start_productions = syntax_grammar
+        .start_symbols
+        .iter()
+        .map(|symbol| start_production(*symbol))
+        .collect::<Vec<_>>();
     let item_set_builder = ParseItemSetBuilder::new(syntax_grammar, lexical_grammar, inlines);
     let following_tokens =
         get_following_tokens(syntax_grammar, lexical_grammar, inlines, &item_set_builder);
@@ -52,6 +58,7 @@ pub fn build_tables(
         lexical_grammar,
         item_set_builder,
         variable_info,
+        &start_productions,
     )?;
     let token_conflict_map = TokenConflictMap::new(lexical_grammar, following_tokens);
     let coincident_token_index = CoincidentTokenIndex::new(&parse_table, lexical_grammar);
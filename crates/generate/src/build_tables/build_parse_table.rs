@@ -14,7 +14,9 @@ use super::{
     item_set_builder::ParseItemSetBuilder,
 };
 use crate::{
-    grammars::{LexicalGrammar, PrecedenceEntry, ReservedWordSetId, SyntaxGrammar, VariableType},
+    grammars::{
+        LexicalGrammar, PrecedenceEntry, Production, ReservedWordSetId, SyntaxGrammar, VariableType,
+    },
     node_types::VariableInfo,
     rules::{Associativity, Precedence, Symbol, SymbolType, TokenSet},
     tables::{
@@ -55,6 +57,7 @@ struct ParseTableBuilder<'a> {
     syntax_grammar: &'a SyntaxGrammar,
     lexical_grammar: &'a LexicalGrammar,
     variable_info: &'a [VariableInfo],
+    start_productions: &'a [Production],
     core_ids_by_core: HashMap<ParseItemSetCore<'a>, usize>,
     state_ids_by_item_set: IndexMap<ParseItemSet<'a>, ParseStateId, BuildHasherDefault<FxHasher>>,
     parse_state_info_by_id: Vec<ParseStateInfo<'a>>,
@@ -244,12 +247,14 @@ impl<'a> ParseTableBuilder<'a> {
         lexical_grammar: &'a LexicalGrammar,
         item_set_builder: ParseItemSetBuilder<'a>,
         variable_info: &'a [VariableInfo],
+        start_productions: &'a [Production],
     ) -> Self {
         Self {
             syntax_grammar,
             lexical_grammar,
             item_set_builder,
             variable_info,
+            start_productions,
             non_terminal_extra_states: Vec::new(),
             state_ids_by_item_set: IndexMap::default(),
             core_ids_by_core: HashMap::new(),
@@ -262,6 +267,7 @@ impl<'a> ParseTableBuilder<'a> {
                 external_lex_states: Vec::new(),
                 production_infos: Vec::new(),
                 max_aliased_production_length: 1,
+                start_states: Vec::new(),
             },
         }
     }
@@ -287,6 +293,25 @@ impl<'a> ParseTableBuilder<'a> {
                 }],
             },
         );
+        self.parse_table.start_states.push(1);
+
+        // Add a starting state for each of the grammar's alternate start rules.
+        for production in self.start_productions {
+            self.parse_table
+                .start_states
+                .push(self.parse_table.states.len());
+            self.add_parse_state(
+                &Vec::new(),
+                &Vec::new(),
+                ParseItemSet {
+                    entries: vec![ParseItemSetEntry {
+                        item: ParseItem::start_with(production),
+                        lookaheads: std::iter::once(Symbol::end()).collect(),
+                        following_reserved_word_set: ReservedWordSetId::default(),
+                    }],
+                },
+            );
+        }
 
         // Compute the possible item sets for non-terminal extras.
         let mut non_terminal_extra_item_sets_by_first_terminal = BTreeMap::new();
@@ -1131,12 +1156,14 @@ pub fn build_parse_table<'a>(
     lexical_grammar: &'a LexicalGrammar,
     item_set_builder: ParseItemSetBuilder<'a>,
     variable_info: &'a [VariableInfo],
+    start_productions: &'a [Production],
 ) -> BuildTableResult<(ParseTable, Vec<ParseStateInfo<'a>>)> {
     ParseTableBuilder::new(
         syntax_grammar,
         lexical_grammar,
         item_set_builder,
         variable_info,
+        start_productions,
     )
     .build()
 }
@@ -10,23 +10,27 @@ use crate::{
         LexicalGrammar, Production, ProductionStep, ReservedWordSetId, SyntaxGrammar,
         NO_RESERVED_WORDS,
     },
-    rules::{Associativity, Precedence, Symbol, SymbolType, TokenSet},
+    rules::{Associativity, Precedence, Symbol, TokenSet},
 };
 
-static START_PRODUCTION: LazyLock<Production> = LazyLock::new(|| Production {
-    dynamic_precedence: 0,
-    steps: vec![ProductionStep {
-        symbol: Symbol {
-            index: 0,
-            kind: SymbolType::NonTerminal,
-        },
-        precedence: Precedence::None,
-        associativity: None,
-        alias: None,
-        field_name: None,
-        reserved_word_set_id: NO_RESERVED_WORDS,
-    }],
-});
+static START_PRODUCTION: LazyLock<Production> =
+    LazyLock::new(|| start_production(Symbol::non_terminal(0)));
+
+/// Create the production of an augmented start rule, which matches a single
+/// occurrence of the given rule.
+pub fn start_production(symbol: Symbol) -> Production {
+    Production {
+        dynamic_precedence: 0,
+        steps: vec![ProductionStep {
+            symbol,
+            precedence: Precedence::None,
+            associativity: None,
+            alias: None,
+            field_name: None,
+            reserved_word_set_id: NO_RESERVED_WORDS,
+        }],
+    }
+}
 
 /// A [`ParseItem`] represents an in-progress match of a single production in a grammar.
 #[derive(Clone, Copy, Debug)]
@@ -96,9 +100,15 @@ pub struct ParseItemSetDisplay<'a>(
 
 impl<'a> ParseItem<'a> {
     pub fn start() -> Self {
+        Self::start_with(&START_PRODUCTION)
+    }
+
+    /// Create an item for an augmented start rule, whose production was
+    /// created by [`start_production`].
+    pub const fn start_with(production: &'a Production) -> Self {
         ParseItem {
             variable_index: u32::MAX,
-            production: &START_PRODUCTION,
+            production,
             step_index: 0,
             has_preceding_inherited_fields: false,
         }
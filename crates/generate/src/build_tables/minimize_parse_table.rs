@@ -194,6 +194,13 @@ impl Minimizer<'_> {
             new_states.push(parse_state);
         }
 
+        for start_state in &mut self.parse_table.start_states {
+            *start_state = state_ids_by_group_id
+                .iter()
+                .position(|g| g.contains(start_state))
+                .unwrap();
+        }
+
         self.parse_table.states = new_states;
     }
 
@@ -427,7 +434,9 @@ impl Minimizer<'_> {
         let mut state_usage_map = vec![false; self.parse_table.states.len()];
 
         state_usage_map[0] = true;
-        state_usage_map[1] = true;
+        for start_state in &self.parse_table.start_states {
+            state_usage_map[*start_state] = true;
+        }
 
         for state in &self.parse_table.states {
             for referenced_state in state.referenced_states() {
@@ -455,6 +464,9 @@ impl Minimizer<'_> {
             }
             original_state_id += 1;
         }
+        for start_state in &mut self.parse_table.start_states {
+            *start_state = state_replacement_map[*start_state];
+        }
     }
 
     fn reorder_states_by_descending_size(&mut self) {
@@ -488,5 +500,8 @@ impl Minimizer<'_> {
                 state
             })
             .collect();
+        for start_state in &mut self.parse_table.start_states {
+            *start_state = new_ids_by_old_id[*start_state];
+        }
     }
 }
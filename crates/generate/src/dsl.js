@@ -256,6 +256,7 @@ function grammar(baseGrammar, options) {
       externals: [],
       inline: [],
       supertypes: [],
+      start_rules: [],
       precedences: [],
       reserved: {},
     };
@@ -450,6 +451,27 @@ function grammar(baseGrammar, options) {
     });
   }
 
+  let start_rules = baseGrammar.start_rules || [];
+  if (options.start_rules) {
+    if (typeof options.start_rules !== "function") {
+      throw new Error("Grammar's 'start_rules' property must be a function.");
+    }
+
+    const baseStartRules = start_rules.map(sym);
+    const startRules = options.start_rules.call(ruleBuilder, ruleBuilder, baseStartRules);
+
+    if (!Array.isArray(startRules)) {
+      throw new Error("Grammar's start_rules must be an array of rules.");
+    }
+
+    start_rules = startRules.map(symbol => {
+      if (symbol.name === 'ReferenceError') {
+        throw new Error(`Start rule \`${symbol.symbol.name}\` is not defined.`);
+      }
+      return symbol.name;
+    });
+  }
+
   let precedences = baseGrammar.precedences;
   if (options.precedences) {
     if (typeof options.precedences !== "function") {
@@ -483,6 +505,7 @@ function grammar(baseGrammar, options) {
       externals,
       inline,
       supertypes,
+      ...(start_rules.length > 0 ? { start_rules } : {}),
       reserved,
     },
   };
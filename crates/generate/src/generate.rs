@@ -21,6 +21,7 @@ pub mod parse_grammar;
 mod prepare_grammar;
 mod render;
 mod rules;
+mod rust_types;
 mod tables;
 
 use build_tables::build_tables;
@@ -31,8 +32,9 @@ use parse_grammar::parse_grammar;
 pub use parse_grammar::ParseGrammarError;
 use prepare_grammar::prepare_grammar;
 pub use prepare_grammar::PrepareGrammarError;
-use render::render_c_code;
+use render::{render_c_code, ABI_VERSION_WITH_START_STATES};
 pub use render::{ABI_VERSION_MAX, ABI_VERSION_MIN};
+pub use rust_types::{generate_rust_types, RustTypesError};
 
 static JSON_COMMENT_REGEX: LazyLock<Regex> = LazyLock::new(|| {
     RegexBuilder::new("^\\s*//.*")
@@ -71,6 +73,8 @@ pub enum GenerateError {
     ParseVersion(#[from] ParseVersionError),
     #[error(transparent)]
     SuperTypeCycle(#[from] SuperTypeCycleError),
+    #[error(transparent)]
+    RustTypes(#[from] RustTypesError),
 }
 
 impl From<std::io::Error> for GenerateError {
@@ -152,6 +156,7 @@ pub fn generate_parser_in_directory<T, U, V>(
     mut abi_version: usize,
     report_symbol_name: Option<&str>,
     js_runtime: Option<&str>,
+    rust_types: bool,
 ) -> GenerateResult<()>
 where
     T: Into<PathBuf>,
@@ -207,6 +212,10 @@ where
         abi_version = ABI_VERSION_MIN;
     }
 
+    if !input_grammar.start_symbols.is_empty() && abi_version < ABI_VERSION_WITH_START_STATES {
+        println!("Warning: The grammar's `start_rules` are only recorded in parsers that are generated with ABI {ABI_VERSION_WITH_START_STATES}. Use `--abi {ABI_VERSION_WITH_START_STATES}` to parse from them.");
+    }
+
     // Generate the parser and related files.
     let GeneratedParser {
         c_code,
@@ -218,6 +227,12 @@ where
         report_symbol_name,
     )?;
 
+    if rust_types {
+        write_file(
+            &src_path.join("node_types.rs"),
+            generate_rust_types(&node_types_json)?,
+        )?;
+    }
     write_file(&src_path.join("parser.c"), c_code)?;
     write_file(&src_path.join("node-types.json"), node_types_json)?;
     write_file(&header_path.join("alloc.h"), ALLOC_HEADER)?;
@@ -38,6 +38,9 @@ pub struct InputGrammar {
     pub external_tokens: Vec<Rule>,
     pub variables_to_inline: Vec<String>,
     pub supertype_symbols: Vec<String>,
+    /// The rules, other than the first one, that can be used as the root of
+    /// a syntax tree.
+    pub start_symbols: Vec<String>,
     pub word_token: Option<String>,
     pub reserved_words: Vec<ReservedWordContext<Rule>>,
 }
@@ -120,6 +123,7 @@ pub struct SyntaxGrammar {
     pub expected_conflicts: Vec<Vec<Symbol>>,
     pub external_tokens: Vec<ExternalToken>,
     pub supertype_symbols: Vec<Symbol>,
+    pub start_symbols: Vec<Symbol>,
     pub variables_to_inline: Vec<Symbol>,
     pub word_token: Option<Symbol>,
     pub precedence_orderings: Vec<Vec<PrecedenceEntry>>,
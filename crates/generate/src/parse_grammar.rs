@@ -100,6 +100,8 @@ pub struct GrammarJSON {
     #[serde(default)]
     supertypes: Vec<String>,
     #[serde(default)]
+    start_rules: Vec<String>,
+    #[serde(default)]
     word: Option<String>,
     #[serde(default)]
     reserved: Map<String, Value>,
@@ -153,11 +155,12 @@ fn variable_is_used(
     grammar_rules: &[(String, Rule)],
     extras: &[Rule],
     externals: &[Rule],
+    start_rules: &[String],
     target_name: &str,
     in_progress: &mut HashSet<String>,
 ) -> bool {
     let root = &grammar_rules.first().unwrap().0;
-    if target_name == root {
+    if target_name == root || start_rules.iter().any(|rule| rule == target_name) {
         return true;
     }
 
@@ -183,7 +186,14 @@ fn variable_is_used(
             if !rule_is_referenced(rule, target_name, false) || in_progress.contains(name) {
                 return false;
             }
-            variable_is_used(grammar_rules, extras, externals, name, in_progress)
+            variable_is_used(
+                grammar_rules,
+                extras,
+                externals,
+                start_rules,
+                name,
+                in_progress,
+            )
         });
     in_progress.remove(target_name);
 
@@ -243,6 +253,7 @@ pub(crate) fn parse_grammar(input: &str) -> ParseGrammarResult<InputGrammar> {
                 &rules,
                 &extra_symbols,
                 &external_tokens,
+                &grammar_json.start_rules,
                 name,
                 &mut in_progress,
             )
@@ -293,6 +304,7 @@ pub(crate) fn parse_grammar(input: &str) -> ParseGrammarResult<InputGrammar> {
         word_token: grammar_json.word,
         expected_conflicts: grammar_json.conflicts,
         supertype_symbols: grammar_json.supertypes,
+        start_symbols: grammar_json.start_rules,
         variables_to_inline: grammar_json.inline,
         precedence_orderings,
         variables,
@@ -44,6 +44,7 @@ pub struct IntermediateGrammar<T, U> {
     external_tokens: Vec<U>,
     variables_to_inline: Vec<Symbol>,
     supertype_symbols: Vec<Symbol>,
+    start_symbols: Vec<Symbol>,
     word_token: Option<Symbol>,
     reserved_word_sets: Vec<ReservedWordContext<T>>,
 }
@@ -68,6 +69,7 @@ impl<T, U> Default for IntermediateGrammar<T, U> {
             external_tokens: Vec::default(),
             variables_to_inline: Vec::default(),
             supertype_symbols: Vec::default(),
+            start_symbols: Vec::default(),
             word_token: Option::default(),
             reserved_word_sets: Vec::default(),
         }
@@ -30,6 +30,8 @@ unless they are used only as the grammar's start rule.
     WordToken(NonTerminalWordTokenError),
     #[error("Reserved word '{0}' must be a token")]
     NonTokenReservedWord(String),
+    #[error("Start rule '{0}' cannot be a token")]
+    TokenStartRule(String),
 }
 
 #[derive(Debug, Error, Serialize)]
@@ -135,6 +137,17 @@ pub(super) fn extract_tokens(
         .map(|symbol| symbol_replacer.replace_symbol(symbol))
         .collect();
 
+    let mut start_symbols = Vec::with_capacity(grammar.start_symbols.len());
+    for symbol in grammar.start_symbols {
+        let symbol = symbol_replacer.replace_symbol(symbol);
+        if symbol.is_terminal() {
+            Err(ExtractTokensError::TokenStartRule(
+                lexical_variables[symbol.index].name.clone(),
+            ))?;
+        }
+        start_symbols.push(symbol);
+    }
+
     let variables_to_inline = grammar
         .variables_to_inline
         .into_iter()
@@ -233,6 +246,7 @@ pub(super) fn extract_tokens(
             extra_symbols,
             variables_to_inline,
             supertype_symbols,
+            start_symbols,
             external_tokens,
             word_token,
             precedence_orderings: grammar.precedence_orderings,
@@ -295,6 +295,7 @@ pub(super) fn flatten_grammar(
         precedence_orderings: grammar.precedence_orderings,
         external_tokens: grammar.external_tokens,
         supertype_symbols: grammar.supertype_symbols,
+        start_symbols: grammar.start_symbols,
         word_token: grammar.word_token,
         reserved_word_sets,
         variables,
@@ -521,6 +522,7 @@ mod tests {
             external_tokens: Vec::new(),
             supertype_symbols: Vec::new(),
             word_token: None,
+            start_symbols: Vec::new(),
             reserved_word_sets: Vec::new(),
             variables: vec![Variable {
                 name: "test".to_string(),
@@ -18,6 +18,12 @@ pub enum InternSymbolsError {
     Undefined(String),
     #[error("Undefined symbol `{0}` in grammar's supertypes array")]
     UndefinedSupertype(String),
+    #[error("Undefined symbol `{0}` in grammar's start rules array")]
+    UndefinedStartRule(String),
+    #[error("The start rule `{0}` must be visible.")]
+    HiddenAlternateStartRule(String),
+    #[error("The start rule `{0}` cannot be inlined.")]
+    InlinedStartRule(String),
     #[error("Undefined symbol `{0}` in grammar's conflicts array")]
     UndefinedConflict(String),
     #[error("Undefined symbol `{0}` as grammar's word token")]
@@ -63,6 +69,27 @@ pub(super) fn intern_symbols(grammar: &InputGrammar) -> InternSymbolsResult<Inte
         })?);
     }
 
+    let mut start_symbols = Vec::with_capacity(grammar.start_symbols.len());
+    for start_symbol_name in &grammar.start_symbols {
+        let symbol = interner
+            .intern_name(start_symbol_name)
+            .filter(Symbol::is_non_terminal)
+            .ok_or_else(|| InternSymbolsError::UndefinedStartRule(start_symbol_name.clone()))?;
+        if variable_type_for_name(start_symbol_name) == VariableType::Hidden {
+            Err(InternSymbolsError::HiddenAlternateStartRule(
+                start_symbol_name.clone(),
+            ))?;
+        }
+        if grammar.variables_to_inline.contains(start_symbol_name) {
+            Err(InternSymbolsError::InlinedStartRule(
+                start_symbol_name.clone(),
+            ))?;
+        }
+        if symbol.index != 0 && !start_symbols.contains(&symbol) {
+            start_symbols.push(symbol);
+        }
+    }
+
     let mut reserved_words = Vec::with_capacity(grammar.reserved_words.len());
     for reserved_word_set in &grammar.reserved_words {
         let mut interned_set = Vec::with_capacity(reserved_word_set.reserved_words.len());
@@ -117,6 +144,7 @@ pub(super) fn intern_symbols(grammar: &InputGrammar) -> InternSymbolsResult<Inte
         expected_conflicts,
         variables_to_inline,
         supertype_symbols,
+        start_symbols,
         word_token,
         precedence_orderings: grammar.precedence_orderings.clone(),
         reserved_word_sets: reserved_words,
@@ -23,6 +23,8 @@ const SMALL_STATE_THRESHOLD: usize = 64;
 pub const ABI_VERSION_MIN: usize = 14;
 pub const ABI_VERSION_MAX: usize = tree_sitter::LANGUAGE_VERSION;
 const ABI_VERSION_WITH_RESERVED_WORDS: usize = 15;
+const ABI_VERSION_WITH_FINGERPRINT: usize = 16;
+pub const ABI_VERSION_WITH_START_STATES: usize = 16;
 const BUILD_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 #[clippy::format_args]
@@ -88,6 +90,7 @@ struct Generator {
     supertype_map: BTreeMap<String, Vec<ChildType>>,
     abi_version: usize,
     metadata: Option<Metadata>,
+    fingerprint: u64,
 }
 
 struct LargeCharacterSetInfo {
@@ -107,6 +110,7 @@ impl Generator {
         self.add_header();
         self.add_includes();
         self.add_pragmas();
+
         self.add_stats();
         self.add_symbol_enum();
         self.add_symbol_names_list();
@@ -126,10 +130,20 @@ impl Generator {
         self.add_non_terminal_alias_map();
         self.add_primary_state_id_list();
 
+        if self.abi_version >= ABI_VERSION_WITH_START_STATES
+            && !self.syntax_grammar.start_symbols.is_empty()
+        {
+            self.add_start_state_list();
+        }
+
         if self.abi_version >= ABI_VERSION_WITH_RESERVED_WORDS && !self.supertype_map.is_empty() {
             self.add_supertype_map();
         }
 
+        // The fingerprint covers the lex tables, which are consumed when the
+        // lex functions are generated.
+        self.fingerprint = self.compute_fingerprint();
+
         let buffer_offset_before_lex_functions = self.buffer.len();
 
         let mut main_lex_table = LexTable::default();
@@ -360,22 +374,7 @@ impl Generator {
     }
 
     fn add_stats(&mut self) {
-        let token_count = self
-            .parse_table
-            .symbols
-            .iter()
-            .filter(|symbol| {
-                if symbol.is_terminal() || symbol.is_eof() {
-                    true
-                } else if symbol.is_external() {
-                    self.syntax_grammar.external_tokens[symbol.index]
-                        .corresponding_internal_token
-                        .is_none()
-                } else {
-                    false
-                }
-            })
-            .count();
+        let token_count = self.token_count();
 
         add_line!(self, "#define LANGUAGE_VERSION {}", self.abi_version);
         add_line!(
@@ -671,6 +670,26 @@ impl Generator {
         add_line!(self, "");
     }
 
+    fn add_start_state_list(&mut self) {
+        // The first start state is the main one, and the others belong to the
+        // alternate start rules, in order.
+        let start_states = self
+            .syntax_grammar
+            .start_symbols
+            .iter()
+            .zip(&self.parse_table.start_states[1..])
+            .map(|(symbol, state)| (self.symbol_ids[symbol].clone(), *state))
+            .collect::<Vec<_>>();
+        add_line!(self, "static const TSStartState ts_start_states[] = {{");
+        indent!(self);
+        for (symbol_id, state) in start_states {
+            add_line!(self, "{{.symbol = {symbol_id}, .state = {state}}},");
+        }
+        dedent!(self);
+        add_line!(self, "}};");
+        add_line!(self, "");
+    }
+
     fn add_field_sequences(&mut self) {
         let mut flat_field_maps = vec![];
         let mut next_flat_field_map_index = 0;
@@ -1662,6 +1681,21 @@ impl Generator {
             add_line!(self, "}},");
         }
 
+        if self.abi_version >= ABI_VERSION_WITH_FINGERPRINT {
+            add_line!(self, ".fingerprint = 0x{:016x}ULL,", self.fingerprint);
+        }
+
+        if self.abi_version >= ABI_VERSION_WITH_START_STATES
+            && !self.syntax_grammar.start_symbols.is_empty()
+        {
+            add_line!(
+                self,
+                ".start_state_count = {},",
+                self.syntax_grammar.start_symbols.len()
+            );
+            add_line!(self, ".start_states = ts_start_states,");
+        }
+
         dedent!(self);
         add_line!(self, "}};");
         add_line!(self, "return &language;");
@@ -1739,6 +1773,242 @@ impl Generator {
         self.symbol_ids.insert(symbol, id);
     }
 
+    fn token_count(&self) -> usize {
+        self.parse_table
+            .symbols
+            .iter()
+            .filter(|symbol| {
+                if symbol.is_terminal() || symbol.is_eof() {
+                    true
+                } else if symbol.is_external() {
+                    self.syntax_grammar.external_tokens[symbol.index]
+                        .corresponding_internal_token
+                        .is_none()
+                } else {
+                    false
+                }
+            })
+            .count()
+    }
+
+    /// Compute the fingerprint that is embedded in the generated parser.
+    ///
+    /// This extends the hash of the language's symbols, fields and parse
+    /// tables with its lex tables, which only exist as code in the generated
+    /// parser, so the runtime can't include them when it computes a
+    /// fingerprint for an older parser.
+    fn compute_fingerprint(&self) -> u64 {
+        let mut hash = self.compute_table_fingerprint();
+        for lex_table in [&self.main_lex_table, &self.keyword_lex_table] {
+            hash = hash_u32(hash, lex_table.states.len() as u32);
+            for state in &lex_table.states {
+                hash = hash_u32(
+                    hash,
+                    state
+                        .accept_action
+                        .map_or(u32::MAX, |symbol| self.symbol_order[&symbol] as u32),
+                );
+                hash = state.eof_action.as_ref().map_or_else(
+                    || hash_u32(hash, u32::MAX),
+                    |action| hash_advance_action(hash, action),
+                );
+                hash = hash_u32(hash, state.advance_actions.len() as u32);
+                for (characters, action) in &state.advance_actions {
+                    let ranges = characters.ranges().collect::<Vec<_>>();
+                    hash = hash_u32(hash, ranges.len() as u32);
+                    for range in ranges {
+                        hash = hash_u32(hash, *range.start() as u32);
+                        hash = hash_u32(hash, *range.end() as u32);
+                    }
+                    hash = hash_advance_action(hash, action);
+                }
+            }
+        }
+        hash
+    }
+
+    /// Compute the hash of the language's symbols, fields and parse tables.
+    ///
+    /// This must produce the same hash as `ts_language__compute_fingerprint`,
+    /// which the runtime uses for parsers that were generated without a
+    /// fingerprint, so the values are hashed in the same order, and as the
+    /// same C types.
+    fn compute_table_fingerprint(&self) -> u64 {
+        let symbol_count = self.parse_table.symbols.len();
+        let symbol_id = |symbol: &Symbol| {
+            if *symbol == Symbol::end_of_nonterminal_extra() {
+                0
+            } else {
+                self.symbol_order[symbol] as u32
+            }
+        };
+        let alias_id = |alias: &Alias| {
+            // Some aliases match an existing symbol in the grammar.
+            self.symbols_for_alias(alias).first().map_or_else(
+                || {
+                    let index = self.unique_aliases.binary_search(alias).unwrap();
+                    (symbol_count + index) as u32
+                },
+                |symbol| symbol_id(&self.symbol_map[symbol]),
+            )
+        };
+
+        let mut hash = FNV_OFFSET;
+        hash = hash_u32(hash, symbol_count as u32);
+        hash = hash_u32(hash, self.unique_aliases.len() as u32);
+        hash = hash_u32(hash, self.token_count() as u32);
+        hash = hash_u32(hash, self.syntax_grammar.external_tokens.len() as u32);
+        hash = hash_u32(hash, self.parse_table.states.len() as u32);
+        hash = hash_u32(hash, self.parse_table.production_infos.len() as u32);
+        hash = hash_u32(hash, self.field_names.len() as u32);
+        hash = hash_u32(
+            hash,
+            self.syntax_grammar.word_token.map_or(0, |s| symbol_id(&s)),
+        );
+
+        // Symbols
+        let mut symbols = self.parse_table.symbols.clone();
+        symbols.sort_unstable_by_key(|symbol| symbol_id(symbol));
+        for symbol in &symbols {
+            let (name, visible, named, supertype) =
+                if let Some(alias) = self.default_aliases.get(symbol) {
+                    (alias.value.as_str(), true, alias.is_named, false)
+                } else {
+                    let (name, kind) = self.metadata_for_symbol(*symbol);
+                    let supertype = kind == VariableType::Hidden
+                        && self.syntax_grammar.supertype_symbols.contains(symbol);
+                    (
+                        name,
+                        matches!(kind, VariableType::Named | VariableType::Anonymous),
+                        matches!(kind, VariableType::Named | VariableType::Hidden),
+                        supertype,
+                    )
+                };
+            hash = hash_string(hash, name);
+            hash = hash_u32(
+                hash,
+                u32::from(visible) | u32::from(named) << 1 | u32::from(supertype) << 2,
+            );
+            hash = hash_u32(hash, symbol_id(&self.symbol_map[symbol]));
+        }
+        for alias in &self.unique_aliases {
+            hash = hash_string(hash, &alias.value);
+            hash = hash_u32(hash, 1 | u32::from(alias.is_named) << 1);
+        }
+
+        // Fields
+        for field_name in &self.field_names {
+            hash = hash_string(hash, field_name);
+        }
+        for (production_id, production_info) in self.parse_table.production_infos.iter().enumerate()
+        {
+            let entry_count = production_info
+                .field_map
+                .values()
+                .map(Vec::len)
+                .sum::<usize>();
+            hash = hash_u32(hash, entry_count as u32);
+            for (field_name, locations) in &production_info.field_map {
+                let field_id = self.field_names.binary_search(field_name).unwrap() + 1;
+                for location in locations {
+                    hash = hash_u32(hash, field_id as u32);
+                    hash = hash_u32(
+                        hash,
+                        u32::from(location.index as u8) | u32::from(location.inherited) << 8,
+                    );
+                }
+            }
+            if production_id > 0 {
+                for i in 0..self.parse_table.max_aliased_production_length {
+                    let alias = production_info
+                        .alias_sequence
+                        .get(i)
+                        .and_then(Option::as_ref);
+                    hash = hash_u32(hash, alias.map_or(0, alias_id));
+                }
+            }
+        }
+
+        // Parse table
+        for (i, state) in self.parse_table.states.iter().enumerate() {
+            if state.is_end_of_non_terminal_extra() {
+                hash = hash_u32(hash, u32::from(u16::MAX));
+                hash = hash_u32(hash, 0);
+                hash = hash_u32(hash, 0);
+            } else {
+                hash = hash_u32(hash, state.lex_state_id as u32);
+                hash = hash_u32(hash, state.external_lex_state_id as u32);
+                hash = hash_u32(
+                    hash,
+                    if self.abi_version >= ABI_VERSION_WITH_RESERVED_WORDS {
+                        self.reserved_word_set_ids_by_parse_state[i] as u32
+                    } else {
+                        0
+                    },
+                );
+            }
+
+            // The runtime combines the entries of each state with a sum, so
+            // they can be hashed in any order.
+            let mut entries_hash = 0u64;
+            for (symbol, entry) in &state.terminal_entries {
+                if entry.actions.is_empty() {
+                    continue;
+                }
+                let mut entry_hash = hash_u32(FNV_OFFSET, symbol_id(symbol));
+                entry_hash = hash_u32(entry_hash, 0);
+                entry_hash = hash_u32(entry_hash, entry.actions.len() as u32);
+                for action in &entry.actions {
+                    match action {
+                        ParseAction::Shift {
+                            state,
+                            is_repetition,
+                        } => {
+                            entry_hash = hash_u32(entry_hash, 0);
+                            entry_hash = hash_u32(entry_hash, *state as u32);
+                            entry_hash = hash_u32(entry_hash, u32::from(*is_repetition) << 1);
+                        }
+                        ParseAction::ShiftExtra => {
+                            entry_hash = hash_u32(entry_hash, 0);
+                            entry_hash = hash_u32(entry_hash, 0);
+                            entry_hash = hash_u32(entry_hash, 1);
+                        }
+                        ParseAction::Reduce {
+                            symbol,
+                            child_count,
+                            dynamic_precedence,
+                            production_id,
+                        } => {
+                            entry_hash = hash_u32(entry_hash, 1);
+                            entry_hash = hash_u32(entry_hash, symbol_id(symbol));
+                            entry_hash = hash_u32(entry_hash, *child_count as u32);
+                            entry_hash =
+                                hash_u32(entry_hash, u32::from(*dynamic_precedence as i16 as u16));
+                            entry_hash = hash_u32(entry_hash, *production_id as u32);
+                        }
+                        ParseAction::Accept => entry_hash = hash_u32(entry_hash, 2),
+                        ParseAction::Recover => entry_hash = hash_u32(entry_hash, 3),
+                    }
+                }
+                entries_hash = entries_hash.wrapping_add(entry_hash);
+            }
+            for (symbol, action) in &state.nonterminal_entries {
+                let next_state = match action {
+                    GotoAction::Goto(state) => *state,
+                    GotoAction::ShiftExtra => i,
+                };
+                let mut entry_hash = hash_u32(FNV_OFFSET, symbol_id(symbol));
+                entry_hash = hash_u32(entry_hash, next_state as u32);
+                entry_hash = hash_u32(entry_hash, 0);
+                entries_hash = entries_hash.wrapping_add(entry_hash);
+            }
+            hash = hash_u32(hash, entries_hash as u32);
+            hash = hash_u32(hash, (entries_hash >> 32) as u32);
+        }
+
+        hash
+    }
+
     fn field_id(&self, field_name: &str) -> String {
         format!("field_{field_name}")
     }
@@ -1918,6 +2188,33 @@ impl Generator {
     }
 }
 
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+
+/// Add the given bytes to a 64-bit FNV-1a hash.
+fn hash_bytes(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Add a 32-bit value to a hash, in little-endian order.
+fn hash_u32(hash: u64, value: u32) -> u64 {
+    hash_bytes(hash, &value.to_le_bytes())
+}
+
+/// Add a lex state's transition to a hash.
+fn hash_advance_action(hash: u64, action: &AdvanceAction) -> u64 {
+    let hash = hash_u32(hash, action.state as u32);
+    hash_u32(hash, u32::from(action.in_main_token))
+}
+
+/// Add a string to a hash, as the runtime sees it: up to its first NUL
+/// character, followed by a NUL terminator.
+fn hash_string(hash: u64, string: &str) -> u64 {
+    let string = string.split('\0').next().unwrap_or_default();
+    hash_bytes(hash_bytes(hash, string.as_bytes()), &[0])
+}
+
 /// Returns a String of C code for the given components of a parser.
 ///
 /// # Arguments
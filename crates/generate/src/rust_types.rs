@@ -0,0 +1,799 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Write,
+};
+
+use heck::{ToSnakeCase, ToUpperCamelCase};
+use serde::Serialize;
+use thiserror::Error;
+
+use super::node_types::{FieldInfoJSON, NodeInfoJSON, NodeTypeJSON};
+
+#[clippy::format_args]
+macro_rules! add_line {
+    ($this: tt, $($arg: tt)*) => {{
+        for _ in 0..$this.indent_level {
+            $this.buffer += "    ";
+        }
+        $this.buffer.write_fmt(format_args!($($arg)*)).unwrap();
+        $this.buffer += "\n";
+    }}
+}
+
+macro_rules! indent {
+    ($this:tt) => {
+        $this.indent_level += 1;
+    };
+}
+
+macro_rules! dedent {
+    ($this:tt) => {
+        assert_ne!($this.indent_level, 0);
+        $this.indent_level -= 1;
+    };
+}
+
+const RESULT: &str = "::core::result::Result";
+const OPTION: &str = "::core::option::Option";
+const ITERATOR: &str = "::core::iter::Iterator";
+
+/// Type names that are used by the generated code itself.
+const RESERVED_TYPE_NAMES: &[&str] = &["Node", "Self", "TreeCursor", "UnexpectedKind"];
+
+/// Method names that every generated wrapper defines.
+const RESERVED_METHOD_NAMES: &[&str] = &["child", "children", "from_node", "node"];
+
+const RUST_KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn",
+    "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl", "in", "let",
+    "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return",
+    "static", "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use",
+    "virtual", "where", "while", "yield",
+];
+
+pub type RustTypesResult<T> = Result<T, RustTypesError>;
+
+#[derive(Debug, Error, Serialize)]
+pub enum RustTypesError {
+    #[error("Failed to parse node types -- {0}")]
+    NodeTypes(String),
+}
+
+impl From<serde_json::Error> for RustTypesError {
+    fn from(value: serde_json::Error) -> Self {
+        Self::NodeTypes(value.to_string())
+    }
+}
+
+/// Generate a Rust module with a strongly typed wrapper for every named node
+/// kind described by the given `node-types.json` contents.
+///
+/// Each regular node kind becomes a struct that wraps a `tree_sitter::Node`
+/// and has one accessor method per field. Each supertype becomes an enum with
+/// one variant per subtype. Syntax nodes are converted into the wrappers with
+/// `TryFrom`, which checks the node's kind.
+pub fn generate_rust_types(node_types_json: &str) -> RustTypesResult<String> {
+    let node_types = serde_json::from_str::<Vec<NodeInfoJSON>>(node_types_json)?;
+    Ok(Generator::new(&node_types).generate())
+}
+
+/// The Rust type used to represent the nodes in a field.
+enum ValueType {
+    Node,
+    Typed(String),
+}
+
+/// An enum whose variants are the possible types of some node.
+struct EnumType<'a> {
+    name: String,
+    description: String,
+    doc: String,
+    variants: Vec<EnumVariant<'a>>,
+    tokens: Vec<&'a NodeTypeJSON>,
+}
+
+struct EnumVariant<'a> {
+    name: String,
+    kind: &'a str,
+    is_supertype: bool,
+}
+
+struct Generator<'a> {
+    buffer: String,
+    indent_level: usize,
+    node_types: &'a [NodeInfoJSON],
+    type_names: HashMap<&'a str, String>,
+    supertypes: HashSet<&'a str>,
+    used_type_names: HashSet<String>,
+    pending_enums: Vec<EnumType<'a>>,
+    uses_unnamed_children: bool,
+}
+
+impl<'a> Generator<'a> {
+    fn new(node_types: &'a [NodeInfoJSON]) -> Self {
+        Self {
+            buffer: String::new(),
+            indent_level: 0,
+            node_types,
+            type_names: HashMap::new(),
+            supertypes: HashSet::new(),
+            used_type_names: RESERVED_TYPE_NAMES
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+            pending_enums: Vec::new(),
+            uses_unnamed_children: false,
+        }
+    }
+
+    fn generate(mut self) -> String {
+        let node_types = self.node_types;
+        for node_type in node_types.iter().filter(|n| n.named) {
+            let name = self.allocate_type_name(&node_type.kind);
+            self.type_names.insert(&node_type.kind, name);
+            if node_type.subtypes.is_some() {
+                self.supertypes.insert(&node_type.kind);
+            }
+        }
+
+        self.add_header();
+        for node_type in node_types.iter().filter(|n| n.named) {
+            if let Some(subtypes) = &node_type.subtypes {
+                let enum_type = self.enum_type(
+                    self.type_names[node_type.kind.as_str()].clone(),
+                    node_type.kind.clone(),
+                    format!(
+                        "A `{}` node, which is one of several subtypes.",
+                        node_type.kind
+                    ),
+                    subtypes,
+                );
+                self.add_enum(&enum_type);
+            } else {
+                self.add_struct(node_type);
+            }
+        }
+        for enum_type in std::mem::take(&mut self.pending_enums) {
+            self.add_enum(&enum_type);
+        }
+        if self.uses_unnamed_children {
+            self.add_unnamed_children_function();
+        }
+
+        self.buffer
+    }
+
+    fn add_header(&mut self) {
+        add_line!(
+            self,
+            "// This file was generated by `tree-sitter generate --rust-types`. Do not edit it."
+        );
+        add_line!(self, "");
+        add_line!(self, "use tree_sitter::{{Node, TreeCursor}};");
+        add_line!(self, "");
+        add_line!(
+            self,
+            "/// The error returned when a node is converted into a wrapper for a different kind of node."
+        );
+        add_line!(self, "#[derive(Clone, Copy, Debug, PartialEq, Eq)]");
+        add_line!(self, "pub struct UnexpectedKind<'tree> {{");
+        indent!(self);
+        add_line!(self, "/// The kind of node that was expected.");
+        add_line!(self, "pub expected: &'static str,");
+        add_line!(self, "/// The node that was found instead.");
+        add_line!(self, "pub node: Node<'tree>,");
+        dedent!(self);
+        add_line!(self, "}}");
+        add_line!(self, "");
+        add_line!(self, "impl ::core::fmt::Display for UnexpectedKind<'_> {{");
+        indent!(self);
+        add_line!(
+            self,
+            "fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {{"
+        );
+        indent!(self);
+        add_line!(
+            self,
+            "write!(f, \"Expected a `{{}}` node, found `{{}}`\", self.expected, self.node.kind())"
+        );
+        dedent!(self);
+        add_line!(self, "}}");
+        dedent!(self);
+        add_line!(self, "}}");
+        add_line!(self, "");
+        add_line!(self, "impl ::std::error::Error for UnexpectedKind<'_> {{}}");
+    }
+
+    fn add_struct(&mut self, node_type: &'a NodeInfoJSON) {
+        let name = self.type_names[node_type.kind.as_str()].clone();
+
+        add_line!(self, "");
+        add_line!(self, "/// A `{}` node.", node_type.kind);
+        add_line!(self, "#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]");
+        add_line!(self, "pub struct {name}<'tree>(Node<'tree>);");
+        add_line!(self, "");
+        add_line!(self, "impl<'tree> {name}<'tree> {{");
+        indent!(self);
+        add_line!(self, "/// The kind of node that this type wraps.");
+        add_line!(self, "pub const KIND: &'static str = {:?};", node_type.kind);
+        add_line!(self, "");
+        add_line!(
+            self,
+            "/// Convert a syntax node into this type, checking its kind."
+        );
+        add_line!(
+            self,
+            "pub fn from_node(node: Node<'tree>) -> {RESULT}<Self, UnexpectedKind<'tree>> {{"
+        );
+        indent!(self);
+        add_line!(self, "if node.is_named() && node.kind() == Self::KIND {{");
+        indent!(self);
+        add_line!(self, "{RESULT}::Ok(Self(node))");
+        dedent!(self);
+        add_line!(self, "}} else {{");
+        indent!(self);
+        add_line!(
+            self,
+            "{RESULT}::Err(UnexpectedKind {{ expected: Self::KIND, node }})"
+        );
+        dedent!(self);
+        add_line!(self, "}}");
+        dedent!(self);
+        add_line!(self, "}}");
+        add_line!(self, "");
+        add_line!(self, "/// Get the underlying syntax node.");
+        add_line!(self, "#[must_use]");
+        add_line!(self, "pub const fn node(&self) -> Node<'tree> {{");
+        indent!(self);
+        add_line!(self, "self.0");
+        dedent!(self);
+        add_line!(self, "}}");
+
+        let mut method_names = HashSet::new();
+        if let Some(fields) = &node_type.fields {
+            for (field_name, field) in fields {
+                let method_name = method_name(field_name, &mut method_names);
+                let value_type = self.value_type(
+                    &field.types,
+                    &format!("{name}{}", field_name.to_upper_camel_case()),
+                    &format!("{}.{field_name}", node_type.kind),
+                    &format!(
+                        "A node in the `{field_name}` field of a `{}` node.",
+                        node_type.kind
+                    ),
+                );
+                self.add_field_accessor(field_name, &method_name, field, &value_type);
+            }
+        }
+        if let Some(children) = &node_type.children {
+            let value_type = self.value_type(
+                &children.types,
+                &format!("{name}Child"),
+                &format!("{} child", node_type.kind),
+                &format!(
+                    "A named child of a `{}` node that is not in a field.",
+                    node_type.kind
+                ),
+            );
+            self.add_children_accessor(children, &value_type);
+        }
+
+        dedent!(self);
+        add_line!(self, "}}");
+        self.add_conversions(&name);
+    }
+
+    fn add_field_accessor(
+        &mut self,
+        field_name: &str,
+        method_name: &str,
+        field: &FieldInfoJSON,
+        value_type: &ValueType,
+    ) {
+        add_line!(self, "");
+        add_line!(self, "/// Get the node's `{field_name}` field.");
+        if field.multiple {
+            add_line!(
+                self,
+                "pub fn {method_name}<'cursor>(&self, cursor: &'cursor mut TreeCursor<'tree>) -> impl {ITERATOR}<Item = {}> + 'cursor {{",
+                value_type.rust_type()
+            );
+            indent!(self);
+            add_line!(
+                self,
+                "self.0.children_by_field_name({field_name:?}, cursor){}",
+                value_type.filter_map()
+            );
+        } else {
+            if field.required {
+                add_line!(self, "///");
+                add_line!(
+                    self,
+                    "/// This field is always present, unless the node contains syntax errors."
+                );
+            }
+            add_line!(self, "#[must_use]");
+            add_line!(
+                self,
+                "pub fn {method_name}(&self) -> {OPTION}<{}> {{",
+                value_type.rust_type()
+            );
+            indent!(self);
+            match value_type {
+                ValueType::Node => add_line!(self, "self.0.child_by_field_name({field_name:?})"),
+                ValueType::Typed(name) => add_line!(
+                    self,
+                    "self.0.child_by_field_name({field_name:?}).and_then(|node| {name}::from_node(node).ok())"
+                ),
+            }
+        }
+        dedent!(self);
+        add_line!(self, "}}");
+    }
+
+    fn add_children_accessor(&mut self, children: &FieldInfoJSON, value_type: &ValueType) {
+        self.uses_unnamed_children = true;
+        add_line!(self, "");
+        if children.multiple {
+            add_line!(
+                self,
+                "/// Get the node's named children that are not in a field."
+            );
+            add_line!(
+                self,
+                "pub fn children<'cursor>(&self, cursor: &'cursor mut TreeCursor<'tree>) -> impl {ITERATOR}<Item = {}> + 'cursor {{",
+                value_type.rust_type()
+            );
+            indent!(self);
+            add_line!(
+                self,
+                "unnamed_children(self.0, cursor){}",
+                value_type.filter_map()
+            );
+        } else {
+            add_line!(
+                self,
+                "/// Get the node's named child that is not in a field."
+            );
+            add_line!(self, "#[must_use]");
+            add_line!(
+                self,
+                "pub fn child(&self) -> {OPTION}<{}> {{",
+                value_type.rust_type()
+            );
+            indent!(self);
+            match value_type {
+                ValueType::Node => add_line!(self, "unnamed_children(self.0, &mut self.0.walk()).next()"),
+                ValueType::Typed(name) => add_line!(
+                    self,
+                    "unnamed_children(self.0, &mut self.0.walk()).find_map(|node| {name}::from_node(node).ok())"
+                ),
+            }
+        }
+        dedent!(self);
+        add_line!(self, "}}");
+    }
+
+    fn add_enum(&mut self, enum_type: &EnumType) {
+        let name = &enum_type.name;
+
+        add_line!(self, "");
+        add_line!(self, "/// {}", enum_type.doc);
+        add_line!(self, "#[allow(clippy::enum_variant_names)]");
+        add_line!(self, "#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]");
+        add_line!(self, "pub enum {name}<'tree> {{");
+        indent!(self);
+        for variant in &enum_type.variants {
+            add_line!(
+                self,
+                "{}({}<'tree>),",
+                variant.name,
+                self.type_names[variant.kind]
+            );
+        }
+        let token_variant = enum_type.token_variant();
+        if let Some(token_variant) = &token_variant {
+            add_line!(self, "/// An unnamed token.");
+            add_line!(self, "{token_variant}(Node<'tree>),");
+        }
+        dedent!(self);
+        add_line!(self, "}}");
+        add_line!(self, "");
+
+        add_line!(self, "impl<'tree> {name}<'tree> {{");
+        indent!(self);
+        add_line!(
+            self,
+            "/// Convert a syntax node into this type, checking its kind."
+        );
+        add_line!(
+            self,
+            "pub fn from_node(node: Node<'tree>) -> {RESULT}<Self, UnexpectedKind<'tree>> {{"
+        );
+        indent!(self);
+        let direct_variants = enum_type
+            .variants
+            .iter()
+            .filter(|v| !v.is_supertype)
+            .collect::<Vec<_>>();
+        if direct_variants.is_empty() && token_variant.is_none() {
+            add_line!(self, "{RESULT}::Ok({{");
+            indent!(self);
+            self.add_supertype_conversions(enum_type);
+            dedent!(self);
+            add_line!(self, "}})");
+        } else {
+            add_line!(self, "{RESULT}::Ok(match (node.kind(), node.is_named()) {{");
+            indent!(self);
+            for variant in direct_variants {
+                add_line!(
+                    self,
+                    "({:?}, true) => Self::{}({}(node)),",
+                    variant.kind,
+                    variant.name,
+                    self.type_names[variant.kind]
+                );
+            }
+            if let Some(token_variant) = &token_variant {
+                let patterns = enum_type
+                    .tokens
+                    .iter()
+                    .map(|token| format!("({:?}, {})", token.kind, token.named))
+                    .collect::<Vec<_>>()
+                    .join(" | ");
+                add_line!(self, "{patterns} => Self::{token_variant}(node),");
+            }
+            add_line!(self, "_ => {{");
+            indent!(self);
+            self.add_supertype_conversions(enum_type);
+            dedent!(self);
+            add_line!(self, "}}");
+            dedent!(self);
+            add_line!(self, "}})");
+        }
+        dedent!(self);
+        add_line!(self, "}}");
+        add_line!(self, "");
+
+        add_line!(self, "/// Get the underlying syntax node.");
+        add_line!(self, "#[must_use]");
+        add_line!(self, "pub const fn node(&self) -> Node<'tree> {{");
+        indent!(self);
+        add_line!(self, "match self {{");
+        indent!(self);
+        for variant in &enum_type.variants {
+            add_line!(self, "Self::{}(value) => value.node(),", variant.name);
+        }
+        if let Some(token_variant) = &token_variant {
+            add_line!(self, "Self::{token_variant}(node) => *node,");
+        }
+        dedent!(self);
+        add_line!(self, "}}");
+        dedent!(self);
+        add_line!(self, "}}");
+        dedent!(self);
+        add_line!(self, "}}");
+        self.add_conversions(name);
+    }
+
+    /// Add a chain of conditionals that try to convert a node into each of the
+    /// enum's supertype variants, returning an error if none of them match.
+    fn add_supertype_conversions(&mut self, enum_type: &EnumType) {
+        let mut prefix = "";
+        for variant in enum_type.variants.iter().filter(|v| v.is_supertype) {
+            add_line!(
+                self,
+                "{prefix}if let {RESULT}::Ok(value) = {}::from_node(node) {{",
+                self.type_names[variant.kind]
+            );
+            indent!(self);
+            add_line!(self, "Self::{}(value)", variant.name);
+            dedent!(self);
+            prefix = "} else ";
+        }
+        let error = format!(
+            "return {RESULT}::Err(UnexpectedKind {{ expected: {:?}, node }});",
+            enum_type.description
+        );
+        if prefix.is_empty() {
+            add_line!(self, "{error}");
+        } else {
+            add_line!(self, "}} else {{");
+            indent!(self);
+            add_line!(self, "{error}");
+            dedent!(self);
+            add_line!(self, "}}");
+        }
+    }
+
+    fn add_conversions(&mut self, name: &str) {
+        add_line!(self, "");
+        add_line!(
+            self,
+            "impl<'tree> ::core::convert::TryFrom<Node<'tree>> for {name}<'tree> {{"
+        );
+        indent!(self);
+        add_line!(self, "type Error = UnexpectedKind<'tree>;");
+        add_line!(self, "");
+        add_line!(
+            self,
+            "fn try_from(node: Node<'tree>) -> {RESULT}<Self, Self::Error> {{"
+        );
+        indent!(self);
+        add_line!(self, "Self::from_node(node)");
+        dedent!(self);
+        add_line!(self, "}}");
+        dedent!(self);
+        add_line!(self, "}}");
+        add_line!(self, "");
+        add_line!(
+            self,
+            "impl<'tree> ::core::convert::From<{name}<'tree>> for Node<'tree> {{"
+        );
+        indent!(self);
+        add_line!(self, "fn from(value: {name}<'tree>) -> Self {{");
+        indent!(self);
+        add_line!(self, "value.node()");
+        dedent!(self);
+        add_line!(self, "}}");
+        dedent!(self);
+        add_line!(self, "}}");
+    }
+
+    fn add_unnamed_children_function(&mut self) {
+        add_line!(self, "");
+        add_line!(
+            self,
+            "fn unnamed_children<'tree, 'cursor>(node: Node<'tree>, cursor: &'cursor mut TreeCursor<'tree>) -> impl {ITERATOR}<Item = Node<'tree>> + 'cursor {{"
+        );
+        indent!(self);
+        add_line!(self, "cursor.reset(node);");
+        add_line!(self, "let mut done = !cursor.goto_first_child();");
+        add_line!(self, "::core::iter::from_fn(move || {{");
+        indent!(self);
+        add_line!(self, "while !done {{");
+        indent!(self);
+        add_line!(self, "let child = cursor.node();");
+        add_line!(self, "let has_field = cursor.field_id().is_some();");
+        add_line!(self, "done = !cursor.goto_next_sibling();");
+        add_line!(self, "if child.is_named() && !has_field {{");
+        indent!(self);
+        add_line!(self, "return {OPTION}::Some(child);");
+        dedent!(self);
+        add_line!(self, "}}");
+        dedent!(self);
+        add_line!(self, "}}");
+        add_line!(self, "{OPTION}::None");
+        dedent!(self);
+        add_line!(self, "}})");
+        dedent!(self);
+        add_line!(self, "}}");
+    }
+
+    /// Determine the Rust type for a node that can have any of the given types,
+    /// creating a new enum if there are several possibilities.
+    fn value_type(
+        &mut self,
+        types: &'a [NodeTypeJSON],
+        enum_name: &str,
+        description: &str,
+        doc: &str,
+    ) -> ValueType {
+        let named_types = types
+            .iter()
+            .filter(|t| t.named && self.type_names.contains_key(t.kind.as_str()))
+            .collect::<Vec<_>>();
+        if named_types.is_empty() {
+            return ValueType::Node;
+        }
+        if named_types.len() == 1 && types.len() == 1 {
+            return ValueType::Typed(self.type_names[types[0].kind.as_str()].clone());
+        }
+
+        let name = self.allocate_type_name(enum_name);
+        let enum_type = self.enum_type(
+            name.clone(),
+            description.to_string(),
+            doc.to_string(),
+            types,
+        );
+        self.pending_enums.push(enum_type);
+        ValueType::Typed(name)
+    }
+
+    fn enum_type(
+        &self,
+        name: String,
+        description: String,
+        doc: String,
+        types: &'a [NodeTypeJSON],
+    ) -> EnumType<'a> {
+        let mut variants = Vec::new();
+        let mut tokens = Vec::new();
+        for node_type in types {
+            if let (true, Some(type_name)) = (
+                node_type.named,
+                self.type_names.get(node_type.kind.as_str()),
+            ) {
+                variants.push(EnumVariant {
+                    name: type_name.clone(),
+                    kind: &node_type.kind,
+                    is_supertype: self.supertypes.contains(node_type.kind.as_str()),
+                });
+            } else {
+                tokens.push(node_type);
+            }
+        }
+        EnumType {
+            name,
+            description,
+            doc,
+            variants,
+            tokens,
+        }
+    }
+
+    fn allocate_type_name(&mut self, kind: &str) -> String {
+        let mut base = kind.to_upper_camel_case();
+        if !base.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            base.insert_str(0, "Node");
+        }
+        let mut name = base.clone();
+        let mut suffix = 2;
+        while self.used_type_names.contains(&name) {
+            name = format!("{base}{suffix}");
+            suffix += 1;
+        }
+        self.used_type_names.insert(name.clone());
+        name
+    }
+}
+
+impl ValueType {
+    fn rust_type(&self) -> String {
+        match self {
+            Self::Node => "Node<'tree>".to_string(),
+            Self::Typed(name) => format!("{name}<'tree>"),
+        }
+    }
+
+    fn filter_map(&self) -> String {
+        match self {
+            Self::Node => String::new(),
+            Self::Typed(name) => format!(".filter_map(|node| {name}::from_node(node).ok())"),
+        }
+    }
+}
+
+impl EnumType<'_> {
+    /// The name of the variant that holds unnamed tokens, if there are any.
+    fn token_variant(&self) -> Option<String> {
+        if self.tokens.is_empty() {
+            return None;
+        }
+        let mut name = "Token".to_string();
+        while self.variants.iter().any(|v| v.name == name) {
+            name.insert_str(0, "Unnamed");
+        }
+        Some(name)
+    }
+}
+
+/// Convert a field name into a method name that is a valid Rust identifier
+/// and is not used by any other method of the same wrapper.
+fn method_name(field_name: &str, used_names: &mut HashSet<String>) -> String {
+    let mut base = field_name.to_snake_case();
+    if !base.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') || base == "_" {
+        base.insert_str(0, "field_");
+    }
+    if matches!(base.as_str(), "self" | "super" | "crate") {
+        base.push('_');
+    }
+    let mut name = base.clone();
+    let mut suffix = 2;
+    while RESERVED_METHOD_NAMES.contains(&name.as_str()) || used_names.contains(&name) {
+        name = format!("{base}_{suffix}");
+        suffix += 1;
+    }
+    used_names.insert(name.clone());
+    if RUST_KEYWORDS.contains(&name.as_str()) {
+        name.insert_str(0, "r#");
+    }
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rust_types_structs_and_fields() {
+        let code = generate_rust_types(
+            r#"[
+                {
+                    "type": "binary_expression",
+                    "named": true,
+                    "fields": {
+                        "left": {"multiple": false, "required": true, "types": [{"type": "identifier", "named": true}]},
+                        "operator": {"multiple": false, "required": true, "types": [{"type": "+", "named": false}, {"type": "-", "named": false}]},
+                        "right": {"multiple": false, "required": false, "types": [{"type": "identifier", "named": true}, {"type": "number", "named": true}]}
+                    }
+                },
+                {"type": "identifier", "named": true},
+                {"type": "number", "named": true},
+                {"type": "+", "named": false},
+                {"type": "-", "named": false}
+            ]"#,
+        )
+        .unwrap();
+
+        assert!(code.contains("pub struct BinaryExpression<'tree>(Node<'tree>);"));
+        assert!(code.contains("pub struct Identifier<'tree>(Node<'tree>);"));
+        assert!(code.contains("pub const KIND: &'static str = \"binary_expression\";"));
+        assert!(code.contains("pub fn left(&self) -> ::core::option::Option<Identifier<'tree>> {"));
+        assert!(code.contains("pub fn operator(&self) -> ::core::option::Option<Node<'tree>> {"));
+        assert!(code.contains(
+            "pub fn right(&self) -> ::core::option::Option<BinaryExpressionRight<'tree>> {"
+        ));
+        assert!(code.contains("pub enum BinaryExpressionRight<'tree> {"));
+        assert!(code.contains("(\"number\", true) => Self::Number(Number(node)),"));
+        assert!(code
+            .contains("impl<'tree> ::core::convert::TryFrom<Node<'tree>> for Identifier<'tree>"));
+        assert!(!code.contains("struct Plus"));
+        assert!(!code.contains("fn unnamed_children"));
+    }
+
+    #[test]
+    fn test_rust_types_supertypes_and_children() {
+        let code = generate_rust_types(
+            r#"[
+                {
+                    "type": "expression",
+                    "named": true,
+                    "subtypes": [{"type": "primary_expression", "named": true}, {"type": "unary_expression", "named": true}]
+                },
+                {
+                    "type": "primary_expression",
+                    "named": true,
+                    "subtypes": [{"type": "self", "named": true}, {"type": "null", "named": false}]
+                },
+                {
+                    "type": "unary_expression",
+                    "named": true,
+                    "fields": {
+                        "type": {"multiple": true, "required": false, "types": [{"type": "self", "named": true}]}
+                    },
+                    "children": {"multiple": false, "required": true, "types": [{"type": "expression", "named": true}]}
+                },
+                {"type": "self", "named": true},
+                {"type": "null", "named": false}
+            ]"#,
+        )
+        .unwrap();
+
+        assert!(code.contains("pub enum Expression<'tree> {"));
+        assert!(code.contains("    PrimaryExpression(PrimaryExpression<'tree>),"));
+        assert!(code.contains(
+            "if let ::core::result::Result::Ok(value) = PrimaryExpression::from_node(node) {"
+        ));
+        assert!(code.contains("    Token(Node<'tree>),"));
+        assert!(code.contains("(\"null\", false) => Self::Token(node),"));
+        // Type names that conflict with keywords or with the generated code
+        // itself are renamed.
+        assert!(code.contains("pub struct Self2<'tree>(Node<'tree>);"));
+        assert!(code.contains("pub fn r#type<'cursor>(&self, cursor: &'cursor mut TreeCursor<'tree>) -> impl ::core::iter::Iterator<Item = Self2<'tree>> + 'cursor {"));
+        assert!(code.contains("pub fn child(&self) -> ::core::option::Option<Expression<'tree>> {"));
+        assert!(code.contains("fn unnamed_children<'tree, 'cursor>("));
+    }
+
+    #[test]
+    fn test_method_names() {
+        let mut used_names = HashSet::new();
+        assert_eq!(method_name("body", &mut used_names), "body");
+        assert_eq!(method_name("type", &mut used_names), "r#type");
+        assert_eq!(method_name("self", &mut used_names), "self_");
+        assert_eq!(method_name("node", &mut used_names), "node_2");
+        assert_eq!(method_name("camelCase", &mut used_names), "camel_case");
+        assert_eq!(method_name("0", &mut used_names), "field_0");
+    }
+}
@@ -72,6 +72,9 @@ pub struct ParseTable {
     pub production_infos: Vec<ProductionInfo>,
     pub max_aliased_production_length: usize,
     pub external_lex_states: Vec<TokenSet>,
+    /// The states in which parsing can begin: the main start state, followed
+    /// by one state for each of the grammar's alternate start rules.
+    pub start_states: Vec<ParseStateId>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
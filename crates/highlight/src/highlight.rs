@@ -190,6 +190,7 @@ struct HighlightIterLayer<'a> {
 
 pub struct _QueryCaptures<'query, 'tree: 'query, T: TextProvider<I>, I: AsRef<[u8]>> {
     ptr: *mut ffi::TSQueryCursor,
+    _allocation_stats: *mut (),
     query: &'query Query,
     text_provider: T,
     buffer1: Vec<u8>,
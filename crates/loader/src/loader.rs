@@ -708,11 +708,11 @@ impl Loader {
         grammar_path: &Path,
         output_path: PathBuf,
         flags: &[&str],
-    ) -> Result<()> {
+    ) -> Result<Language> {
         let src_path = grammar_path.join("src");
         let mut config = CompileConfig::new(&src_path, None, Some(output_path));
         config.flags = flags;
-        self.load_language_at_path(config).map(|_| ())
+        self.load_language_at_path(config)
     }
 
     pub fn load_language_at_path(&self, mut config: CompileConfig) -> Result<Language> {
@@ -0,0 +1,213 @@
+#[cfg(not(feature = "std"))]
+use alloc::{alloc::handle_alloc_error, boxed::Box};
+use core::{
+    alloc::{GlobalAlloc, Layout},
+    ffi::c_void,
+    fmt, ptr,
+    sync::atomic::{AtomicBool, AtomicPtr, Ordering},
+};
+#[cfg(feature = "std")]
+use std::{alloc::handle_alloc_error, error};
+
+use crate::ffi;
+
+/// The allocator that was installed with [`install_allocator`].
+static ALLOCATOR: AtomicPtr<&'static (dyn GlobalAlloc + Sync)> = AtomicPtr::new(ptr::null_mut());
+
+/// Whether the library's allocation functions have been replaced, either by
+/// [`install_allocator`] or by [`set_allocator`](crate::set_allocator).
+pub static IS_ALLOCATOR_SET: AtomicBool = AtomicBool::new(false);
+
+/// The size of the header that stores the size of each allocation. This is
+/// also the alignment of every allocation, which matches the alignment that
+/// `malloc` guarantees.
+const HEADER_SIZE: usize = 16;
+
+/// An error that occurred when calling [`install_allocator`].
+#[derive(Debug, PartialEq, Eq)]
+pub enum AllocatorError {
+    /// An allocator was already installed.
+    AlreadyInstalled,
+    /// The library has already allocated memory with its default allocator.
+    InUse,
+}
+
+/// Install a Rust allocator as the allocator for all of the memory that the
+/// core library allocates, including the memory of parsers, trees, queries,
+/// and external scanners.
+///
+/// The allocator can only be installed once, and only before the library has
+/// allocated any memory, because memory that was allocated by the default
+/// allocator cannot be freed by a different one. Call this at the start of
+/// the program, before creating any [`Parser`](crate::Parser),
+/// [`Query`](crate::Query), or [`QueryCursor`](crate::QueryCursor), and
+/// before any other thread uses the library.
+///
+/// # Errors
+///
+/// Returns [`AllocatorError::AlreadyInstalled`] if an allocator has already
+/// been installed, either with this function or with
+/// [`set_allocator`](crate::set_allocator), and [`AllocatorError::InUse`] if
+/// the library has already allocated memory.
+///
+/// # Example
+///
+/// ```
+/// use std::alloc::System;
+///
+/// static ALLOCATOR: System = System;
+///
+/// tree_sitter::install_allocator(&ALLOCATOR).unwrap();
+/// ```
+pub fn install_allocator<A: GlobalAlloc + Sync>(
+    allocator: &'static A,
+) -> Result<(), AllocatorError> {
+    if IS_ALLOCATOR_SET.swap(true, Ordering::AcqRel) {
+        return Err(AllocatorError::AlreadyInstalled);
+    }
+
+    // The allocator is stored before the library can call the allocation
+    // functions, and the library checks that its default allocator is unused
+    // and replaces it in one step.
+    let allocator: &'static (dyn GlobalAlloc + Sync) = allocator;
+    let allocator = Box::into_raw(Box::new(allocator));
+    ALLOCATOR.store(allocator, Ordering::Release);
+    let is_replaced = unsafe {
+        ffi::ts_set_allocator_if_unused(
+            Some(allocator_malloc),
+            Some(allocator_calloc),
+            Some(allocator_realloc),
+            Some(allocator_free),
+        )
+    };
+    if !is_replaced {
+        ALLOCATOR.store(ptr::null_mut(), Ordering::Release);
+        drop(unsafe { Box::from_raw(allocator) });
+        IS_ALLOCATOR_SET.store(false, Ordering::Release);
+        return Err(AllocatorError::InUse);
+    }
+    unsafe { crate::FREE_FN = allocator_free };
+    Ok(())
+}
+
+fn allocator() -> &'static dyn GlobalAlloc {
+    // The allocation functions are only passed to the library after the
+    // allocator has been stored.
+    unsafe { *ALLOCATOR.load(Ordering::Acquire) }
+}
+
+/// Get the layout of an allocation of the given size, including its header.
+fn layout(size: usize) -> Option<Layout> {
+    let size = size.checked_add(HEADER_SIZE)?;
+    Layout::from_size_align(size, HEADER_SIZE).ok()
+}
+
+/// Store the size of an allocation in its header, and return the pointer to
+/// the memory after the header.
+unsafe fn finish_allocation(base: *mut u8, layout: Layout) -> *mut c_void {
+    if base.is_null() {
+        handle_alloc_error(layout);
+    }
+    base.cast::<usize>()
+        .write_unaligned(layout.size() - HEADER_SIZE);
+    base.add(HEADER_SIZE).cast::<c_void>()
+}
+
+/// Get the start of the allocation that contains the given pointer, along
+/// with its layout.
+const unsafe fn allocation(ptr: *mut c_void) -> (*mut u8, Layout) {
+    let base = ptr.cast::<u8>().sub(HEADER_SIZE);
+    let size = base.cast::<usize>().read_unaligned();
+    (
+        base,
+        Layout::from_size_align_unchecked(size + HEADER_SIZE, HEADER_SIZE),
+    )
+}
+
+unsafe extern "C" fn allocator_malloc(size: usize) -> *mut c_void {
+    match layout(size) {
+        Some(layout) => finish_allocation(allocator().alloc(layout), layout),
+        None => ptr::null_mut(),
+    }
+}
+
+unsafe extern "C" fn allocator_calloc(count: usize, size: usize) -> *mut c_void {
+    match count.checked_mul(size).and_then(layout) {
+        Some(layout) => finish_allocation(allocator().alloc_zeroed(layout), layout),
+        None => ptr::null_mut(),
+    }
+}
+
+unsafe extern "C" fn allocator_realloc(ptr: *mut c_void, size: usize) -> *mut c_void {
+    if ptr.is_null() {
+        return allocator_malloc(size);
+    }
+    let Some(new_layout) = layout(size) else {
+        return ptr::null_mut();
+    };
+    let (base, layout) = allocation(ptr);
+    finish_allocation(
+        allocator().realloc(base, layout, new_layout.size()),
+        new_layout,
+    )
+}
+
+unsafe extern "C" fn allocator_free(ptr: *mut c_void) {
+    if !ptr.is_null() {
+        let (base, layout) = allocation(ptr);
+        allocator().dealloc(base, layout);
+    }
+}
+
+/// Run `f`, and attribute the memory that the current thread allocates while
+/// it runs to the object that `owner` returns for its result.
+#[cfg(not(feature = "tracking-allocator"))]
+#[inline(always)]
+pub fn track<R>(f: impl FnOnce() -> R, _owner: impl FnOnce(&R) -> *const c_void) -> R {
+    f()
+}
+
+/// Discard the allocations that were attributed to the given object, once it
+/// has been deleted.
+#[cfg(not(feature = "tracking-allocator"))]
+#[inline(always)]
+pub const fn forget(_owner: *const c_void) {}
+
+/// The allocations that are attributed to an object that records them
+/// itself, such as a query cursor.
+#[cfg(not(feature = "tracking-allocator"))]
+#[derive(Debug, Default)]
+pub struct LocalStats;
+
+#[cfg(not(feature = "tracking-allocator"))]
+impl LocalStats {
+    pub const fn new() -> Self {
+        Self
+    }
+
+    /// Run `f`, and attribute the memory that the current thread allocates
+    /// while it runs to these stats.
+    #[inline(always)]
+    pub fn track<R>(&self, f: impl FnOnce() -> R) -> R {
+        f()
+    }
+}
+
+#[cfg(feature = "tracking-allocator")]
+pub use crate::tracking_allocator::{forget, track, LocalStats};
+
+impl fmt::Display for AllocatorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AlreadyInstalled => write!(f, "An allocator was already installed"),
+            Self::InUse => write!(
+                f,
+                "The library has already allocated memory with the default allocator"
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+impl error::Error for AllocatorError {}
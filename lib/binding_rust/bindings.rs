@@ -1,6 +1,6 @@
 /* automatically generated by rust-bindgen 0.71.1 */
 
-pub const TREE_SITTER_LANGUAGE_VERSION: u32 = 15;
+pub const TREE_SITTER_LANGUAGE_VERSION: u32 = 16;
 pub const TREE_SITTER_MIN_COMPATIBLE_LANGUAGE_VERSION: u32 = 13;
 pub type TSStateId = u16;
 pub type TSSymbol = u16;
@@ -168,6 +168,12 @@ pub const TSQueryErrorCapture: TSQueryError = 4;
 pub const TSQueryErrorStructure: TSQueryError = 5;
 pub const TSQueryErrorLanguage: TSQueryError = 6;
 pub type TSQueryError = ::core::ffi::c_uint;
+pub const TSSnapshotErrorNone: TSSnapshotError = 0;
+pub const TSSnapshotErrorFormat: TSSnapshotError = 1;
+pub const TSSnapshotErrorVersion: TSSnapshotError = 2;
+pub const TSSnapshotErrorLanguage: TSSnapshotError = 3;
+pub const TSSnapshotErrorLanguageVersion: TSSnapshotError = 4;
+pub type TSSnapshotError = ::core::ffi::c_uint;
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
 pub struct TSQueryCursorState {
@@ -205,6 +211,14 @@ extern "C" {
     #[doc = " Set the language that the parser should use for parsing.\n\n Returns a boolean indicating whether or not the language was successfully\n assigned. True means assignment succeeded. False means there was a version\n mismatch: the language was generated with an incompatible version of the\n Tree-sitter CLI. Check the language's ABI version using [`ts_language_abi_version`]\n and compare it to this library's [`TREE_SITTER_LANGUAGE_VERSION`] and\n [`TREE_SITTER_MIN_COMPATIBLE_LANGUAGE_VERSION`] constants."]
     pub fn ts_parser_set_language(self_: *mut TSParser, language: *const TSLanguage) -> bool;
 }
+extern "C" {
+    #[doc = " Set the rule from which the parser should begin parsing.\n\n By default, the parser parses entire documents using the grammar's first\n rule. A grammar can declare additional entry points with the `start_rules`\n option, which makes it possible to parse fragments of a document, such as a\n single expression. Pass the id of one of those rules to start parsing from\n it, or zero to restore the default.\n\n Returns a boolean indicating whether or not the start symbol was assigned.\n False means that the parser has no language assigned, or that the language\n has no start rule with the given symbol. Assigning a start symbol resets\n the parser, like [`ts_parser_reset`], and assigning a language restores the\n default start symbol."]
+    pub fn ts_parser_set_start_symbol(self_: *mut TSParser, symbol: TSSymbol) -> bool;
+}
+extern "C" {
+    #[doc = " Get the rule from which the parser begins parsing, or zero if it uses the\n grammar's first rule."]
+    pub fn ts_parser_start_symbol(self_: *const TSParser) -> TSSymbol;
+}
 extern "C" {
     #[doc = " Set the ranges of text that the parser should include when parsing.\n\n By default, the parser will always include entire documents. This function\n allows you to parse only a *portion* of a document but still return a syntax\n tree whose ranges match up with the document as a whole. You can also pass\n multiple disjoint ranges.\n\n The second and third parameters specify the location and length of an array\n of ranges. The parser does *not* take ownership of these ranges; it copies\n the data, so it doesn't matter how these ranges are allocated.\n\n If `count` is zero, then the entire document will be parsed. Otherwise,\n the given ranges must be ordered from earliest to latest in the document,\n and they must not overlap. That is, the following must hold for all:\n\n `i < count - 1`: `ranges[i].end_byte <= ranges[i + 1].start_byte`\n\n If this requirement is not satisfied, the operation will fail, the ranges\n will not be assigned, and this function will return `false`. On success,\n this function returns `true`"]
     pub fn ts_parser_set_included_ranges(
@@ -309,6 +323,10 @@ extern "C" {
     #[doc = " Get the language that was used to parse the syntax tree."]
     pub fn ts_tree_language(self_: *const TSTree) -> *const TSLanguage;
 }
+extern "C" {
+    #[doc = " Get a number that identifies the syntax tree.\n\n Every tree, including every copy made with [`ts_tree_copy`], is assigned a\n different id when it is created, so unlike the tree's address, the id is\n not reused after the tree is deleted."]
+    pub fn ts_tree_id(self_: *const TSTree) -> u32;
+}
 extern "C" {
     #[doc = " Get the array of included ranges that was used to parse the syntax tree.\n\n The returned pointer must be freed by the caller."]
     pub fn ts_tree_included_ranges(self_: *const TSTree, length: *mut u32) -> *mut TSRange;
@@ -325,10 +343,43 @@ extern "C" {
         length: *mut u32,
     ) -> *mut TSRange;
 }
+extern "C" {
+    #[doc = " Get the number of bytes of heap memory that are used by the syntax tree.\n\n A tree that was created by incremental parsing shares its unchanged\n subtrees with the old tree, so that memory is counted for both of them.\n Use [`ts_tree_shared_memory_usage`] to find out how much of it is shared."]
+    pub fn ts_tree_memory_usage(self_: *const TSTree) -> usize;
+}
+extern "C" {
+    #[doc = " Get the number of bytes of heap memory that the syntax tree shares with\n another tree, such as the old tree that was passed to [`ts_parser_parse`]\n when the syntax tree was created.\n\n If `node_count` is not `NULL`, the number of nodes in the shared subtrees\n is written to it."]
+    pub fn ts_tree_shared_memory_usage(
+        self_: *const TSTree,
+        other: *const TSTree,
+        node_count: *mut u32,
+    ) -> usize;
+}
+extern "C" {
+    #[doc = " Get the parse state that the parser is in after the last token that ends\n at or before the given byte offset, skipping extras.\n\n Tokens inside of `ERROR` nodes and tokens that were changed by\n [`ts_tree_edit`] do not advance the state. Use [`ts_lookahead_iterator_new`]\n to find the symbols that are valid in the returned state."]
+    pub fn ts_tree_parse_state_at(self_: *const TSTree, byte_offset: u32) -> TSStateId;
+}
+extern "C" {
+    #[doc = " Get the parse state in which the parse that produced the tree began. This is\n `1` unless the parser's start symbol was changed with\n [`ts_parser_set_start_symbol`]."]
+    pub fn ts_tree_start_state(self_: *const TSTree) -> TSStateId;
+}
 extern "C" {
     #[doc = " Write a DOT graph describing the syntax tree to the given file."]
     pub fn ts_tree_print_dot_graph(self_: *const TSTree, file_descriptor: ::core::ffi::c_int);
 }
+extern "C" {
+    #[doc = " Serialize the syntax tree into a versioned binary snapshot.\n\n The snapshot contains the tree's full structure, its included ranges, and\n the identity of its language (name and ABI version), so that it can later\n be restored with [`ts_tree_deserialize`] and used for incremental parsing.\n\n The returned buffer is allocated using `malloc` and the caller is responsible\n for freeing it using `free`. The length of the buffer will be written to the\n given `length` pointer."]
+    pub fn ts_tree_serialize(self_: *const TSTree, length: *mut u32) -> *mut ::core::ffi::c_char;
+}
+extern "C" {
+    #[doc = " Restore a syntax tree from a snapshot created by [`ts_tree_serialize`].\n\n The snapshot must have been created from a tree that was parsed with the\n same language. If the snapshot is malformed, was written by an incompatible\n version of the library, or belongs to a different language, this returns\n `NULL` and writes the reason to the `error` parameter."]
+    pub fn ts_tree_deserialize(
+        language: *const TSLanguage,
+        data: *const ::core::ffi::c_char,
+        length: u32,
+        error: *mut TSSnapshotError,
+    ) -> *mut TSTree;
+}
 extern "C" {
     #[doc = " Get the node's type as a null-terminated string."]
     pub fn ts_node_type(self_: TSNode) -> *const ::core::ffi::c_char;
@@ -603,6 +654,19 @@ extern "C" {
     #[doc = " Delete a query, freeing all of the memory that it used."]
     pub fn ts_query_delete(self_: *mut TSQuery);
 }
+extern "C" {
+    #[doc = " Serialize a query into a versioned binary snapshot.\n\n The snapshot contains the query's compiled patterns, predicates, capture\n names and the results of its analysis, along with the identity of its\n language (name and ABI version), so that it can later be restored with\n [`ts_query_deserialize`] without parsing or analyzing the query's source.\n\n The returned buffer is allocated using `malloc` and the caller is responsible\n for freeing it using `free`. The length of the buffer will be written to the\n given `length` pointer."]
+    pub fn ts_query_serialize(self_: *const TSQuery, length: *mut u32) -> *mut ::core::ffi::c_char;
+}
+extern "C" {
+    #[doc = " Restore a query from a snapshot created by [`ts_query_serialize`].\n\n The snapshot must have been created from a query for the same language. If\n the snapshot is malformed, was written by an incompatible version of the\n library, or belongs to a different language, this returns `NULL` and writes\n the reason to the `error` parameter."]
+    pub fn ts_query_deserialize(
+        language: *const TSLanguage,
+        data: *const ::core::ffi::c_char,
+        length: u32,
+        error: *mut TSSnapshotError,
+    ) -> *mut TSQuery;
+}
 extern "C" {
     #[doc = " Get the number of patterns, captures, or string literals in the query."]
     pub fn ts_query_pattern_count(self_: *const TSQuery) -> u32;
@@ -613,6 +677,10 @@ extern "C" {
 extern "C" {
     pub fn ts_query_string_count(self_: *const TSQuery) -> u32;
 }
+extern "C" {
+    #[doc = " Get the language that the query was created for."]
+    pub fn ts_query_language(self_: *const TSQuery) -> *const TSLanguage;
+}
 extern "C" {
     #[doc = " Get the byte offset where the given pattern starts in the query's source.\n\n This can be useful when combining queries by concatenating their source\n code strings."]
     pub fn ts_query_start_byte_for_pattern(self_: *const TSQuery, pattern_index: u32) -> u32;
@@ -621,6 +689,10 @@ extern "C" {
     #[doc = " Get the byte offset where the given pattern ends in the query's source.\n\n This can be useful when combining queries by concatenating their source\n code strings."]
     pub fn ts_query_end_byte_for_pattern(self_: *const TSQuery, pattern_index: u32) -> u32;
 }
+extern "C" {
+    #[doc = " Get the row where the given pattern starts in the query's source.\n\n Unlike the source itself, this is stored in query snapshots, so it can be\n used to report the location of a pattern in a restored query."]
+    pub fn ts_query_start_row_for_pattern(self_: *const TSQuery, pattern_index: u32) -> u32;
+}
 extern "C" {
     #[doc = " Get all of the predicates for the given pattern in the query.\n\n The predicates are represented as a single array of steps. There are three\n types of steps in this array, which correspond to the three legal values for\n the `type` field:\n - `TSQueryPredicateStepTypeCapture` - Steps with this type represent names\n    of captures. Their `value_id` can be used with the\n   [`ts_query_capture_name_for_id`] function to obtain the name of the capture.\n - `TSQueryPredicateStepTypeString` - Steps with this type represent literal\n    strings. Their `value_id` can be used with the\n    [`ts_query_string_value_for_id`] function to obtain their string value.\n - `TSQueryPredicateStepTypeDone` - Steps with this type are *sentinels*\n    that represent the end of an individual predicate. If a pattern has two\n    predicates, then there will be two steps with this `type` in the array."]
     pub fn ts_query_predicates_for_pattern(
@@ -635,6 +707,10 @@ extern "C" {
 extern "C" {
     pub fn ts_query_is_pattern_non_local(self_: *const TSQuery, pattern_index: u32) -> bool;
 }
+extern "C" {
+    #[doc = " Check if two queries for the same language were compiled into the same\n patterns, with the same predicates and captures, regardless of where the\n patterns appear in the queries' source code, and of how it is formatted."]
+    pub fn ts_query_is_equivalent(self_: *const TSQuery, other: *const TSQuery) -> bool;
+}
 extern "C" {
     pub fn ts_query_is_pattern_guaranteed_at_step(self_: *const TSQuery, byte_offset: u32) -> bool;
 }
@@ -827,6 +903,10 @@ extern "C" {
     #[doc = " Get the metadata for this language. This information is generated by the\n CLI, and relies on the language author providing the correct metadata in\n the language's `tree-sitter.json` file.\n\n See also [`TSMetadata`]."]
     pub fn ts_language_metadata(self_: *const TSLanguage) -> *const TSLanguageMetadata;
 }
+extern "C" {
+    #[doc = " Get a hash of the tables of this language, which changes whenever the\n language's symbols, fields, parse tables or lex tables change, even if its\n semantic version does not. This can be used as part of the key for caches\n of data that depend on the language, like syntax trees or compiled queries.\n\n Parsers that were generated with ABI version 16 or later embed the hash, so\n it is cheap to read. For older parsers, a hash of the language's symbols,\n fields and parse tables is computed each time this function is called. It\n cannot cover the lex tables, which are compiled into the parser's lex\n functions, so two older parsers that only differ in their tokens' patterns\n have the same fingerprint."]
+    pub fn ts_language_fingerprint(self_: *const TSLanguage) -> u64;
+}
 extern "C" {
     #[doc = " Get the next parse state. Combine this with lookahead iterators to generate\n completion suggestions or valid symbols in error nodes. Use\n [`ts_node_grammar_symbol`] for valid symbols."]
     pub fn ts_language_next_state(
@@ -961,3 +1041,25 @@ extern "C" {
         new_free: ::core::option::Option<unsafe extern "C" fn(arg1: *mut ::core::ffi::c_void)>,
     );
 }
+extern "C" {
+    #[doc = " Set the allocation functions used by the library, like [`ts_set_allocator`],\n but only if the library has not yet allocated any memory with its default\n allocation functions. The check and the claim on the allocator are a single\n atomic operation, so the default functions stop marking themselves as used\n once this succeeds.\n\n Returns `false`, without changing the allocation functions, if the default\n functions have already allocated memory, or if this function was already\n called successfully."]
+    pub fn ts_set_allocator_if_unused(
+        new_malloc: ::core::option::Option<
+            unsafe extern "C" fn(arg1: usize) -> *mut ::core::ffi::c_void,
+        >,
+        new_calloc: ::core::option::Option<
+            unsafe extern "C" fn(arg1: usize, arg2: usize) -> *mut ::core::ffi::c_void,
+        >,
+        new_realloc: ::core::option::Option<
+            unsafe extern "C" fn(
+                arg1: *mut ::core::ffi::c_void,
+                arg2: usize,
+            ) -> *mut ::core::ffi::c_void,
+        >,
+        new_free: ::core::option::Option<unsafe extern "C" fn(arg1: *mut ::core::ffi::c_void)>,
+    ) -> bool;
+}
+extern "C" {
+    #[doc = " Check if the library has allocated any memory with its default allocation\n functions. Once it has, an allocator that is passed to [`ts_set_allocator`]\n must be capable of freeing that memory."]
+    pub fn ts_default_allocator_is_used() -> bool;
+}
@@ -0,0 +1,167 @@
+use core::ops;
+
+use crate::{Node, ParseOptions, Parser, TextProvider, Tree};
+
+/// The longest UTF-8 or UTF-16 encoded code point. Slices that are passed to
+/// the parser are never shorter than this, except at the end of the text, so
+/// that no character is split between two slices.
+const MAX_CODE_POINT_SIZE: usize = 4;
+
+/// A text buffer that stores its contents as a sequence of byte chunks, such
+/// as a rope.
+///
+/// Implementing this trait allows a buffer to be parsed with
+/// [`Parser::parse_chunked_text`] and queried with a [`ChunkedTextProvider`]
+/// without copying its contents into a contiguous slice. Chunks may be split
+/// anywhere, including in the middle of a UTF-8 character.
+pub trait ChunkedText {
+    /// Get the chunk that contains the given byte offset, along with the byte
+    /// offset at which that chunk starts.
+    ///
+    /// If `byte_offset` is at or past the end of the text, this may return any
+    /// chunk that does not contain it, such as an empty one.
+    fn chunk_at_byte(&self, byte_offset: usize) -> (&[u8], usize);
+
+    /// Iterate over the chunks of text in the given byte range. The first and
+    /// last chunks are trimmed to the bounds of the range.
+    fn chunks_in_range(&self, range: ops::Range<usize>) -> TextChunks<'_, Self> {
+        TextChunks { text: self, range }
+    }
+
+    /// Get a [`TextProvider`] that reads node text from this buffer.
+    fn text_provider(&self) -> ChunkedTextProvider<'_, Self> {
+        ChunkedTextProvider(self)
+    }
+}
+
+/// An iterator over the chunks of a [`ChunkedText`] in a given byte range.
+///
+/// This struct is created by [`ChunkedText::chunks_in_range`].
+pub struct TextChunks<'a, T: ?Sized> {
+    text: &'a T,
+    range: ops::Range<usize>,
+}
+
+impl<'a, T: ChunkedText + ?Sized> Iterator for TextChunks<'a, T> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.range.is_empty() {
+            return None;
+        }
+        let (chunk, chunk_start) = self.text.chunk_at_byte(self.range.start);
+        let start = self.range.start.checked_sub(chunk_start)?;
+        let end = chunk.len().min(self.range.end - chunk_start);
+        if start >= end {
+            return None;
+        }
+        self.range.start = chunk_start + end;
+        Some(&chunk[start..end])
+    }
+}
+
+/// A [`TextProvider`] that reads the text of nodes from a [`ChunkedText`].
+///
+/// This struct is created by [`ChunkedText::text_provider`].
+pub struct ChunkedTextProvider<'a, T: ?Sized>(&'a T);
+
+impl<'a, T: ChunkedText + ?Sized> TextProvider<&'a [u8]> for ChunkedTextProvider<'a, T> {
+    type I = TextChunks<'a, T>;
+
+    fn text(&mut self, node: Node) -> Self::I {
+        self.0.chunks_in_range(node.byte_range())
+    }
+}
+
+/// A slice of text that is passed to the parser, which is either borrowed
+/// from a chunk, or copied from the end of one chunk and the start of the
+/// next, if the chunk ends close to the requested offset.
+enum InputSlice<'a> {
+    Borrowed(&'a [u8]),
+    Joined([u8; 2 * MAX_CODE_POINT_SIZE], usize),
+}
+
+impl AsRef<[u8]> for InputSlice<'_> {
+    fn as_ref(&self) -> &[u8] {
+        match self {
+            Self::Borrowed(slice) => slice,
+            Self::Joined(bytes, len) => &bytes[..*len],
+        }
+    }
+}
+
+impl Parser {
+    /// Parse text that is stored in a [`ChunkedText`] buffer, such as a rope.
+    ///
+    /// The chunks are passed to the parser without copying, except where a
+    /// chunk boundary splits a multi-byte character.
+    ///
+    /// # Arguments:
+    /// * `text` The UTF8-encoded text to parse.
+    /// * `old_tree` A previous syntax tree parsed from the same document. If the text of the
+    ///   document has changed since `old_tree` was created, then you must edit `old_tree` to match
+    ///   the new text using [`Tree::edit`].
+    /// * `options` Options for parsing the text. This can be used to set a progress callback.
+    pub fn parse_chunked_text<T: ChunkedText + ?Sized>(
+        &mut self,
+        text: &T,
+        old_tree: Option<&Tree>,
+        options: Option<ParseOptions>,
+    ) -> Option<Tree> {
+        let slice_at = |offset: usize| {
+            let (chunk, chunk_start) = text.chunk_at_byte(offset);
+            offset
+                .checked_sub(chunk_start)
+                .and_then(|start| chunk.get(start..))
+                .unwrap_or_default()
+        };
+
+        self.parse_with_options(
+            &mut |offset, _| {
+                let slice = slice_at(offset);
+                if slice.is_empty() || slice.len() >= MAX_CODE_POINT_SIZE {
+                    return InputSlice::Borrowed(slice);
+                }
+
+                let mut bytes = [0; 2 * MAX_CODE_POINT_SIZE];
+                let mut len = 0;
+                while len < MAX_CODE_POINT_SIZE {
+                    let slice = slice_at(offset + len);
+                    if slice.is_empty() {
+                        break;
+                    }
+                    let count = slice.len().min(bytes.len() - len);
+                    bytes[len..len + count].copy_from_slice(&slice[..count]);
+                    len += count;
+                }
+                InputSlice::Joined(bytes, len)
+            },
+            old_tree,
+            options,
+        )
+    }
+}
+
+#[cfg(feature = "ropey")]
+#[cfg_attr(docsrs, doc(cfg(feature = "ropey")))]
+impl ChunkedText for ropey::Rope {
+    fn chunk_at_byte(&self, byte_offset: usize) -> (&[u8], usize) {
+        if byte_offset >= self.len_bytes() {
+            return (&[], byte_offset);
+        }
+        let (chunk, chunk_start, _, _) = self.chunk_at_byte(byte_offset);
+        (chunk.as_bytes(), chunk_start)
+    }
+}
+
+#[cfg(feature = "ropey")]
+#[cfg_attr(docsrs, doc(cfg(feature = "ropey")))]
+impl ChunkedText for ropey::RopeSlice<'_> {
+    fn chunk_at_byte(&self, byte_offset: usize) -> (&[u8], usize) {
+        if byte_offset >= self.len_bytes() {
+            return (&[], byte_offset);
+        }
+        let (chunk, chunk_start, _, _) = self.chunk_at_byte(byte_offset);
+        (chunk.as_bytes(), chunk_start)
+    }
+}
@@ -0,0 +1,63 @@
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use crate::{ffi, Tree};
+
+impl Tree {
+    /// Get the parse state in which the parse that produced this tree began.
+    ///
+    /// This is `1` unless the parser's start symbol was changed with
+    /// [`Parser::set_start_symbol`](crate::Parser::set_start_symbol).
+    #[doc(alias = "ts_tree_start_state")]
+    #[must_use]
+    pub fn start_state(&self) -> u16 {
+        unsafe { ffi::ts_tree_start_state(self.0.as_ptr()) }
+    }
+
+    /// Get the parse state that the parser is in after the last token that
+    /// ends at or before the given byte offset, skipping extras.
+    ///
+    /// The state accounts for the reductions that happened after that token,
+    /// unlike [`Node::next_parse_state`](crate::Node::next_parse_state),
+    /// which only knows the state in which the token was lexed. Tokens inside
+    /// of `ERROR` nodes, and tokens that were changed by [`Tree::edit`], do
+    /// not advance the state.
+    #[doc(alias = "ts_tree_parse_state_at")]
+    #[must_use]
+    pub fn parse_state_at(&self, byte_offset: usize) -> u16 {
+        let byte_offset = u32::try_from(byte_offset).unwrap_or(u32::MAX);
+        unsafe { ffi::ts_tree_parse_state_at(self.0.as_ptr(), byte_offset) }
+    }
+
+    /// Get the symbols that could legally appear at the given byte offset,
+    /// for example to offer completions in an editor.
+    ///
+    /// The result contains the ids of the visible terminal and nonterminal
+    /// symbols, along with the supertypes, that are valid in the
+    /// [parse state](Tree::parse_state_at) at that offset, sorted and without
+    /// duplicates. Use [`Language::node_kind_for_id`] to get their names.
+    ///
+    /// If `byte_offset` is inside of a token, the symbols that could replace
+    /// that token are returned. Extras, such as comments, are skipped when
+    /// looking for the preceding token. Tokens inside of `ERROR` nodes are
+    /// skipped too, because the parser did not accept them. If the tree has
+    /// been edited but not reparsed yet, the tokens that were changed by the
+    /// edits are skipped, and `byte_offset` refers to the edited text.
+    ///
+    /// [`Language::node_kind_for_id`]: crate::Language::node_kind_for_id
+    #[must_use]
+    pub fn expected_symbols_at(&self, byte_offset: usize) -> Vec<u16> {
+        let language = self.language();
+        let Some(lookahead) = language.lookahead_iterator(self.parse_state_at(byte_offset)) else {
+            return Vec::new();
+        };
+        let mut result = lookahead
+            .filter(|symbol| {
+                language.node_kind_is_visible(*symbol) || language.node_kind_is_supertype(*symbol)
+            })
+            .collect::<Vec<_>>();
+        result.sort_unstable();
+        result.dedup();
+        result
+    }
+}
@@ -0,0 +1,240 @@
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use crate::{Node, Range, Tree};
+
+/// The kind of a [`SyntaxDiagnostic`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SyntaxDiagnosticKind {
+    /// The parser encountered text that is not valid at its position. It was
+    /// skipped, and is contained in an `ERROR` node.
+    UnexpectedToken,
+    /// The parser inserted a zero-width `MISSING` node in order to recover
+    /// from an error.
+    MissingNode,
+    /// The input ended before a construct was complete, such as a block
+    /// without its closing brace, or an unclosed string.
+    Unterminated,
+}
+
+/// A syntax error in a [`Tree`], as returned by [`Tree::diagnostics`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SyntaxDiagnostic<'tree> {
+    /// The kind of syntax error.
+    pub kind: SyntaxDiagnosticKind,
+    /// The `ERROR` or `MISSING` node that the diagnostic was created from.
+    pub node: Node<'tree>,
+    /// The range of the node.
+    pub range: Range,
+    /// The range of the text that caused the error. For an unexpected token,
+    /// this is the first token that was skipped. For an unterminated
+    /// construct, this is the token that starts it. For a missing node, this
+    /// is the same as `range`.
+    pub offending_range: Range,
+    /// The symbols that would have been valid at the position of the error,
+    /// as computed by a [`LookaheadIterator`](crate::LookaheadIterator).
+    pub expected_symbols: Vec<u16>,
+}
+
+impl SyntaxDiagnostic<'_> {
+    /// Get the names of the expected symbols, without duplicates.
+    #[must_use]
+    pub fn expected_kinds(&self) -> Vec<&'static str> {
+        let language = self.node.language();
+        let mut result = Vec::<&str>::with_capacity(self.expected_symbols.len());
+        for &symbol in &self.expected_symbols {
+            if let Some(name) = language.node_kind_for_id(symbol) {
+                if !result.contains(&name) {
+                    result.push(name);
+                }
+            }
+        }
+        result
+    }
+}
+
+impl Tree {
+    /// Get the syntax errors in this tree, in the order in which they appear
+    /// in the source code.
+    ///
+    /// Each `ERROR` and `MISSING` node in the tree yields one diagnostic.
+    /// Errors that are nested inside of an `ERROR` node are not reported
+    /// separately.
+    #[must_use]
+    pub fn diagnostics(&self) -> Vec<SyntaxDiagnostic<'_>> {
+        // The nodes with errors, along with the parse state after the last
+        // token that precedes them, and the number of tokens before them.
+        let mut error_nodes = Vec::new();
+        let mut state = self.start_state();
+        let mut token_count = 0;
+
+        let mut cursor = self.walk();
+        'outer: loop {
+            let node = cursor.node();
+            if node.is_error() {
+                error_nodes.push((node, state, token_count));
+                token_count += 1;
+            } else if node.is_missing() {
+                error_nodes.push((node, state, token_count));
+                state = node.next_parse_state();
+            } else if node.has_error() {
+                if cursor.goto_first_child() {
+                    continue;
+                }
+            } else if let Some(last_token_state) = last_token_state(node) {
+                state = last_token_state;
+                token_count += 1;
+            }
+
+            while !cursor.goto_next_sibling() {
+                if !cursor.goto_parent() {
+                    break 'outer;
+                }
+            }
+        }
+
+        let language = self.language();
+        let expected_symbols_in = |state: u16| {
+            is_valid_state(state)
+                .then(|| language.lookahead_iterator(state))
+                .flatten()
+                .map(Iterator::collect::<Vec<_>>)
+        };
+
+        let mut result = Vec::with_capacity(error_nodes.len());
+        for (node, state, tokens_before) in error_nodes {
+            let tokens_after = token_count - tokens_before - usize::from(node.is_error());
+            let at_end = tokens_after == 0;
+
+            if node.is_missing() {
+                let mut expected_symbols = expected_symbols_in(state).unwrap_or_default();
+                if !expected_symbols.contains(&node.grammar_id()) {
+                    expected_symbols.push(node.grammar_id());
+                }
+                let (kind, offending_range) = if at_end {
+                    let opening_token = node
+                        .parent()
+                        .and_then(first_token)
+                        .filter(|token| *token != node);
+                    (
+                        SyntaxDiagnosticKind::Unterminated,
+                        opening_token.unwrap_or(node).range(),
+                    )
+                } else {
+                    (SyntaxDiagnosticKind::MissingNode, node.range())
+                };
+                result.push(SyntaxDiagnostic {
+                    kind,
+                    node,
+                    range: node.range(),
+                    offending_range,
+                    expected_symbols,
+                });
+                continue;
+            }
+
+            // Find the first token in the error that was not valid in the
+            // state in which the parser encountered it. If there is none, the
+            // error was caused by the end of the input.
+            let tokens = tokens(node);
+            let offending = tokens.iter().find_map(|token| {
+                let expected_symbols = expected_symbols_in(token.parse_state())?;
+                (!expected_symbols.contains(&token.grammar_id()))
+                    .then_some((*token, expected_symbols))
+            });
+            let (kind, token, expected_symbols) = match offending {
+                Some((token, expected_symbols)) => (
+                    SyntaxDiagnosticKind::UnexpectedToken,
+                    token,
+                    expected_symbols,
+                ),
+                None if at_end && !tokens.is_empty() => {
+                    let expected_symbols = tokens
+                        .iter()
+                        .rev()
+                        .find_map(|token| expected_symbols_in(token.next_parse_state()))
+                        .unwrap_or_default();
+                    (
+                        SyntaxDiagnosticKind::Unterminated,
+                        tokens[0],
+                        expected_symbols,
+                    )
+                }
+                None => {
+                    let token = tokens.first().copied().unwrap_or(node);
+                    let state = tokens.first().map_or(state, Node::parse_state);
+                    let expected_symbols = expected_symbols_in(state).unwrap_or_default();
+                    (
+                        SyntaxDiagnosticKind::UnexpectedToken,
+                        token,
+                        expected_symbols,
+                    )
+                }
+            };
+            result.push(SyntaxDiagnostic {
+                kind,
+                node,
+                range: node.range(),
+                offending_range: token.range(),
+                expected_symbols,
+            });
+        }
+        result
+    }
+}
+
+/// Check if a node's parse state refers to an actual state, rather than the
+/// error state, or no state at all.
+const fn is_valid_state(state: u16) -> bool {
+    state != 0 && state != u16::MAX
+}
+
+/// Get the tokens in the given node's subtree, skipping extras.
+fn tokens(node: Node) -> Vec<Node> {
+    let mut result = Vec::new();
+    let mut cursor = node.walk();
+    'outer: loop {
+        let node = cursor.node();
+        if node.child_count() == 0 {
+            if !node.is_extra() {
+                result.push(node);
+            }
+        } else if cursor.goto_first_child() {
+            continue;
+        }
+        while !cursor.goto_next_sibling() {
+            if !cursor.goto_parent() {
+                break 'outer;
+            }
+        }
+    }
+    result
+}
+
+/// Get the first token in the given node's subtree, skipping extras.
+fn first_token(node: Node) -> Option<Node> {
+    if node.child_count() == 0 {
+        return (!node.is_extra()).then_some(node);
+    }
+    let mut cursor = node.walk();
+    cursor.goto_first_child();
+    loop {
+        if let Some(token) = first_token(cursor.node()) {
+            return Some(token);
+        }
+        if !cursor.goto_next_sibling() {
+            return None;
+        }
+    }
+}
+
+/// Get the parse state after the last token in the given node's subtree,
+/// skipping extras.
+fn last_token_state(node: Node) -> Option<u16> {
+    if node.child_count() == 0 {
+        return (!node.is_extra()).then(|| node.next_parse_state());
+    }
+    (0..node.child_count())
+        .rev()
+        .find_map(|i| last_token_state(node.child(i)?))
+}
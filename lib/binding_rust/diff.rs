@@ -0,0 +1,744 @@
+//! Structural differences between syntax trees.
+//!
+//! The [`diff`] function matches the nodes of two syntax trees and computes an
+//! edit script that turns the old tree into the new one, in the style of the
+//! [GumTree](https://doi.org/10.1145/2642937.2642982) algorithm. Unlike
+//! [`Tree::changed_ranges`](crate::Tree::changed_ranges), the trees do not
+//! need to be related by edits: they can be parsed from any two texts in the
+//! same language.
+
+use core::{cmp::Reverse, ops};
+
+#[cfg(not(feature = "std"))]
+use alloc::{collections::BTreeMap, vec, vec::Vec};
+#[cfg(feature = "std")]
+use std::{collections::BTreeMap, vec};
+
+use crate::{
+    util::{fnv_hash, FNV_OFFSET},
+    Node,
+};
+
+/// The maximum number of pairs of identical subtrees of the same height that
+/// are ranked by the similarity of their parents in the first phase of the
+/// diff. Larger groups of identical subtrees, such as repeated statements, are
+/// matched in order instead, which keeps the diff from taking quadratic time.
+const MAX_AMBIGUOUS_PAIRS: usize = 256;
+
+/// The maximum product of the lengths of two sequences of children that are
+/// aligned by computing their longest common subsequence.
+const MAX_LCS_SIZE: usize = 1 << 20;
+
+/// Options for computing a structural diff with [`diff`].
+#[derive(Clone, Copy, Debug)]
+pub struct DiffOptions {
+    /// The minimum height of subtrees that are matched by comparing their
+    /// structure and text in the first phase of the diff. Smaller subtrees
+    /// are only matched when their ancestors are.
+    pub min_height: usize,
+    /// The minimum ratio of common descendants that two nodes need to have to
+    /// be matched in the second phase of the diff, between `0.0` and `1.0`.
+    pub min_dice: f64,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        Self {
+            min_height: 2,
+            min_dice: 0.5,
+        }
+    }
+}
+
+impl DiffOptions {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub const fn min_height(mut self, min_height: usize) -> Self {
+        self.min_height = min_height;
+        self
+    }
+
+    #[must_use]
+    pub const fn min_dice(mut self, min_dice: f64) -> Self {
+        self.min_dice = min_dice;
+        self
+    }
+}
+
+/// An operation in an [`EditScript`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Edit<'a, 'b> {
+    /// A node of the new tree that has no counterpart in the old tree was
+    /// inserted as the `index`th child of `parent`. The node's descendants
+    /// that have no counterpart are inserted along with it; the others are
+    /// reported as moves.
+    Insert {
+        node: Node<'b>,
+        parent: Node<'b>,
+        index: usize,
+    },
+    /// A node of the old tree that has no counterpart in the new tree was
+    /// deleted. The node's descendants that have no counterpart are deleted
+    /// along with it; the others are reported as moves.
+    Delete { node: Node<'a> },
+    /// A node's text or kind changed. Only leaf nodes and the root nodes can
+    /// be updated.
+    Update { old: Node<'a>, new: Node<'b> },
+    /// A node was moved to become the `index`th child of `parent`, either
+    /// because its parent changed, or because it was reordered among its
+    /// siblings.
+    Move {
+        old: Node<'a>,
+        new: Node<'b>,
+        parent: Node<'b>,
+        index: usize,
+    },
+}
+
+/// The result of a structural [`diff`]: the nodes that were matched between
+/// the two trees, and the edits that turn the old tree into the new one.
+#[derive(Clone, Debug)]
+pub struct EditScript<'a, 'b> {
+    edits: Vec<Edit<'a, 'b>>,
+    matches: Vec<(Node<'a>, Node<'b>)>,
+}
+
+impl<'a, 'b> EditScript<'a, 'b> {
+    /// Get the edits, ordered by the position of their node in the new tree,
+    /// followed by the deletions in the order of the old tree.
+    #[must_use]
+    pub fn edits(&self) -> &[Edit<'a, 'b>] {
+        &self.edits
+    }
+
+    /// Get the pairs of old and new nodes that correspond to each other, in
+    /// the order of the old tree.
+    #[must_use]
+    pub fn matches(&self) -> &[(Node<'a>, Node<'b>)] {
+        &self.matches
+    }
+
+    /// Check if the two trees are structurally identical.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.edits.is_empty()
+    }
+}
+
+impl<'a, 'b> IntoIterator for EditScript<'a, 'b> {
+    type Item = Edit<'a, 'b>;
+    type IntoIter = vec::IntoIter<Edit<'a, 'b>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.edits.into_iter()
+    }
+}
+
+/// Compute the structural differences between the syntax trees rooted at `old`
+/// and `new`.
+///
+/// The sources are the texts that the two trees were parsed from. They are
+/// used to compare leaf nodes, so that a renamed identifier, for example, is
+/// reported as an update.
+///
+/// Nodes are matched in two phases. First, the largest subtrees that have
+/// identical structure and text in both trees are matched. Then, nodes of the
+/// same kind whose descendants are mostly matched to each other are matched,
+/// and so are their remaining children, where their kinds line up. The root
+/// nodes are always matched.
+#[must_use]
+pub fn diff<'a, 'b>(
+    old: Node<'a>,
+    old_source: &[u8],
+    new: Node<'b>,
+    new_source: &[u8],
+    options: Option<DiffOptions>,
+) -> EditScript<'a, 'b> {
+    let mut matcher = Matcher {
+        old: TreeIndex::new(old, old_source),
+        new: TreeIndex::new(new, new_source),
+        old_to_new: Vec::new(),
+        new_to_old: Vec::new(),
+        options: options.unwrap_or_default(),
+    };
+    matcher.old_to_new = vec![None; matcher.old.nodes.len()];
+    matcher.new_to_old = vec![None; matcher.new.nodes.len()];
+    matcher.match_top_down();
+    matcher.match_bottom_up();
+    matcher.edit_script()
+}
+
+/// A node of a [`TreeIndex`], which refers to other nodes by their index in
+/// pre-order.
+struct NodeInfo<'t> {
+    node: Node<'t>,
+    parent: Option<usize>,
+    /// The index of this node among its parent's children.
+    child_index: usize,
+    children: Vec<usize>,
+    /// The number of nodes in this node's subtree, including itself.
+    size: usize,
+    /// The length of the longest path from this node to a leaf, plus one.
+    height: usize,
+    /// A hash of the kinds and leaf text of this node's subtree.
+    hash: u64,
+}
+
+/// The nodes of a syntax tree, in pre-order.
+struct TreeIndex<'t, 's> {
+    nodes: Vec<NodeInfo<'t>>,
+    source: &'s [u8],
+}
+
+impl<'t, 's> TreeIndex<'t, 's> {
+    fn new(root: Node<'t>, source: &'s [u8]) -> Self {
+        let mut nodes = Vec::<NodeInfo>::new();
+        let mut ancestors = Vec::<usize>::new();
+        let mut cursor = root.walk();
+        'outer: loop {
+            let index = nodes.len();
+            let parent = ancestors.last().copied();
+            let mut child_index = 0;
+            if let Some(parent) = parent {
+                child_index = nodes[parent].children.len();
+                nodes[parent].children.push(index);
+            }
+            nodes.push(NodeInfo {
+                node: cursor.node(),
+                parent,
+                child_index,
+                children: Vec::new(),
+                size: 1,
+                height: 1,
+                hash: 0,
+            });
+            if cursor.goto_first_child() {
+                ancestors.push(index);
+                continue;
+            }
+            while !cursor.goto_next_sibling() {
+                if ancestors.pop().is_none() || !cursor.goto_parent() {
+                    break 'outer;
+                }
+            }
+        }
+
+        // Children always come after their parents in pre-order.
+        let mut result = Self { nodes, source };
+        for index in (0..result.nodes.len()).rev() {
+            let info = &result.nodes[index];
+            let mut hash = fnv_hash(FNV_OFFSET, &info.node.kind_id().to_le_bytes());
+            if info.children.is_empty() {
+                hash = fnv_hash(hash, result.text(index));
+            }
+            let (mut size, mut height) = (1, 1);
+            for &child in &info.children {
+                let child = &result.nodes[child];
+                size += child.size;
+                height = height.max(child.height + 1);
+                hash = fnv_hash(hash, &child.hash.to_le_bytes());
+            }
+            let info = &mut result.nodes[index];
+            info.size = size;
+            info.height = height;
+            info.hash = hash;
+        }
+        result
+    }
+
+    fn text(&self, index: usize) -> &'s [u8] {
+        let range = self.nodes[index].node.byte_range();
+        self.source.get(range).unwrap_or_default()
+    }
+
+    /// Get the indices of the given node's descendants, excluding itself.
+    fn descendants(&self, index: usize) -> ops::Range<usize> {
+        index + 1..index + self.nodes[index].size
+    }
+
+    fn is_descendant(&self, descendant: usize, ancestor: usize) -> bool {
+        self.descendants(ancestor).contains(&descendant)
+    }
+
+    fn post_order(&self) -> Vec<usize> {
+        let mut result = Vec::with_capacity(self.nodes.len());
+        let mut stack = vec![(0, 0)];
+        while let Some((index, child_index)) = stack.pop() {
+            if let Some(&child) = self.nodes[index].children.get(child_index) {
+                stack.push((index, child_index + 1));
+                stack.push((child, 0));
+            } else {
+                result.push(index);
+            }
+        }
+        result
+    }
+
+    /// Check if two nodes have the same kind and, for leaves, the same text.
+    fn same_label(&self, index: usize, other: &TreeIndex, other_index: usize) -> bool {
+        let (node, other_node) = (self.nodes[index].node, other.nodes[other_index].node);
+        if node.kind_id() != other_node.kind_id() {
+            return false;
+        }
+        node.child_count() > 0 || self.text(index) == other.text(other_index)
+    }
+}
+
+/// A priority queue of nodes, ordered by height.
+struct HeightQueue(BTreeMap<usize, Vec<usize>>);
+
+impl HeightQueue {
+    fn push(&mut self, tree: &TreeIndex, index: usize) {
+        self.0
+            .entry(tree.nodes[index].height)
+            .or_default()
+            .push(index);
+    }
+
+    fn open(&mut self, tree: &TreeIndex, index: usize) {
+        for &child in &tree.nodes[index].children {
+            self.push(tree, child);
+        }
+    }
+
+    fn peek_height(&self) -> Option<usize> {
+        self.0.keys().next_back().copied()
+    }
+
+    fn pop(&mut self) -> Vec<usize> {
+        self.0
+            .pop_last()
+            .map(|(_, nodes)| nodes)
+            .unwrap_or_default()
+    }
+}
+
+struct Matcher<'a, 'b, 's> {
+    old: TreeIndex<'a, 's>,
+    new: TreeIndex<'b, 's>,
+    old_to_new: Vec<Option<usize>>,
+    new_to_old: Vec<Option<usize>>,
+    options: DiffOptions,
+}
+
+impl<'a, 'b> Matcher<'a, 'b, '_> {
+    fn add_match(&mut self, old: usize, new: usize) {
+        self.old_to_new[old] = Some(new);
+        self.new_to_old[new] = Some(old);
+    }
+
+    /// Check if two subtrees have the same shape, kinds and leaf text, so
+    /// that subtrees whose hashes collide are not matched.
+    fn is_isomorphic(&self, old: usize, new: usize) -> bool {
+        let size = self.old.nodes[old].size;
+        size == self.new.nodes[new].size
+            && (0..size).all(|offset| {
+                let (old, new) = (old + offset, new + offset);
+                self.old.nodes[old].children.len() == self.new.nodes[new].children.len()
+                    && self.old.same_label(old, &self.new, new)
+            })
+    }
+
+    /// Match every node of two subtrees, if they are isomorphic.
+    fn add_subtree_match(&mut self, old: usize, new: usize) {
+        if !self.is_isomorphic(old, new) {
+            return;
+        }
+        for offset in 0..self.old.nodes[old].size {
+            self.add_match(old + offset, new + offset);
+        }
+    }
+
+    /// Get the ratio of descendants of `old` and `new` that are matched to
+    /// each other.
+    fn dice(&self, old: usize, new: usize) -> f64 {
+        let common = self
+            .old
+            .descendants(old)
+            .filter(|&d| self.old_to_new[d].is_some_and(|m| self.new.is_descendant(m, new)))
+            .count();
+        let total = self.old.nodes[old].size + self.new.nodes[new].size - 2;
+        if total == 0 {
+            return 0.0;
+        }
+        2.0 * common as f64 / total as f64
+    }
+
+    /// Match the tallest isomorphic subtrees of the two trees.
+    fn match_top_down(&mut self) {
+        let mut old_queue = HeightQueue(BTreeMap::new());
+        let mut new_queue = HeightQueue(BTreeMap::new());
+        old_queue.push(&self.old, 0);
+        new_queue.push(&self.new, 0);
+
+        while let (Some(old_height), Some(new_height)) =
+            (old_queue.peek_height(), new_queue.peek_height())
+        {
+            if old_height.min(new_height) < self.options.min_height {
+                break;
+            }
+            if old_height > new_height {
+                for index in old_queue.pop() {
+                    old_queue.open(&self.old, index);
+                }
+                continue;
+            }
+            if new_height > old_height {
+                for index in new_queue.pop() {
+                    new_queue.open(&self.new, index);
+                }
+                continue;
+            }
+
+            let old_nodes = old_queue.pop();
+            let new_nodes = new_queue.pop();
+            let mut groups = BTreeMap::<u64, (Vec<usize>, Vec<usize>)>::new();
+            for &index in &old_nodes {
+                groups
+                    .entry(self.old.nodes[index].hash)
+                    .or_default()
+                    .0
+                    .push(index);
+            }
+            for &index in &new_nodes {
+                if let Some(group) = groups.get_mut(&self.new.nodes[index].hash) {
+                    group.1.push(index);
+                }
+            }
+
+            // Subtrees with a unique counterpart are matched right away. The
+            // others are matched in order of how similar their parents are,
+            // unless there are so many of them that comparing all of their
+            // parents would be too slow, in which case they are matched in
+            // the order in which they appear.
+            let mut candidates = Vec::new();
+            for (old_group, new_group) in groups.into_values() {
+                match (old_group.as_slice(), new_group.as_slice()) {
+                    (_, []) => {}
+                    (&[old], &[new]) => self.add_subtree_match(old, new),
+                    _ if old_group.len() * new_group.len() > MAX_AMBIGUOUS_PAIRS => {
+                        for (&old, &new) in old_group.iter().zip(&new_group) {
+                            self.add_subtree_match(old, new);
+                        }
+                    }
+                    _ => {
+                        for &old in &old_group {
+                            for &new in &new_group {
+                                candidates.push((old, new));
+                            }
+                        }
+                    }
+                }
+            }
+            let mut parent_dice = BTreeMap::new();
+            let mut candidates = candidates
+                .into_iter()
+                .map(|(old, new)| {
+                    let dice = match (self.old.nodes[old].parent, self.new.nodes[new].parent) {
+                        (Some(old_parent), Some(new_parent)) => *parent_dice
+                            .entry((old_parent, new_parent))
+                            .or_insert_with(|| self.dice(old_parent, new_parent)),
+                        _ => 0.0,
+                    };
+                    (dice, old, new)
+                })
+                .collect::<Vec<_>>();
+            candidates.sort_by(|a, b| b.0.total_cmp(&a.0));
+            for (_, old, new) in candidates {
+                if self.old_to_new[old].is_none() && self.new_to_old[new].is_none() {
+                    self.add_subtree_match(old, new);
+                }
+            }
+
+            for index in old_nodes {
+                if self.old_to_new[index].is_none() {
+                    old_queue.open(&self.old, index);
+                }
+            }
+            for index in new_nodes {
+                if self.new_to_old[index].is_none() {
+                    new_queue.open(&self.new, index);
+                }
+            }
+        }
+    }
+
+    /// Match the remaining inner nodes whose descendants are mostly matched to
+    /// each other, and then their children.
+    fn match_bottom_up(&mut self) {
+        for old in self.old.post_order() {
+            if self.old_to_new[old].is_some() {
+                continue;
+            }
+            if old == 0 {
+                if self.new_to_old[0].is_none() {
+                    self.add_match(0, 0);
+                    self.recover(0, 0);
+                }
+                continue;
+            }
+            if self.old.nodes[old].children.is_empty() {
+                continue;
+            }
+
+            let kind_id = self.old.nodes[old].node.kind_id();
+            let mut candidates = Vec::new();
+            for descendant in self.old.descendants(old) {
+                let Some(mut new) = self.old_to_new[descendant] else {
+                    continue;
+                };
+                while let Some(parent) = self.new.nodes[new].parent {
+                    new = parent;
+                    if self.new_to_old[new].is_none()
+                        && self.new.nodes[new].node.kind_id() == kind_id
+                    {
+                        candidates.push(new);
+                    }
+                }
+            }
+            candidates.sort_unstable();
+            candidates.dedup();
+
+            let best = candidates
+                .into_iter()
+                .map(|new| (self.dice(old, new), Reverse(new)))
+                .max_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+            if let Some((dice, Reverse(new))) = best {
+                if dice >= self.options.min_dice {
+                    self.add_match(old, new);
+                    self.recover(old, new);
+                }
+            }
+        }
+    }
+
+    /// Match the unmatched children of two matched nodes: first those that
+    /// are isomorphic, and then those of the same kind, in order. The
+    /// children that are matched by kind are recovered in turn, from a stack
+    /// of pairs rather than by recursion, so that deeply nested trees can't
+    /// overflow the call stack.
+    fn recover(&mut self, old: usize, new: usize) {
+        let mut stack = vec![(old, new)];
+        while let Some((old, new)) = stack.pop() {
+            let old_children = self.unmatched_old_children(old);
+            let new_children = self.unmatched_new_children(new);
+            for (old_child, new_child) in
+                longest_common_subsequence(&old_children, &new_children, |a, b| {
+                    self.old.nodes[a].hash == self.new.nodes[b].hash
+                })
+            {
+                self.add_subtree_match(old_child, new_child);
+            }
+
+            let old_children = self.unmatched_old_children(old);
+            let new_children = self.unmatched_new_children(new);
+            let matches = longest_common_subsequence(&old_children, &new_children, |a, b| {
+                self.old.nodes[a].node.kind_id() == self.new.nodes[b].node.kind_id()
+            });
+            for &(old_child, new_child) in &matches {
+                self.add_match(old_child, new_child);
+            }
+            stack.extend(matches.into_iter().rev());
+        }
+    }
+
+    fn unmatched_old_children(&self, old: usize) -> Vec<usize> {
+        let children = self.old.nodes[old].children.iter().copied();
+        children.filter(|&c| self.old_to_new[c].is_none()).collect()
+    }
+
+    fn unmatched_new_children(&self, new: usize) -> Vec<usize> {
+        let children = self.new.nodes[new].children.iter().copied();
+        children.filter(|&c| self.new_to_old[c].is_none()).collect()
+    }
+
+    /// Find the matched children of `new` that are out of order relative to
+    /// their counterparts in the old tree.
+    fn reordered_children(&self, new: usize, reordered: &mut [bool]) {
+        let Some(old) = self.new_to_old[new] else {
+            return;
+        };
+        let (new_children, old_children): (Vec<_>, Vec<_>) = self.new.nodes[new]
+            .children
+            .iter()
+            .filter_map(|&c| {
+                let m = self.new_to_old[c]?;
+                (self.old.nodes[m].parent == Some(old)).then_some((c, m))
+            })
+            .unzip();
+
+        // The old children are numbered in pre-order, so the ones that stay in
+        // order are those in the longest increasing subsequence.
+        for &child in &new_children {
+            reordered[child] = true;
+        }
+        for i in longest_increasing_subsequence(&old_children) {
+            reordered[new_children[i]] = false;
+        }
+    }
+
+    fn child_index(&self, new: usize) -> (Node<'b>, usize) {
+        let info = &self.new.nodes[new];
+        let parent = info.parent.unwrap();
+        (self.new.nodes[parent].node, info.child_index)
+    }
+
+    fn edit_script(&self) -> EditScript<'a, 'b> {
+        let mut reordered = vec![false; self.new.nodes.len()];
+        for new in 0..self.new.nodes.len() {
+            self.reordered_children(new, &mut reordered);
+        }
+
+        let mut edits = Vec::new();
+        for (new, info) in self.new.nodes.iter().enumerate() {
+            let Some(old) = self.new_to_old[new] else {
+                if info.parent.is_some_and(|p| self.new_to_old[p].is_some()) {
+                    let (parent, index) = self.child_index(new);
+                    edits.push(Edit::Insert {
+                        node: info.node,
+                        parent,
+                        index,
+                    });
+                }
+                continue;
+            };
+
+            let old_node = self.old.nodes[old].node;
+            if !self.old.same_label(old, &self.new, new) {
+                edits.push(Edit::Update {
+                    old: old_node,
+                    new: info.node,
+                });
+            }
+            if let Some(parent) = info.parent {
+                if self.new_to_old[parent] != self.old.nodes[old].parent || reordered[new] {
+                    let (parent, index) = self.child_index(new);
+                    edits.push(Edit::Move {
+                        old: old_node,
+                        new: info.node,
+                        parent,
+                        index,
+                    });
+                }
+            }
+        }
+
+        let mut matches = Vec::new();
+        for (old, info) in self.old.nodes.iter().enumerate() {
+            if let Some(new) = self.old_to_new[old] {
+                matches.push((info.node, self.new.nodes[new].node));
+            } else if info.parent.is_some_and(|p| self.old_to_new[p].is_some()) {
+                edits.push(Edit::Delete { node: info.node });
+            }
+        }
+
+        EditScript { edits, matches }
+    }
+}
+
+/// Find the longest sequence of pairs of elements of `a` and `b` that are
+/// equal according to `eq`, and that appear in the same order in both.
+///
+/// The common prefix and suffix are matched directly. If the remaining
+/// sequences are too long to compare every element of one to every element of
+/// the other, only the elements at the same offsets are compared.
+fn longest_common_subsequence(
+    a: &[usize],
+    b: &[usize],
+    eq: impl Fn(usize, usize) -> bool,
+) -> Vec<(usize, usize)> {
+    let prefix = a.iter().zip(b).take_while(|&(&a, &b)| eq(a, b)).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|&(&a, &b)| eq(a, b))
+        .count();
+    let (a_middle, b_middle) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+
+    let mut result = a[..prefix]
+        .iter()
+        .copied()
+        .zip(b[..prefix].iter().copied())
+        .collect::<Vec<_>>();
+    if a_middle.len() * b_middle.len() > MAX_LCS_SIZE {
+        let pairs = a_middle.iter().copied().zip(b_middle.iter().copied());
+        result.extend(pairs.filter(|&(a, b)| eq(a, b)));
+    } else {
+        result.extend(full_longest_common_subsequence(a_middle, b_middle, &eq));
+    }
+    result.extend(
+        a[a.len() - suffix..]
+            .iter()
+            .copied()
+            .zip(b[b.len() - suffix..].iter().copied()),
+    );
+    result
+}
+
+fn full_longest_common_subsequence(
+    a: &[usize],
+    b: &[usize],
+    eq: impl Fn(usize, usize) -> bool,
+) -> Vec<(usize, usize)> {
+    if a.is_empty() || b.is_empty() {
+        return Vec::new();
+    }
+
+    let width = b.len() + 1;
+    let mut lengths = vec![0_u32; (a.len() + 1) * width];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lengths[i * width + j] = if eq(a[i], b[j]) {
+                lengths[(i + 1) * width + j + 1] + 1
+            } else {
+                lengths[(i + 1) * width + j].max(lengths[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut result = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if eq(a[i], b[j]) {
+            result.push((a[i], b[j]));
+            i += 1;
+            j += 1;
+        } else if lengths[(i + 1) * width + j] >= lengths[i * width + j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    result
+}
+
+/// Find the indices of the elements of a longest strictly increasing
+/// subsequence of `values`, preferring the elements that come first.
+fn longest_increasing_subsequence(values: &[usize]) -> Vec<usize> {
+    // Walking backwards, `starts[k]` is the index of the largest value that
+    // starts an increasing subsequence of length `k + 1`.
+    let mut starts = Vec::<usize>::new();
+    let mut successors = vec![None; values.len()];
+    for (i, &value) in values.iter().enumerate().rev() {
+        let k = starts.partition_point(|&start| values[start] > value);
+        successors[i] = k.checked_sub(1).map(|k| starts[k]);
+        if k == starts.len() {
+            starts.push(i);
+        } else {
+            starts[k] = i;
+        }
+    }
+
+    let mut result = Vec::with_capacity(starts.len());
+    let mut next = starts.last().copied();
+    while let Some(i) = next {
+        result.push(i);
+        next = successors[i];
+    }
+    result
+}
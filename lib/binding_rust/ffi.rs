@@ -145,11 +145,14 @@ impl TreeCursor<'_> {
 impl Query {
     /// Reconstructs a [`Query`] from a raw pointer.
     ///
+    /// The query's source is not needed, because the query records where
+    /// each of its patterns starts.
+    ///
     /// # Safety
     ///
     /// `ptr` must be non-null.
-    pub unsafe fn from_raw(ptr: *mut TSQuery, source: &str) -> Result<Self, QueryError> {
-        Self::from_raw_parts(ptr, source)
+    pub unsafe fn from_raw(ptr: *mut TSQuery, _source: &str) -> Result<Self, QueryError> {
+        Self::from_raw_parts(ptr)
     }
 
     /// Consumes the [`Query`], returning a raw pointer to the underlying C structure.
@@ -169,6 +172,7 @@ impl QueryCursor {
     pub const unsafe fn from_raw(ptr: *mut TSQueryCursor) -> Self {
         Self {
             ptr: NonNull::new_unchecked(ptr),
+            allocation_stats: crate::allocator::LocalStats::new(),
         }
     }
 
@@ -0,0 +1,142 @@
+use core::ops;
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use crate::{InputEdit, Point};
+
+impl InputEdit {
+    /// Compute the edit that turns `old_text` into `new_text`.
+    ///
+    /// The edited range is found by trimming the longest common prefix and
+    /// suffix of the two texts, so the result describes a single contiguous
+    /// replacement. If the texts are equal, the edit is empty.
+    #[must_use]
+    pub fn from_diff(old_text: &[u8], new_text: &[u8]) -> Self {
+        let prefix_len = old_text
+            .iter()
+            .zip(new_text)
+            .take_while(|(a, b)| a == b)
+            .count();
+        let suffix_len = old_text[prefix_len..]
+            .iter()
+            .rev()
+            .zip(new_text[prefix_len..].iter().rev())
+            .take_while(|(a, b)| a == b)
+            .count();
+
+        let start_position = advance_point(Point::default(), &old_text[..prefix_len]);
+        let old_end_byte = old_text.len() - suffix_len;
+        let new_end_byte = new_text.len() - suffix_len;
+        Self {
+            start_byte: prefix_len,
+            old_end_byte,
+            new_end_byte,
+            start_position,
+            old_end_position: advance_point(start_position, &old_text[prefix_len..old_end_byte]),
+            new_end_position: advance_point(start_position, &new_text[prefix_len..new_end_byte]),
+        }
+    }
+
+    /// Compute the edit that replaces the given byte range of `text` with
+    /// `replacement`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is out of bounds for `text`.
+    #[must_use]
+    pub fn from_replacement(text: &[u8], range: ops::Range<usize>, replacement: &[u8]) -> Self {
+        let start_position = Point::from_byte_offset(text, range.start);
+        replacement_edit(text, start_position, range, replacement)
+    }
+
+    /// Compute the edits that apply a set of replacements to `text`.
+    ///
+    /// Each replacement is a byte range of the original `text`, along with the
+    /// text that should replace it. The ranges may be given in any order, but
+    /// they must not overlap.
+    ///
+    /// The returned edits are ordered from the end of the text to the start,
+    /// so that each edit's offsets remain valid after the previous edits have
+    /// been applied. Pass them to [`Tree::edit`](crate::Tree::edit) in that
+    /// order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any range is out of bounds for `text`, or if two ranges
+    /// overlap.
+    #[must_use]
+    pub fn from_replacements<R: AsRef<[u8]>>(
+        text: &[u8],
+        replacements: &[(ops::Range<usize>, R)],
+    ) -> Vec<Self> {
+        let mut replacements = replacements.iter().collect::<Vec<_>>();
+        replacements.sort_by_key(|(range, _)| (range.start, range.end));
+
+        let mut offset = 0;
+        let mut position = Point::default();
+        let mut result = Vec::with_capacity(replacements.len());
+        for (range, replacement) in replacements {
+            assert!(
+                range.start >= offset,
+                "replacement range {range:?} overlaps another replacement"
+            );
+            position = advance_point(position, &text[offset..range.start]);
+            result.push(replacement_edit(
+                text,
+                position,
+                range.clone(),
+                replacement.as_ref(),
+            ));
+            offset = range.end;
+            position = result.last().unwrap().old_end_position;
+        }
+        result.reverse();
+        result
+    }
+}
+
+impl Point {
+    /// Get the row and column of the given byte offset in `text`.
+    ///
+    /// Rows are separated by `\n` characters, and columns are measured in
+    /// bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `byte_offset` is greater than the length of `text`.
+    #[must_use]
+    pub fn from_byte_offset(text: &[u8], byte_offset: usize) -> Self {
+        advance_point(Self::default(), &text[..byte_offset])
+    }
+}
+
+fn replacement_edit(
+    text: &[u8],
+    start_position: Point,
+    range: ops::Range<usize>,
+    replacement: &[u8],
+) -> InputEdit {
+    InputEdit {
+        start_byte: range.start,
+        old_end_byte: range.end,
+        new_end_byte: range.start + replacement.len(),
+        start_position,
+        old_end_position: advance_point(start_position, &text[range]),
+        new_end_position: advance_point(start_position, replacement),
+    }
+}
+
+/// Get the position that is reached by starting at `point` and moving past
+/// `text`.
+fn advance_point(point: Point, text: &[u8]) -> Point {
+    let mut result = point;
+    for (i, line) in text.split(|&b| b == b'\n').enumerate() {
+        if i > 0 {
+            result.row += 1;
+            result.column = 0;
+        }
+        result.column += line.len();
+    }
+    result
+}
@@ -0,0 +1,498 @@
+#[cfg(not(feature = "std"))]
+use alloc::{collections::VecDeque, string::String, vec, vec::Vec};
+use core::iter;
+#[cfg(feature = "std")]
+use std::collections::VecDeque;
+
+use crate::{
+    InputEdit, Language, Node, Parser, Point, Query, QueryCursor, QueryError, QueryMatch, Range,
+    StreamingIterator, Tree,
+};
+
+/// A language that can appear in a [`LayeredTree`], along with the query that
+/// finds the other languages injected into it.
+///
+/// The injections query uses the same captures and properties as the
+/// `tree-sitter-highlight` crate:
+/// * `@injection.content` captures the nodes whose text is parsed as another
+///   language.
+/// * `@injection.language` captures a node whose text is the name of that
+///   language. The name can also be set with `#set! injection.language`, or
+///   taken from the current layer or its parent with `#set! injection.self`
+///   and `#set! injection.parent`.
+/// * `#set! injection.include-children` includes the text of the content
+///   node's children, which is excluded by default.
+/// * `#set! injection.combined` parses all of the content nodes matched by
+///   the pattern as one document, rather than each of them separately.
+#[derive(Debug)]
+pub struct InjectionConfig {
+    name: String,
+    language: Language,
+    query: Query,
+    combined_patterns: Vec<bool>,
+    content_capture_index: Option<u32>,
+    language_capture_index: Option<u32>,
+}
+
+/// One of the syntax trees in a [`LayeredTree`].
+#[derive(Debug)]
+pub struct LanguageLayer<'c> {
+    config: &'c InjectionConfig,
+    tree: Tree,
+    depth: usize,
+    parent: Option<usize>,
+}
+
+/// A set of syntax trees for a document that contains multiple languages.
+///
+/// The document is first parsed as a single root language. The root tree's
+/// injections query is then used to find the ranges of the document that
+/// belong to other languages, which are parsed into their own trees using
+/// [`Parser::set_included_ranges`], and so on recursively.
+///
+/// To update a layered tree after the document changes, [`edit`](Self::edit)
+/// it and pass it as the old tree to [`parse`](Self::parse). Every layer is
+/// then reparsed incrementally.
+#[derive(Debug)]
+pub struct LayeredTree<'c> {
+    layers: Vec<LanguageLayer<'c>>,
+}
+
+/// An injected language found in a layer, which has yet to be parsed.
+struct Injection<'c> {
+    config: &'c InjectionConfig,
+    ranges: Vec<Range>,
+    parent: Option<usize>,
+    depth: usize,
+}
+
+impl InjectionConfig {
+    /// Create an injection configuration for a language.
+    ///
+    /// # Arguments:
+    /// * `language` The language.
+    /// * `name` The name by which other languages' injections refer to this
+    ///   language.
+    /// * `injections_query` The source of the query that finds the languages
+    ///   injected into this one. It may be empty.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query is invalid.
+    pub fn new(
+        language: Language,
+        name: impl Into<String>,
+        injections_query: &str,
+    ) -> Result<Self, QueryError> {
+        let query = Query::new(&language, injections_query)?;
+        let combined_patterns = (0..query.pattern_count())
+            .map(|i| {
+                query
+                    .property_settings(i)
+                    .iter()
+                    .any(|prop| &*prop.key == "injection.combined")
+            })
+            .collect();
+        Ok(Self {
+            name: name.into(),
+            language,
+            content_capture_index: query.capture_index_for_name("injection.content"),
+            language_capture_index: query.capture_index_for_name("injection.language"),
+            query,
+            combined_patterns,
+        })
+    }
+
+    /// Get the name of the language.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the language.
+    #[must_use]
+    pub const fn language(&self) -> &Language {
+        &self.language
+    }
+
+    /// Get the injections query.
+    #[must_use]
+    pub const fn query(&self) -> &Query {
+        &self.query
+    }
+}
+
+impl<'c> LanguageLayer<'c> {
+    /// Get the configuration of this layer's language.
+    #[must_use]
+    pub const fn config(&self) -> &'c InjectionConfig {
+        self.config
+    }
+
+    /// Get this layer's syntax tree.
+    #[must_use]
+    pub const fn tree(&self) -> &Tree {
+        &self.tree
+    }
+
+    /// Get the number of layers that this layer is nested in. The root layer
+    /// has a depth of zero.
+    #[must_use]
+    pub const fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Get the index of the layer that this layer was injected into, or `None`
+    /// for the root layer.
+    #[must_use]
+    pub const fn parent(&self) -> Option<usize> {
+        self.parent
+    }
+
+    /// Get the ranges of the document that were parsed as this layer.
+    #[must_use]
+    pub fn included_ranges(&self) -> Vec<Range> {
+        self.tree.included_ranges()
+    }
+
+    /// Check if the given byte offset lies within one of this layer's ranges.
+    #[must_use]
+    pub fn contains_byte(&self, offset: usize) -> bool {
+        self.included_ranges()
+            .iter()
+            .any(|range| range.start_byte <= offset && offset < range.end_byte)
+    }
+}
+
+impl<'c> LayeredTree<'c> {
+    /// Parse a document that contains multiple languages.
+    ///
+    /// The parser's language and included ranges are changed while parsing
+    /// each layer. Afterwards, its included ranges are reset to the whole
+    /// document.
+    ///
+    /// Returns `None` if the root language is incompatible with the parser,
+    /// or if a parse is halted by a timeout or cancellation flag that is set
+    /// on the parser. Injected layers whose language is incompatible are
+    /// skipped.
+    ///
+    /// # Arguments:
+    /// * `parser` The parser to use for every layer.
+    /// * `source` The UTF8-encoded text of the document.
+    /// * `config` The configuration of the root language.
+    /// * `old_tree` A previous layered tree of the same document. If the text
+    ///   of the document has changed since `old_tree` was created, then you
+    ///   must edit `old_tree` to match the new text using
+    ///   [`LayeredTree::edit`].
+    /// * `resolver` A function that returns the configuration for a language
+    ///   name found by an injections query, or `None` if the language is
+    ///   unknown. Injections of unknown languages are skipped.
+    pub fn parse(
+        parser: &mut Parser,
+        source: &[u8],
+        config: &'c InjectionConfig,
+        old_tree: Option<&Self>,
+        mut resolver: impl FnMut(&str) -> Option<&'c InjectionConfig>,
+    ) -> Option<Self> {
+        let old_layers = old_tree.map_or(&[][..], |tree| &tree.layers);
+        let mut reused = vec![false; old_layers.len()];
+        // The index of the old layer that each new layer was reparsed from.
+        let mut old_indices = Vec::<Option<usize>>::new();
+
+        let mut layers = Vec::<LanguageLayer>::new();
+        let mut queue = VecDeque::from([Injection {
+            config,
+            ranges: Vec::new(),
+            parent: None,
+            depth: 0,
+        }]);
+        let result = loop {
+            let Some(injection) = queue.pop_front() else {
+                break Some(Self { layers });
+            };
+            if parser.set_language(&injection.config.language).is_err()
+                || parser.set_included_ranges(&injection.ranges).is_err()
+            {
+                if injection.parent.is_none() {
+                    break None;
+                }
+                continue;
+            }
+
+            let old_parent = injection.parent.and_then(|parent| old_indices[parent]);
+            let old_index = old_layers.iter().enumerate().position(|(i, layer)| {
+                !reused[i]
+                    && layer.parent == old_parent
+                    && layer.config.name == injection.config.name
+                    && (injection.ranges.is_empty()
+                        || ranges_overlap(&layer.included_ranges(), &injection.ranges))
+            });
+            if let Some(old_index) = old_index {
+                reused[old_index] = true;
+            }
+
+            let Some(tree) = parser.parse(source, old_index.map(|i| &old_layers[i].tree)) else {
+                break None;
+            };
+
+            let index = layers.len();
+            let parent_name = injection
+                .parent
+                .map(|parent| layers[parent].config.name.as_str());
+            for (config, ranges) in
+                injections(&tree, source, injection.config, parent_name, &mut resolver)
+            {
+                // Skip injections that would parse the same text as the same
+                // language again, so that self-injections cannot recurse
+                // forever.
+                if config.name == injection.config.name && ranges == tree.included_ranges() {
+                    continue;
+                }
+                queue.push_back(Injection {
+                    config,
+                    ranges,
+                    parent: Some(index),
+                    depth: injection.depth + 1,
+                });
+            }
+
+            layers.push(LanguageLayer {
+                config: injection.config,
+                tree,
+                depth: injection.depth,
+                parent: injection.parent,
+            });
+            old_indices.push(old_index);
+        };
+
+        parser.set_included_ranges(&[]).unwrap();
+        result
+    }
+
+    /// Edit every layer's syntax tree to keep it in sync with source code that
+    /// has been edited.
+    pub fn edit(&mut self, edit: &InputEdit) {
+        for layer in &mut self.layers {
+            layer.tree.edit(edit);
+        }
+    }
+
+    /// Get the root layer, which contains the whole document.
+    #[must_use]
+    pub fn root_layer(&self) -> &LanguageLayer<'c> {
+        &self.layers[0]
+    }
+
+    /// Get all of the layers, ordered so that each layer comes after the layer
+    /// that it was injected into.
+    #[must_use]
+    pub fn layers(&self) -> &[LanguageLayer<'c>] {
+        &self.layers
+    }
+
+    /// Get the innermost layer that contains the given byte offset.
+    #[must_use]
+    pub fn layer_at(&self, offset: usize) -> &LanguageLayer<'c> {
+        self.layers
+            .iter()
+            .filter(|layer| layer.contains_byte(offset))
+            .max_by_key(|layer| layer.depth)
+            .unwrap_or_else(|| self.root_layer())
+    }
+
+    /// Get the innermost layer that contains the given byte offset, along with
+    /// the smallest node in that layer that spans the offset.
+    #[must_use]
+    pub fn node_at(&self, offset: usize) -> (&LanguageLayer<'c>, Node<'_>) {
+        let layer = self.layer_at(offset);
+        let root = layer.tree.root_node();
+        let node = root
+            .descendant_for_byte_range(offset, offset)
+            .unwrap_or(root);
+        (layer, node)
+    }
+}
+
+/// Run a layer's injections query, and return the injected languages along
+/// with the ranges of the document that belong to them.
+fn injections<'c>(
+    tree: &Tree,
+    source: &[u8],
+    config: &'c InjectionConfig,
+    parent_name: Option<&str>,
+    resolver: &mut impl FnMut(&str) -> Option<&'c InjectionConfig>,
+) -> Vec<(&'c InjectionConfig, Vec<Range>)> {
+    let parent_ranges = tree.included_ranges();
+    let mut result = Vec::new();
+
+    // The language name, content nodes, and whether to include children, for
+    // each combined pattern.
+    let mut combined = config
+        .combined_patterns
+        .iter()
+        .map(|_| (None, Vec::new(), false))
+        .collect::<Vec<_>>();
+
+    let mut cursor = QueryCursor::new();
+    let mut matches = cursor.matches(&config.query, tree.root_node(), source);
+    while let Some(query_match) = matches.next() {
+        let (language_name, content_node, include_children) =
+            injection_for_match(config, parent_name, query_match, source);
+        if config.combined_patterns[query_match.pattern_index] {
+            let entry = &mut combined[query_match.pattern_index];
+            if language_name.is_some() {
+                entry.0 = language_name;
+            }
+            entry.1.extend(content_node);
+            entry.2 = include_children;
+            continue;
+        }
+        if let (Some(language_name), Some(content_node)) = (language_name, content_node) {
+            if let Some(config) = resolver(language_name) {
+                let ranges = intersect_ranges(&parent_ranges, &[content_node], include_children);
+                if !ranges.is_empty() {
+                    result.push((config, ranges));
+                }
+            }
+        }
+    }
+
+    for (language_name, content_nodes, include_children) in combined {
+        if let (Some(language_name), false) = (language_name, content_nodes.is_empty()) {
+            if let Some(config) = resolver(language_name) {
+                let ranges = intersect_ranges(&parent_ranges, &content_nodes, include_children);
+                if !ranges.is_empty() {
+                    result.push((config, ranges));
+                }
+            }
+        }
+    }
+    result
+}
+
+/// Get the language name and content node of an injection match, along with
+/// whether the content node's children should be included.
+fn injection_for_match<'a>(
+    config: &'a InjectionConfig,
+    parent_name: Option<&'a str>,
+    query_match: &QueryMatch<'_, 'a>,
+    source: &'a [u8],
+) -> (Option<&'a str>, Option<Node<'a>>, bool) {
+    let mut language_name = None;
+    let mut content_node = None;
+    for capture in query_match.captures {
+        let index = Some(capture.index);
+        if index == config.language_capture_index {
+            language_name = capture.node.utf8_text(source).ok();
+        } else if index == config.content_capture_index {
+            content_node = Some(capture.node);
+        }
+    }
+
+    let mut include_children = false;
+    for prop in config.query.property_settings(query_match.pattern_index) {
+        match prop.key.as_ref() {
+            "injection.language" if language_name.is_none() => {
+                language_name = prop.value.as_deref();
+            }
+            "injection.self" if language_name.is_none() => {
+                language_name = Some(config.name.as_str());
+            }
+            "injection.parent" if language_name.is_none() => language_name = parent_name,
+            "injection.include-children" => include_children = true,
+            _ => {}
+        }
+    }
+    (language_name, content_node, include_children)
+}
+
+/// Compute the ranges of the given nodes, excluding the ranges of their
+/// children unless `includes_children` is set, and clipped to the ranges of
+/// the layer that contains them.
+fn intersect_ranges(
+    parent_ranges: &[Range],
+    nodes: &[Node],
+    includes_children: bool,
+) -> Vec<Range> {
+    let mut cursor = nodes[0].walk();
+    let mut result = Vec::new();
+    let mut parent_range_iter = parent_ranges.iter();
+    let Some(mut parent_range) = parent_range_iter.next() else {
+        return result;
+    };
+    for node in nodes {
+        let mut preceding_range = Range {
+            start_byte: 0,
+            start_point: Point::new(0, 0),
+            end_byte: node.start_byte(),
+            end_point: node.start_position(),
+        };
+        let following_range = Range {
+            start_byte: node.end_byte(),
+            start_point: node.end_position(),
+            end_byte: usize::MAX,
+            end_point: Point::new(usize::MAX, usize::MAX),
+        };
+
+        for excluded_range in node
+            .children(&mut cursor)
+            .filter(|_| !includes_children)
+            .map(|child| child.range())
+            .chain(iter::once(following_range))
+        {
+            let mut range = Range {
+                start_byte: preceding_range.end_byte,
+                start_point: preceding_range.end_point,
+                end_byte: excluded_range.start_byte,
+                end_point: excluded_range.start_point,
+            };
+            preceding_range = excluded_range;
+
+            if range.end_byte < parent_range.start_byte {
+                continue;
+            }
+
+            while parent_range.start_byte <= range.end_byte {
+                if parent_range.end_byte > range.start_byte {
+                    if range.start_byte < parent_range.start_byte {
+                        range.start_byte = parent_range.start_byte;
+                        range.start_point = parent_range.start_point;
+                    }
+
+                    if parent_range.end_byte < range.end_byte {
+                        if range.start_byte < parent_range.end_byte {
+                            result.push(Range {
+                                start_byte: range.start_byte,
+                                start_point: range.start_point,
+                                end_byte: parent_range.end_byte,
+                                end_point: parent_range.end_point,
+                            });
+                        }
+                        range.start_byte = parent_range.end_byte;
+                        range.start_point = parent_range.end_point;
+                    } else {
+                        if range.start_byte < range.end_byte {
+                            result.push(range);
+                        }
+                        break;
+                    }
+                }
+
+                if let Some(next_range) = parent_range_iter.next() {
+                    parent_range = next_range;
+                } else {
+                    return result;
+                }
+            }
+        }
+    }
+    result
+}
+
+/// Check if any range in `a` overlaps any range in `b`.
+fn ranges_overlap(a: &[Range], b: &[Range]) -> bool {
+    a.iter().any(|a| {
+        b.iter()
+            .any(|b| a.start_byte < b.end_byte && b.start_byte < a.end_byte)
+    })
+}
@@ -2,7 +2,31 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
+mod allocator;
+mod chunked_text;
+mod completion;
+mod diagnostics;
+pub mod diff;
 pub mod ffi;
+mod input_edit;
+mod layered_tree;
+mod line_index;
+mod logging;
+mod node_handle;
+mod parse_future;
+#[cfg(feature = "std")]
+mod pool;
+mod predicate;
+mod query_builder;
+#[cfg(feature = "std")]
+mod reader;
+mod rewriter;
+mod selector;
+mod structural_hash;
+#[cfg(feature = "tracking-allocator")]
+mod tracking_allocator;
+mod traversal;
+mod tree_stats;
 mod util;
 
 #[cfg(not(feature = "std"))]
@@ -31,6 +55,38 @@ use std::os::windows::io::AsRawHandle;
 pub use streaming_iterator::{StreamingIterator, StreamingIteratorMut};
 use tree_sitter_language::LanguageFn;
 
+pub use allocator::{install_allocator, AllocatorError};
+pub use chunked_text::{ChunkedText, ChunkedTextProvider, TextChunks};
+pub use diagnostics::{SyntaxDiagnostic, SyntaxDiagnosticKind};
+pub use layered_tree::{InjectionConfig, LanguageLayer, LayeredTree};
+pub use line_index::{LineIndex, LspPosition, LspRange, PositionEncoding};
+#[cfg(feature = "log")]
+#[cfg_attr(docsrs, doc(cfg(feature = "log")))]
+pub use logging::log_logger;
+#[cfg(feature = "tracing")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tracing")))]
+pub use logging::tracing_logger;
+pub use logging::LogEvent;
+pub use node_handle::NodeHandle;
+pub use parse_future::ParseFuture;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub use pool::{ParseBatchOptions, ParserPool};
+use predicate::CustomPredicate;
+pub use predicate::{PredicateArg, PredicateText, UnregisteredPredicates};
+pub use query_builder::{PatternPredicateArg, QueryBuilder, QueryPattern};
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub use reader::InputEncoding;
+pub use rewriter::{Rewrite, RewriteError, Rewriter};
+pub use selector::{Selector, SelectorError, SelectorErrorKind, SelectorMatches};
+pub use structural_hash::StructuralHashOptions;
+#[cfg(feature = "tracking-allocator")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tracking-allocator")))]
+pub use tracking_allocator::{AllocationStats, TrackingAllocator};
+pub use traversal::{BreadthFirst, Leaves, Postorder, Preorder, VisitAction, Visitor};
+pub use tree_stats::TreeStats;
+
 #[cfg(feature = "wasm")]
 mod wasm_language;
 #[cfg(feature = "wasm")]
@@ -220,7 +276,7 @@ impl Drop for QueryCursorOptionsDrop {
 }
 
 /// A type of log message.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum LogType {
     Parse,
     Lex,
@@ -259,6 +315,9 @@ pub struct Query {
     property_settings: Box<[Box<[QueryProperty]>]>,
     property_predicates: Box<[Box<[(QueryProperty, bool)]>]>,
     general_predicates: Box<[Box<[QueryPredicate]>]>,
+    pattern_rows: Box<[usize]>,
+    custom_predicates: Vec<CustomPredicate>,
+    unregistered_predicates: UnregisteredPredicates,
 }
 
 /// A quantifier for captures
@@ -288,6 +347,7 @@ impl From<ffi::TSQuantifier> for CaptureQuantifier {
 #[doc(alias = "TSQueryCursor")]
 pub struct QueryCursor {
     ptr: NonNull<ffi::TSQueryCursor>,
+    allocation_stats: allocator::LocalStats,
 }
 
 /// A key-value pair associated with a particular pattern in a [`Query`].
@@ -322,6 +382,8 @@ pub struct QueryMatch<'cursor, 'tree> {
 /// A sequence of [`QueryMatch`]es associated with a given [`QueryCursor`].
 pub struct QueryMatches<'query, 'tree: 'query, T: TextProvider<I>, I: AsRef<[u8]>> {
     ptr: *mut ffi::TSQueryCursor,
+    /// The stats of the cursor, which stays borrowed while this exists.
+    allocation_stats: *mut allocator::LocalStats,
     query: &'query Query,
     text_provider: T,
     buffer1: Vec<u8>,
@@ -337,6 +399,8 @@ pub struct QueryMatches<'query, 'tree: 'query, T: TextProvider<I>, I: AsRef<[u8]
 /// be used to access the new capture inside of the [`QueryMatch::captures`]'s [`captures`].
 pub struct QueryCaptures<'query, 'tree: 'query, T: TextProvider<I>, I: AsRef<[u8]>> {
     ptr: *mut ffi::TSQueryCursor,
+    /// The stats of the cursor, which stays borrowed while this exists.
+    allocation_stats: *mut allocator::LocalStats,
     query: &'query Query,
     text_provider: T,
     buffer1: Vec<u8>,
@@ -377,6 +441,23 @@ pub enum LanguageError {
 #[derive(Debug, PartialEq, Eq)]
 pub struct IncludedRangesError(pub usize);
 
+/// An error that occurred in [`Parser::set_start_symbol`].
+#[derive(Debug, PartialEq, Eq)]
+pub struct StartSymbolError(pub u16);
+
+/// An error that occurred in [`Tree::deserialize`] or [`Query::deserialize`].
+#[derive(Debug, PartialEq, Eq)]
+pub enum SnapshotError {
+    /// The snapshot is truncated or otherwise malformed.
+    Format,
+    /// The snapshot was written with an unsupported snapshot format version.
+    Version,
+    /// The snapshot belongs to a different language.
+    Language,
+    /// The snapshot was written for a different ABI version of the language.
+    LanguageVersion,
+}
+
 /// An error that occurred when trying to create a [`Query`].
 #[derive(Debug, PartialEq, Eq)]
 pub struct QueryError {
@@ -463,6 +544,24 @@ impl Language {
         }
     }
 
+    /// Get a hash of the tables of this language, for use in the keys of
+    /// caches of syntax trees, compiled queries and other data that depend on
+    /// the language.
+    ///
+    /// Unlike the [`metadata`](Language::metadata), the fingerprint changes
+    /// whenever the language's symbols, fields, parse tables or lex tables
+    /// change. Parsers that were generated with ABI version 16 or later embed
+    /// it, so it is cheap to read. For older parsers, it is computed from the
+    /// symbols, fields and parse tables on each call. It cannot cover their
+    /// lex tables, which are compiled into their lex functions, so two older
+    /// parsers that only differ in their tokens' patterns have the same
+    /// fingerprint.
+    #[doc(alias = "ts_language_fingerprint")]
+    #[must_use]
+    pub fn fingerprint(&self) -> u64 {
+        unsafe { ffi::ts_language_fingerprint(self.0) }
+    }
+
     /// Get the number of distinct node types in this language.
     #[doc(alias = "ts_language_symbol_count")]
     #[must_use]
@@ -689,6 +788,36 @@ impl Parser {
         (!ptr.is_null()).then_some(LanguageRef(ptr, PhantomData))
     }
 
+    /// Set the rule from which the parser should begin parsing.
+    ///
+    /// By default, the parser parses entire documents using the grammar's
+    /// first rule. A grammar can declare additional entry points with the
+    /// `start_rules` option, which makes it possible to parse fragments of a
+    /// document, such as a single expression. Pass the id of one of those
+    /// rules, as returned by [`Language::id_for_node_kind`], to start parsing
+    /// from it, or zero to restore the default.
+    ///
+    /// Returns a [`StartSymbolError`] if the parser has no language, or if
+    /// the language has no start rule with the given symbol. Setting the
+    /// start symbol resets the parser, like [`Parser::reset`], and setting
+    /// the language restores the default start symbol.
+    #[doc(alias = "ts_parser_set_start_symbol")]
+    pub fn set_start_symbol(&mut self, symbol: u16) -> Result<(), StartSymbolError> {
+        if unsafe { ffi::ts_parser_set_start_symbol(self.0.as_ptr(), symbol) } {
+            Ok(())
+        } else {
+            Err(StartSymbolError(symbol))
+        }
+    }
+
+    /// Get the rule from which the parser begins parsing, or zero if it uses
+    /// the grammar's first rule.
+    #[doc(alias = "ts_parser_start_symbol")]
+    #[must_use]
+    pub fn start_symbol(&self) -> u16 {
+        unsafe { ffi::ts_parser_start_symbol(self.0.as_ptr()) }
+    }
+
     /// Get the parser's current logger.
     #[doc(alias = "ts_parser_logger")]
     #[must_use]
@@ -923,11 +1052,16 @@ impl Parser {
 
         let c_old_tree = old_tree.map_or(ptr::null_mut(), |t| t.0.as_ptr());
         unsafe {
-            let c_new_tree = ffi::ts_parser_parse_with_options(
-                self.0.as_ptr(),
-                c_old_tree,
-                c_input,
-                parse_options,
+            let c_new_tree = allocator::track(
+                || {
+                    ffi::ts_parser_parse_with_options(
+                        self.0.as_ptr(),
+                        c_old_tree,
+                        c_input,
+                        parse_options,
+                    )
+                },
+                |tree| tree.cast(),
             );
 
             NonNull::new(c_new_tree).map(Tree)
@@ -1061,11 +1195,16 @@ impl Parser {
 
         let c_old_tree = old_tree.map_or(ptr::null_mut(), |t| t.0.as_ptr());
         unsafe {
-            let c_new_tree = ffi::ts_parser_parse_with_options(
-                self.0.as_ptr(),
-                c_old_tree,
-                c_input,
-                parse_options,
+            let c_new_tree = allocator::track(
+                || {
+                    ffi::ts_parser_parse_with_options(
+                        self.0.as_ptr(),
+                        c_old_tree,
+                        c_input,
+                        parse_options,
+                    )
+                },
+                |tree| tree.cast(),
             );
 
             NonNull::new(c_new_tree).map(Tree)
@@ -1178,11 +1317,16 @@ impl Parser {
 
         let c_old_tree = old_tree.map_or(ptr::null_mut(), |t| t.0.as_ptr());
         unsafe {
-            let c_new_tree = ffi::ts_parser_parse_with_options(
-                self.0.as_ptr(),
-                c_old_tree,
-                c_input,
-                parse_options,
+            let c_new_tree = allocator::track(
+                || {
+                    ffi::ts_parser_parse_with_options(
+                        self.0.as_ptr(),
+                        c_old_tree,
+                        c_input,
+                        parse_options,
+                    )
+                },
+                |tree| tree.cast(),
             );
 
             NonNull::new(c_new_tree).map(Tree)
@@ -1286,11 +1430,16 @@ impl Parser {
 
         let c_old_tree = old_tree.map_or(ptr::null_mut(), |t| t.0.as_ptr());
         unsafe {
-            let c_new_tree = ffi::ts_parser_parse_with_options(
-                self.0.as_ptr(),
-                c_old_tree,
-                c_input,
-                parse_options,
+            let c_new_tree = allocator::track(
+                || {
+                    ffi::ts_parser_parse_with_options(
+                        self.0.as_ptr(),
+                        c_old_tree,
+                        c_input,
+                        parse_options,
+                    )
+                },
+                |tree| tree.cast(),
             );
 
             NonNull::new(c_new_tree).map(Tree)
@@ -1535,6 +1684,60 @@ impl Tree {
         }
     }
 
+    /// Get the number of bytes of heap memory that are used by the syntax
+    /// tree.
+    ///
+    /// A tree that was created by incremental parsing shares its unchanged
+    /// subtrees with the old tree, so that memory is counted for both of them.
+    /// [`Tree::stats`] reports how much of it is shared.
+    #[doc(alias = "ts_tree_memory_usage")]
+    #[must_use]
+    pub fn memory_usage(&self) -> usize {
+        unsafe { ffi::ts_tree_memory_usage(self.0.as_ptr()) }
+    }
+
+    /// Serialize this syntax tree into a versioned binary snapshot.
+    ///
+    /// The snapshot includes the tree's structure, its included ranges, and
+    /// the name and ABI version of its language. It can be restored with
+    /// [`Tree::deserialize`], and the restored tree can be edited and passed
+    /// to [`Parser::parse`] for incremental parsing.
+    #[doc(alias = "ts_tree_serialize")]
+    #[must_use]
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut length = 0u32;
+        unsafe {
+            let ptr = ffi::ts_tree_serialize(self.0.as_ptr(), core::ptr::addr_of_mut!(length));
+            let result = slice::from_raw_parts(ptr.cast::<u8>(), length as usize).to_vec();
+            (FREE_FN)(ptr.cast::<c_void>());
+            result
+        }
+    }
+
+    /// Restore a syntax tree from a snapshot created by [`Tree::serialize`].
+    ///
+    /// Returns an error if the snapshot is malformed, or if it was created
+    /// from a tree of a different language or language ABI version.
+    #[doc(alias = "ts_tree_deserialize")]
+    pub fn deserialize(language: &Language, bytes: &[u8]) -> Result<Self, SnapshotError> {
+        let length = u32::try_from(bytes.len()).map_err(|_| SnapshotError::Format)?;
+        let mut error = ffi::TSSnapshotErrorNone;
+        let ptr = unsafe {
+            ffi::ts_tree_deserialize(
+                language.0,
+                bytes.as_ptr().cast::<c_char>(),
+                length,
+                core::ptr::addr_of_mut!(error),
+            )
+        };
+        NonNull::new(ptr).map(Self).ok_or(match error {
+            ffi::TSSnapshotErrorVersion => SnapshotError::Version,
+            ffi::TSSnapshotErrorLanguage => SnapshotError::Language,
+            ffi::TSSnapshotErrorLanguageVersion => SnapshotError::LanguageVersion,
+            _ => SnapshotError::Format,
+        })
+    }
+
     /// Print a graph of the tree to the given file descriptor.
     /// The graph is formatted in the DOT language. You may want to pipe this
     /// graph directly to a `dot(1)` process in order to generate SVG
@@ -1570,6 +1773,7 @@ impl fmt::Debug for Tree {
 
 impl Drop for Tree {
     fn drop(&mut self) {
+        allocator::forget(self.0.as_ptr().cast());
         unsafe { ffi::ts_tree_delete(self.0.as_ptr()) }
     }
 }
@@ -2497,11 +2701,11 @@ impl Query {
             });
         }
 
-        unsafe { Self::from_raw_parts(ptr, source) }
+        unsafe { Self::from_raw_parts(ptr) }
     }
 
     #[doc(hidden)]
-    unsafe fn from_raw_parts(ptr: *mut ffi::TSQuery, source: &str) -> Result<Self, QueryError> {
+    unsafe fn from_raw_parts(ptr: *mut ffi::TSQuery) -> Result<Self, QueryError> {
         let ptr = {
             struct TSQueryDrop(*mut ffi::TSQuery);
             impl Drop for TSQueryDrop {
@@ -2522,6 +2726,7 @@ impl Query {
         let mut property_predicates_vec = Vec::with_capacity(pattern_count);
         let mut property_settings_vec = Vec::with_capacity(pattern_count);
         let mut general_predicates_vec = Vec::with_capacity(pattern_count);
+        let mut pattern_rows = Vec::with_capacity(pattern_count);
 
         // Build a vector of strings to store the capture names.
         for i in 0..capture_count {
@@ -2575,12 +2780,8 @@ impl Query {
                     .unwrap_or_default()
             };
 
-            let byte_offset = unsafe { ffi::ts_query_start_byte_for_pattern(ptr.0, i as u32) };
-            let row = source
-                .char_indices()
-                .take_while(|(i, _)| *i < byte_offset as usize)
-                .filter(|(_, c)| *c == '\n')
-                .count();
+            let row = unsafe { ffi::ts_query_start_row_for_pattern(ptr.0, i as u32) as usize };
+            pattern_rows.push(row);
 
             use ffi::TSQueryPredicateStepType as T;
             const TYPE_DONE: T = ffi::TSQueryPredicateStepTypeDone;
@@ -2774,6 +2975,9 @@ impl Query {
             property_predicates: property_predicates_vec.into(),
             property_settings: property_settings_vec.into(),
             general_predicates: general_predicates_vec.into(),
+            pattern_rows: pattern_rows.into(),
+            custom_predicates: Vec::new(),
+            unregistered_predicates: UnregisteredPredicates::Ignore,
         };
 
         core::mem::forget(ptr);
@@ -2781,6 +2985,16 @@ impl Query {
         Ok(result)
     }
 
+    /// Get the language that the query was created for.
+    #[doc(alias = "ts_query_language")]
+    #[must_use]
+    pub fn language(&self) -> LanguageRef<'_> {
+        LanguageRef(
+            unsafe { ffi::ts_query_language(self.ptr.as_ptr()) },
+            PhantomData,
+        )
+    }
+
     /// Get the byte offset where the given pattern starts in the query's
     /// source.
     #[doc(alias = "ts_query_start_byte_for_pattern")]
@@ -2811,6 +3025,92 @@ impl Query {
         }
     }
 
+    /// Get the row where the given pattern starts in the query's source.
+    ///
+    /// Unlike the source itself, this is kept in the query's snapshots, so it
+    /// is also available for a query restored with [`Query::deserialize`].
+    #[doc(alias = "ts_query_start_row_for_pattern")]
+    #[must_use]
+    pub fn start_row_for_pattern(&self, pattern_index: usize) -> usize {
+        assert!(
+            pattern_index < self.pattern_rows.len(),
+            "Pattern index is {pattern_index} but the pattern count is {}",
+            self.pattern_rows.len(),
+        );
+        self.pattern_rows[pattern_index]
+    }
+
+    /// Serialize this query into a versioned binary snapshot.
+    ///
+    /// The snapshot includes the query's compiled patterns, predicates and
+    /// capture names, along with the results of analyzing the patterns, and
+    /// the name and ABI version of its language. It can be restored with
+    /// [`Query::deserialize`], which is much faster than [`Query::new`] for
+    /// large queries.
+    #[doc(alias = "ts_query_serialize")]
+    #[must_use]
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut length = 0u32;
+        unsafe {
+            let ptr = ffi::ts_query_serialize(self.ptr.as_ptr(), core::ptr::addr_of_mut!(length));
+            let result = slice::from_raw_parts(ptr.cast::<u8>(), length as usize).to_vec();
+            (FREE_FN)(ptr.cast::<c_void>());
+            result
+        }
+    }
+
+    /// Restore a query from a snapshot created by [`Query::serialize`].
+    ///
+    /// Returns an error if the snapshot is malformed, or if it was created
+    /// from a query for a different language or language ABI version.
+    #[doc(alias = "ts_query_deserialize")]
+    pub fn deserialize(language: &Language, bytes: &[u8]) -> Result<Self, SnapshotError> {
+        let length = u32::try_from(bytes.len()).map_err(|_| SnapshotError::Format)?;
+        let mut error = ffi::TSSnapshotErrorNone;
+        let ptr = unsafe {
+            ffi::ts_query_deserialize(
+                language.0,
+                bytes.as_ptr().cast::<c_char>(),
+                length,
+                core::ptr::addr_of_mut!(error),
+            )
+        };
+        if ptr.is_null() {
+            return Err(match error {
+                ffi::TSSnapshotErrorVersion => SnapshotError::Version,
+                ffi::TSSnapshotErrorLanguage => SnapshotError::Language,
+                ffi::TSSnapshotErrorLanguageVersion => SnapshotError::LanguageVersion,
+                _ => SnapshotError::Format,
+            });
+        }
+
+        // The capture names and string values are assumed to be valid UTF8,
+        // which a corrupted snapshot does not guarantee.
+        let is_valid_utf8 =
+            |get: unsafe extern "C" fn(*const ffi::TSQuery, u32, *mut u32) -> *const c_char,
+             count: u32| {
+                (0..count).all(|i| unsafe {
+                    let mut length = 0u32;
+                    let value = get(ptr, i, core::ptr::addr_of_mut!(length)).cast::<u8>();
+                    str::from_utf8(slice::from_raw_parts(value, length as usize)).is_ok()
+                })
+            };
+        let is_valid = unsafe {
+            is_valid_utf8(
+                ffi::ts_query_capture_name_for_id,
+                ffi::ts_query_capture_count(ptr),
+            ) && is_valid_utf8(
+                ffi::ts_query_string_value_for_id,
+                ffi::ts_query_string_count(ptr),
+            )
+        };
+        if !is_valid {
+            unsafe { ffi::ts_query_delete(ptr) };
+            return Err(SnapshotError::Format);
+        }
+        unsafe { Self::from_raw_parts(ptr) }.map_err(|_| SnapshotError::Format)
+    }
+
     /// Get the number of patterns in the query.
     #[doc(alias = "ts_query_pattern_count")]
     #[must_use]
@@ -2862,6 +3162,9 @@ impl Query {
     /// * `eq?` and `not-eq?`
     /// * `is?` and `is-not?`
     /// * `set!`
+    ///
+    /// Predicates with a handler from [`register_predicate`](Query::register_predicate)
+    /// are still included.
     #[must_use]
     pub const fn general_predicates(&self, index: usize) -> &[QueryPredicate] {
         &self.general_predicates[index]
@@ -2905,6 +3208,16 @@ impl Query {
         unsafe { ffi::ts_query_is_pattern_non_local(self.ptr.as_ptr(), index as u32) }
     }
 
+    /// Check if this query was compiled into the same patterns, with the same
+    /// predicates and captures, as another query for the same language,
+    /// regardless of where the patterns appear in the queries' source code,
+    /// and of how it is formatted.
+    #[doc(alias = "ts_query_is_equivalent")]
+    #[must_use]
+    pub fn is_equivalent(&self, other: &Self) -> bool {
+        unsafe { ffi::ts_query_is_equivalent(self.ptr.as_ptr(), other.ptr.as_ptr()) }
+    }
+
     /// Check if a given step in a query is 'definite'.
     ///
     /// A query step is 'definite' if its parent pattern will be guaranteed to
@@ -2992,6 +3305,7 @@ impl QueryCursor {
     pub fn new() -> Self {
         Self {
             ptr: unsafe { NonNull::new_unchecked(ffi::ts_query_cursor_new()) },
+            allocation_stats: allocator::LocalStats::new(),
         }
     }
 
@@ -3068,6 +3382,7 @@ impl QueryCursor {
         unsafe { ffi::ts_query_cursor_exec(ptr, query.ptr.as_ptr(), node.0) };
         QueryMatches {
             ptr,
+            allocation_stats: &mut self.allocation_stats,
             query,
             text_provider,
             buffer1: Vec::default(),
@@ -3125,6 +3440,7 @@ impl QueryCursor {
         }
         QueryMatches {
             ptr,
+            allocation_stats: &mut self.allocation_stats,
             query,
             text_provider,
             buffer1: Vec::default(),
@@ -3155,6 +3471,7 @@ impl QueryCursor {
         unsafe { ffi::ts_query_cursor_exec(ptr, query.ptr.as_ptr(), node.0) };
         QueryCaptures {
             ptr,
+            allocation_stats: &mut self.allocation_stats,
             query,
             text_provider,
             buffer1: Vec::default(),
@@ -3211,6 +3528,7 @@ impl QueryCursor {
         }
         QueryCaptures {
             ptr,
+            allocation_stats: &mut self.allocation_stats,
             query,
             text_provider,
             buffer1: Vec::default(),
@@ -3318,6 +3636,8 @@ impl<'tree> QueryMatch<'_, 'tree> {
         buffer2: &mut Vec<u8>,
         text_provider: &mut impl TextProvider<I>,
     ) -> bool {
+        // Regular expressions can only be matched against contiguous text, so
+        // text that is split into multiple chunks is copied into a buffer.
         struct NodeText<'a, T> {
             buffer: &'a mut Vec<u8>,
             first_chunk: Option<T>,
@@ -3349,8 +3669,7 @@ impl<'tree> QueryMatch<'_, 'tree> {
             }
         }
 
-        let mut node_text1 = NodeText::new(buffer1);
-        let mut node_text2 = NodeText::new(buffer2);
+        let mut node_text = NodeText::new(buffer1);
 
         query.text_predicates[self.pattern_index]
             .iter()
@@ -3359,11 +3678,9 @@ impl<'tree> QueryMatch<'_, 'tree> {
                     let mut nodes_1 = self.nodes_for_capture_index(*i);
                     let mut nodes_2 = self.nodes_for_capture_index(*j);
                     while let (Some(node1), Some(node2)) = (nodes_1.next(), nodes_2.next()) {
-                        let mut text1 = text_provider.text(node1);
-                        let mut text2 = text_provider.text(node2);
-                        let text1 = node_text1.get_text(&mut text1);
-                        let text2 = node_text2.get_text(&mut text2);
-                        let is_positive_match = text1 == text2;
+                        let text1 = text_provider.text(node1);
+                        let text2 = text_provider.text(node2);
+                        let is_positive_match = chunked_texts_eq(text1, text2);
                         if is_positive_match != *is_positive && *match_all_nodes {
                             return false;
                         }
@@ -3376,9 +3693,8 @@ impl<'tree> QueryMatch<'_, 'tree> {
                 TextPredicateCapture::EqString(i, s, is_positive, match_all_nodes) => {
                     let nodes = self.nodes_for_capture_index(*i);
                     for node in nodes {
-                        let mut text = text_provider.text(node);
-                        let text = node_text1.get_text(&mut text);
-                        let is_positive_match = text == s.as_bytes();
+                        let text = text_provider.text(node);
+                        let is_positive_match = chunked_text_eq(text, s.as_bytes());
                         if is_positive_match != *is_positive && *match_all_nodes {
                             return false;
                         }
@@ -3392,7 +3708,7 @@ impl<'tree> QueryMatch<'_, 'tree> {
                     let nodes = self.nodes_for_capture_index(*i);
                     for node in nodes {
                         let mut text = text_provider.text(node);
-                        let text = node_text1.get_text(&mut text);
+                        let text = node_text.get_text(&mut text);
                         let is_positive_match = r.is_match(text);
                         if is_positive_match != *is_positive && *match_all_nodes {
                             return false;
@@ -3406,15 +3722,63 @@ impl<'tree> QueryMatch<'_, 'tree> {
                 TextPredicateCapture::AnyString(i, v, is_positive) => {
                     let nodes = self.nodes_for_capture_index(*i);
                     for node in nodes {
-                        let mut text = text_provider.text(node);
-                        let text = node_text1.get_text(&mut text);
-                        if (v.iter().any(|s| text == s.as_bytes())) != *is_positive {
+                        let is_match = v
+                            .iter()
+                            .any(|s| chunked_text_eq(text_provider.text(node), s.as_bytes()));
+                        if is_match != *is_positive {
                             return false;
                         }
                     }
                     true
                 }
             })
+            && self.satisfies_custom_predicates(query, buffer2, text_provider)
+    }
+}
+
+/// Compare text that is split into chunks with a contiguous string, without
+/// copying the chunks.
+fn chunked_text_eq<I: AsRef<[u8]>>(chunks: impl Iterator<Item = I>, mut text: &[u8]) -> bool {
+    for chunk in chunks {
+        let Some(rest) = text.strip_prefix(chunk.as_ref()) else {
+            return false;
+        };
+        text = rest;
+    }
+    text.is_empty()
+}
+
+/// Compare two texts that are split into chunks, which may have different
+/// boundaries, without copying the chunks.
+fn chunked_texts_eq<I: AsRef<[u8]>>(
+    mut chunks1: impl Iterator<Item = I>,
+    mut chunks2: impl Iterator<Item = I>,
+) -> bool {
+    let (mut chunk1, mut chunk2) = (chunks1.next(), chunks2.next());
+    let (mut offset1, mut offset2) = (0, 0);
+    loop {
+        let text1 = chunk1.as_ref().map(|c| &c.as_ref()[offset1..]);
+        let text2 = chunk2.as_ref().map(|c| &c.as_ref()[offset2..]);
+        match (text1, text2) {
+            (None, None) => return true,
+            (Some([]), _) => {
+                chunk1 = chunks1.next();
+                offset1 = 0;
+            }
+            (_, Some([])) => {
+                chunk2 = chunks2.next();
+                offset2 = 0;
+            }
+            (Some(text1), Some(text2)) => {
+                let len = text1.len().min(text2.len());
+                if text1[..len] != text2[..len] {
+                    return false;
+                }
+                offset1 += len;
+                offset2 += len;
+            }
+            _ => return false,
+        }
     }
 }
 
@@ -3441,7 +3805,10 @@ impl<'query, 'tree: 'query, T: TextProvider<I>, I: AsRef<[u8]>> StreamingIterato
         self.current_match = unsafe {
             loop {
                 let mut m = MaybeUninit::<ffi::TSQueryMatch>::uninit();
-                if ffi::ts_query_cursor_next_match(self.ptr, m.as_mut_ptr()) {
+                let ptr = self.ptr;
+                if (*self.allocation_stats)
+                    .track(|| ffi::ts_query_cursor_next_match(ptr, m.as_mut_ptr()))
+                {
                     let result = QueryMatch::new(&m.assume_init(), self.ptr);
                     if result.satisfies_text_predicates(
                         self.query,
@@ -3481,11 +3848,14 @@ impl<'query, 'tree: 'query, T: TextProvider<I>, I: AsRef<[u8]>> StreamingIterato
             loop {
                 let mut capture_index = 0u32;
                 let mut m = MaybeUninit::<ffi::TSQueryMatch>::uninit();
-                if ffi::ts_query_cursor_next_capture(
-                    self.ptr,
-                    m.as_mut_ptr(),
-                    core::ptr::addr_of_mut!(capture_index),
-                ) {
+                let ptr = self.ptr;
+                if (*self.allocation_stats).track(|| {
+                    ffi::ts_query_cursor_next_capture(
+                        ptr,
+                        m.as_mut_ptr(),
+                        core::ptr::addr_of_mut!(capture_index),
+                    )
+                }) {
                     let result = QueryMatch::new(&m.assume_init(), self.ptr);
                     if result.satisfies_text_predicates(
                         self.query,
@@ -3727,6 +4097,12 @@ impl fmt::Display for IncludedRangesError {
     }
 }
 
+impl fmt::Display for StartSymbolError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Symbol {} is not a start rule of the language", self.0)
+    }
+}
+
 impl fmt::Display for LanguageError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -3744,6 +4120,22 @@ impl fmt::Display for LanguageError {
     }
 }
 
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Format => write!(f, "Malformed snapshot"),
+            Self::Version => write!(f, "Unsupported snapshot version"),
+            Self::Language => write!(f, "Snapshot belongs to a different language"),
+            Self::LanguageVersion => {
+                write!(
+                    f,
+                    "Snapshot was created for a different language ABI version"
+                )
+            }
+        }
+    }
+}
+
 impl fmt::Display for QueryError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let msg = match self.kind {
@@ -3888,6 +4280,8 @@ static mut FREE_FN: unsafe extern "C" fn(ptr: *mut c_void) = free;
 
 /// Sets the memory allocation functions that the core library should use.
 ///
+/// Once this has been called, [`install_allocator`] can no longer be used.
+///
 /// # Safety
 ///
 /// This function uses FFI and mutates a static global.
@@ -3899,6 +4293,7 @@ pub unsafe fn set_allocator(
     new_free: Option<unsafe extern "C" fn(ptr: *mut c_void)>,
 ) {
     FREE_FN = new_free.unwrap_or(free);
+    allocator::IS_ALLOCATOR_SET.store(true, core::sync::atomic::Ordering::Release);
     ffi::ts_set_allocator(new_malloc, new_calloc, new_realloc, new_free);
 }
 
@@ -3907,10 +4302,16 @@ pub unsafe fn set_allocator(
 impl error::Error for IncludedRangesError {}
 #[cfg(feature = "std")]
 #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+impl error::Error for StartSymbolError {}
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
 impl error::Error for LanguageError {}
 #[cfg(feature = "std")]
 #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
 impl error::Error for QueryError {}
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+impl error::Error for SnapshotError {}
 
 unsafe impl Send for Language {}
 unsafe impl Sync for Language {}
@@ -0,0 +1,291 @@
+#[cfg(not(feature = "std"))]
+use alloc::{vec, vec::Vec};
+use core::str;
+
+use crate::{Node, Point, Range};
+
+/// The unit in which the columns of an [`LspPosition`] are measured, as
+/// negotiated by the `positionEncoding` capability of the Language Server
+/// Protocol.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum PositionEncoding {
+    /// UTF8 code units, or bytes, which is how the columns of a [`Point`]
+    /// are measured.
+    Utf8,
+    /// UTF16 code units. This is the default encoding of the Language Server
+    /// Protocol.
+    #[default]
+    Utf16,
+    /// UTF32 code units, or Unicode scalar values.
+    Utf32,
+}
+
+/// A position in a text document, with a column measured in a
+/// [`PositionEncoding`], like the `Position` of the Language Server Protocol.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct LspPosition {
+    /// The zero-based line number.
+    pub line: usize,
+    /// The zero-based column, in the units of the position's encoding.
+    pub character: usize,
+}
+
+/// A range of [`LspPosition`]s, like the `Range` of the Language Server
+/// Protocol.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct LspRange {
+    /// The position of the start of the range.
+    pub start: LspPosition,
+    /// The position just past the end of the range.
+    pub end: LspPosition,
+}
+
+/// An index of the lines and multi-byte characters of a text, which converts
+/// between byte offsets, [`Point`]s and [`LspPosition`]s in logarithmic time.
+///
+/// Lines are separated by `\n`, as they are for the rows of a [`Point`]. Bytes
+/// that are not valid UTF8 are counted as one code unit in every encoding.
+///
+/// Positions that lie past the end of their line are clamped to the end of
+/// the line, and positions that lie within a multi-byte character are moved
+/// to the start of the character.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LineIndex {
+    len: usize,
+    line_starts: Vec<usize>,
+    wide_chars: Vec<WideChar>,
+    utf16_shrink: usize,
+    utf32_shrink: usize,
+}
+
+/// A character that is encoded as more than one byte in UTF8.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct WideChar {
+    offset: usize,
+    len: usize,
+    /// The number of code units by which the text before this character is
+    /// shorter in UTF16 and in UTF32 than in UTF8.
+    utf16_shrink_before: usize,
+    utf32_shrink_before: usize,
+}
+
+impl PositionEncoding {
+    /// Get the number of code units of a character with the given length in
+    /// UTF8.
+    const fn char_len(self, utf8_len: usize) -> usize {
+        match self {
+            Self::Utf8 => utf8_len,
+            Self::Utf16 if utf8_len == 4 => 2,
+            Self::Utf16 | Self::Utf32 => 1,
+        }
+    }
+}
+
+impl LineIndex {
+    /// Create an index of the given text.
+    #[must_use]
+    pub fn new(text: &[u8]) -> Self {
+        let mut result = Self {
+            len: text.len(),
+            line_starts: vec![0],
+            ..Self::default()
+        };
+        result.line_starts.extend(
+            text.iter()
+                .enumerate()
+                .filter(|(_, byte)| **byte == b'\n')
+                .map(|(i, _)| i + 1),
+        );
+
+        let mut offset = 0;
+        let mut rest = text;
+        while !rest.is_empty() {
+            let (valid, invalid_len) = match str::from_utf8(rest) {
+                Ok(valid) => (valid, 0),
+                Err(error) => {
+                    let valid = &rest[..error.valid_up_to()];
+                    let invalid_len = error.error_len().unwrap_or(rest.len() - valid.len());
+                    (str::from_utf8(valid).unwrap_or_default(), invalid_len)
+                }
+            };
+            for (i, c) in valid.char_indices() {
+                let len = c.len_utf8();
+                if len > 1 {
+                    result.wide_chars.push(WideChar {
+                        offset: offset + i,
+                        len,
+                        utf16_shrink_before: result.utf16_shrink,
+                        utf32_shrink_before: result.utf32_shrink,
+                    });
+                    result.utf16_shrink += len - PositionEncoding::Utf16.char_len(len);
+                    result.utf32_shrink += len - 1;
+                }
+            }
+            let consumed = valid.len() + invalid_len;
+            offset += consumed;
+            rest = &rest[consumed..];
+        }
+        result
+    }
+
+    /// Get the number of lines in the text. A text that ends with a newline
+    /// has an empty last line.
+    #[must_use]
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// Get the byte offset at which the given line starts.
+    #[must_use]
+    pub fn line_start(&self, line: usize) -> Option<usize> {
+        self.line_starts.get(line).copied()
+    }
+
+    /// Convert a byte offset to a [`Point`].
+    #[must_use]
+    pub fn byte_to_point(&self, offset: usize) -> Point {
+        let offset = offset.min(self.len);
+        let row = self.line_starts.partition_point(|start| *start <= offset) - 1;
+        Point::new(row, offset - self.line_starts[row])
+    }
+
+    /// Convert a [`Point`] to a byte offset.
+    #[must_use]
+    pub fn point_to_byte(&self, point: Point) -> usize {
+        if point.row >= self.line_count() {
+            return self.len;
+        }
+        let start = self.line_starts[point.row];
+        start + point.column.min(self.line_end(point.row) - start)
+    }
+
+    /// Convert a [`Point`] to an [`LspPosition`] in the given encoding.
+    #[must_use]
+    pub fn point_to_position(&self, point: Point, encoding: PositionEncoding) -> LspPosition {
+        self.byte_to_position(self.point_to_byte(point), encoding)
+    }
+
+    /// Convert an [`LspPosition`] in the given encoding to a [`Point`].
+    #[must_use]
+    pub fn position_to_point(&self, position: LspPosition, encoding: PositionEncoding) -> Point {
+        self.byte_to_point(self.position_to_byte(position, encoding))
+    }
+
+    /// Convert a byte offset to an [`LspPosition`] in the given encoding.
+    #[must_use]
+    pub fn byte_to_position(&self, offset: usize, encoding: PositionEncoding) -> LspPosition {
+        let mut offset = offset.min(self.len);
+        let line = self.byte_to_point(offset).row;
+        let start = self.line_starts[line];
+        let first = self.wide_chars.partition_point(|c| c.offset < start);
+        let mut next = self.wide_chars.partition_point(|c| c.offset < offset);
+        if next > first {
+            let previous = self.wide_chars[next - 1];
+            if offset < previous.offset + previous.len {
+                offset = previous.offset;
+                next -= 1;
+            }
+        }
+        LspPosition {
+            line,
+            character: offset
+                - start
+                - (self.shrink_before(next, encoding) - self.shrink_before(first, encoding)),
+        }
+    }
+
+    /// Convert an [`LspPosition`] in the given encoding to a byte offset.
+    #[must_use]
+    pub fn position_to_byte(&self, position: LspPosition, encoding: PositionEncoding) -> usize {
+        if position.line >= self.line_count() {
+            return self.len;
+        }
+        let start = self.line_starts[position.line];
+        let end = self.line_end(position.line);
+        let first = self.wide_chars.partition_point(|c| c.offset < start);
+        let last = self.wide_chars.partition_point(|c| c.offset < end);
+        let base_shrink = self.shrink_before(first, encoding);
+        let column = |c: &WideChar| c.offset - start - (c.shrink_before(encoding) - base_shrink);
+
+        let next = first
+            + self.wide_chars[first..last].partition_point(|c| column(c) < position.character);
+        if next > first {
+            let previous = &self.wide_chars[next - 1];
+            if position.character < column(previous) + encoding.char_len(previous.len) {
+                return previous.offset;
+            }
+        }
+        let offset = start + position.character + self.shrink_before(next, encoding) - base_shrink;
+        offset.min(end)
+    }
+
+    /// Convert a [`Range`] to an [`LspRange`] in the given encoding.
+    #[must_use]
+    pub fn range_to_lsp_range(&self, range: &Range, encoding: PositionEncoding) -> LspRange {
+        LspRange {
+            start: self.byte_to_position(range.start_byte, encoding),
+            end: self.byte_to_position(range.end_byte, encoding),
+        }
+    }
+
+    /// Get the byte offset of the end of the given line, excluding its
+    /// newline.
+    fn line_end(&self, line: usize) -> usize {
+        self.line_starts
+            .get(line + 1)
+            .map_or(self.len, |next_start| next_start - 1)
+    }
+
+    /// Get the number of code units by which the text before the wide
+    /// character at `index` is shorter in the given encoding than in UTF8.
+    fn shrink_before(&self, index: usize, encoding: PositionEncoding) -> usize {
+        match self.wide_chars.get(index) {
+            Some(c) => c.shrink_before(encoding),
+            None => match encoding {
+                PositionEncoding::Utf8 => 0,
+                PositionEncoding::Utf16 => self.utf16_shrink,
+                PositionEncoding::Utf32 => self.utf32_shrink,
+            },
+        }
+    }
+}
+
+impl WideChar {
+    const fn shrink_before(&self, encoding: PositionEncoding) -> usize {
+        match encoding {
+            PositionEncoding::Utf8 => 0,
+            PositionEncoding::Utf16 => self.utf16_shrink_before,
+            PositionEncoding::Utf32 => self.utf32_shrink_before,
+        }
+    }
+}
+
+impl Range {
+    /// Get this range as an [`LspRange`] in the given encoding, using an index
+    /// of the text that it belongs to.
+    #[must_use]
+    pub fn lsp_range(&self, index: &LineIndex, encoding: PositionEncoding) -> LspRange {
+        index.range_to_lsp_range(self, encoding)
+    }
+}
+
+impl Node<'_> {
+    /// Get the range of this node as an [`LspRange`] in the given encoding,
+    /// using an index of the text that the node was parsed from.
+    #[must_use]
+    pub fn lsp_range(&self, index: &LineIndex, encoding: PositionEncoding) -> LspRange {
+        index.range_to_lsp_range(&self.range(), encoding)
+    }
+
+    /// Get the start of this node as an [`LspPosition`] in the given encoding.
+    #[must_use]
+    pub fn start_lsp_position(&self, index: &LineIndex, encoding: PositionEncoding) -> LspPosition {
+        index.byte_to_position(self.start_byte(), encoding)
+    }
+
+    /// Get the end of this node as an [`LspPosition`] in the given encoding.
+    #[must_use]
+    pub fn end_lsp_position(&self, index: &LineIndex, encoding: PositionEncoding) -> LspPosition {
+        index.byte_to_position(self.end_byte(), encoding)
+    }
+}
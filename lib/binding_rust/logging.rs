@@ -0,0 +1,182 @@
+#[cfg(all(not(feature = "std"), any(feature = "log", feature = "tracing")))]
+use alloc::boxed::Box;
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+#[cfg(any(feature = "log", feature = "tracing"))]
+use crate::Logger;
+use crate::{LogType, Point};
+
+/// A structured view of a message that a [`Parser`](crate::Parser) passes to
+/// its logger.
+///
+/// The messages have the form `action key:value, key:value`, such as
+/// `shift state:12` or `lex_internal state:3, row:0, column:4`. The fields
+/// that describe the parse state, the symbol and the position in the
+/// document are extracted, and are `None` if the message does not have them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LogEvent<'a> {
+    /// Whether the message came from the parser or from the lexer.
+    pub log_type: LogType,
+    /// The first word of the message, which names the action of the parser
+    /// or lexer, such as `shift`, `reduce`, `lex_internal`, or `consume`.
+    pub action: &'a str,
+    /// The parse state or lex state, from the `state` field.
+    pub state: Option<u16>,
+    /// The name of a symbol, from the `sym` or `symbol` field.
+    pub symbol: Option<&'a str>,
+    /// The position in the document, from the `row` and `col` or `column`
+    /// fields.
+    pub position: Option<Point>,
+    /// The full message.
+    pub message: &'a str,
+}
+
+impl<'a> LogEvent<'a> {
+    /// Extract the fields of a message that was passed to a parser's logger.
+    #[must_use]
+    pub fn parse(log_type: LogType, message: &'a str) -> Self {
+        let (action, fields) = message.split_once(' ').unwrap_or((message, ""));
+        let mut event = Self {
+            log_type,
+            action,
+            state: None,
+            symbol: None,
+            position: None,
+            message,
+        };
+
+        let mut row = None;
+        let mut column = None;
+        for (key, value) in split_fields(fields) {
+            match key {
+                "state" => event.state = value.parse().ok(),
+                "sym" | "symbol" => event.symbol = Some(value),
+                "row" => row = value.parse().ok(),
+                "col" | "column" => column = value.parse().ok(),
+                _ => {}
+            }
+        }
+        if let (Some(row), Some(column)) = (row, column) {
+            event.position = Some(Point::new(row, column));
+        }
+        event
+    }
+}
+
+/// Split the `key:value` fields of a log message.
+///
+/// The fields are separated by `, `, but symbol names can contain that
+/// separator too, so a piece of the message only starts a new field if it
+/// begins with a key.
+fn split_fields(text: &str) -> Vec<(&str, &str)> {
+    let mut fields = Vec::<(&str, usize, usize)>::new();
+    let mut start = 0;
+    for piece in text.split(", ") {
+        let end = start + piece.len();
+        match piece.split_once(':') {
+            Some((key, _))
+                if !key.is_empty() && key.bytes().all(|b| b.is_ascii_lowercase() || b == b'_') =>
+            {
+                fields.push((key, start + key.len() + 1, end));
+            }
+            _ => {
+                if let Some(field) = fields.last_mut() {
+                    field.2 = end;
+                }
+            }
+        }
+        start = end + 2;
+    }
+    fields
+        .into_iter()
+        .map(|(key, start, end)| (key, &text[start..end]))
+        .collect()
+}
+
+/// Create a logger that emits a parser's log messages as records of the
+/// [`log`] crate.
+///
+/// Parse messages are emitted at the `debug` level with the target
+/// `tree_sitter::parse`, and lex messages at the `trace` level with the
+/// target `tree_sitter::lex`. Each record carries the fields of its
+/// [`LogEvent`] as key-values: `action`, `state`, `symbol`, `row` and
+/// `column`.
+///
+/// ```
+/// let mut parser = tree_sitter::Parser::new();
+/// parser.set_logger(Some(tree_sitter::log_logger()));
+/// ```
+#[cfg(feature = "log")]
+#[cfg_attr(docsrs, doc(cfg(feature = "log")))]
+#[must_use]
+pub fn log_logger() -> Logger<'static> {
+    Box::new(|log_type, message| {
+        let event = LogEvent::parse(log_type, message);
+        let row = event.position.map(|position| position.row);
+        let column = event.position.map(|position| position.column);
+        match log_type {
+            LogType::Parse => log::debug!(
+                target: "tree_sitter::parse",
+                action = event.action,
+                state = event.state,
+                symbol = event.symbol,
+                row = row,
+                column = column;
+                "{message}"
+            ),
+            LogType::Lex => log::trace!(
+                target: "tree_sitter::lex",
+                action = event.action,
+                state = event.state,
+                symbol = event.symbol,
+                row = row,
+                column = column;
+                "{message}"
+            ),
+        }
+    })
+}
+
+/// Create a logger that emits a parser's log messages as [`tracing`] events.
+///
+/// Parse messages are emitted at the `DEBUG` level with the target
+/// `tree_sitter::parse`, and lex messages at the `TRACE` level with the
+/// target `tree_sitter::lex`. Each event carries the fields of its
+/// [`LogEvent`]: `action`, `state`, `symbol`, `row` and `column`. Fields
+/// that the message does not have are left empty.
+///
+/// ```
+/// let mut parser = tree_sitter::Parser::new();
+/// parser.set_logger(Some(tree_sitter::tracing_logger()));
+/// ```
+#[cfg(feature = "tracing")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tracing")))]
+#[must_use]
+pub fn tracing_logger() -> Logger<'static> {
+    Box::new(|log_type, message| {
+        let event = LogEvent::parse(log_type, message);
+        let row = event.position.map(|position| position.row);
+        let column = event.position.map(|position| position.column);
+        match log_type {
+            LogType::Parse => tracing::debug!(
+                target: "tree_sitter::parse",
+                action = event.action,
+                state = event.state,
+                symbol = event.symbol,
+                row = row,
+                column = column,
+                "{message}"
+            ),
+            LogType::Lex => tracing::trace!(
+                target: "tree_sitter::lex",
+                action = event.action,
+                state = event.state,
+                symbol = event.symbol,
+                row = row,
+                column = column,
+                "{message}"
+            ),
+        }
+    })
+}
@@ -0,0 +1,172 @@
+use crate::{InputEdit, Node, Point, Range, Tree};
+
+/// An owned reference to a syntax node that does not borrow its [`Tree`].
+///
+/// A handle records the id of the tree that the node belongs to, the
+/// node's [descendant index](crate::TreeCursor::descendant_index) within that
+/// tree, and the node's kind and position. It can be stored in long-lived data
+/// structures, sent to other threads, and later resolved back into a [`Node`]
+/// with [`NodeHandle::resolve`].
+///
+/// When the source code is edited, pass the same [`InputEdit`] that is passed
+/// to [`Tree::edit`] to [`NodeHandle::edit`] in order to keep the handle in
+/// sync. Handles to nodes that are touched by an edit are invalidated, and
+/// resolve to `None`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct NodeHandle {
+    tree_id: u32,
+    descendant_index: usize,
+    kind_id: u16,
+    range: Range,
+    invalidated: bool,
+}
+
+impl NodeHandle {
+    /// Create a handle for the given node.
+    ///
+    /// This walks from the root of the node's tree down to the node, so it
+    /// takes time proportional to the node's depth.
+    #[must_use]
+    pub fn new(node: Node) -> Self {
+        let tree = node.0.tree;
+        let root = Node::new(unsafe { crate::ffi::ts_tree_root_node(tree) }).unwrap();
+        let mut cursor = root.walk();
+        let mut current = root;
+        while current != node {
+            let Some(child) = current.child_with_descendant(node) else {
+                break;
+            };
+            if !cursor.goto_first_child() {
+                break;
+            }
+            while cursor.node() != child {
+                if !cursor.goto_next_sibling() {
+                    break;
+                }
+            }
+            current = child;
+        }
+
+        Self {
+            tree_id: unsafe { crate::ffi::ts_tree_id(tree) },
+            descendant_index: cursor.descendant_index(),
+            kind_id: node.kind_id(),
+            range: node.range(),
+            invalidated: false,
+        }
+    }
+
+    /// Get the index of the node among all of the descendants of its tree's
+    /// root node, at the time that the handle was created.
+    #[must_use]
+    pub const fn descendant_index(&self) -> usize {
+        self.descendant_index
+    }
+
+    /// Get the numerical id of the node's type.
+    #[must_use]
+    pub const fn kind_id(&self) -> u16 {
+        self.kind_id
+    }
+
+    /// Get the node's range, adjusted for any edits that have been applied to
+    /// this handle.
+    #[must_use]
+    pub const fn range(&self) -> Range {
+        self.range
+    }
+
+    /// Check if this handle was invalidated by an edit.
+    #[must_use]
+    pub const fn is_invalidated(&self) -> bool {
+        self.invalidated
+    }
+
+    /// Check if the handle was created from a node of the given tree.
+    #[must_use]
+    pub fn belongs_to(&self, tree: &Tree) -> bool {
+        self.tree_id == unsafe { crate::ffi::ts_tree_id(tree.0.as_ptr()) }
+    }
+
+    /// Adjust the handle's position to account for an edit to the source code.
+    ///
+    /// Nodes that lie entirely before or after the edited range are shifted
+    /// accordingly. Nodes that overlap the edited range, or that contain an
+    /// insertion point, are invalidated.
+    pub fn edit(&mut self, edit: &InputEdit) {
+        if self.invalidated {
+            return;
+        }
+
+        let range = &mut self.range;
+        if range.start_byte >= edit.old_end_byte {
+            range.start_byte = edit.new_end_byte + (range.start_byte - edit.old_end_byte);
+            range.end_byte = edit.new_end_byte + (range.end_byte - edit.old_end_byte);
+            range.start_point = shift_point(range.start_point, edit);
+            range.end_point = shift_point(range.end_point, edit);
+        } else if range.end_byte > edit.start_byte {
+            self.invalidated = true;
+        }
+    }
+
+    /// Find the node that this handle refers to in the given tree.
+    ///
+    /// If `tree` is the tree that the handle was created from (possibly
+    /// edited since then), the node is located directly by its descendant
+    /// index. Otherwise, such as when `tree` was produced by reparsing an
+    /// edited tree, the node is looked up by its range and kind.
+    ///
+    /// Returns `None` if the handle was invalidated, or if the tree does not
+    /// contain a node of the same kind at the same position.
+    #[must_use]
+    pub fn resolve<'tree>(&self, tree: &'tree Tree) -> Option<Node<'tree>> {
+        if self.invalidated {
+            return None;
+        }
+
+        let root = tree.root_node();
+        if self.belongs_to(tree) {
+            let mut cursor = root.walk();
+            cursor.goto_descendant(self.descendant_index);
+            let node = cursor.node();
+            return self.matches(node).then_some(node);
+        }
+
+        let mut node =
+            root.descendant_for_byte_range(self.range.start_byte, self.range.end_byte)?;
+        loop {
+            if self.matches(node) {
+                return Some(node);
+            }
+            let parent = node.parent()?;
+            if parent.byte_range() != node.byte_range() {
+                return None;
+            }
+            node = parent;
+        }
+    }
+
+    fn matches(&self, node: Node) -> bool {
+        node.kind_id() == self.kind_id && node.range() == self.range
+    }
+}
+
+impl From<Node<'_>> for NodeHandle {
+    fn from(node: Node) -> Self {
+        Self::new(node)
+    }
+}
+
+const fn shift_point(point: Point, edit: &InputEdit) -> Point {
+    if point.row == edit.old_end_position.row {
+        Point::new(
+            edit.new_end_position.row,
+            edit.new_end_position.column + (point.column - edit.old_end_position.column),
+        )
+    } else {
+        Point::new(
+            edit.new_end_position.row + (point.row - edit.old_end_position.row),
+            point.column,
+        )
+    }
+}
@@ -0,0 +1,122 @@
+use core::{
+    future::Future,
+    num::NonZeroUsize,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use crate::{ParseOptions, ParseState, Parser, Tree};
+
+/// The default number of progress checks that a [`ParseFuture`] performs
+/// before yielding to the executor. The parser checks its progress roughly
+/// every hundred parse operations.
+const DEFAULT_YIELD_INTERVAL: NonZeroUsize = match NonZeroUsize::new(10) {
+    Some(interval) => interval,
+    None => unreachable!(),
+};
+
+/// A future that parses a text in steps, yielding to the executor between
+/// them.
+///
+/// This struct is created by [`Parser::parse_async`]. It does not depend on
+/// any particular async runtime: each time it is polled, it continues the
+/// parse until the parser has checked its progress a number of times, then
+/// pauses the parse, wakes its own waker and returns [`Poll::Pending`]. The
+/// next poll resumes the paused parse where it left off.
+///
+/// Dropping the future before it completes cancels the parse and
+/// [resets](Parser::reset) the parser.
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct ParseFuture<'a, T> {
+    parser: &'a mut Parser,
+    text: T,
+    old_tree: Option<&'a Tree>,
+    yield_interval: NonZeroUsize,
+    finished: bool,
+}
+
+// The future never pins its text, so it can be moved between polls regardless
+// of whether `T` is `Unpin`.
+impl<T> Unpin for ParseFuture<'_, T> {}
+
+impl<T> ParseFuture<'_, T> {
+    /// Set the number of times that the parser checks its progress before
+    /// the future yields to the executor. The parser checks its progress
+    /// roughly every hundred parse operations, so smaller intervals make the
+    /// parse more responsive to other tasks, at the cost of some overhead.
+    pub const fn yield_interval(mut self, progress_checks: NonZeroUsize) -> Self {
+        self.yield_interval = progress_checks;
+        self
+    }
+}
+
+impl<T: AsRef<[u8]>> Future for ParseFuture<'_, T> {
+    type Output = Option<Tree>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        assert!(!this.finished, "`ParseFuture` polled after completion");
+
+        let yield_interval = this.yield_interval.get();
+        let mut progress_checks = 0;
+        let mut paused = false;
+        let mut progress = |_: &ParseState| {
+            progress_checks += 1;
+            paused = progress_checks >= yield_interval;
+            paused
+        };
+
+        let bytes = this.text.as_ref();
+        let len = bytes.len();
+        let tree = this.parser.parse_with_options(
+            &mut |i, _| (i < len).then(|| &bytes[i..]).unwrap_or_default(),
+            this.old_tree,
+            Some(ParseOptions::new().progress_callback(&mut progress)),
+        );
+
+        if tree.is_none() && paused {
+            cx.waker().wake_by_ref();
+            return Poll::Pending;
+        }
+        this.finished = true;
+        Poll::Ready(tree)
+    }
+}
+
+impl<T> Drop for ParseFuture<'_, T> {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.parser.reset();
+        }
+    }
+}
+
+impl Parser {
+    /// Parse a slice of UTF8 text asynchronously.
+    ///
+    /// The returned future parses the text in steps, yielding to the executor
+    /// between them so that a long parse does not block other tasks. It can
+    /// be awaited on any executor. See [`ParseFuture`] for details.
+    ///
+    /// The future resolves to `None` if the parse is halted by a timeout or
+    /// cancellation flag that is set on the parser.
+    ///
+    /// # Arguments:
+    /// * `text` The UTF8-encoded text to parse.
+    /// * `old_tree` A previous syntax tree parsed from the same document. If the text of the
+    ///   document has changed since `old_tree` was created, then you must edit `old_tree` to match
+    ///   the new text using [`Tree::edit`].
+    pub fn parse_async<'a, T: AsRef<[u8]>>(
+        &'a mut self,
+        text: T,
+        old_tree: Option<&'a Tree>,
+    ) -> ParseFuture<'a, T> {
+        ParseFuture {
+            parser: self,
+            text,
+            old_tree,
+            yield_interval: DEFAULT_YIELD_INTERVAL,
+            finished: false,
+        }
+    }
+}
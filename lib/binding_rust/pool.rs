@@ -0,0 +1,210 @@
+use core::sync::atomic::{AtomicUsize, Ordering};
+use std::{
+    num::NonZeroUsize,
+    sync::Mutex,
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{Language, LanguageError, ParseOptions, ParseState, Parser, Tree};
+
+/// A callback that receives the index of the input that is being parsed, and
+/// the parse state, during a batch parse.
+type BatchProgressCallback<'a> = &'a (dyn Fn(usize, &ParseState) -> bool + Sync);
+
+/// Options for parsing a batch of inputs with a [`ParserPool`].
+#[derive(Default)]
+pub struct ParseBatchOptions<'a> {
+    progress_callback: Option<BatchProgressCallback<'a>>,
+    timeout: Option<Duration>,
+}
+
+impl<'a> ParseBatchOptions<'a> {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set a callback that is invoked periodically while each input is
+    /// parsed, with the index of that input. Returning `true` from the
+    /// callback cancels the parse of that input, which then yields `None`.
+    ///
+    /// The callback is called from multiple threads at once.
+    #[must_use]
+    pub fn progress_callback<F: Fn(usize, &ParseState) -> bool + Sync>(
+        mut self,
+        callback: &'a F,
+    ) -> Self {
+        self.progress_callback = Some(callback);
+        self
+    }
+
+    /// Set the maximum duration that parsing each input is allowed to take.
+    /// Inputs that take longer yield `None`.
+    #[must_use]
+    pub const fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+}
+
+/// A set of [`Parser`]s that parse many inputs in parallel on a fixed number
+/// of threads.
+///
+/// Parsers are created lazily and kept in the pool between batches, so each
+/// batch reuses the parsers that were allocated by the previous ones.
+pub struct ParserPool {
+    thread_count: NonZeroUsize,
+    parsers: Mutex<Vec<Parser>>,
+}
+
+impl ParserPool {
+    /// Create a new pool that parses on the given number of threads. If
+    /// `thread_count` is zero, the number of threads is determined by
+    /// [`std::thread::available_parallelism`].
+    #[must_use]
+    pub fn new(thread_count: usize) -> Self {
+        let thread_count = NonZeroUsize::new(thread_count)
+            .or_else(|| thread::available_parallelism().ok())
+            .unwrap_or(NonZeroUsize::MIN);
+        Self {
+            thread_count,
+            parsers: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Get the number of threads that this pool parses on.
+    #[must_use]
+    pub const fn thread_count(&self) -> usize {
+        self.thread_count.get()
+    }
+
+    /// Parse a batch of UTF8-encoded inputs with the given language.
+    ///
+    /// Returns the resulting syntax trees in the same order as `inputs`. An
+    /// input's tree is `None` if its parse was cancelled by the progress
+    /// callback or timed out.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the language cannot be assigned to a parser, as
+    /// with [`Parser::set_language`].
+    pub fn parse<T: AsRef<[u8]> + Sync>(
+        &self,
+        language: &Language,
+        inputs: &[T],
+        options: Option<ParseBatchOptions>,
+    ) -> Result<Vec<Option<Tree>>, LanguageError> {
+        let options = options.unwrap_or_default();
+        self.map(language, inputs, |parser, index, input| {
+            let start_time = Instant::now();
+            let mut progress = |state: &ParseState| {
+                options
+                    .timeout
+                    .is_some_and(|timeout| start_time.elapsed() > timeout)
+                    || options
+                        .progress_callback
+                        .is_some_and(|callback| callback(index, state))
+            };
+            let parse_options = ParseOptions::new().progress_callback(&mut progress);
+            let bytes = input.as_ref();
+            let len = bytes.len();
+            parser.parse_with_options(
+                &mut |i, _| (i < len).then(|| &bytes[i..]).unwrap_or_default(),
+                None,
+                Some(parse_options),
+            )
+        })
+    }
+
+    /// Run a function on each of the given inputs in parallel, passing it a
+    /// parser from the pool that has been assigned the given language.
+    ///
+    /// This can be used to parse inputs that need custom handling, such as a
+    /// particular encoding or an old tree. Each parser is [reset](Parser::reset)
+    /// after it is used, but any other configuration that `f` applies to it,
+    /// such as a logger or included ranges, should be undone by `f`.
+    ///
+    /// Returns the function's results in the same order as `inputs`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the language cannot be assigned to a parser, as
+    /// with [`Parser::set_language`].
+    pub fn map<T, R, F>(
+        &self,
+        language: &Language,
+        inputs: &[T],
+        f: F,
+    ) -> Result<Vec<R>, LanguageError>
+    where
+        T: Sync,
+        R: Send,
+        F: Fn(&mut Parser, usize, &T) -> R + Sync,
+    {
+        let mut parser = self.take_parser();
+        if let Err(error) = parser.set_language(language) {
+            self.return_parser(parser);
+            return Err(error);
+        }
+
+        let worker_count = self.thread_count.get().min(inputs.len());
+        let next_index = AtomicUsize::new(0);
+        let work = |parser: &mut Parser| {
+            let mut results = Vec::new();
+            loop {
+                let index = next_index.fetch_add(1, Ordering::Relaxed);
+                let Some(input) = inputs.get(index) else {
+                    break;
+                };
+                results.push((index, f(parser, index, input)));
+                parser.reset();
+            }
+            results
+        };
+
+        let mut indexed_results = thread::scope(|scope| {
+            let workers = (1..worker_count)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut parser = self.take_parser();
+                        parser.set_language(language).unwrap();
+                        let results = work(&mut parser);
+                        self.return_parser(parser);
+                        results
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            let mut results = work(&mut parser);
+            for worker in workers {
+                match worker.join() {
+                    Ok(worker_results) => results.extend(worker_results),
+                    Err(payload) => std::panic::resume_unwind(payload),
+                }
+            }
+            results
+        });
+        self.return_parser(parser);
+
+        indexed_results.sort_unstable_by_key(|(index, _)| *index);
+        Ok(indexed_results
+            .into_iter()
+            .map(|(_, result)| result)
+            .collect())
+    }
+
+    fn take_parser(&self) -> Parser {
+        self.parsers.lock().unwrap().pop().unwrap_or_default()
+    }
+
+    fn return_parser(&self, parser: Parser) {
+        self.parsers.lock().unwrap().push(parser);
+    }
+}
+
+impl Default for ParserPool {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
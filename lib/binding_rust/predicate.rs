@@ -0,0 +1,204 @@
+#[cfg(not(feature = "std"))]
+use alloc::{boxed::Box, format, vec::Vec};
+use core::{fmt, str};
+
+use crate::{
+    predicate_error, Node, Query, QueryError, QueryMatch, QueryPredicateArg, TextProvider,
+};
+
+type PredicateHandler =
+    dyn Fn(&[PredicateArg<'_, '_>], &mut PredicateText<'_>) -> bool + Send + Sync;
+
+/// A predicate that was registered with [`Query::register_predicate`].
+pub struct CustomPredicate {
+    name: Box<str>,
+    handler: Box<PredicateHandler>,
+}
+
+/// What to do with predicates that are neither built in nor registered.
+///
+/// This is set with [`Query::set_unregistered_predicates`], and honored by
+/// [`QueryCursor::matches`](crate::QueryCursor::matches) and
+/// [`QueryCursor::captures`](crate::QueryCursor::captures).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum UnregisteredPredicates {
+    /// Treat the predicates as satisfied, and leave them to the caller, who
+    /// can find them with [`Query::general_predicates`].
+    #[default]
+    Ignore,
+    /// Treat the predicates as unsatisfied, so that the patterns containing
+    /// them never match. This includes directives like `#strip!`, which need
+    /// a handler that returns `true` to be allowed.
+    Reject,
+}
+
+/// An argument to a custom predicate, as it is passed to the predicate's
+/// handler for a particular match.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PredicateArg<'a, 'tree> {
+    /// The nodes that were captured by a capture argument. This is empty if
+    /// the capture is optional and did not match anything.
+    Capture(&'a [Node<'tree>]),
+    /// A string argument.
+    String(&'a str),
+}
+
+/// Access to the text of the nodes that are passed to a custom predicate.
+pub struct PredicateText<'a> {
+    read: &'a mut dyn FnMut(Node, &mut Vec<u8>),
+    buffer: &'a mut Vec<u8>,
+}
+
+impl PredicateText<'_> {
+    /// Get the text of a node.
+    ///
+    /// The text is stored in a buffer that is reused by the next call.
+    pub fn text(&mut self, node: Node) -> &[u8] {
+        self.buffer.clear();
+        (self.read)(node, self.buffer);
+        self.buffer
+    }
+
+    /// Get the text of a node as a string, or `None` if it is not valid UTF8.
+    ///
+    /// The text is stored in a buffer that is reused by the next call.
+    pub fn text_str(&mut self, node: Node) -> Option<&str> {
+        str::from_utf8(self.text(node)).ok()
+    }
+}
+
+impl Query {
+    /// Register a handler for a custom predicate, such as `#lua-match?`.
+    ///
+    /// Predicates that are not built into Tree-sitter are normally only
+    /// returned by [`general_predicates`](Query::general_predicates), and are
+    /// left to the caller to check. Once a handler is registered for a
+    /// predicate's name, [`QueryCursor::matches`](crate::QueryCursor::matches)
+    /// and [`QueryCursor::captures`](crate::QueryCursor::captures) skip the
+    /// matches for which the handler returns `false`, exactly as they do for
+    /// built-in predicates like `#eq?` and `#match?`.
+    ///
+    /// The handler is called with the predicate's arguments and with a way
+    /// to read the text of the captured nodes. Registering a handler for a
+    /// name that already has one replaces it.
+    pub fn register_predicate<F>(&mut self, name: &str, handler: F) -> &mut Self
+    where
+        F: Fn(&[PredicateArg<'_, '_>], &mut PredicateText<'_>) -> bool + Send + Sync + 'static,
+    {
+        let predicate = CustomPredicate {
+            name: name.into(),
+            handler: Box::new(handler),
+        };
+        match self.custom_predicates.iter_mut().find(|p| *p.name == *name) {
+            Some(existing) => *existing = predicate,
+            None => self.custom_predicates.push(predicate),
+        }
+        self
+    }
+
+    /// Set what happens to predicates that are neither built in nor
+    /// registered with [`register_predicate`](Query::register_predicate)
+    /// when the query is run.
+    ///
+    /// By default, they are [ignored](UnregisteredPredicates::Ignore).
+    pub fn set_unregistered_predicates(&mut self, behavior: UnregisteredPredicates) -> &mut Self {
+        self.unregistered_predicates = behavior;
+        self
+    }
+
+    /// Get what happens to predicates that are neither built in nor
+    /// registered with [`register_predicate`](Query::register_predicate)
+    /// when the query is run.
+    #[must_use]
+    pub const fn unregistered_predicates(&self) -> UnregisteredPredicates {
+        self.unregistered_predicates
+    }
+
+    /// Check that every predicate in the query is either built in, registered
+    /// with [`register_predicate`](Query::register_predicate), or one of the
+    /// `allowed` predicates that the caller checks itself.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`QueryErrorKind::Predicate`](crate::QueryErrorKind::Predicate)
+    /// error for the first predicate that is none of these.
+    pub fn check_predicates(&self, allowed: &[&str]) -> Result<(), QueryError> {
+        for (i, predicates) in self.general_predicates.iter().enumerate() {
+            for predicate in predicates.iter() {
+                let operator = &*predicate.operator;
+                if !allowed.contains(&operator)
+                    && !self.custom_predicates.iter().any(|p| *p.name == *operator)
+                {
+                    return Err(predicate_error(
+                        self.pattern_rows[i],
+                        format!("Unknown predicate #{operator}"),
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl QueryMatch<'_, '_> {
+    /// Check the match against the query's custom predicates.
+    pub(crate) fn satisfies_custom_predicates<I: AsRef<[u8]>>(
+        &self,
+        query: &Query,
+        buffer: &mut Vec<u8>,
+        text_provider: &mut impl TextProvider<I>,
+    ) -> bool {
+        let reject_unregistered = query.unregistered_predicates == UnregisteredPredicates::Reject;
+        if query.custom_predicates.is_empty() && !reject_unregistered {
+            return true;
+        }
+
+        let mut read = |node: Node, buffer: &mut Vec<u8>| {
+            for chunk in text_provider.text(node) {
+                buffer.extend_from_slice(chunk.as_ref());
+            }
+        };
+        let mut text = PredicateText {
+            read: &mut read,
+            buffer,
+        };
+
+        query.general_predicates[self.pattern_index]
+            .iter()
+            .all(|predicate| {
+                let Some(custom) = query
+                    .custom_predicates
+                    .iter()
+                    .find(|p| p.name == predicate.operator)
+                else {
+                    return !reject_unregistered;
+                };
+                let nodes = predicate
+                    .args
+                    .iter()
+                    .map(|arg| match arg {
+                        QueryPredicateArg::Capture(i) => self.nodes_for_capture_index(*i).collect(),
+                        QueryPredicateArg::String(_) => Vec::new(),
+                    })
+                    .collect::<Vec<_>>();
+                let args = predicate
+                    .args
+                    .iter()
+                    .zip(&nodes)
+                    .map(|(arg, nodes)| match arg {
+                        QueryPredicateArg::Capture(_) => PredicateArg::Capture(nodes),
+                        QueryPredicateArg::String(s) => PredicateArg::String(s),
+                    })
+                    .collect::<Vec<_>>();
+                (custom.handler)(&args, &mut text)
+            })
+    }
+}
+
+impl fmt::Debug for CustomPredicate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("CustomPredicate")
+            .field("name", &self.name)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,945 @@
+#[cfg(not(feature = "std"))]
+use alloc::{boxed::Box, format, string::String, vec::Vec};
+use core::fmt;
+
+use crate::{CaptureQuantifier, Language, Query, QueryError, QueryErrorKind};
+
+/// A builder that constructs the patterns of a [`Query`] from typed parts,
+/// rather than from source code.
+///
+/// Each pattern is checked against the builder's language when it is added,
+/// and written in a canonical form, with one pattern per line. The positions
+/// of errors refer to that canonical text, which is available through the
+/// builder's [`Display`](fmt::Display) implementation.
+///
+/// ```
+/// # use tree_sitter::{Language, PatternPredicateArg, QueryBuilder, QueryPattern};
+/// # fn test(language: &Language) -> Result<(), tree_sitter::QueryError> {
+/// let mut builder = QueryBuilder::new(language);
+/// builder.pattern(
+///     QueryPattern::node("call_expression")
+///         .field("function", QueryPattern::node("identifier").capture("name"))
+///         .predicate(
+///             "eq?",
+///             [
+///                 PatternPredicateArg::capture("name"),
+///                 PatternPredicateArg::string("require"),
+///             ],
+///         ),
+/// )?;
+/// assert_eq!(
+///     builder.to_string(),
+///     "((call_expression function: (identifier) @name) (#eq? @name \"require\"))\n",
+/// );
+/// let query = builder.build()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct QueryBuilder {
+    language: Language,
+    patterns: Vec<QueryPattern>,
+    source: String,
+}
+
+/// A pattern in a [`QueryBuilder`], along with its captures, quantifier and
+/// predicates.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QueryPattern {
+    kind: PatternKind,
+    field: Option<Box<str>>,
+    quantifier: CaptureQuantifier,
+    captures: Vec<Box<str>>,
+    predicates: Vec<PatternPredicate>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum PatternKind {
+    /// A named node, written as `(kind ...)`, or `(_ ...)` if it has no kind.
+    Node {
+        kind: Option<Box<str>>,
+        supertype: Option<Box<str>>,
+        negated_fields: Vec<Box<str>>,
+        children: Vec<PatternChild>,
+    },
+    /// Any node, written as `_`.
+    Wildcard,
+    /// An anonymous node, written as a string.
+    Anonymous(Box<str>),
+    /// A missing node, written as `(MISSING kind)`.
+    Missing {
+        kind: Option<Box<str>>,
+        named: bool,
+    },
+    Alternation(Vec<QueryPattern>),
+    Group(Vec<PatternChild>),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum PatternChild {
+    Pattern(QueryPattern),
+    Anchor,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct PatternPredicate {
+    operator: Box<str>,
+    args: Vec<PatternPredicateArg>,
+}
+
+/// An argument to a predicate in a [`QueryPattern`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PatternPredicateArg {
+    /// The name of a capture, without the leading `@`.
+    Capture(Box<str>),
+    String(Box<str>),
+}
+
+impl QueryBuilder {
+    /// Create a builder for a query on the given language.
+    #[must_use]
+    pub fn new(language: &Language) -> Self {
+        Self {
+            language: language.clone(),
+            patterns: Vec::new(),
+            source: String::new(),
+        }
+    }
+
+    /// Create a builder with the patterns of an existing query, so that they
+    /// can be inspected, extended, or written in their canonical form.
+    ///
+    /// A query does not retain its source code, so the source that it was
+    /// created from must be given as well. The patterns are read from the
+    /// source, and their canonical form is compiled and checked against the
+    /// query with [`Query::is_equivalent`]. Returns `None` if the source is
+    /// not the query's source, or if its canonical form does not compile to
+    /// the same patterns as the query.
+    #[must_use]
+    pub fn from_query(query: &Query, source: &str) -> Option<Self> {
+        let patterns = SourceParser::new(source).patterns()?;
+        if patterns.len() != query.pattern_count()
+            || patterns
+                .iter()
+                .enumerate()
+                .any(|(i, (start_byte, _))| *start_byte != query.start_byte_for_pattern(i))
+        {
+            return None;
+        }
+        let mut result = Self::new(&query.language());
+        for (_, pattern) in patterns {
+            Writer::new(&mut result.source, None, 0)
+                .pattern(&pattern)
+                .ok()?;
+            result.source.push('\n');
+            result.patterns.push(pattern);
+        }
+        let canonical = result.build().ok()?;
+        canonical.is_equivalent(query).then_some(result)
+    }
+
+    /// Add a pattern to the query.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pattern refers to a node kind or a field that
+    /// does not exist in the builder's language, if a predicate refers to a
+    /// capture that is not in the pattern, or if a name cannot be written in
+    /// a query. The error's position refers to the canonical text of the
+    /// query, in which the pattern's row is its index.
+    pub fn pattern(&mut self, pattern: QueryPattern) -> Result<&mut Self, QueryError> {
+        let start = self.source.len();
+        let row = self.patterns.len();
+        let result =
+            Writer::new(&mut self.source, Some(&self.language), row).checked_pattern(&pattern);
+        if let Err(error) = result {
+            self.source.truncate(start);
+            return Err(error);
+        }
+        self.source.push('\n');
+        self.patterns.push(pattern);
+        Ok(self)
+    }
+
+    /// Get the patterns that have been added to the builder.
+    #[must_use]
+    pub fn patterns(&self) -> &[QueryPattern] {
+        &self.patterns
+    }
+
+    /// Compile the patterns into a [`Query`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if Tree-sitter rejects the query, for example because
+    /// a pattern is impossible in the builder's language.
+    pub fn build(&self) -> Result<Query, QueryError> {
+        Query::new(&self.language, &self.source)
+    }
+}
+
+impl QueryPattern {
+    /// Create a pattern that matches a named node of the given kind.
+    #[must_use]
+    pub fn node(kind: &str) -> Self {
+        Self::new(PatternKind::Node {
+            kind: Some(kind.into()),
+            supertype: None,
+            negated_fields: Vec::new(),
+            children: Vec::new(),
+        })
+    }
+
+    /// Create a pattern that matches any named node, written as `(_)`.
+    #[must_use]
+    pub const fn named_wildcard() -> Self {
+        Self::new(PatternKind::Node {
+            kind: None,
+            supertype: None,
+            negated_fields: Vec::new(),
+            children: Vec::new(),
+        })
+    }
+
+    /// Create a pattern that matches any node, named or anonymous, written as
+    /// `_`.
+    #[must_use]
+    pub const fn wildcard() -> Self {
+        Self::new(PatternKind::Wildcard)
+    }
+
+    /// Create a pattern that matches an anonymous node with the given text.
+    #[must_use]
+    pub fn anonymous(text: &str) -> Self {
+        Self::new(PatternKind::Anonymous(text.into()))
+    }
+
+    /// Create a pattern that matches a `MISSING` node that the parser inserted
+    /// during error recovery, optionally of a given named kind.
+    #[must_use]
+    pub fn missing(kind: Option<&str>) -> Self {
+        Self::new(PatternKind::Missing {
+            kind: kind.map(Into::into),
+            named: true,
+        })
+    }
+
+    /// Create a pattern that matches any one of the given patterns.
+    #[must_use]
+    pub fn alternation(patterns: impl IntoIterator<Item = Self>) -> Self {
+        Self::new(PatternKind::Alternation(patterns.into_iter().collect()))
+    }
+
+    /// Create a pattern that matches a sequence of sibling nodes.
+    #[must_use]
+    pub fn group(patterns: impl IntoIterator<Item = Self>) -> Self {
+        Self::new(PatternKind::Group(
+            patterns.into_iter().map(PatternChild::Pattern).collect(),
+        ))
+    }
+
+    /// Restrict a node pattern to nodes that appear as the given supertype,
+    /// written as `(supertype/kind)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this is not a pattern created by [`node`](Self::node).
+    #[must_use]
+    pub fn supertype(mut self, name: &str) -> Self {
+        match &mut self.kind {
+            PatternKind::Node {
+                kind: Some(_),
+                supertype,
+                ..
+            } => *supertype = Some(name.into()),
+            _ => panic!("Only node patterns can have a supertype"),
+        }
+        self
+    }
+
+    /// Add a child pattern.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this is not a node pattern or a group.
+    #[must_use]
+    pub fn child(mut self, pattern: Self) -> Self {
+        self.children_mut().push(PatternChild::Pattern(pattern));
+        self
+    }
+
+    /// Add a child pattern that must be the value of the given field.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this is not a node pattern or a group.
+    #[must_use]
+    pub fn field(self, name: &str, mut pattern: Self) -> Self {
+        pattern.field = Some(name.into());
+        self.child(pattern)
+    }
+
+    /// Add an anchor, written as `.`, which requires the surrounding child
+    /// patterns to match immediate siblings, or the first or last child of
+    /// the node.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this is not a node pattern or a group.
+    #[must_use]
+    pub fn anchor(mut self) -> Self {
+        self.children_mut().push(PatternChild::Anchor);
+        self
+    }
+
+    /// Require that the node does not have a value for the given field,
+    /// written as `!field`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this is not a node pattern.
+    #[must_use]
+    pub fn negated_field(mut self, name: &str) -> Self {
+        match &mut self.kind {
+            PatternKind::Node { negated_fields, .. } => negated_fields.push(name.into()),
+            _ => panic!("Only node patterns can have negated fields"),
+        }
+        self
+    }
+
+    /// Allow the pattern to match zero or one times, written as `?`.
+    #[must_use]
+    pub const fn optional(self) -> Self {
+        self.quantified(CaptureQuantifier::ZeroOrOne)
+    }
+
+    /// Allow the pattern to match any number of times, written as `*`.
+    #[must_use]
+    pub const fn zero_or_more(self) -> Self {
+        self.quantified(CaptureQuantifier::ZeroOrMore)
+    }
+
+    /// Allow the pattern to match one or more times, written as `+`.
+    #[must_use]
+    pub const fn one_or_more(self) -> Self {
+        self.quantified(CaptureQuantifier::OneOrMore)
+    }
+
+    /// Capture the nodes that match the pattern with the given name, without
+    /// the leading `@`.
+    #[must_use]
+    pub fn capture(mut self, name: &str) -> Self {
+        self.captures.push(name.into());
+        self
+    }
+
+    /// Add a predicate to the pattern. The operator is written without the
+    /// leading `#`, and must end in `?` or `!`, like `eq?` or `set!`.
+    #[must_use]
+    pub fn predicate(
+        mut self,
+        operator: &str,
+        args: impl IntoIterator<Item = PatternPredicateArg>,
+    ) -> Self {
+        self.predicates.push(PatternPredicate {
+            operator: operator.into(),
+            args: args.into_iter().collect(),
+        });
+        self
+    }
+
+    const fn new(kind: PatternKind) -> Self {
+        Self {
+            kind,
+            field: None,
+            quantifier: CaptureQuantifier::One,
+            captures: Vec::new(),
+            predicates: Vec::new(),
+        }
+    }
+
+    const fn quantified(mut self, quantifier: CaptureQuantifier) -> Self {
+        self.quantifier = quantifier;
+        self
+    }
+
+    fn children_mut(&mut self) -> &mut Vec<PatternChild> {
+        match &mut self.kind {
+            PatternKind::Node { children, .. } | PatternKind::Group(children) => children,
+            _ => panic!("Only node patterns and groups can have children"),
+        }
+    }
+
+    /// Collect the names of the captures in this pattern and its children.
+    fn capture_names<'a>(&'a self, result: &mut Vec<&'a str>) {
+        result.extend(self.captures.iter().map(|name| &**name));
+        match &self.kind {
+            PatternKind::Node { children, .. } | PatternKind::Group(children) => {
+                for child in children {
+                    if let PatternChild::Pattern(pattern) = child {
+                        pattern.capture_names(result);
+                    }
+                }
+            }
+            PatternKind::Alternation(patterns) => {
+                for pattern in patterns {
+                    pattern.capture_names(result);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl PatternPredicateArg {
+    /// Create an argument that refers to a capture.
+    #[must_use]
+    pub fn capture(name: &str) -> Self {
+        Self::Capture(name.into())
+    }
+
+    /// Create a string argument.
+    #[must_use]
+    pub fn string(value: &str) -> Self {
+        Self::String(value.into())
+    }
+}
+
+impl fmt::Display for QueryBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+impl fmt::Display for QueryPattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut result = String::new();
+        Writer::new(&mut result, None, 0)
+            .pattern(self)
+            .map_err(|_| fmt::Error)?;
+        f.write_str(&result)
+    }
+}
+
+/// Writes patterns in their canonical form, checking their names against a
+/// language if one is given.
+struct Writer<'a> {
+    out: &'a mut String,
+    line_start: usize,
+    row: usize,
+    language: Option<&'a Language>,
+    captures: Vec<&'a str>,
+}
+
+impl<'a> Writer<'a> {
+    fn new(out: &'a mut String, language: Option<&'a Language>, row: usize) -> Self {
+        Self {
+            line_start: out.len(),
+            out,
+            row,
+            language,
+            captures: Vec::new(),
+        }
+    }
+
+    fn checked_pattern(&mut self, pattern: &'a QueryPattern) -> Result<(), QueryError> {
+        pattern.capture_names(&mut self.captures);
+        self.pattern(pattern)
+    }
+
+    fn pattern(&mut self, pattern: &QueryPattern) -> Result<(), QueryError> {
+        if let Some(field) = &pattern.field {
+            self.field_name(field)?;
+            self.out.push_str(": ");
+        }
+
+        // Predicates apply to the entire pattern, and can refer to captures
+        // anywhere within it, so unless they are part of a group, they are
+        // written after the pattern in a group of their own.
+        let wrap_predicates =
+            !pattern.predicates.is_empty() && !matches!(pattern.kind, PatternKind::Group(_));
+        if wrap_predicates {
+            self.out.push('(');
+        }
+
+        match &pattern.kind {
+            PatternKind::Node {
+                kind,
+                supertype,
+                negated_fields,
+                children,
+            } => {
+                self.out.push('(');
+                if let Some(supertype) = supertype {
+                    self.node_kind(supertype, true)?;
+                    self.out.push('/');
+                }
+                match kind {
+                    Some(kind) => self.node_kind(kind, true)?,
+                    None => self.out.push('_'),
+                }
+                for field in negated_fields {
+                    self.out.push_str(" !");
+                    self.field_name(field)?;
+                }
+                for child in children {
+                    self.out.push(' ');
+                    self.child(child)?;
+                }
+                self.out.push(')');
+            }
+            PatternKind::Wildcard => self.out.push('_'),
+            PatternKind::Anonymous(text) => self.node_kind(text, false)?,
+            PatternKind::Missing { kind, named } => {
+                self.out.push_str("(MISSING");
+                if let Some(kind) = kind {
+                    self.out.push(' ');
+                    self.node_kind(kind, *named)?;
+                }
+                self.out.push(')');
+            }
+            PatternKind::Alternation(patterns) => {
+                self.out.push('[');
+                for (i, pattern) in patterns.iter().enumerate() {
+                    if i > 0 {
+                        self.out.push(' ');
+                    }
+                    self.pattern(pattern)?;
+                }
+                self.out.push(']');
+            }
+            PatternKind::Group(children) => {
+                self.out.push('(');
+                for (i, child) in children.iter().enumerate() {
+                    if i > 0 {
+                        self.out.push(' ');
+                    }
+                    self.child(child)?;
+                }
+                self.predicates(&pattern.predicates)?;
+                self.out.push(')');
+            }
+        }
+
+        match pattern.quantifier {
+            CaptureQuantifier::ZeroOrOne => self.out.push('?'),
+            CaptureQuantifier::ZeroOrMore => self.out.push('*'),
+            CaptureQuantifier::OneOrMore => self.out.push('+'),
+            CaptureQuantifier::One | CaptureQuantifier::Zero => {}
+        }
+        for capture in &pattern.captures {
+            self.out.push_str(" @");
+            self.name(capture)?;
+            self.out.push_str(capture);
+        }
+
+        if wrap_predicates {
+            self.predicates(&pattern.predicates)?;
+            self.out.push(')');
+        }
+        Ok(())
+    }
+
+    fn child(&mut self, child: &PatternChild) -> Result<(), QueryError> {
+        match child {
+            PatternChild::Pattern(pattern) => self.pattern(pattern),
+            PatternChild::Anchor => {
+                self.out.push('.');
+                Ok(())
+            }
+        }
+    }
+
+    fn predicates(&mut self, predicates: &[PatternPredicate]) -> Result<(), QueryError> {
+        for predicate in predicates {
+            self.out.push_str(" (#");
+            let operator = &*predicate.operator;
+            if self.language.is_some()
+                && !operator.strip_suffix(['?', '!']).is_some_and(is_identifier)
+            {
+                return Err(self.error(QueryErrorKind::Predicate, operator));
+            }
+            self.out.push_str(operator);
+            for arg in &predicate.args {
+                self.out.push(' ');
+                match arg {
+                    PatternPredicateArg::Capture(name) => {
+                        self.out.push('@');
+                        if self.language.is_some() && !self.captures.contains(&&**name) {
+                            return Err(self.error(QueryErrorKind::Capture, name));
+                        }
+                        self.out.push_str(name);
+                    }
+                    PatternPredicateArg::String(value) => self.string(value),
+                }
+            }
+            self.out.push(')');
+        }
+        Ok(())
+    }
+
+    fn node_kind(&mut self, kind: &str, named: bool) -> Result<(), QueryError> {
+        if named {
+            self.name(kind)?;
+        }
+        if let Some(language) = self.language {
+            if language.id_for_node_kind(kind, named) == 0 {
+                // Like Tree-sitter, point at the contents of a string rather
+                // than at its opening quote.
+                let mut error = self.error(QueryErrorKind::NodeType, kind);
+                if !named {
+                    error.column += 1;
+                    error.offset += 1;
+                }
+                return Err(error);
+            }
+        }
+        if named {
+            self.out.push_str(kind);
+        } else {
+            self.string(kind);
+        }
+        Ok(())
+    }
+
+    fn field_name(&mut self, name: &str) -> Result<(), QueryError> {
+        self.name(name)?;
+        if let Some(language) = self.language {
+            if language.field_id_for_name(name).is_none() {
+                return Err(self.error(QueryErrorKind::Field, name));
+            }
+        }
+        self.out.push_str(name);
+        Ok(())
+    }
+
+    /// Check that a name can be written without quotes.
+    fn name(&self, name: &str) -> Result<(), QueryError> {
+        if self.language.is_some() && !is_identifier(name) {
+            return Err(self.error(QueryErrorKind::Syntax, name));
+        }
+        Ok(())
+    }
+
+    fn string(&mut self, value: &str) {
+        self.out.push('"');
+        for c in value.chars() {
+            match c {
+                '"' => self.out.push_str("\\\""),
+                '\\' => self.out.push_str("\\\\"),
+                '\n' => self.out.push_str("\\n"),
+                '\r' => self.out.push_str("\\r"),
+                '\t' => self.out.push_str("\\t"),
+                '\0' => self.out.push_str("\\0"),
+                _ => self.out.push(c),
+            }
+        }
+        self.out.push('"');
+    }
+
+    /// Create an error for a name that is about to be written.
+    fn error(&self, kind: QueryErrorKind, name: &str) -> QueryError {
+        QueryError {
+            row: self.row,
+            column: self.out.len() - self.line_start,
+            offset: self.out.len(),
+            message: format!("\"{name}\""),
+            kind,
+        }
+    }
+}
+
+/// Parses the source code of a query that Tree-sitter has already accepted
+/// into [`QueryPattern`]s.
+struct SourceParser<'a> {
+    source: &'a str,
+    offset: usize,
+}
+
+impl<'a> SourceParser<'a> {
+    const fn new(source: &'a str) -> Self {
+        Self { source, offset: 0 }
+    }
+
+    /// Parse the top-level patterns, along with the byte offsets at which
+    /// they start.
+    fn patterns(&mut self) -> Option<Vec<(usize, QueryPattern)>> {
+        let mut result = Vec::new();
+        loop {
+            self.skip_whitespace();
+            if self.peek().is_none() {
+                return Some(result);
+            }
+            let start_byte = self.offset;
+            result.push((start_byte, self.pattern()?));
+        }
+    }
+
+    fn pattern(&mut self) -> Option<QueryPattern> {
+        let mut pattern = match self.peek()? {
+            '(' => self.parenthesized()?,
+            '[' => {
+                self.bump();
+                let mut pattern = QueryPattern::alternation([]);
+                loop {
+                    self.skip_whitespace();
+                    match self.peek()? {
+                        ']' => {
+                            self.bump();
+                            break;
+                        }
+                        '(' if self.is_predicate() => {
+                            let predicate = self.predicate()?;
+                            pattern.predicates.push(predicate);
+                        }
+                        _ => {
+                            let alternative = self.pattern()?;
+                            if let PatternKind::Alternation(patterns) = &mut pattern.kind {
+                                patterns.push(alternative);
+                            }
+                        }
+                    }
+                }
+                pattern
+            }
+            '"' => QueryPattern::new(PatternKind::Anonymous(self.string()?)),
+            '_' => {
+                self.bump();
+                QueryPattern::wildcard()
+            }
+            _ => {
+                let field = self.identifier()?;
+                self.skip_whitespace();
+                if !self.eat(':') {
+                    return None;
+                }
+                self.skip_whitespace();
+                let mut pattern = self.pattern()?;
+                pattern.field = Some(field.into());
+                return Some(pattern);
+            }
+        };
+
+        loop {
+            self.skip_whitespace();
+            let quantifier = match self.peek() {
+                Some('?') => CaptureQuantifier::ZeroOrOne,
+                Some('*') => CaptureQuantifier::ZeroOrMore,
+                Some('+') => CaptureQuantifier::OneOrMore,
+                Some('@') => {
+                    self.bump();
+                    pattern.captures.push(self.identifier()?.into());
+                    continue;
+                }
+                _ => return Some(pattern),
+            };
+            self.bump();
+            pattern.quantifier = join_quantifiers(pattern.quantifier, quantifier);
+        }
+    }
+
+    fn parenthesized(&mut self) -> Option<QueryPattern> {
+        self.bump();
+        self.skip_whitespace();
+        if matches!(self.peek()?, '(' | '[' | '"') {
+            let mut pattern = QueryPattern::new(PatternKind::Group(Vec::new()));
+            self.children(&mut pattern)?;
+
+            // A single pattern with predicates is written as a group, but
+            // the predicates belong to the pattern.
+            if let PatternKind::Group(children) = &mut pattern.kind {
+                if let [PatternChild::Pattern(child)] = children.as_mut_slice() {
+                    if !pattern.predicates.is_empty() {
+                        child.predicates.append(&mut pattern.predicates);
+                        return Some(child.clone());
+                    }
+                }
+            }
+            return Some(pattern);
+        }
+
+        let name = self.identifier()?;
+        if name == "MISSING" {
+            self.skip_whitespace();
+            let kind = match self.peek()? {
+                '"' => Some((self.string()?, false)),
+                ')' => None,
+                _ => Some((self.identifier()?.into(), true)),
+            };
+            self.skip_whitespace();
+            if !self.eat(')') {
+                return None;
+            }
+            return Some(QueryPattern::new(PatternKind::Missing {
+                named: kind.as_ref().map_or(true, |(_, named)| *named),
+                kind: kind.map(|(kind, _)| kind),
+            }));
+        }
+
+        self.skip_whitespace();
+        let mut pattern = if name == "_" {
+            QueryPattern::named_wildcard()
+        } else if self.eat('/') {
+            QueryPattern::node(self.identifier()?).supertype(name)
+        } else {
+            QueryPattern::node(name)
+        };
+        self.children(&mut pattern)?;
+        Some(pattern)
+    }
+
+    fn children(&mut self, pattern: &mut QueryPattern) -> Option<()> {
+        loop {
+            self.skip_whitespace();
+            match self.peek()? {
+                ')' => {
+                    self.bump();
+                    return Some(());
+                }
+                '.' => {
+                    self.bump();
+                    pattern.children_mut().push(PatternChild::Anchor);
+                }
+                '!' => {
+                    self.bump();
+                    self.skip_whitespace();
+                    let name = self.identifier()?;
+                    match &mut pattern.kind {
+                        PatternKind::Node { negated_fields, .. } => {
+                            negated_fields.push(name.into());
+                        }
+                        _ => return None,
+                    }
+                }
+                '(' if self.is_predicate() => {
+                    let predicate = self.predicate()?;
+                    pattern.predicates.push(predicate);
+                }
+                _ => {
+                    let child = self.pattern()?;
+                    pattern.children_mut().push(PatternChild::Pattern(child));
+                }
+            }
+        }
+    }
+
+    /// Check if a parenthesis starts a predicate, which `query.c` recognizes
+    /// by a `#` or `.` after the parenthesis.
+    fn is_predicate(&self) -> bool {
+        let rest = self.source[self.offset + 1..].trim_start();
+        rest.starts_with(['#', '.'])
+    }
+
+    fn predicate(&mut self) -> Option<PatternPredicate> {
+        self.bump();
+        self.skip_whitespace();
+        self.bump();
+        let name = self.identifier()?;
+        let suffix = self.bump().filter(|c| matches!(c, '?' | '!'))?;
+        let mut predicate = PatternPredicate {
+            operator: format!("{name}{suffix}").into(),
+            args: Vec::new(),
+        };
+        loop {
+            self.skip_whitespace();
+            let arg = match self.peek()? {
+                ')' => {
+                    self.bump();
+                    return Some(predicate);
+                }
+                '@' => {
+                    self.bump();
+                    PatternPredicateArg::capture(self.identifier()?)
+                }
+                '"' => PatternPredicateArg::String(self.string()?),
+                _ => PatternPredicateArg::string(self.identifier()?),
+            };
+            predicate.args.push(arg);
+        }
+    }
+
+    fn identifier(&mut self) -> Option<&'a str> {
+        let start = self.offset;
+        if !self.peek().is_some_and(is_identifier_start) {
+            return None;
+        }
+        while self.peek().is_some_and(is_identifier_char) {
+            self.bump();
+        }
+        Some(&self.source[start..self.offset])
+    }
+
+    fn string(&mut self) -> Option<Box<str>> {
+        self.bump();
+        let mut result = String::new();
+        loop {
+            match self.bump()? {
+                '"' => return Some(result.into()),
+                '\\' => result.push(match self.bump()? {
+                    'n' => '\n',
+                    'r' => '\r',
+                    't' => '\t',
+                    '0' => '\0',
+                    c => c,
+                }),
+                c => result.push(c),
+            }
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        loop {
+            match self.peek() {
+                Some(';') => while self.bump().is_some_and(|c| c != '\n') {},
+                Some(c) if c.is_whitespace() => {
+                    self.bump();
+                }
+                _ => return,
+            }
+        }
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        let result = self.peek() == Some(c);
+        if result {
+            self.bump();
+        }
+        result
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.source[self.offset..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.offset += c.len_utf8();
+        Some(c)
+    }
+}
+
+/// Check if a character can start an identifier. Like `iswalnum` in the C
+/// locale that `query.c` runs in, only ASCII letters and digits are accepted.
+const fn is_identifier_start(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '-'
+}
+
+const fn is_identifier_char(c: char) -> bool {
+    is_identifier_start(c) || c == '.'
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(is_identifier_start) && chars.all(is_identifier_char)
+}
+
+/// Combine two consecutive quantifiers, like `?+`.
+const fn join_quantifiers(left: CaptureQuantifier, right: CaptureQuantifier) -> CaptureQuantifier {
+    use CaptureQuantifier::{One, OneOrMore, Zero, ZeroOrMore, ZeroOrOne};
+    match (left, right) {
+        (One, quantifier) | (quantifier, One) => quantifier,
+        (Zero | ZeroOrOne, Zero | ZeroOrOne) => ZeroOrOne,
+        (OneOrMore, OneOrMore) => OneOrMore,
+        _ => ZeroOrMore,
+    }
+}
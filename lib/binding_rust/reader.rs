@@ -0,0 +1,392 @@
+use core::{
+    ffi::{c_char, c_void},
+    ptr::{self, NonNull},
+};
+use std::io::{self, Read, Seek, SeekFrom};
+
+use crate::{
+    allocator, ffi, Decode, ParseOptions, ParseProgressCallback, ParseState, Parser, Tree,
+};
+
+/// The number of bytes that are read from the underlying reader at a time.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// The number of chunks that are cached when parsing from a seekable reader.
+const CACHED_CHUNK_COUNT: usize = 16;
+
+/// The longest encoded code point in any of the built-in encodings. Slices
+/// that are passed to the parser are never shorter than this, except at the
+/// end of the input, so that no character is split between two chunks.
+const MAX_CODE_POINT_SIZE: usize = 4;
+
+/// The text encoding of input that is read from an [`io::Read`] source.
+///
+/// This is used by [`Parser::parse_reader`] and
+/// [`Parser::parse_buffered_reader`], which operate on raw bytes.
+#[derive(Clone, Copy, Debug)]
+pub struct InputEncoding {
+    encoding: ffi::TSInputEncoding,
+    decode: ffi::DecodeFunction,
+}
+
+impl InputEncoding {
+    /// UTF-8 encoded text.
+    pub const UTF8: Self = Self {
+        encoding: ffi::TSInputEncodingUTF8,
+        decode: None,
+    };
+
+    /// UTF-16 little-endian encoded text.
+    pub const UTF16_LE: Self = Self {
+        encoding: ffi::TSInputEncodingUTF16LE,
+        decode: None,
+    };
+
+    /// UTF-16 big-endian encoded text.
+    pub const UTF16_BE: Self = Self {
+        encoding: ffi::TSInputEncodingUTF16BE,
+        decode: None,
+    };
+
+    /// Text in a custom encoding, which is decoded with the given [`Decode`]
+    /// implementation, as in [`Parser::parse_custom_encoding`].
+    #[must_use]
+    pub fn custom<D: Decode>() -> Self {
+        unsafe extern "C" fn decode_fn<D: Decode>(
+            data: *const u8,
+            len: u32,
+            code_point: *mut i32,
+        ) -> u32 {
+            let (c, len) = D::decode(core::slice::from_raw_parts(data, len as usize));
+            if let Some(code_point) = code_point.as_mut() {
+                *code_point = c;
+            }
+            len
+        }
+
+        Self {
+            encoding: ffi::TSInputEncodingCustom,
+            decode: Some(decode_fn::<D>),
+        }
+    }
+}
+
+impl Default for InputEncoding {
+    fn default() -> Self {
+        Self::UTF8
+    }
+}
+
+/// A source of text that can be read at arbitrary byte offsets.
+trait ChunkSource {
+    /// Return the bytes starting at the given offset. An empty slice
+    /// indicates the end of the input.
+    fn read_at(&mut self, offset: usize) -> io::Result<&[u8]>;
+}
+
+/// Reads from a seekable reader, keeping the most recently used chunks in
+/// memory.
+struct SeekableSource<R> {
+    reader: R,
+    base: u64,
+    chunks: Vec<(usize, Vec<u8>)>,
+}
+
+impl<R: Read + Seek> SeekableSource<R> {
+    fn new(mut reader: R) -> io::Result<Self> {
+        let base = reader.stream_position()?;
+        Ok(Self {
+            reader,
+            base,
+            chunks: Vec::with_capacity(CACHED_CHUNK_COUNT),
+        })
+    }
+
+    fn load_chunk(&mut self, index: usize) -> io::Result<Vec<u8>> {
+        let mut data = if self.chunks.len() == CACHED_CHUNK_COUNT {
+            let (_, mut data) = self.chunks.pop().unwrap();
+            data.clear();
+            data
+        } else {
+            Vec::with_capacity(CHUNK_SIZE)
+        };
+        self.reader
+            .seek(SeekFrom::Start(self.base + (index * CHUNK_SIZE) as u64))?;
+        (&mut self.reader)
+            .take(CHUNK_SIZE as u64)
+            .read_to_end(&mut data)?;
+        Ok(data)
+    }
+}
+
+impl<R: Read + Seek> ChunkSource for SeekableSource<R> {
+    fn read_at(&mut self, offset: usize) -> io::Result<&[u8]> {
+        let index = offset / CHUNK_SIZE;
+        if let Some(position) = self.chunks.iter().position(|(i, _)| *i == index) {
+            self.chunks[..=position].rotate_right(1);
+        } else {
+            let data = self.load_chunk(index)?;
+            self.chunks.insert(0, (index, data));
+        }
+        let data = &self.chunks[0].1;
+        Ok(data.get(offset % CHUNK_SIZE..).unwrap_or_default())
+    }
+}
+
+/// Reads from a non-seekable reader, retaining a bounded window of text
+/// before the furthest offset that has been read.
+struct StreamSource<R> {
+    reader: R,
+    buffer: Vec<u8>,
+    buffer_start: usize,
+    backtrack_window: usize,
+    eof: bool,
+}
+
+impl<R: Read> StreamSource<R> {
+    const fn new(reader: R, backtrack_window: usize) -> Self {
+        Self {
+            reader,
+            buffer: Vec::new(),
+            buffer_start: 0,
+            backtrack_window,
+            eof: false,
+        }
+    }
+}
+
+impl<R: Read> ChunkSource for StreamSource<R> {
+    fn read_at(&mut self, offset: usize) -> io::Result<&[u8]> {
+        if offset < self.buffer_start {
+            return Err(io::Error::other(format!(
+                "byte offset {offset} is outside of the backtrack window, which starts at {}",
+                self.buffer_start
+            )));
+        }
+
+        while !self.eof && offset + MAX_CODE_POINT_SIZE > self.buffer_start + self.buffer.len() {
+            let read = (&mut self.reader)
+                .take(CHUNK_SIZE as u64)
+                .read_to_end(&mut self.buffer)?;
+            self.eof = read == 0;
+        }
+
+        // Discard text that is no longer reachable, but only once a whole
+        // chunk can be dropped, to avoid shifting the buffer on every read.
+        let keep_start = offset.saturating_sub(self.backtrack_window);
+        if keep_start >= self.buffer_start + CHUNK_SIZE {
+            self.buffer.drain(..keep_start - self.buffer_start);
+            self.buffer_start = keep_start;
+        }
+
+        Ok(self
+            .buffer
+            .get(offset - self.buffer_start..)
+            .unwrap_or_default())
+    }
+}
+
+struct Payload<S> {
+    source: S,
+    seam: [u8; 2 * MAX_CODE_POINT_SIZE],
+    error: Option<io::Error>,
+}
+
+impl<S: ChunkSource> Payload<S> {
+    /// Return the text at the given offset. If the source returns a slice
+    /// that is too short to hold a complete code point, the text is copied
+    /// together with the start of the following slice into a small buffer.
+    fn read(&mut self, offset: usize) -> io::Result<&[u8]> {
+        let available = self.source.read_at(offset)?.len();
+        if available == 0 || available >= MAX_CODE_POINT_SIZE {
+            return self.source.read_at(offset);
+        }
+
+        let mut len = 0;
+        while len < MAX_CODE_POINT_SIZE {
+            let slice = self.source.read_at(offset + len)?;
+            if slice.is_empty() {
+                break;
+            }
+            let count = slice.len().min(self.seam.len() - len);
+            self.seam[len..len + count].copy_from_slice(&slice[..count]);
+            len += count;
+        }
+        Ok(&self.seam[..len])
+    }
+}
+
+fn parse_source<S: ChunkSource>(
+    parser: &mut Parser,
+    source: S,
+    encoding: InputEncoding,
+    old_tree: Option<&Tree>,
+    options: Option<ParseOptions>,
+) -> io::Result<Option<Tree>> {
+    // This C function is passed to Tree-sitter as the progress callback.
+    unsafe extern "C" fn progress(state: *mut ffi::TSParseState) -> bool {
+        let callback = (*state)
+            .payload
+            .cast::<ParseProgressCallback>()
+            .as_mut()
+            .unwrap();
+        callback(&ParseState::from_raw(state))
+    }
+
+    // This C function is passed to Tree-sitter as the input callback. Once
+    // reading has failed, it reports the end of the input, and the error is
+    // returned after parsing has finished.
+    unsafe extern "C" fn read<S: ChunkSource>(
+        payload: *mut c_void,
+        byte_offset: u32,
+        _position: ffi::TSPoint,
+        bytes_read: *mut u32,
+    ) -> *const c_char {
+        let payload = payload.cast::<Payload<S>>().as_mut().unwrap();
+        if payload.error.is_some() {
+            *bytes_read = 0;
+            return ptr::null();
+        }
+        match payload.read(byte_offset as usize) {
+            Ok(slice) => {
+                *bytes_read = slice.len() as u32;
+                slice.as_ptr().cast::<c_char>()
+            }
+            Err(error) => {
+                payload.error = Some(error);
+                *bytes_read = 0;
+                ptr::null()
+            }
+        }
+    }
+
+    let empty_options = ffi::TSParseOptions {
+        payload: ptr::null_mut(),
+        progress_callback: None,
+    };
+
+    let mut callback_ptr;
+    let parse_options = if let Some(options) = options {
+        if let Some(cb) = options.progress_callback {
+            callback_ptr = cb;
+            ffi::TSParseOptions {
+                payload: core::ptr::addr_of_mut!(callback_ptr).cast::<c_void>(),
+                progress_callback: Some(progress),
+            }
+        } else {
+            empty_options
+        }
+    } else {
+        empty_options
+    };
+
+    let mut payload = Payload {
+        source,
+        seam: [0; 2 * MAX_CODE_POINT_SIZE],
+        error: None,
+    };
+
+    let c_input = ffi::TSInput {
+        payload: ptr::addr_of_mut!(payload).cast::<c_void>(),
+        read: Some(read::<S>),
+        encoding: encoding.encoding,
+        decode: encoding.decode,
+    };
+
+    let c_old_tree = old_tree.map_or(ptr::null_mut(), |t| t.0.as_ptr());
+    let tree = unsafe {
+        let c_new_tree = allocator::track(
+            || {
+                ffi::ts_parser_parse_with_options(
+                    parser.0.as_ptr(),
+                    c_old_tree,
+                    c_input,
+                    parse_options,
+                )
+            },
+            |tree| tree.cast(),
+        );
+        NonNull::new(c_new_tree).map(Tree)
+    };
+
+    match payload.error {
+        Some(error) => {
+            parser.reset();
+            Err(error)
+        }
+        None => Ok(tree),
+    }
+}
+
+impl Parser {
+    /// Parse text that is read from a seekable reader, such as a [`File`](std::fs::File).
+    ///
+    /// The text is read in chunks, and a bounded number of recently used chunks
+    /// are kept in memory, so the whole document never needs to be loaded at
+    /// once. Byte offsets in the resulting tree are relative to the reader's
+    /// position when this method is called.
+    ///
+    /// # Arguments:
+    /// * `reader` The reader to parse text from.
+    /// * `encoding` The encoding of the text.
+    /// * `old_tree` A previous syntax tree parsed from the same document. If the text of the
+    ///   document has changed since `old_tree` was created, then you must edit `old_tree` to match
+    ///   the new text using [`Tree::edit`].
+    /// * `options` Options for parsing the text. This can be used to set a progress callback.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if reading from or seeking in `reader` fails. In that
+    /// case, the parser is [reset](Parser::reset).
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    pub fn parse_reader<R: Read + Seek>(
+        &mut self,
+        reader: R,
+        encoding: InputEncoding,
+        old_tree: Option<&Tree>,
+        options: Option<ParseOptions>,
+    ) -> io::Result<Option<Tree>> {
+        let source = SeekableSource::new(reader)?;
+        parse_source(self, source, encoding, old_tree, options)
+    }
+
+    /// Parse text that is read from a reader that does not support seeking,
+    /// such as a pipe or a network stream.
+    ///
+    /// The parser occasionally needs to re-read text that it has already
+    /// consumed, for example when a token needs lookahead. Text is therefore
+    /// buffered, and at least `backtrack_window` bytes before the furthest
+    /// position that has been read remain available. Tokens longer than the
+    /// window cannot be re-read, and cause an error.
+    ///
+    /// Because the text cannot be read twice, a parse that is halted by a
+    /// progress callback or a timeout cannot be resumed with the same reader.
+    ///
+    /// # Arguments:
+    /// * `reader` The reader to parse text from.
+    /// * `backtrack_window` The number of bytes that are kept in memory behind
+    ///   the current read position.
+    /// * `encoding` The encoding of the text.
+    /// * `old_tree` A previous syntax tree parsed from the same document. If the text of the
+    ///   document has changed since `old_tree` was created, then you must edit `old_tree` to match
+    ///   the new text using [`Tree::edit`].
+    /// * `options` Options for parsing the text. This can be used to set a progress callback.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if reading from `reader` fails, or if the parser tries
+    /// to re-read text that is outside of the backtrack window. In that case,
+    /// the parser is [reset](Parser::reset).
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    pub fn parse_buffered_reader<R: Read>(
+        &mut self,
+        reader: R,
+        backtrack_window: usize,
+        encoding: InputEncoding,
+        old_tree: Option<&Tree>,
+        options: Option<ParseOptions>,
+    ) -> io::Result<Option<Tree>> {
+        let source = StreamSource::new(reader, backtrack_window);
+        parse_source(self, source, encoding, old_tree, options)
+    }
+}
@@ -0,0 +1,174 @@
+use core::{fmt, ops};
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::error;
+
+use crate::{InputEdit, Node, Tree};
+
+/// A builder for changes to the source code of a syntax tree, expressed in
+/// terms of its nodes.
+///
+/// Changes are recorded with [`replace`](Rewriter::replace),
+/// [`insert_before`](Rewriter::insert_before),
+/// [`insert_after`](Rewriter::insert_after) and
+/// [`delete`](Rewriter::delete), and then applied all at once by
+/// [`rewrite`](Rewriter::rewrite). The text outside of the changed nodes is
+/// preserved exactly.
+pub struct Rewriter<'a> {
+    source: &'a [u8],
+    changes: Vec<Change>,
+}
+
+/// The order in which changes at the same offset are applied.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum ChangeKind {
+    InsertAfter,
+    InsertBefore,
+    Replace,
+}
+
+struct Change {
+    range: ops::Range<usize>,
+    kind: ChangeKind,
+    text: Vec<u8>,
+}
+
+/// The result of applying the changes of a [`Rewriter`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Rewrite {
+    /// The rewritten source code.
+    pub source: Vec<u8>,
+    /// The edits that turn the original source code into the rewritten one,
+    /// ordered from the end of the text to the start, so that each edit's
+    /// offsets remain valid after the previous ones have been applied.
+    pub edits: Vec<InputEdit>,
+}
+
+/// An error that occurred in [`Rewriter::rewrite`], when two changes affect
+/// overlapping ranges of the source code.
+#[derive(Debug, PartialEq, Eq)]
+pub struct RewriteError {
+    /// The byte range of the change that starts first.
+    pub range: ops::Range<usize>,
+    /// The byte range of the change that overlaps it.
+    pub other_range: ops::Range<usize>,
+}
+
+impl<'a> Rewriter<'a> {
+    /// Create a rewriter for the source code that a syntax tree was parsed
+    /// from.
+    #[must_use]
+    pub const fn new(source: &'a [u8]) -> Self {
+        Self {
+            source,
+            changes: Vec::new(),
+        }
+    }
+
+    /// Replace the text of `node` with `text`.
+    pub fn replace(&mut self, node: Node, text: impl AsRef<[u8]>) -> &mut Self {
+        self.push(node.byte_range(), ChangeKind::Replace, text.as_ref())
+    }
+
+    /// Insert `text` immediately before `node`.
+    pub fn insert_before(&mut self, node: Node, text: impl AsRef<[u8]>) -> &mut Self {
+        let offset = node.start_byte();
+        self.push(offset..offset, ChangeKind::InsertBefore, text.as_ref())
+    }
+
+    /// Insert `text` immediately after `node`.
+    pub fn insert_after(&mut self, node: Node, text: impl AsRef<[u8]>) -> &mut Self {
+        let offset = node.end_byte();
+        self.push(offset..offset, ChangeKind::InsertAfter, text.as_ref())
+    }
+
+    /// Delete the text of `node`. Any surrounding whitespace or separators are
+    /// left untouched.
+    pub fn delete(&mut self, node: Node) -> &mut Self {
+        self.push(node.byte_range(), ChangeKind::Replace, &[])
+    }
+
+    /// Apply the recorded changes to the source code.
+    ///
+    /// Text that is inserted at the same offset appears in the order in which
+    /// it was recorded, with text inserted after a node placed before text
+    /// inserted before the next one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if two replacements or deletions overlap, or if text is
+    /// inserted strictly inside a node that is replaced or deleted.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a changed node lies outside of the source code.
+    pub fn rewrite(&self) -> Result<Rewrite, RewriteError> {
+        let mut changes = self.changes.iter().collect::<Vec<_>>();
+        changes.sort_by_key(|change| (change.range.start, change.range.end, change.kind));
+
+        let mut last_range = 0..0;
+        for change in &changes {
+            if change.range.start < last_range.end {
+                return Err(RewriteError {
+                    range: last_range,
+                    other_range: change.range.clone(),
+                });
+            }
+            if !change.range.is_empty() {
+                last_range = change.range.clone();
+            }
+        }
+
+        let mut source = Vec::with_capacity(self.source.len());
+        let mut offset = 0;
+        for change in &changes {
+            source.extend_from_slice(&self.source[offset..change.range.start]);
+            source.extend_from_slice(&change.text);
+            offset = change.range.end;
+        }
+        source.extend_from_slice(&self.source[offset..]);
+
+        let replacements = changes
+            .iter()
+            .map(|change| (change.range.clone(), change.text.as_slice()))
+            .collect::<Vec<_>>();
+        let edits = InputEdit::from_replacements(self.source, &replacements);
+        Ok(Rewrite { source, edits })
+    }
+
+    fn push(&mut self, range: ops::Range<usize>, kind: ChangeKind, text: &[u8]) -> &mut Self {
+        self.changes.push(Change {
+            range,
+            kind,
+            text: text.to_vec(),
+        });
+        self
+    }
+}
+
+impl Rewrite {
+    /// Apply this rewrite's edits to `tree`, the syntax tree of the original
+    /// source code, so that it can be passed as the old tree when parsing the
+    /// rewritten source code.
+    pub fn edit_tree(&self, tree: &mut Tree) {
+        for edit in &self.edits {
+            tree.edit(edit);
+        }
+    }
+}
+
+impl fmt::Display for RewriteError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Rewrite of range {:?} overlaps rewrite of range {:?}",
+            self.other_range, self.range
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+impl error::Error for RewriteError {}
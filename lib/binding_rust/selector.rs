@@ -0,0 +1,544 @@
+use core::fmt;
+
+#[cfg(not(feature = "std"))]
+use alloc::{boxed::Box, format, string::String, vec::Vec};
+#[cfg(feature = "std")]
+use std::error;
+
+use crate::{FieldId, Language, Node, Preorder, Tree, TreeCursor};
+
+/// A compiled selector, for finding nodes with a compact syntax that is
+/// modeled after CSS selectors.
+///
+/// A selector is a comma-separated list of alternatives. Each alternative is
+/// a sequence of compound selectors, joined by combinators:
+///
+/// * `kind` matches named nodes of the given kind, `"text"` matches
+///   anonymous nodes of the given kind, and `*` matches any named node.
+/// * `field:kind` additionally requires the node to be the value of the given
+///   field in its parent.
+/// * `:has(selector)` requires the node to have a descendant that matches
+///   the selector. The selector can start with a combinator, for example
+///   `:has(> string)` for a child.
+/// * `:not(selector)` requires the node to not match the selector.
+/// * `:nth-child(n)`, `:first-child` and `:last-child` require the node to
+///   have the given 1-based position among the named children of its
+///   parent.
+/// * `a b`, `a > b`, `a + b` and `a ~ b` match `b` when it is a descendant, a
+///   child, the next named sibling or a later sibling of a node that matches
+///   `a`.
+///
+/// For example, `function_definition > name:identifier` matches the
+/// identifiers in the `name` field of function definitions.
+///
+/// Selectors are matched by walking the tree with a [`TreeCursor`], which
+/// cannot leave the node that it started from, so the node where a search
+/// starts has no parent or siblings, and is not in a field.
+#[derive(Clone, Debug)]
+pub struct Selector {
+    alternatives: Vec<Complex>,
+}
+
+/// An error that occurred when trying to create a [`Selector`].
+#[derive(Debug, PartialEq, Eq)]
+pub struct SelectorError {
+    /// The byte offset in the selector where the error occurred.
+    pub offset: usize,
+    pub message: String,
+    pub kind: SelectorErrorKind,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum SelectorErrorKind {
+    Syntax,
+    NodeType,
+    Field,
+}
+
+/// An iterator over the nodes that match a [`Selector`], in pre-order,
+/// returned by [`Node::select`].
+pub struct SelectorMatches<'selector, 'tree> {
+    selector: &'selector Selector,
+    nodes: Preorder<'tree>,
+}
+
+/// A sequence of compound selectors, each joined to the previous one by its
+/// combinator.
+#[derive(Clone, Debug)]
+struct Complex {
+    compounds: Vec<(Combinator, Compound)>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Combinator {
+    Descendant,
+    Child,
+    NextSibling,
+    LaterSibling,
+}
+
+#[derive(Clone, Debug, Default)]
+struct Compound {
+    /// Whether the node must be the one where the match started, which is
+    /// used for the relative selectors of `:has`.
+    scope: bool,
+    kind: Option<(u16, bool)>,
+    field: Option<FieldId>,
+    pseudo_classes: Vec<PseudoClass>,
+}
+
+#[derive(Clone, Debug)]
+enum PseudoClass {
+    Has(Box<Selector>),
+    Not(Box<Selector>),
+    NthChild(usize),
+    LastChild,
+}
+
+const PSEUDO_CLASS_NAMES: &[&str] = &["has", "not", "nth-child", "first-child", "last-child"];
+
+impl Selector {
+    /// Compile a selector for the given language.
+    ///
+    /// Returns an error if the selector is not valid, or if it contains node
+    /// kinds or field names that do not exist in the language.
+    pub fn new(language: &Language, source: &str) -> Result<Self, SelectorError> {
+        let mut parser = SelectorParser {
+            language,
+            source,
+            offset: 0,
+        };
+        let selector = parser.parse_selector(false)?;
+        parser.skip_whitespace();
+        if parser.offset < source.len() {
+            return Err(parser.error(SelectorErrorKind::Syntax, "Unexpected character"));
+        }
+        Ok(selector)
+    }
+
+    /// Check if the cursor's current node matches this selector.
+    #[must_use]
+    pub fn matches(&self, cursor: &TreeCursor) -> bool {
+        self.alternatives
+            .iter()
+            .any(|complex| complex.matches(complex.compounds.len() - 1, cursor))
+    }
+}
+
+impl Complex {
+    fn matches(&self, index: usize, cursor: &TreeCursor) -> bool {
+        let (combinator, compound) = &self.compounds[index];
+        if !compound.matches(cursor) {
+            return false;
+        }
+        if index == 0 {
+            return true;
+        }
+
+        let mut cursor = cursor.clone();
+        match combinator {
+            Combinator::Child => cursor.goto_parent() && self.matches(index - 1, &cursor),
+            Combinator::Descendant => {
+                while cursor.goto_parent() {
+                    if self.matches(index - 1, &cursor) {
+                        return true;
+                    }
+                }
+                false
+            }
+            Combinator::NextSibling => {
+                goto_previous_named_sibling(&mut cursor) && self.matches(index - 1, &cursor)
+            }
+            Combinator::LaterSibling => {
+                while goto_previous_named_sibling(&mut cursor) {
+                    if self.matches(index - 1, &cursor) {
+                        return true;
+                    }
+                }
+                false
+            }
+        }
+    }
+}
+
+impl Compound {
+    fn matches(&self, cursor: &TreeCursor) -> bool {
+        let node = cursor.node();
+        // The scope is the node where the cursor was created, which is the
+        // only one with a descendant index of zero.
+        if self.scope && cursor.descendant_index() > 0 {
+            return false;
+        }
+        match self.kind {
+            Some((kind_id, is_named)) => {
+                if node.kind_id() != kind_id || node.is_named() != is_named {
+                    return false;
+                }
+            }
+            None => {
+                if !self.scope && !node.is_named() {
+                    return false;
+                }
+            }
+        }
+        if self.field.is_some() && cursor.field_id() != self.field {
+            return false;
+        }
+        self.pseudo_classes
+            .iter()
+            .all(|pseudo_class| match pseudo_class {
+                PseudoClass::Has(selector) => {
+                    let mut descendants = node.preorder();
+                    descendants.next();
+                    while descendants.next().is_some() {
+                        if selector.matches(descendants.cursor()) {
+                            return true;
+                        }
+                    }
+                    false
+                }
+                PseudoClass::Not(selector) => !selector.matches(cursor),
+                PseudoClass::NthChild(n) => {
+                    let mut cursor = cursor.clone();
+                    let mut position = 1;
+                    while goto_previous_named_sibling(&mut cursor) {
+                        position += 1;
+                    }
+                    position == *n
+                }
+                PseudoClass::LastChild => {
+                    let mut cursor = cursor.clone();
+                    while cursor.goto_next_sibling() {
+                        if cursor.node().is_named() {
+                            return false;
+                        }
+                    }
+                    true
+                }
+            })
+    }
+}
+
+fn goto_previous_named_sibling(cursor: &mut TreeCursor) -> bool {
+    while cursor.goto_previous_sibling() {
+        if cursor.node().is_named() {
+            return true;
+        }
+    }
+    false
+}
+
+struct SelectorParser<'a> {
+    language: &'a Language,
+    source: &'a str,
+    offset: usize,
+}
+
+impl<'a> SelectorParser<'a> {
+    /// Parse a comma-separated list of complex selectors. If `relative` is
+    /// set, each one can start with a combinator, and is matched relative to
+    /// the node where the match started.
+    fn parse_selector(&mut self, relative: bool) -> Result<Selector, SelectorError> {
+        let mut alternatives = Vec::new();
+        loop {
+            alternatives.push(self.parse_complex(relative)?);
+            self.skip_whitespace();
+            if !self.eat(',') {
+                break;
+            }
+        }
+        Ok(Selector { alternatives })
+    }
+
+    fn parse_complex(&mut self, relative: bool) -> Result<Complex, SelectorError> {
+        let mut compounds = Vec::new();
+        self.skip_whitespace();
+        if relative {
+            let scope = Compound {
+                scope: true,
+                ..Compound::default()
+            };
+            compounds.push((Combinator::Descendant, scope));
+            let combinator = self.parse_combinator().unwrap_or(Combinator::Descendant);
+            compounds.push((combinator, self.parse_compound()?));
+        } else {
+            compounds.push((Combinator::Descendant, self.parse_compound()?));
+        }
+
+        loop {
+            let had_whitespace = self.skip_whitespace();
+            let combinator = match self.parse_combinator() {
+                Some(combinator) => combinator,
+                None if had_whitespace && !matches!(self.peek(), None | Some(',' | ')')) => {
+                    Combinator::Descendant
+                }
+                None => break,
+            };
+            compounds.push((combinator, self.parse_compound()?));
+        }
+        Ok(Complex { compounds })
+    }
+
+    fn parse_combinator(&mut self) -> Option<Combinator> {
+        let combinator = match self.peek()? {
+            '>' => Combinator::Child,
+            '+' => Combinator::NextSibling,
+            '~' => Combinator::LaterSibling,
+            _ => return None,
+        };
+        self.offset += 1;
+        self.skip_whitespace();
+        Some(combinator)
+    }
+
+    fn parse_compound(&mut self) -> Result<Compound, SelectorError> {
+        let mut compound = Compound::default();
+        let start = self.offset;
+        match self.peek() {
+            Some('*') => self.offset += 1,
+            Some('"') => compound.kind = Some(self.parse_anonymous_kind()?),
+            Some(c) if is_name_char(c) => {
+                let name = self.take_while(is_name_char);
+                // A name that is followed by a colon is a field name, unless
+                // the colon starts a pseudo-class of a node kind.
+                let field_id = self.language.field_id_for_name(name);
+                let is_field = self.peek() == Some(':')
+                    && !self.at_pseudo_class()
+                    && (field_id.is_some() || self.language.id_for_node_kind(name, true) == 0);
+                if is_field {
+                    self.offset += 1;
+                    compound.field = Some(field_id.ok_or_else(|| SelectorError {
+                        offset: start,
+                        message: name.into(),
+                        kind: SelectorErrorKind::Field,
+                    })?);
+                    let start = self.offset;
+                    match self.peek() {
+                        Some('*') => self.offset += 1,
+                        Some('"') => compound.kind = Some(self.parse_anonymous_kind()?),
+                        Some(c) if is_name_char(c) => {
+                            let name = self.take_while(is_name_char);
+                            compound.kind = Some(self.named_kind(name, start)?);
+                        }
+                        _ => {
+                            return Err(self.error(SelectorErrorKind::Syntax, "Expected node kind"))
+                        }
+                    }
+                } else {
+                    compound.kind = Some(self.named_kind(name, start)?);
+                }
+            }
+            Some(':') => {}
+            _ => return Err(self.error(SelectorErrorKind::Syntax, "Expected selector")),
+        }
+
+        while self.peek() == Some(':') {
+            let start = self.offset;
+            self.offset += 1;
+            let name = self.take_while(|c| is_name_char(c) || c == '-');
+            let pseudo_class = match name {
+                "has" | "not" => {
+                    self.expect('(')?;
+                    let selector = Box::new(self.parse_selector(name == "has")?);
+                    self.expect(')')?;
+                    if name == "has" {
+                        PseudoClass::Has(selector)
+                    } else {
+                        PseudoClass::Not(selector)
+                    }
+                }
+                "nth-child" => {
+                    self.expect('(')?;
+                    self.skip_whitespace();
+                    let digits = self.take_while(|c| c.is_ascii_digit());
+                    let n = digits
+                        .parse::<usize>()
+                        .ok()
+                        .filter(|n| *n > 0)
+                        .ok_or_else(|| {
+                            self.error(SelectorErrorKind::Syntax, "Expected a positive integer")
+                        })?;
+                    self.skip_whitespace();
+                    self.expect(')')?;
+                    PseudoClass::NthChild(n)
+                }
+                "first-child" => PseudoClass::NthChild(1),
+                "last-child" => PseudoClass::LastChild,
+                _ => {
+                    return Err(SelectorError {
+                        offset: start,
+                        message: format!("Unknown pseudo-class :{name}"),
+                        kind: SelectorErrorKind::Syntax,
+                    })
+                }
+            };
+            compound.pseudo_classes.push(pseudo_class);
+        }
+        Ok(compound)
+    }
+
+    fn parse_anonymous_kind(&mut self) -> Result<(u16, bool), SelectorError> {
+        let start = self.offset;
+        self.offset += 1;
+        let mut kind = String::new();
+        loop {
+            match self.next() {
+                Some('"') => break,
+                Some('\\') => match self.next() {
+                    Some('n') => kind.push('\n'),
+                    Some('t') => kind.push('\t'),
+                    Some(c) => kind.push(c),
+                    None => break,
+                },
+                Some(c) => kind.push(c),
+                None => {
+                    return Err(SelectorError {
+                        offset: start,
+                        message: "Unterminated string".into(),
+                        kind: SelectorErrorKind::Syntax,
+                    })
+                }
+            }
+        }
+        match self.language.id_for_node_kind(&kind, false) {
+            0 => Err(SelectorError {
+                offset: start,
+                message: kind,
+                kind: SelectorErrorKind::NodeType,
+            }),
+            id => Ok((id, false)),
+        }
+    }
+
+    fn named_kind(&self, name: &str, start: usize) -> Result<(u16, bool), SelectorError> {
+        match self.language.id_for_node_kind(name, true) {
+            0 => Err(SelectorError {
+                offset: start,
+                message: name.into(),
+                kind: SelectorErrorKind::NodeType,
+            }),
+            id => Ok((id, true)),
+        }
+    }
+
+    /// Check if the `:` at the current offset starts a pseudo-class, rather
+    /// than separating a field name from a node kind.
+    fn at_pseudo_class(&self) -> bool {
+        let rest = &self.source[self.offset + 1..];
+        let end = rest
+            .find(|c| !is_name_char(c) && c != '-')
+            .unwrap_or(rest.len());
+        PSEUDO_CLASS_NAMES.contains(&&rest[..end])
+    }
+
+    fn skip_whitespace(&mut self) -> bool {
+        !self.take_while(char::is_whitespace).is_empty()
+    }
+
+    fn take_while(&mut self, f: impl Fn(char) -> bool) -> &'a str {
+        let start = self.offset;
+        let rest = &self.source[start..];
+        self.offset += rest.find(|c| !f(c)).unwrap_or(rest.len());
+        &self.source[start..self.offset]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.source[self.offset..].chars().next()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.offset += c.len_utf8();
+        Some(c)
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.offset += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), SelectorError> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.error(SelectorErrorKind::Syntax, &format!("Expected '{c}'")))
+        }
+    }
+
+    fn error(&self, kind: SelectorErrorKind, message: &str) -> SelectorError {
+        SelectorError {
+            offset: self.offset,
+            message: message.into(),
+            kind,
+        }
+    }
+}
+
+const fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+impl<'tree> Iterator for SelectorMatches<'_, 'tree> {
+    type Item = Node<'tree>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let node = self.nodes.next()?;
+            if self.selector.matches(self.nodes.cursor()) {
+                return Some(node);
+            }
+        }
+    }
+}
+
+impl<'tree> Node<'tree> {
+    /// Iterate over this node and its descendants that match the given
+    /// selector, in pre-order.
+    #[must_use]
+    pub fn select<'selector>(
+        &self,
+        selector: &'selector Selector,
+    ) -> SelectorMatches<'selector, 'tree> {
+        SelectorMatches {
+            selector,
+            nodes: self.preorder(),
+        }
+    }
+}
+
+impl Tree {
+    /// Iterate over the nodes of this tree that match the given selector, in
+    /// pre-order.
+    ///
+    /// See [`Node::select`].
+    #[must_use]
+    pub fn select<'selector>(
+        &self,
+        selector: &'selector Selector,
+    ) -> SelectorMatches<'selector, '_> {
+        self.root_node().select(selector)
+    }
+}
+
+impl fmt::Display for SelectorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let msg = match self.kind {
+            SelectorErrorKind::Field => "Invalid field name ",
+            SelectorErrorKind::NodeType => "Invalid node type ",
+            SelectorErrorKind::Syntax => "Invalid syntax: ",
+        };
+        write!(
+            f,
+            "Selector error at offset {}. {msg}{}",
+            self.offset, self.message
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+impl error::Error for SelectorError {}
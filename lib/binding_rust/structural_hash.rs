@@ -0,0 +1,232 @@
+#[cfg(not(feature = "std"))]
+use alloc::vec;
+
+use crate::{
+    util::{fnv_hash, FNV_OFFSET},
+    Node, TreeCursor,
+};
+
+/// Options for computing a [`Node::structural_hash`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StructuralHashOptions<'a> {
+    /// Whether the text of leaf nodes is part of the hash. Without it, only
+    /// the kinds and fields of the nodes are hashed, so subtrees that differ
+    /// only in their identifiers or literals hash equally.
+    pub include_text: bool,
+    /// Whether to skip extra nodes, such as comments in most grammars.
+    pub ignore_extras: bool,
+    /// The ids of the node kinds to skip, along with their subtrees.
+    pub ignored_kinds: &'a [u16],
+}
+
+impl<'a> StructuralHashOptions<'a> {
+    /// Create options that hash the kinds and fields of every node in a
+    /// subtree, but not the text of its leaves.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Include the text of leaf nodes in the hash.
+    #[must_use]
+    pub const fn include_text(mut self, include_text: bool) -> Self {
+        self.include_text = include_text;
+        self
+    }
+
+    /// Skip extra nodes, such as comments in most grammars.
+    #[must_use]
+    pub const fn ignore_extras(mut self, ignore_extras: bool) -> Self {
+        self.ignore_extras = ignore_extras;
+        self
+    }
+
+    /// Skip nodes with any of the given kind ids, along with their subtrees.
+    /// This can be used to skip comments in grammars where they are not
+    /// extras.
+    #[must_use]
+    pub const fn ignored_kinds(mut self, ignored_kinds: &'a [u16]) -> Self {
+        self.ignored_kinds = ignored_kinds;
+        self
+    }
+
+    /// Check if the given node is skipped, along with its subtree, when it
+    /// appears within a hashed subtree.
+    #[must_use]
+    pub fn is_ignored(&self, node: Node) -> bool {
+        (self.ignore_extras && node.is_extra()) || self.ignored_kinds.contains(&node.kind_id())
+    }
+}
+
+impl Node<'_> {
+    /// Compute a hash of the structure of this node's subtree.
+    ///
+    /// The hash combines the kind id of each node in the subtree, the id of
+    /// the field in which each descendant appears, and, if
+    /// [`include_text`](StructuralHashOptions::include_text) is set, the text
+    /// of the leaves. The kind and field of this node in its own parent are
+    /// not included, so identical subtrees hash equally wherever they appear.
+    ///
+    /// The hash is stable across runs and platforms, but the ids that it is
+    /// computed from can change between versions of a grammar.
+    #[must_use]
+    pub fn structural_hash(&self, source: &[u8], options: &StructuralHashOptions) -> u64 {
+        let mut cursor = self.walk();
+
+        // The hashes of the nodes from this node to the cursor's current node,
+        // each with the id of the field in which the node appears.
+        let mut stack = vec![(leaf_hash(*self, source, options), 0)];
+        loop {
+            if cursor.goto_first_child() {
+                if goto_included_sibling(&mut cursor, options) {
+                    stack.push(node_entry(&cursor, source, options));
+                    continue;
+                }
+                cursor.goto_parent();
+            }
+
+            loop {
+                let (hash, field_id) = stack.pop().unwrap();
+                let Some((parent_hash, _)) = stack.last_mut() else {
+                    return hash;
+                };
+                *parent_hash = fnv_hash(*parent_hash, &field_id.to_le_bytes());
+                *parent_hash = fnv_hash(*parent_hash, &hash.to_le_bytes());
+
+                if cursor.goto_next_sibling() && goto_included_sibling(&mut cursor, options) {
+                    stack.push(node_entry(&cursor, source, options));
+                    break;
+                }
+                cursor.goto_parent();
+            }
+        }
+    }
+
+    /// Check if this node's subtree is identical to another node's subtree,
+    /// by the same measure as [`Node::structural_hash`]. Subtrees with equal
+    /// hashes are almost always identical, so this can be used to rule out
+    /// the rare collisions.
+    #[must_use]
+    pub fn structurally_eq(
+        &self,
+        source: &[u8],
+        other: &Node,
+        other_source: &[u8],
+        options: &StructuralHashOptions,
+    ) -> bool {
+        if !leaf_eq(*self, source, *other, other_source, options) {
+            return false;
+        }
+
+        let mut cursor = self.walk();
+        let mut other_cursor = other.walk();
+        let mut depth = 0;
+        loop {
+            let has_child = goto_first_included_child(&mut cursor, options);
+            if has_child != goto_first_included_child(&mut other_cursor, options) {
+                return false;
+            }
+            if has_child {
+                if !entry_eq(&cursor, source, &other_cursor, other_source, options) {
+                    return false;
+                }
+                depth += 1;
+                continue;
+            }
+
+            loop {
+                if depth == 0 {
+                    return true;
+                }
+                let has_sibling = goto_next_included_sibling(&mut cursor, options);
+                if has_sibling != goto_next_included_sibling(&mut other_cursor, options) {
+                    return false;
+                }
+                if has_sibling {
+                    if !entry_eq(&cursor, source, &other_cursor, other_source, options) {
+                        return false;
+                    }
+                    break;
+                }
+                cursor.goto_parent();
+                other_cursor.goto_parent();
+                depth -= 1;
+            }
+        }
+    }
+}
+
+fn goto_first_included_child(cursor: &mut TreeCursor, options: &StructuralHashOptions) -> bool {
+    if cursor.goto_first_child() {
+        if goto_included_sibling(cursor, options) {
+            return true;
+        }
+        cursor.goto_parent();
+    }
+    false
+}
+
+fn goto_next_included_sibling(cursor: &mut TreeCursor, options: &StructuralHashOptions) -> bool {
+    cursor.goto_next_sibling() && goto_included_sibling(cursor, options)
+}
+
+/// Move the cursor forward to the first sibling, starting from its current
+/// node, that is not ignored.
+fn goto_included_sibling(cursor: &mut TreeCursor, options: &StructuralHashOptions) -> bool {
+    while options.is_ignored(cursor.node()) {
+        if !cursor.goto_next_sibling() {
+            return false;
+        }
+    }
+    true
+}
+
+fn node_entry(cursor: &TreeCursor, source: &[u8], options: &StructuralHashOptions) -> (u64, u16) {
+    let field_id = cursor.field_id().map_or(0, u16::from);
+    (leaf_hash(cursor.node(), source, options), field_id)
+}
+
+fn entry_eq(
+    cursor: &TreeCursor,
+    source: &[u8],
+    other_cursor: &TreeCursor,
+    other_source: &[u8],
+    options: &StructuralHashOptions,
+) -> bool {
+    cursor.field_id() == other_cursor.field_id()
+        && leaf_eq(
+            cursor.node(),
+            source,
+            other_cursor.node(),
+            other_source,
+            options,
+        )
+}
+
+/// Compare the parts of two nodes that [`leaf_hash`] hashes.
+fn leaf_eq(
+    node: Node,
+    source: &[u8],
+    other: Node,
+    other_source: &[u8],
+    options: &StructuralHashOptions,
+) -> bool {
+    node.kind_id() == other.kind_id()
+        && (!options.include_text
+            || node.child_count() != 0
+            || leaf_text(node, source) == leaf_text(other, other_source))
+}
+
+/// Compute the part of a node's hash that does not depend on its children.
+fn leaf_hash(node: Node, source: &[u8], options: &StructuralHashOptions) -> u64 {
+    let hash = fnv_hash(FNV_OFFSET, &node.kind_id().to_le_bytes());
+    if options.include_text && node.child_count() == 0 {
+        fnv_hash(hash, leaf_text(node, source))
+    } else {
+        hash
+    }
+}
+
+fn leaf_text<'a>(node: Node, source: &'a [u8]) -> &'a [u8] {
+    source.get(node.byte_range()).unwrap_or_default()
+}
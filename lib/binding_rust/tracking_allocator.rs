@@ -0,0 +1,289 @@
+use core::ffi::c_void;
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Mutex, PoisonError,
+    },
+};
+
+use crate::{install_allocator, AllocatorError, QueryCursor, Tree};
+
+/// Whether a [`TrackingAllocator`] has been installed for the core library.
+static IS_TRACKING: AtomicBool = AtomicBool::new(false);
+
+/// The allocations that have been attributed to each tree, keyed by the
+/// address of the underlying object.
+static OWNER_STATS: Mutex<BTreeMap<usize, AllocationStats>> = Mutex::new(BTreeMap::new());
+
+thread_local! {
+    /// The allocations that the current thread has made with a tracking
+    /// allocator.
+    static THREAD_STATS: Cell<AllocationStats> = const {
+        Cell::new(AllocationStats {
+            allocations: 0,
+            allocated_bytes: 0,
+            deallocations: 0,
+            deallocated_bytes: 0,
+        })
+    };
+}
+
+/// Totals of the memory allocations made by a [`TrackingAllocator`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AllocationStats {
+    /// The number of allocations, including reallocations.
+    pub allocations: usize,
+    /// The number of bytes that were allocated.
+    pub allocated_bytes: usize,
+    /// The number of deallocations, including reallocations.
+    pub deallocations: usize,
+    /// The number of bytes that were deallocated.
+    pub deallocated_bytes: usize,
+}
+
+impl AllocationStats {
+    /// Get the number of allocated bytes that have not been deallocated.
+    #[must_use]
+    pub const fn retained_bytes(&self) -> usize {
+        self.allocated_bytes.saturating_sub(self.deallocated_bytes)
+    }
+
+    fn record_allocation(&mut self, size: usize) {
+        self.allocations += 1;
+        self.allocated_bytes += size;
+    }
+
+    fn record_deallocation(&mut self, size: usize) {
+        self.deallocations += 1;
+        self.deallocated_bytes += size;
+    }
+
+    fn add(&mut self, other: &Self) {
+        self.allocations += other.allocations;
+        self.allocated_bytes += other.allocated_bytes;
+        self.deallocations += other.deallocations;
+        self.deallocated_bytes += other.deallocated_bytes;
+    }
+
+    const fn difference(&self, earlier: &Self) -> Self {
+        Self {
+            allocations: self.allocations - earlier.allocations,
+            allocated_bytes: self.allocated_bytes - earlier.allocated_bytes,
+            deallocations: self.deallocations - earlier.deallocations,
+            deallocated_bytes: self.deallocated_bytes - earlier.deallocated_bytes,
+        }
+    }
+}
+
+/// An allocator that counts the memory allocated through it.
+///
+/// Once it has been installed for the core library with
+/// [`install`](TrackingAllocator::install), the memory that is allocated
+/// while parsing is attributed to the resulting tree, and the memory that is
+/// allocated while running a query is attributed to the query cursor. The
+/// totals are available from [`Tree::allocation_stats`] and
+/// [`QueryCursor::allocation_stats`].
+///
+/// ```
+/// use tree_sitter::{Parser, TrackingAllocator};
+///
+/// static ALLOCATOR: TrackingAllocator = TrackingAllocator::new();
+///
+/// ALLOCATOR.install().unwrap();
+/// let parser = Parser::new();
+/// assert!(ALLOCATOR.totals().retained_bytes() > 0);
+/// ```
+#[derive(Debug, Default)]
+pub struct TrackingAllocator<A = System> {
+    inner: A,
+    allocations: AtomicUsize,
+    allocated_bytes: AtomicUsize,
+    deallocations: AtomicUsize,
+    deallocated_bytes: AtomicUsize,
+}
+
+impl TrackingAllocator {
+    /// Create a tracking allocator that allocates memory with the system
+    /// allocator.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self::with_allocator(System)
+    }
+}
+
+impl<A: GlobalAlloc + Sync> TrackingAllocator<A> {
+    /// Create a tracking allocator that allocates memory with the given
+    /// allocator.
+    #[must_use]
+    pub const fn with_allocator(inner: A) -> Self {
+        Self {
+            inner,
+            allocations: AtomicUsize::new(0),
+            allocated_bytes: AtomicUsize::new(0),
+            deallocations: AtomicUsize::new(0),
+            deallocated_bytes: AtomicUsize::new(0),
+        }
+    }
+
+    /// Install this allocator for the core library with
+    /// [`install_allocator`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an allocator can no longer be installed.
+    pub fn install(&'static self) -> Result<(), AllocatorError> {
+        install_allocator(self)?;
+        IS_TRACKING.store(true, Ordering::Release);
+        Ok(())
+    }
+
+    /// Get the totals of all of the allocations made by this allocator.
+    #[must_use]
+    pub fn totals(&self) -> AllocationStats {
+        AllocationStats {
+            allocations: self.allocations.load(Ordering::Relaxed),
+            allocated_bytes: self.allocated_bytes.load(Ordering::Relaxed),
+            deallocations: self.deallocations.load(Ordering::Relaxed),
+            deallocated_bytes: self.deallocated_bytes.load(Ordering::Relaxed),
+        }
+    }
+
+    fn record_allocation(&self, size: usize) {
+        self.allocations.fetch_add(1, Ordering::Relaxed);
+        self.allocated_bytes.fetch_add(size, Ordering::Relaxed);
+        update_thread_stats(|stats| stats.record_allocation(size));
+    }
+
+    fn record_deallocation(&self, size: usize) {
+        self.deallocations.fetch_add(1, Ordering::Relaxed);
+        self.deallocated_bytes.fetch_add(size, Ordering::Relaxed);
+        update_thread_stats(|stats| stats.record_deallocation(size));
+    }
+}
+
+unsafe impl<A: GlobalAlloc + Sync> GlobalAlloc for TrackingAllocator<A> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let result = self.inner.alloc(layout);
+        if !result.is_null() {
+            self.record_allocation(layout.size());
+        }
+        result
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let result = self.inner.alloc_zeroed(layout);
+        if !result.is_null() {
+            self.record_allocation(layout.size());
+        }
+        result
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let result = self.inner.realloc(ptr, layout, new_size);
+        if !result.is_null() {
+            self.record_deallocation(layout.size());
+            self.record_allocation(new_size);
+        }
+        result
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        self.inner.dealloc(ptr, layout);
+        self.record_deallocation(layout.size());
+    }
+}
+
+fn update_thread_stats(f: impl FnOnce(&mut AllocationStats)) {
+    // The thread's stats are unavailable while it is being destroyed.
+    let _ = THREAD_STATS.try_with(|cell| {
+        let mut stats = cell.get();
+        f(&mut stats);
+        cell.set(stats);
+    });
+}
+
+fn thread_stats() -> AllocationStats {
+    THREAD_STATS.try_with(Cell::get).unwrap_or_default()
+}
+
+fn owner_stats() -> std::sync::MutexGuard<'static, BTreeMap<usize, AllocationStats>> {
+    OWNER_STATS.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Run `f`, and attribute the memory that the current thread allocates while
+/// it runs to the object that `owner` returns for its result.
+pub fn track<R>(f: impl FnOnce() -> R, owner: impl FnOnce(&R) -> *const c_void) -> R {
+    if !IS_TRACKING.load(Ordering::Acquire) {
+        return f();
+    }
+
+    let start = thread_stats();
+    let result = f();
+    let stats = thread_stats().difference(&start);
+    let owner = owner(&result);
+    if !owner.is_null() {
+        owner_stats().entry(owner as usize).or_default().add(&stats);
+    }
+    result
+}
+
+/// The allocations that are attributed to an object that records them
+/// itself, such as a query cursor. This avoids locking [`OWNER_STATS`] for
+/// allocations that are recorded as often as each query match.
+#[derive(Debug, Default)]
+pub struct LocalStats(Option<AllocationStats>);
+
+impl LocalStats {
+    pub const fn new() -> Self {
+        Self(None)
+    }
+
+    /// Run `f`, and attribute the memory that the current thread allocates
+    /// while it runs to these stats.
+    pub fn track<R>(&mut self, f: impl FnOnce() -> R) -> R {
+        if !IS_TRACKING.load(Ordering::Acquire) {
+            return f();
+        }
+
+        let start = thread_stats();
+        let result = f();
+        let stats = thread_stats().difference(&start);
+        self.0
+            .get_or_insert_with(AllocationStats::default)
+            .add(&stats);
+        result
+    }
+}
+
+/// Discard the allocations that were attributed to the given object, once it
+/// has been deleted.
+pub fn forget(owner: *const c_void) {
+    if IS_TRACKING.load(Ordering::Acquire) {
+        owner_stats().remove(&(owner as usize));
+    }
+}
+
+impl Tree {
+    /// Get the totals of the memory that was allocated while parsing this
+    /// tree, if a [`TrackingAllocator`] was installed.
+    ///
+    /// For a tree that was created by incremental parsing, this only includes
+    /// the memory that was allocated by the incremental parse. Copies of a
+    /// tree have no totals of their own.
+    #[must_use]
+    pub fn allocation_stats(&self) -> Option<AllocationStats> {
+        owner_stats().get(&(self.0.as_ptr() as usize)).copied()
+    }
+}
+
+impl QueryCursor {
+    /// Get the totals of the memory that was allocated while running queries
+    /// with this cursor, if a [`TrackingAllocator`] was installed.
+    #[must_use]
+    pub const fn allocation_stats(&self) -> Option<AllocationStats> {
+        self.allocation_stats.0
+    }
+}
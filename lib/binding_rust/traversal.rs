@@ -0,0 +1,395 @@
+use core::iter::FusedIterator;
+
+use crate::{Node, Tree, TreeCursor};
+
+/// An iterator over the nodes of a subtree in pre-order, returned by
+/// [`Node::preorder`].
+///
+/// Each node is yielded before its descendants. Call
+/// [`skip_subtree`](Preorder::skip_subtree) after a node is yielded to skip
+/// its descendants.
+pub struct Preorder<'tree> {
+    cursor: TreeCursor<'tree>,
+    started: bool,
+    done: bool,
+    skip: bool,
+}
+
+/// An iterator over the nodes of a subtree in post-order, returned by
+/// [`Node::postorder`].
+///
+/// Each node is yielded after all of its descendants.
+pub struct Postorder<'tree> {
+    cursor: TreeCursor<'tree>,
+    started: bool,
+    done: bool,
+}
+
+/// An iterator over the nodes of a subtree in breadth-first order, returned
+/// by [`Node::breadth_first`].
+///
+/// The nodes are yielded one level of depth at a time. To avoid allocating a
+/// queue, each level is found by walking the subtree again down to that
+/// depth, so walking the whole subtree takes time proportional to its size
+/// times its depth. For the same reason, subtrees cannot be skipped; use
+/// [`Node::preorder`] for that.
+pub struct BreadthFirst<'tree> {
+    root: Node<'tree>,
+    cursor: TreeCursor<'tree>,
+    /// The depth of the nodes that are being yielded.
+    depth: u32,
+    /// The depth of the cursor's current node.
+    cursor_depth: u32,
+    started: bool,
+    done: bool,
+    /// Whether any node at the current depth has children.
+    level_found: bool,
+}
+
+/// An iterator over the leaves of a subtree, from left to right, returned by
+/// [`Node::leaves`].
+pub struct Leaves<'tree> {
+    cursor: TreeCursor<'tree>,
+    started: bool,
+    done: bool,
+}
+
+/// What a [`Visitor`] should do after entering a node.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum VisitAction {
+    /// Visit the node's children, then leave the node.
+    #[default]
+    Continue,
+    /// Leave the node without visiting its children.
+    SkipChildren,
+    /// Stop the traversal without leaving any more nodes.
+    Stop,
+}
+
+/// A set of callbacks that are invoked while walking a subtree with
+/// [`Node::visit`].
+///
+/// The callbacks receive the kind id of each node, so that implementations
+/// can `match` on the ids of the kinds that they care about, and fall back to
+/// the default for the rest.
+pub trait Visitor<'tree> {
+    /// Called when a node is entered, before any of its children.
+    fn enter(&mut self, kind_id: u16, node: Node<'tree>) -> VisitAction {
+        let _ = (kind_id, node);
+        VisitAction::Continue
+    }
+
+    /// Called when a node is left, after all of its children.
+    fn leave(&mut self, kind_id: u16, node: Node<'tree>) {
+        let _ = (kind_id, node);
+    }
+}
+
+/// Move the cursor to the next node in pre-order that is not a descendant of
+/// its current node, returning the number of levels that it moved up, or
+/// `None` if the cursor's root was reached.
+///
+/// The cursor can't move past the node that it was created for, so this
+/// doesn't need [`TreeCursor::depth`], which takes time proportional to the
+/// depth of the cursor.
+fn goto_next_subtree(cursor: &mut TreeCursor) -> Option<u32> {
+    let mut levels = 0;
+    loop {
+        if cursor.goto_next_sibling() {
+            return Some(levels);
+        }
+        if !cursor.goto_parent() {
+            return None;
+        }
+        levels += 1;
+    }
+}
+
+fn goto_first_leaf(cursor: &mut TreeCursor) {
+    while cursor.goto_first_child() {}
+}
+
+impl<'tree> Preorder<'tree> {
+    /// Skip the descendants of the node that was last yielded.
+    pub fn skip_subtree(&mut self) {
+        self.skip = true;
+    }
+
+    /// Get the cursor, which is positioned on the node that was last yielded.
+    #[must_use]
+    pub const fn cursor(&self) -> &TreeCursor<'tree> {
+        &self.cursor
+    }
+}
+
+impl<'tree> Iterator for Preorder<'tree> {
+    type Item = Node<'tree>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        if self.started {
+            let skip = core::mem::take(&mut self.skip);
+            if (skip || !self.cursor.goto_first_child())
+                && goto_next_subtree(&mut self.cursor).is_none()
+            {
+                self.done = true;
+                return None;
+            }
+        }
+        self.started = true;
+        Some(self.cursor.node())
+    }
+}
+
+impl FusedIterator for Preorder<'_> {}
+
+impl<'tree> Postorder<'tree> {
+    /// Get the cursor, which is positioned on the node that was last yielded.
+    #[must_use]
+    pub const fn cursor(&self) -> &TreeCursor<'tree> {
+        &self.cursor
+    }
+}
+
+impl<'tree> Iterator for Postorder<'tree> {
+    type Item = Node<'tree>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        if !self.started {
+            self.started = true;
+            goto_first_leaf(&mut self.cursor);
+        } else if self.cursor.goto_next_sibling() {
+            goto_first_leaf(&mut self.cursor);
+        } else if !self.cursor.goto_parent() {
+            self.done = true;
+            return None;
+        }
+        Some(self.cursor.node())
+    }
+}
+
+impl FusedIterator for Postorder<'_> {}
+
+impl<'tree> BreadthFirst<'tree> {
+    /// Get the cursor, which is positioned on the node that was last yielded.
+    #[must_use]
+    pub const fn cursor(&self) -> &TreeCursor<'tree> {
+        &self.cursor
+    }
+
+    /// Move the cursor to the next node at the current depth, returning
+    /// `false` once every node at that depth has been visited.
+    fn goto_next_at_depth(&mut self, mut descend: bool) -> bool {
+        loop {
+            if descend && self.cursor_depth < self.depth && self.cursor.goto_first_child() {
+                self.cursor_depth += 1;
+                if self.cursor_depth == self.depth {
+                    return true;
+                }
+                continue;
+            }
+            let Some(levels) = goto_next_subtree(&mut self.cursor) else {
+                return false;
+            };
+            self.cursor_depth -= levels;
+            descend = true;
+            if self.cursor_depth == self.depth {
+                return true;
+            }
+        }
+    }
+}
+
+impl<'tree> Iterator for BreadthFirst<'tree> {
+    type Item = Node<'tree>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        if !self.started {
+            self.started = true;
+            return Some(self.cursor.node());
+        }
+
+        let mut descend = false;
+        loop {
+            if self.goto_next_at_depth(descend) {
+                if self.cursor.node().child_count() > 0 {
+                    self.level_found = true;
+                }
+                return Some(self.cursor.node());
+            }
+            if !self.level_found {
+                self.done = true;
+                return None;
+            }
+            self.depth += 1;
+            self.level_found = false;
+            self.cursor.reset(self.root);
+            self.cursor_depth = 0;
+            descend = true;
+        }
+    }
+}
+
+impl FusedIterator for BreadthFirst<'_> {}
+
+impl<'tree> Leaves<'tree> {
+    /// Get the cursor, which is positioned on the node that was last yielded.
+    #[must_use]
+    pub const fn cursor(&self) -> &TreeCursor<'tree> {
+        &self.cursor
+    }
+}
+
+impl<'tree> Iterator for Leaves<'tree> {
+    type Item = Node<'tree>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        if self.started && goto_next_subtree(&mut self.cursor).is_none() {
+            self.done = true;
+            return None;
+        }
+        self.started = true;
+        goto_first_leaf(&mut self.cursor);
+        Some(self.cursor.node())
+    }
+}
+
+impl FusedIterator for Leaves<'_> {}
+
+impl<'tree> Node<'tree> {
+    /// Iterate over this node and its descendants in pre-order, so that each
+    /// node comes before its descendants.
+    ///
+    /// The iterator is built on a [`TreeCursor`], so like a cursor, it only
+    /// visits this node's subtree.
+    #[must_use]
+    pub fn preorder(&self) -> Preorder<'tree> {
+        Preorder {
+            cursor: self.walk(),
+            started: false,
+            done: false,
+            skip: false,
+        }
+    }
+
+    /// Iterate over this node and its descendants in post-order, so that each
+    /// node comes after its descendants.
+    #[must_use]
+    pub fn postorder(&self) -> Postorder<'tree> {
+        Postorder {
+            cursor: self.walk(),
+            started: false,
+            done: false,
+        }
+    }
+
+    /// Iterate over this node and its descendants in breadth-first order, so
+    /// that each node comes after all of the nodes that are less deep.
+    #[must_use]
+    pub fn breadth_first(&self) -> BreadthFirst<'tree> {
+        BreadthFirst {
+            root: *self,
+            cursor: self.walk(),
+            depth: 0,
+            cursor_depth: 0,
+            started: false,
+            done: false,
+            level_found: self.child_count() > 0,
+        }
+    }
+
+    /// Iterate over the descendants of this node that have no children, from
+    /// left to right. If this node has no children, it is the only leaf.
+    #[must_use]
+    pub fn leaves(&self) -> Leaves<'tree> {
+        Leaves {
+            cursor: self.walk(),
+            started: false,
+            done: false,
+        }
+    }
+
+    /// Walk this node and its descendants in pre-order, calling the
+    /// visitor's [`enter`](Visitor::enter) callback before each node's
+    /// children and its [`leave`](Visitor::leave) callback after them.
+    ///
+    /// Returns `false` if the visitor stopped the traversal.
+    pub fn visit(&self, visitor: &mut impl Visitor<'tree>) -> bool {
+        let mut cursor = self.walk();
+        loop {
+            let node = cursor.node();
+            match visitor.enter(node.kind_id(), node) {
+                VisitAction::Continue => {
+                    if cursor.goto_first_child() {
+                        continue;
+                    }
+                }
+                VisitAction::SkipChildren => {}
+                VisitAction::Stop => return false,
+            }
+
+            loop {
+                let node = cursor.node();
+                visitor.leave(node.kind_id(), node);
+                if cursor.goto_next_sibling() {
+                    break;
+                }
+                if !cursor.goto_parent() {
+                    return true;
+                }
+            }
+        }
+    }
+}
+
+impl Tree {
+    /// Iterate over the nodes of this tree in pre-order.
+    ///
+    /// See [`Node::preorder`].
+    #[must_use]
+    pub fn preorder(&self) -> Preorder<'_> {
+        self.root_node().preorder()
+    }
+
+    /// Iterate over the nodes of this tree in post-order.
+    ///
+    /// See [`Node::postorder`].
+    #[must_use]
+    pub fn postorder(&self) -> Postorder<'_> {
+        self.root_node().postorder()
+    }
+
+    /// Iterate over the nodes of this tree in breadth-first order.
+    ///
+    /// See [`Node::breadth_first`].
+    #[must_use]
+    pub fn breadth_first(&self) -> BreadthFirst<'_> {
+        self.root_node().breadth_first()
+    }
+
+    /// Iterate over the leaves of this tree, from left to right.
+    ///
+    /// See [`Node::leaves`].
+    #[must_use]
+    pub fn leaves(&self) -> Leaves<'_> {
+        self.root_node().leaves()
+    }
+
+    /// Walk the nodes of this tree with a [`Visitor`].
+    ///
+    /// See [`Node::visit`].
+    pub fn visit<'tree>(&'tree self, visitor: &mut impl Visitor<'tree>) -> bool {
+        self.root_node().visit(visitor)
+    }
+}
@@ -0,0 +1,88 @@
+use crate::{ffi, Tree};
+
+/// A summary of the size and shape of a syntax tree, returned by
+/// [`Tree::stats`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TreeStats {
+    /// The number of nodes in the tree, both named and anonymous.
+    pub node_count: usize,
+    /// The number of `ERROR` and `MISSING` nodes in the tree.
+    pub error_count: usize,
+    /// The depth of the most deeply nested node. The root node has a depth
+    /// of zero.
+    pub max_depth: usize,
+    /// The number of bytes of heap memory that are used by the tree, as
+    /// returned by [`Tree::memory_usage`].
+    pub memory_usage: usize,
+    /// The number of nodes in subtrees that are shared with the previous
+    /// tree.
+    pub shared_node_count: usize,
+    /// The number of bytes of heap memory that are shared with the previous
+    /// tree.
+    pub shared_memory_usage: usize,
+}
+
+impl TreeStats {
+    /// Get the average number of bytes of memory that are used per node.
+    #[must_use]
+    pub fn bytes_per_node(&self) -> f64 {
+        if self.node_count == 0 {
+            0.0
+        } else {
+            self.memory_usage as f64 / self.node_count as f64
+        }
+    }
+}
+
+impl Tree {
+    /// Compute a summary of the size and shape of this syntax tree.
+    ///
+    /// If `previous` is the old tree that was passed to [`Parser::parse`]
+    /// when this tree was created, the summary also reports how much of this
+    /// tree was reused from it by the incremental parse. The shared subtrees
+    /// are not copied, so their memory is only allocated once.
+    ///
+    /// [`Parser::parse`]: crate::Parser::parse
+    #[doc(alias = "ts_tree_shared_memory_usage")]
+    #[must_use]
+    pub fn stats(&self, previous: Option<&Self>) -> TreeStats {
+        let mut stats = TreeStats {
+            memory_usage: self.memory_usage(),
+            ..TreeStats::default()
+        };
+
+        if let Some(previous) = previous {
+            let mut node_count = 0u32;
+            stats.shared_memory_usage = unsafe {
+                ffi::ts_tree_shared_memory_usage(
+                    self.0.as_ptr(),
+                    previous.0.as_ptr(),
+                    core::ptr::addr_of_mut!(node_count),
+                )
+            };
+            stats.shared_node_count = node_count as usize;
+        }
+
+        let mut cursor = self.walk();
+        let mut depth = 0;
+        loop {
+            let node = cursor.node();
+            stats.node_count += 1;
+            stats.max_depth = stats.max_depth.max(depth);
+            if node.is_error() || node.is_missing() {
+                stats.error_count += 1;
+            }
+
+            if cursor.goto_first_child() {
+                depth += 1;
+                continue;
+            }
+            while !cursor.goto_next_sibling() {
+                if !cursor.goto_parent() {
+                    return stats;
+                }
+                depth -= 1;
+            }
+        }
+    }
+}
@@ -26,7 +26,7 @@ extern "C" {
  * The Tree-sitter library is generally backwards-compatible with languages
  * generated using older CLI versions, but is not forwards-compatible.
  */
-#define TREE_SITTER_LANGUAGE_VERSION 15
+#define TREE_SITTER_LANGUAGE_VERSION 16
 
 /**
  * The earliest ABI version that is supported by the current version of the
@@ -173,6 +173,14 @@ typedef enum TSQueryError {
   TSQueryErrorLanguage,
 } TSQueryError;
 
+typedef enum TSSnapshotError {
+  TSSnapshotErrorNone = 0,
+  TSSnapshotErrorFormat,
+  TSSnapshotErrorVersion,
+  TSSnapshotErrorLanguage,
+  TSSnapshotErrorLanguageVersion,
+} TSSnapshotError;
+
 typedef struct TSQueryCursorState {
   void *payload;
   uint32_t current_byte_offset;
@@ -228,6 +236,29 @@ const TSLanguage *ts_parser_language(const TSParser *self);
  */
 bool ts_parser_set_language(TSParser *self, const TSLanguage *language);
 
+/**
+ * Set the rule from which the parser should begin parsing.
+ *
+ * By default, the parser parses entire documents using the grammar's first
+ * rule. A grammar can declare additional entry points with the `start_rules`
+ * option, which makes it possible to parse fragments of a document, such as a
+ * single expression. Pass the id of one of those rules to start parsing from
+ * it, or zero to restore the default.
+ *
+ * Returns a boolean indicating whether or not the start symbol was assigned.
+ * False means that the parser has no language assigned, or that the language
+ * has no start rule with the given symbol. Assigning a start symbol resets
+ * the parser, like [`ts_parser_reset`], and assigning a language restores the
+ * default start symbol.
+ */
+bool ts_parser_set_start_symbol(TSParser *self, TSSymbol symbol);
+
+/**
+ * Get the rule from which the parser begins parsing, or zero if it uses the
+ * grammar's first rule.
+ */
+TSSymbol ts_parser_start_symbol(const TSParser *self);
+
 /**
  * Set the ranges of text that the parser should include when parsing.
  *
@@ -466,6 +497,15 @@ TSNode ts_tree_root_node_with_offset(
  */
 const TSLanguage *ts_tree_language(const TSTree *self);
 
+/**
+ * Get a number that identifies the syntax tree.
+ *
+ * Every tree, including every copy made with [`ts_tree_copy`], is assigned a
+ * different id when it is created, so unlike the tree's address, the id is
+ * not reused after the tree is deleted.
+ */
+uint32_t ts_tree_id(const TSTree *self);
+
 /**
  * Get the array of included ranges that was used to parse the syntax tree.
  *
@@ -509,11 +549,79 @@ TSRange *ts_tree_get_changed_ranges(
   uint32_t *length
 );
 
+/**
+ * Get the number of bytes of heap memory that are used by the syntax tree.
+ *
+ * A tree that was created by incremental parsing shares its unchanged
+ * subtrees with the old tree, so that memory is counted for both of them.
+ * Use [`ts_tree_shared_memory_usage`] to find out how much of it is shared.
+ */
+size_t ts_tree_memory_usage(const TSTree *self);
+
+/**
+ * Get the number of bytes of heap memory that the syntax tree shares with
+ * another tree, such as the old tree that was passed to [`ts_parser_parse`]
+ * when the syntax tree was created.
+ *
+ * If `node_count` is not `NULL`, the number of nodes in the shared subtrees
+ * is written to it.
+ */
+size_t ts_tree_shared_memory_usage(
+  const TSTree *self,
+  const TSTree *other,
+  uint32_t *node_count
+);
+
+/**
+ * Get the parse state that the parser is in after the last token that ends
+ * at or before the given byte offset, skipping extras.
+ *
+ * Tokens inside of `ERROR` nodes and tokens that were changed by
+ * [`ts_tree_edit`] do not advance the state. Use [`ts_lookahead_iterator_new`]
+ * to find the symbols that are valid in the returned state.
+ */
+TSStateId ts_tree_parse_state_at(const TSTree *self, uint32_t byte_offset);
+
+/**
+ * Get the parse state in which the parse that produced the tree began. This is
+ * `1` unless the parser's start symbol was changed with
+ * [`ts_parser_set_start_symbol`].
+ */
+TSStateId ts_tree_start_state(const TSTree *self);
+
 /**
  * Write a DOT graph describing the syntax tree to the given file.
  */
 void ts_tree_print_dot_graph(const TSTree *self, int file_descriptor);
 
+/**
+ * Serialize the syntax tree into a versioned binary snapshot.
+ *
+ * The snapshot contains the tree's full structure, its included ranges, and
+ * the identity of its language (name and ABI version), so that it can later
+ * be restored with [`ts_tree_deserialize`] and used for incremental parsing.
+ *
+ * The returned buffer is allocated using `malloc` and the caller is responsible
+ * for freeing it using `free`. The length of the buffer will be written to the
+ * given `length` pointer.
+ */
+char *ts_tree_serialize(const TSTree *self, uint32_t *length);
+
+/**
+ * Restore a syntax tree from a snapshot created by [`ts_tree_serialize`].
+ *
+ * The snapshot must have been created from a tree that was parsed with the
+ * same language. If the snapshot is malformed, was written by an incompatible
+ * version of the library, or belongs to a different language, this returns
+ * `NULL` and writes the reason to the `error` parameter.
+ */
+TSTree *ts_tree_deserialize(
+  const TSLanguage *language,
+  const char *data,
+  uint32_t length,
+  TSSnapshotError *error
+);
+
 /******************/
 /* Section - Node */
 /******************/
@@ -922,6 +1030,35 @@ TSQuery *ts_query_new(
  */
 void ts_query_delete(TSQuery *self);
 
+/**
+ * Serialize a query into a versioned binary snapshot.
+ *
+ * The snapshot contains the query's compiled patterns, predicates, capture
+ * names and the results of its analysis, along with the identity of its
+ * language (name and ABI version), so that it can later be restored with
+ * [`ts_query_deserialize`] without parsing or analyzing the query's source.
+ *
+ * The returned buffer is allocated using `malloc` and the caller is responsible
+ * for freeing it using `free`. The length of the buffer will be written to the
+ * given `length` pointer.
+ */
+char *ts_query_serialize(const TSQuery *self, uint32_t *length);
+
+/**
+ * Restore a query from a snapshot created by [`ts_query_serialize`].
+ *
+ * The snapshot must have been created from a query for the same language. If
+ * the snapshot is malformed, was written by an incompatible version of the
+ * library, or belongs to a different language, this returns `NULL` and writes
+ * the reason to the `error` parameter.
+ */
+TSQuery *ts_query_deserialize(
+  const TSLanguage *language,
+  const char *data,
+  uint32_t length,
+  TSSnapshotError *error
+);
+
 /**
  * Get the number of patterns, captures, or string literals in the query.
  */
@@ -929,6 +1066,11 @@ uint32_t ts_query_pattern_count(const TSQuery *self);
 uint32_t ts_query_capture_count(const TSQuery *self);
 uint32_t ts_query_string_count(const TSQuery *self);
 
+/**
+ * Get the language that the query was created for.
+ */
+const TSLanguage *ts_query_language(const TSQuery *self);
+
 /**
  * Get the byte offset where the given pattern starts in the query's source.
  *
@@ -945,6 +1087,14 @@ uint32_t ts_query_start_byte_for_pattern(const TSQuery *self, uint32_t pattern_i
  */
 uint32_t ts_query_end_byte_for_pattern(const TSQuery *self, uint32_t pattern_index);
 
+/**
+ * Get the row where the given pattern starts in the query's source.
+ *
+ * Unlike the source itself, this is stored in query snapshots, so it can be
+ * used to report the location of a pattern in a restored query.
+ */
+uint32_t ts_query_start_row_for_pattern(const TSQuery *self, uint32_t pattern_index);
+
 /**
  * Get all of the predicates for the given pattern in the query.
  *
@@ -982,6 +1132,13 @@ bool ts_query_is_pattern_rooted(const TSQuery *self, uint32_t pattern_index);
  */
 bool ts_query_is_pattern_non_local(const TSQuery *self, uint32_t pattern_index);
 
+/**
+ * Check if two queries for the same language were compiled into the same
+ * patterns, with the same predicates and captures, regardless of where the
+ * patterns appear in the queries' source code, and of how it is formatted.
+ */
+bool ts_query_is_equivalent(const TSQuery *self, const TSQuery *other);
+
 /*
  * Check if a given pattern is guaranteed to match once a given step is reached.
  * The step is specified by its byte offset in the query's source code.
@@ -1290,6 +1447,21 @@ uint32_t ts_language_abi_version(const TSLanguage *self);
  */
 const TSLanguageMetadata *ts_language_metadata(const TSLanguage *self);
 
+/**
+ * Get a hash of the tables of this language, which changes whenever the
+ * language's symbols, fields, parse tables or lex tables change, even if its
+ * semantic version does not. This can be used as part of the key for caches
+ * of data that depend on the language, like syntax trees or compiled queries.
+ *
+ * Parsers that were generated with ABI version 16 or later embed the hash, so
+ * it is cheap to read. For older parsers, a hash of the language's symbols,
+ * fields and parse tables is computed each time this function is called. It
+ * cannot cover the lex tables, which are compiled into the parser's lex
+ * functions, so two older parsers that only differ in their tokens' patterns
+ * have the same fingerprint.
+ */
+uint64_t ts_language_fingerprint(const TSLanguage *self);
+
 /**
  * Get the next parse state. Combine this with lookahead iterators to generate
  * completion suggestions or valid symbols in error nodes. Use
@@ -1465,6 +1637,31 @@ void ts_set_allocator(
 	void (*new_free)(void *)
 );
 
+/**
+ * Set the allocation functions used by the library, like [`ts_set_allocator`],
+ * but only if the library has not yet allocated any memory with its default
+ * allocation functions. The check and the claim on the allocator are a single
+ * atomic operation, so the default functions stop marking themselves as used
+ * once this succeeds.
+ *
+ * Returns `false`, without changing the allocation functions, if the default
+ * functions have already allocated memory, or if this function was already
+ * called successfully.
+ */
+bool ts_set_allocator_if_unused(
+  void *(*new_malloc)(size_t),
+  void *(*new_calloc)(size_t, size_t),
+  void *(*new_realloc)(void *, size_t),
+  void (*new_free)(void *)
+);
+
+/**
+ * Check if the library has allocated any memory with its default allocation
+ * functions. Once it has, an allocator that is passed to [`ts_set_allocator`]
+ * must be capable of freeing that memory.
+ */
+bool ts_default_allocator_is_used(void);
+
 #ifdef __cplusplus
 }
 #endif
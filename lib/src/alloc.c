@@ -1,8 +1,29 @@
 #include "alloc.h"
+#include "atomic.h"
 #include "tree_sitter/api.h"
 #include <stdlib.h>
 
+typedef enum {
+  TSAllocatorStateUnused,
+  // Memory has been allocated by the default allocation functions, so it can
+  // only be freed by the default `free` function.
+  TSAllocatorStateDefaultUsed,
+  // The allocation functions were replaced by `ts_set_allocator_if_unused`
+  // before the default ones allocated any memory.
+  TSAllocatorStateReplaced,
+} TSAllocatorState;
+
+static volatile uint32_t ts_allocator_state = TSAllocatorStateUnused;
+
+// The state only changes once, so the common case is a relaxed load.
+static inline void ts_mark_default_allocator_used(void) {
+  if (atomic_load_u32(&ts_allocator_state) == TSAllocatorStateUnused) {
+    atomic_compare_exchange(&ts_allocator_state, TSAllocatorStateUnused, TSAllocatorStateDefaultUsed);
+  }
+}
+
 static void *ts_malloc_default(size_t size) {
+  ts_mark_default_allocator_used();
   void *result = malloc(size);
   if (size > 0 && !result) {
     fprintf(stderr, "tree-sitter failed to allocate %zu bytes", size);
@@ -12,6 +33,7 @@ static void *ts_malloc_default(size_t size) {
 }
 
 static void *ts_calloc_default(size_t count, size_t size) {
+  ts_mark_default_allocator_used();
   void *result = calloc(count, size);
   if (count > 0 && !result) {
     fprintf(stderr, "tree-sitter failed to allocate %zu bytes", count * size);
@@ -21,6 +43,7 @@ static void *ts_calloc_default(size_t count, size_t size) {
 }
 
 static void *ts_realloc_default(void *buffer, size_t size) {
+  ts_mark_default_allocator_used();
   void *result = realloc(buffer, size);
   if (size > 0 && !result) {
     fprintf(stderr, "tree-sitter failed to reallocate %zu bytes", size);
@@ -46,3 +69,20 @@ void ts_set_allocator(
   ts_current_realloc = new_realloc ? new_realloc : ts_realloc_default;
   ts_current_free = new_free ? new_free : free;
 }
+
+bool ts_set_allocator_if_unused(
+  void *(*new_malloc)(size_t size),
+  void *(*new_calloc)(size_t count, size_t size),
+  void *(*new_realloc)(void *ptr, size_t size),
+  void (*new_free)(void *ptr)
+) {
+  if (!atomic_compare_exchange(&ts_allocator_state, TSAllocatorStateUnused, TSAllocatorStateReplaced)) {
+    return false;
+  }
+  ts_set_allocator(new_malloc, new_calloc, new_realloc, new_free);
+  return true;
+}
+
+bool ts_default_allocator_is_used(void) {
+  return atomic_load_u32(&ts_allocator_state) == TSAllocatorStateDefaultUsed;
+}
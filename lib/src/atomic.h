@@ -1,6 +1,7 @@
 #ifndef TREE_SITTER_ATOMIC_H_
 #define TREE_SITTER_ATOMIC_H_
 
+#include <stdbool.h>
 #include <stddef.h>
 #include <stdint.h>
 #include <stdlib.h>
@@ -21,6 +22,16 @@ static inline uint32_t atomic_dec(volatile uint32_t *p) {
   return *p;
 }
 
+static inline uint32_t atomic_load_u32(const volatile uint32_t *p) {
+  return *p;
+}
+
+static inline bool atomic_compare_exchange(volatile uint32_t *p, uint32_t expected, uint32_t desired) {
+  if (*p != expected) return false;
+  *p = desired;
+  return true;
+}
+
 #elif defined(_WIN32)
 
 #include <windows.h>
@@ -37,6 +48,14 @@ static inline uint32_t atomic_dec(volatile uint32_t *p) {
   return InterlockedDecrement((long volatile *)p);
 }
 
+static inline uint32_t atomic_load_u32(const volatile uint32_t *p) {
+  return *p;
+}
+
+static inline bool atomic_compare_exchange(volatile uint32_t *p, uint32_t expected, uint32_t desired) {
+  return InterlockedCompareExchange((long volatile *)p, (long)desired, (long)expected) == (long)expected;
+}
+
 #else
 
 static inline size_t atomic_load(const volatile size_t *p) {
@@ -63,6 +82,22 @@ static inline uint32_t atomic_dec(volatile uint32_t *p) {
   #endif
 }
 
+static inline uint32_t atomic_load_u32(const volatile uint32_t *p) {
+  #ifdef __ATOMIC_RELAXED
+    return __atomic_load_n(p, __ATOMIC_RELAXED);
+  #else
+    return __sync_fetch_and_add((volatile uint32_t *)p, 0U);
+  #endif
+}
+
+static inline bool atomic_compare_exchange(volatile uint32_t *p, uint32_t expected, uint32_t desired) {
+  #ifdef __ATOMIC_RELAXED
+    return __atomic_compare_exchange_n(p, &expected, desired, false, __ATOMIC_SEQ_CST, __ATOMIC_SEQ_CST);
+  #else
+    return __sync_bool_compare_and_swap(p, expected, desired);
+  #endif
+}
+
 #endif
 
 #endif  // TREE_SITTER_ATOMIC_H_
@@ -61,6 +61,125 @@ const TSLanguageMetadata *ts_language_metadata(const TSLanguage *self) {
     return self->abi_version >= LANGUAGE_VERSION_WITH_RESERVED_WORDS ? &self->metadata : NULL;
 }
 
+#define FNV_OFFSET 0xcbf29ce484222325ULL
+#define FNV_PRIME 0x00000100000001b3ULL
+
+static uint64_t ts_language__hash_bytes(uint64_t hash, const void *data, size_t length) {
+  const uint8_t *bytes = data;
+  for (size_t i = 0; i < length; i++) {
+    hash ^= bytes[i];
+    hash *= FNV_PRIME;
+  }
+  return hash;
+}
+
+static uint64_t ts_language__hash_u32(uint64_t hash, uint32_t value) {
+  uint8_t bytes[4] = {
+    (uint8_t)value,
+    (uint8_t)(value >> 8),
+    (uint8_t)(value >> 16),
+    (uint8_t)(value >> 24),
+  };
+  return ts_language__hash_bytes(hash, bytes, sizeof(bytes));
+}
+
+static uint64_t ts_language__hash_string(uint64_t hash, const char *string) {
+  return string
+    ? ts_language__hash_bytes(hash, string, strlen(string) + 1)
+    : ts_language__hash_u32(hash, 0);
+}
+
+// Hash the tables of a language that was generated without a fingerprint.
+// The lex tables are compiled into the language's lex functions, so only the
+// symbols, fields and parse tables are covered. The generator computes the
+// same hash from its own tables, in `render.rs`, before adding its lex tables,
+// so the two must be kept in sync. The values are hashed one at a time, rather
+// than as raw structs, so that padding bytes are never read.
+static uint64_t ts_language__compute_fingerprint(const TSLanguage *self) {
+  uint64_t hash = FNV_OFFSET;
+  hash = ts_language__hash_u32(hash, self->symbol_count);
+  hash = ts_language__hash_u32(hash, self->alias_count);
+  hash = ts_language__hash_u32(hash, self->token_count);
+  hash = ts_language__hash_u32(hash, self->external_token_count);
+  hash = ts_language__hash_u32(hash, self->state_count);
+  hash = ts_language__hash_u32(hash, self->production_id_count);
+  hash = ts_language__hash_u32(hash, self->field_count);
+  hash = ts_language__hash_u32(hash, self->keyword_capture_token);
+
+  // Symbols
+  for (TSSymbol symbol = 0; symbol < self->symbol_count + self->alias_count; symbol++) {
+    TSSymbolMetadata metadata = ts_language_symbol_metadata(self, symbol);
+    hash = ts_language__hash_string(hash, self->symbol_names[symbol]);
+    hash = ts_language__hash_u32(hash, metadata.visible | metadata.named << 1 | metadata.supertype << 2);
+    if (symbol < self->symbol_count) {
+      hash = ts_language__hash_u32(hash, ts_language_public_symbol(self, symbol));
+    }
+  }
+
+  // Fields
+  for (TSFieldId field_id = 1; field_id <= self->field_count; field_id++) {
+    hash = ts_language__hash_string(hash, self->field_names[field_id]);
+  }
+  for (uint32_t production_id = 0; production_id < self->production_id_count; production_id++) {
+    const TSFieldMapEntry *entry, *end;
+    ts_language_field_map(self, production_id, &entry, &end);
+    hash = ts_language__hash_u32(hash, (uint32_t)(end - entry));
+    for (; entry < end; entry++) {
+      hash = ts_language__hash_u32(hash, entry->field_id);
+      hash = ts_language__hash_u32(hash, entry->child_index | entry->inherited << 8);
+    }
+    if (self->alias_sequences) {
+      for (uint32_t i = 0; production_id > 0 && i < self->max_alias_sequence_length; i++) {
+        hash = ts_language__hash_u32(hash, ts_language_alias_at(self, production_id, i));
+      }
+    }
+  }
+
+  // Parse table
+  for (TSStateId state = 0; state < self->state_count; state++) {
+    TSLexerMode lex_mode = ts_language_lex_mode_for_state(self, state);
+    hash = ts_language__hash_u32(hash, lex_mode.lex_state);
+    hash = ts_language__hash_u32(hash, lex_mode.external_lex_state);
+    hash = ts_language__hash_u32(hash, lex_mode.reserved_word_set_id);
+
+    // The entries of a state are combined with a sum, so that the fingerprint
+    // doesn't depend on the order in which they are laid out in the table.
+    uint64_t entries_hash = 0;
+    LookaheadIterator iterator = ts_language_lookaheads(self, state);
+    while (ts_lookahead_iterator__next(&iterator)) {
+      if (iterator.action_count == 0 && iterator.next_state == 0) continue;
+      uint64_t entry_hash = ts_language__hash_u32(FNV_OFFSET, iterator.symbol);
+      entry_hash = ts_language__hash_u32(entry_hash, iterator.next_state);
+      entry_hash = ts_language__hash_u32(entry_hash, iterator.action_count);
+      for (uint16_t i = 0; i < iterator.action_count; i++) {
+        TSParseAction action = iterator.actions[i];
+        entry_hash = ts_language__hash_u32(entry_hash, action.type);
+        if (action.type == TSParseActionTypeShift) {
+          entry_hash = ts_language__hash_u32(entry_hash, action.shift.state);
+          entry_hash = ts_language__hash_u32(entry_hash, action.shift.extra | action.shift.repetition << 1);
+        } else if (action.type == TSParseActionTypeReduce) {
+          entry_hash = ts_language__hash_u32(entry_hash, action.reduce.symbol);
+          entry_hash = ts_language__hash_u32(entry_hash, action.reduce.child_count);
+          entry_hash = ts_language__hash_u32(entry_hash, (uint16_t)action.reduce.dynamic_precedence);
+          entry_hash = ts_language__hash_u32(entry_hash, action.reduce.production_id);
+        }
+      }
+      entries_hash += entry_hash;
+    }
+    hash = ts_language__hash_u32(hash, (uint32_t)entries_hash);
+    hash = ts_language__hash_u32(hash, (uint32_t)(entries_hash >> 32));
+  }
+
+  return hash;
+}
+
+uint64_t ts_language_fingerprint(const TSLanguage *self) {
+  if (self->abi_version >= LANGUAGE_VERSION_WITH_FINGERPRINT) {
+    return self->fingerprint;
+  }
+  return ts_language__compute_fingerprint(self);
+}
+
 const char *ts_language_name(const TSLanguage *self) {
   return self->abi_version >= LANGUAGE_VERSION_WITH_RESERVED_WORDS ? self->name : NULL;
 }
@@ -143,6 +262,33 @@ TSSymbol ts_language_public_symbol(
   return self->public_symbol_map[symbol];
 }
 
+// Get the parse state in which parsing begins for the given start rule, or
+// zero if the symbol is not one of the grammar's start rules. The symbol zero
+// stands for the grammar's main rule.
+TSStateId ts_language_start_state(const TSLanguage *self, TSSymbol symbol) {
+  if (symbol == 0) return 1;
+  if (symbol < self->token_count || symbol >= self->symbol_count) return 0;
+
+  if (self->abi_version >= LANGUAGE_VERSION_WITH_START_STATES) {
+    for (uint32_t i = 0; i < self->start_state_count; i++) {
+      if (self->start_states[i].symbol == symbol) return self->start_states[i].state;
+    }
+    return 0;
+  }
+
+  // Older languages do not list their start states. Each start rule has its
+  // own start state, so find the one whose successor state, after the start
+  // rule has been reduced, accepts the end of input.
+  for (TSStateId state = 1; state < self->state_count; state++) {
+    TSStateId next_state = ts_language_next_state(self, state, symbol);
+    if (next_state == 0) continue;
+    uint32_t count;
+    const TSParseAction *actions = ts_language_actions(self, next_state, ts_builtin_sym_end, &count);
+    if (count > 0 && actions[count - 1].type == TSParseActionTypeAccept) return state;
+  }
+  return 0;
+}
+
 TSStateId ts_language_next_state(
   const TSLanguage *self,
   TSStateId state,
@@ -10,6 +10,8 @@ extern "C" {
 
 #define ts_builtin_sym_error_repeat (ts_builtin_sym_error - 1)
 
+#define LANGUAGE_VERSION_WITH_START_STATES 16
+#define LANGUAGE_VERSION_WITH_FINGERPRINT 16
 #define LANGUAGE_VERSION_WITH_RESERVED_WORDS 15
 #define LANGUAGE_VERSION_WITH_PRIMARY_STATES 14
 
@@ -40,6 +42,7 @@ TSLexerMode ts_language_lex_mode_for_state(const TSLanguage *self, TSStateId sta
 bool ts_language_is_reserved_word(const TSLanguage *self, TSStateId state, TSSymbol symbol);
 TSSymbolMetadata ts_language_symbol_metadata(const TSLanguage *self, TSSymbol symbol);
 TSSymbol ts_language_public_symbol(const TSLanguage *self, TSSymbol symbol);
+TSStateId ts_language_start_state(const TSLanguage *self, TSSymbol symbol);
 
 static inline const TSParseAction *ts_language_actions(
   const TSLanguage *self,
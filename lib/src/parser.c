@@ -114,6 +114,8 @@ struct TSParser {
   TSParseOptions parse_options;
   TSParseState parse_state;
   unsigned included_range_difference_index;
+  TSSymbol start_symbol;
+  TSStateId start_state;
   bool has_scanner_error;
   bool canceled_balancing;
   bool has_error;
@@ -1932,7 +1934,7 @@ static bool ts_parser_has_outstanding_parse(TSParser *self) {
   return (
     self->canceled_balancing ||
     self->external_scanner_payload ||
-    ts_stack_state(self->stack, 0) != 1 ||
+    ts_stack_state(self->stack, 0) != self->start_state ||
     ts_stack_node_count_since_error(self->stack, 0) != 0
   );
 }
@@ -1952,6 +1954,8 @@ TSParser *ts_parser_new(void) {
   self->cancellation_flag = NULL;
   self->timeout_duration = 0;
   self->language = NULL;
+  self->start_symbol = 0;
+  self->start_state = 1;
   self->has_scanner_error = false;
   self->has_error = false;
   self->canceled_balancing = false;
@@ -2015,6 +2019,26 @@ bool ts_parser_set_language(TSParser *self, const TSLanguage *language) {
   }
 
   self->language = ts_language_copy(language);
+  self->start_symbol = 0;
+  self->start_state = 1;
+  ts_stack_set_start_state(self->stack, 1);
+  return true;
+}
+
+TSSymbol ts_parser_start_symbol(const TSParser *self) {
+  return self->start_symbol;
+}
+
+bool ts_parser_set_start_symbol(TSParser *self, TSSymbol symbol) {
+  if (!self->language) return false;
+
+  TSStateId start_state = ts_language_start_state(self->language, symbol);
+  if (start_state == 0) return false;
+
+  ts_parser_reset(self);
+  self->start_symbol = symbol;
+  self->start_state = start_state;
+  ts_stack_set_start_state(self->stack, start_state);
   return true;
 }
 
@@ -2219,6 +2243,7 @@ balance:
   result = ts_tree_new(
     self->finished_tree,
     self->language,
+    self->start_state,
     self->lexer.included_ranges,
     self->lexer.included_range_count
   );
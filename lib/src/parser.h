@@ -43,6 +43,13 @@ typedef struct {
   bool supertype;
 } TSSymbolMetadata;
 
+// The parse state in which parsing begins for one of the grammar's alternate
+// start rules.
+typedef struct {
+  TSSymbol symbol;
+  TSStateId state;
+} TSStartState;
+
 typedef struct TSLexer TSLexer;
 
 struct TSLexer {
@@ -149,6 +156,9 @@ struct TSLanguage {
   const TSMapSlice *supertype_map_slices;
   const TSSymbol *supertype_map_entries;
   TSLanguageMetadata metadata;
+  uint64_t fingerprint;
+  uint32_t start_state_count;
+  const TSStartState *start_states;
 };
 
 static inline bool set_contains(const TSCharacterRange *ranges, uint32_t len, int32_t lookahead) {
@@ -13,6 +13,7 @@
 #include "./clock.h"
 #include "./language.h"
 #include "./point.h"
+#include "./snapshot.h"
 #include "./tree_cursor.h"
 #include "./unicode.h"
 #include <wctype.h>
@@ -158,6 +159,7 @@ typedef struct {
   Slice predicate_steps;
   uint32_t start_byte;
   uint32_t end_byte;
+  uint32_t start_row;
   bool is_non_local;
 } QueryPattern;
 
@@ -2818,7 +2820,13 @@ TSQuery *ts_query_new(
   // Parse all of the S-expressions in the given string.
   Stream stream = stream_new(source, source_len);
   stream_skip_whitespace(&stream);
+  uint32_t row = 0;
+  const char *row_start = source;
   while (stream.input < stream.end) {
+    for (; row_start < stream.input; row_start++) {
+      if (*row_start == '\n') row++;
+    }
+
     uint32_t pattern_index = self->patterns.size;
     uint32_t start_step_index = self->steps.size;
     uint32_t start_predicate_step_index = self->predicate_steps.size;
@@ -2826,6 +2834,7 @@ TSQuery *ts_query_new(
       .steps = (Slice) {.offset = start_step_index},
       .predicate_steps = (Slice) {.offset = start_predicate_step_index},
       .start_byte = stream_offset(&stream),
+      .start_row = row,
       .is_non_local = false,
     }));
     CaptureQuantifiers capture_quantifiers = capture_quantifiers_new();
@@ -2937,6 +2946,370 @@ void ts_query_delete(TSQuery *self) {
   }
 }
 
+#define QUERY_SNAPSHOT_MAGIC "TSQY"
+#define QUERY_SNAPSHOT_VERSION 2
+
+static void query_snapshot__write_symbol_table(SnapshotWriter *writer, const SymbolTable *table) {
+  snapshot_write_u32(writer, table->characters.size);
+  snapshot_write_bytes(writer, table->characters.contents, table->characters.size);
+  snapshot_write_u32(writer, table->slices.size);
+  for (unsigned i = 0; i < table->slices.size; i++) {
+    snapshot_write_u32(writer, table->slices.contents[i].offset);
+    snapshot_write_u32(writer, table->slices.contents[i].length);
+  }
+}
+
+static bool query_snapshot__read_symbol_table(SnapshotReader *reader, SymbolTable *table) {
+  uint32_t character_count = snapshot_read_u32(reader);
+  const uint8_t *characters = snapshot_read_bytes(reader, character_count);
+  if (reader->failed) return false;
+  array_extend(&table->characters, character_count, (const char *)characters);
+
+  // Each slice occupies 8 bytes.
+  uint32_t slice_count = snapshot_read_u32(reader);
+  if (reader->failed || slice_count > snapshot_remaining(reader) / 8) return false;
+  array_reserve(&table->slices, slice_count);
+  for (unsigned i = 0; i < slice_count; i++) {
+    Slice slice;
+    slice.offset = snapshot_read_u32(reader);
+    slice.length = snapshot_read_u32(reader);
+    if (slice.offset > character_count || slice.length > character_count - slice.offset) {
+      return false;
+    }
+    array_push(&table->slices, slice);
+  }
+  return true;
+}
+
+static bool query_snapshot__slice_is_valid(Slice slice, uint32_t size) {
+  return slice.offset <= size && slice.length <= size - slice.offset;
+}
+
+char *ts_query_serialize(const TSQuery *self, uint32_t *length) {
+  SnapshotWriter writer = array_new();
+  const char *name = ts_language_name(self->language);
+  uint32_t name_length = name ? (uint32_t)strlen(name) : 0;
+
+  snapshot_write_bytes(&writer, QUERY_SNAPSHOT_MAGIC, 4);
+  snapshot_write_u32(&writer, QUERY_SNAPSHOT_VERSION);
+  snapshot_write_u32(&writer, ts_language_abi_version(self->language));
+  snapshot_write_u32(&writer, name_length);
+  snapshot_write_bytes(&writer, name, name_length);
+  snapshot_write_u32(&writer, ts_language_symbol_count(self->language));
+  snapshot_write_u32(&writer, ts_language_field_count(self->language));
+
+  query_snapshot__write_symbol_table(&writer, &self->captures);
+  query_snapshot__write_symbol_table(&writer, &self->predicate_values);
+
+  snapshot_write_u32(&writer, self->capture_quantifiers.size);
+  for (unsigned i = 0; i < self->capture_quantifiers.size; i++) {
+    const CaptureQuantifiers *quantifiers = &self->capture_quantifiers.contents[i];
+    snapshot_write_u32(&writer, quantifiers->size);
+    snapshot_write_bytes(&writer, quantifiers->contents, quantifiers->size);
+  }
+
+  snapshot_write_u32(&writer, self->steps.size);
+  for (unsigned i = 0; i < self->steps.size; i++) {
+    const QueryStep *step = &self->steps.contents[i];
+    snapshot_write_u16(&writer, step->symbol);
+    snapshot_write_u16(&writer, step->supertype_symbol);
+    snapshot_write_u16(&writer, step->field);
+    for (unsigned j = 0; j < MAX_STEP_CAPTURE_COUNT; j++) {
+      snapshot_write_u16(&writer, step->capture_ids[j]);
+    }
+    snapshot_write_u16(&writer, step->depth);
+    snapshot_write_u16(&writer, step->alternative_index);
+    snapshot_write_u16(&writer, step->negated_field_list_id);
+    snapshot_write_u16(&writer, (uint16_t)(
+      step->is_named |
+      step->is_immediate << 1 |
+      step->is_last_child << 2 |
+      step->is_pass_through << 3 |
+      step->is_dead_end << 4 |
+      step->alternative_is_immediate << 5 |
+      step->contains_captures << 6 |
+      step->root_pattern_guaranteed << 7 |
+      step->parent_pattern_guaranteed << 8 |
+      step->is_missing << 9
+    ));
+  }
+
+  snapshot_write_u32(&writer, self->pattern_map.size);
+  for (unsigned i = 0; i < self->pattern_map.size; i++) {
+    const PatternEntry *entry = &self->pattern_map.contents[i];
+    snapshot_write_u16(&writer, entry->step_index);
+    snapshot_write_u16(&writer, entry->pattern_index);
+    snapshot_write_u8(&writer, entry->is_rooted);
+  }
+
+  snapshot_write_u32(&writer, self->predicate_steps.size);
+  for (unsigned i = 0; i < self->predicate_steps.size; i++) {
+    const TSQueryPredicateStep *step = &self->predicate_steps.contents[i];
+    snapshot_write_u8(&writer, (uint8_t)step->type);
+    snapshot_write_u32(&writer, step->value_id);
+  }
+
+  snapshot_write_u32(&writer, self->patterns.size);
+  for (unsigned i = 0; i < self->patterns.size; i++) {
+    const QueryPattern *pattern = &self->patterns.contents[i];
+    snapshot_write_u32(&writer, pattern->steps.offset);
+    snapshot_write_u32(&writer, pattern->steps.length);
+    snapshot_write_u32(&writer, pattern->predicate_steps.offset);
+    snapshot_write_u32(&writer, pattern->predicate_steps.length);
+    snapshot_write_u32(&writer, pattern->start_byte);
+    snapshot_write_u32(&writer, pattern->end_byte);
+    snapshot_write_u32(&writer, pattern->start_row);
+    snapshot_write_u8(&writer, pattern->is_non_local);
+  }
+
+  snapshot_write_u32(&writer, self->step_offsets.size);
+  for (unsigned i = 0; i < self->step_offsets.size; i++) {
+    snapshot_write_u32(&writer, self->step_offsets.contents[i].byte_offset);
+    snapshot_write_u16(&writer, self->step_offsets.contents[i].step_index);
+  }
+
+  snapshot_write_u32(&writer, self->negated_fields.size);
+  for (unsigned i = 0; i < self->negated_fields.size; i++) {
+    snapshot_write_u16(&writer, self->negated_fields.contents[i]);
+  }
+
+  snapshot_write_u32(&writer, self->repeat_symbols_with_rootless_patterns.size);
+  for (unsigned i = 0; i < self->repeat_symbols_with_rootless_patterns.size; i++) {
+    snapshot_write_u16(&writer, self->repeat_symbols_with_rootless_patterns.contents[i]);
+  }
+
+  snapshot_write_u16(&writer, self->wildcard_root_pattern_count);
+
+  *length = writer.size;
+  return (char *)writer.contents;
+}
+
+// Read the contents of a query snapshot into `self`, checking that every index
+// refers to an existing element, so that a corrupted snapshot cannot cause the
+// query to be executed out of bounds.
+static bool ts_query__deserialize_contents(TSQuery *self, SnapshotReader *reader) {
+  if (
+    !query_snapshot__read_symbol_table(reader, &self->captures) ||
+    !query_snapshot__read_symbol_table(reader, &self->predicate_values)
+  ) return false;
+  uint32_t capture_count = self->captures.slices.size;
+  uint32_t string_count = self->predicate_values.slices.size;
+
+  uint32_t pattern_count = snapshot_read_u32(reader);
+  if (reader->failed || pattern_count > snapshot_remaining(reader) / 4) return false;
+  array_reserve(&self->capture_quantifiers, pattern_count);
+  for (unsigned i = 0; i < pattern_count; i++) {
+    uint32_t quantifier_count = snapshot_read_u32(reader);
+    const uint8_t *quantifiers = snapshot_read_bytes(reader, quantifier_count);
+    if (reader->failed || quantifier_count > capture_count) return false;
+    for (unsigned j = 0; j < quantifier_count; j++) {
+      if (quantifiers[j] > TSQuantifierOneOrMore) return false;
+    }
+    CaptureQuantifiers capture_quantifiers = capture_quantifiers_new();
+    array_extend(&capture_quantifiers, quantifier_count, quantifiers);
+    array_push(&self->capture_quantifiers, capture_quantifiers);
+  }
+
+  // Each step occupies 20 bytes.
+  uint32_t step_count = snapshot_read_u32(reader);
+  if (reader->failed || step_count > snapshot_remaining(reader) / 20) return false;
+  array_reserve(&self->steps, step_count);
+  for (unsigned i = 0; i < step_count; i++) {
+    QueryStep step;
+    step.symbol = snapshot_read_u16(reader);
+    step.supertype_symbol = snapshot_read_u16(reader);
+    step.field = snapshot_read_u16(reader);
+    for (unsigned j = 0; j < MAX_STEP_CAPTURE_COUNT; j++) {
+      step.capture_ids[j] = snapshot_read_u16(reader);
+      if (step.capture_ids[j] != NONE && step.capture_ids[j] >= capture_count) return false;
+    }
+    step.depth = snapshot_read_u16(reader);
+    step.alternative_index = snapshot_read_u16(reader);
+    step.negated_field_list_id = snapshot_read_u16(reader);
+    uint16_t flags = snapshot_read_u16(reader);
+    step.is_named = flags & 1;
+    step.is_immediate = (flags >> 1) & 1;
+    step.is_last_child = (flags >> 2) & 1;
+    step.is_pass_through = (flags >> 3) & 1;
+    step.is_dead_end = (flags >> 4) & 1;
+    step.alternative_is_immediate = (flags >> 5) & 1;
+    step.contains_captures = (flags >> 6) & 1;
+    step.root_pattern_guaranteed = (flags >> 7) & 1;
+    step.parent_pattern_guaranteed = (flags >> 8) & 1;
+    step.is_missing = (flags >> 9) & 1;
+    if (step.alternative_index != NONE && step.alternative_index >= step_count) return false;
+    array_push(&self->steps, step);
+  }
+  if (step_count == 0 || array_back(&self->steps)->depth != PATTERN_DONE_MARKER) return false;
+
+  // Each pattern map entry occupies 5 bytes.
+  uint32_t entry_count = snapshot_read_u32(reader);
+  if (reader->failed || entry_count > snapshot_remaining(reader) / 5) return false;
+  array_reserve(&self->pattern_map, entry_count);
+  for (unsigned i = 0; i < entry_count; i++) {
+    PatternEntry entry;
+    entry.step_index = snapshot_read_u16(reader);
+    entry.pattern_index = snapshot_read_u16(reader);
+    entry.is_rooted = snapshot_read_u8(reader);
+    if (entry.step_index >= step_count || entry.pattern_index >= pattern_count) return false;
+    array_push(&self->pattern_map, entry);
+  }
+
+  // Each predicate step occupies 5 bytes.
+  uint32_t predicate_step_count = snapshot_read_u32(reader);
+  if (reader->failed || predicate_step_count > snapshot_remaining(reader) / 5) return false;
+  array_reserve(&self->predicate_steps, predicate_step_count);
+  for (unsigned i = 0; i < predicate_step_count; i++) {
+    TSQueryPredicateStep step;
+    uint8_t type = snapshot_read_u8(reader);
+    step.value_id = snapshot_read_u32(reader);
+    switch (type) {
+      case TSQueryPredicateStepTypeDone:
+        step.type = TSQueryPredicateStepTypeDone;
+        break;
+      case TSQueryPredicateStepTypeCapture:
+        if (step.value_id >= capture_count) return false;
+        step.type = TSQueryPredicateStepTypeCapture;
+        break;
+      case TSQueryPredicateStepTypeString:
+        if (step.value_id >= string_count) return false;
+        step.type = TSQueryPredicateStepTypeString;
+        break;
+      default:
+        return false;
+    }
+    array_push(&self->predicate_steps, step);
+  }
+
+  // Each pattern occupies 29 bytes, and there is one set of capture
+  // quantifiers for each pattern.
+  if (snapshot_read_u32(reader) != pattern_count || reader->failed) return false;
+  if (pattern_count > snapshot_remaining(reader) / 29) return false;
+  array_reserve(&self->patterns, pattern_count);
+  for (unsigned i = 0; i < pattern_count; i++) {
+    QueryPattern pattern;
+    pattern.steps.offset = snapshot_read_u32(reader);
+    pattern.steps.length = snapshot_read_u32(reader);
+    pattern.predicate_steps.offset = snapshot_read_u32(reader);
+    pattern.predicate_steps.length = snapshot_read_u32(reader);
+    pattern.start_byte = snapshot_read_u32(reader);
+    pattern.end_byte = snapshot_read_u32(reader);
+    pattern.start_row = snapshot_read_u32(reader);
+    pattern.is_non_local = snapshot_read_u8(reader);
+    if (
+      !query_snapshot__slice_is_valid(pattern.steps, step_count) ||
+      !query_snapshot__slice_is_valid(pattern.predicate_steps, predicate_step_count)
+    ) return false;
+    array_push(&self->patterns, pattern);
+  }
+
+  // Each step offset occupies 6 bytes.
+  uint32_t step_offset_count = snapshot_read_u32(reader);
+  if (reader->failed || step_offset_count > snapshot_remaining(reader) / 6) return false;
+  array_reserve(&self->step_offsets, step_offset_count);
+  for (unsigned i = 0; i < step_offset_count; i++) {
+    StepOffset step_offset;
+    step_offset.byte_offset = snapshot_read_u32(reader);
+    step_offset.step_index = snapshot_read_u16(reader);
+    if (step_offset.step_index >= step_count) return false;
+    array_push(&self->step_offsets, step_offset);
+  }
+
+  uint32_t negated_field_count = snapshot_read_u32(reader);
+  if (reader->failed || negated_field_count > snapshot_remaining(reader) / 2) return false;
+  array_reserve(&self->negated_fields, negated_field_count);
+  for (unsigned i = 0; i < negated_field_count; i++) {
+    array_push(&self->negated_fields, snapshot_read_u16(reader));
+  }
+  // Each list of negated fields is terminated by a zero.
+  if (negated_field_count == 0 || *array_back(&self->negated_fields) != 0) return false;
+  for (unsigned i = 0; i < step_count; i++) {
+    if (self->steps.contents[i].negated_field_list_id >= negated_field_count) return false;
+  }
+
+  uint32_t repeat_symbol_count = snapshot_read_u32(reader);
+  if (reader->failed || repeat_symbol_count > snapshot_remaining(reader) / 2) return false;
+  array_reserve(&self->repeat_symbols_with_rootless_patterns, repeat_symbol_count);
+  for (unsigned i = 0; i < repeat_symbol_count; i++) {
+    array_push(&self->repeat_symbols_with_rootless_patterns, snapshot_read_u16(reader));
+  }
+
+  self->wildcard_root_pattern_count = snapshot_read_u16(reader);
+  return
+    !reader->failed &&
+    snapshot_remaining(reader) == 0 &&
+    self->wildcard_root_pattern_count <= entry_count;
+}
+
+TSQuery *ts_query_deserialize(
+  const TSLanguage *language,
+  const char *data,
+  uint32_t length,
+  TSSnapshotError *error
+) {
+  SnapshotReader reader = snapshot_reader_new((const uint8_t *)data, length);
+  *error = TSSnapshotErrorFormat;
+
+  const uint8_t *magic = snapshot_read_bytes(&reader, 4);
+  if (!magic || memcmp(magic, QUERY_SNAPSHOT_MAGIC, 4) != 0) return NULL;
+
+  uint32_t version = snapshot_read_u32(&reader);
+  if (reader.failed) return NULL;
+  if (version != QUERY_SNAPSHOT_VERSION) {
+    *error = TSSnapshotErrorVersion;
+    return NULL;
+  }
+
+  uint32_t abi_version = snapshot_read_u32(&reader);
+  uint32_t name_length = snapshot_read_u32(&reader);
+  const uint8_t *name = snapshot_read_bytes(&reader, name_length);
+  uint32_t symbol_count = snapshot_read_u32(&reader);
+  uint32_t field_count = snapshot_read_u32(&reader);
+  if (reader.failed) return NULL;
+
+  const char *language_name = ts_language_name(language);
+  if (
+    (name_length > 0 && language_name && (
+      strlen(language_name) != name_length ||
+      memcmp(language_name, name, name_length) != 0
+    )) ||
+    symbol_count != ts_language_symbol_count(language) ||
+    field_count != ts_language_field_count(language)
+  ) {
+    *error = TSSnapshotErrorLanguage;
+    return NULL;
+  }
+  if (abi_version != ts_language_abi_version(language)) {
+    *error = TSSnapshotErrorLanguageVersion;
+    return NULL;
+  }
+
+  TSQuery *self = ts_malloc(sizeof(TSQuery));
+  *self = (TSQuery) {
+    .steps = array_new(),
+    .pattern_map = array_new(),
+    .captures = symbol_table_new(),
+    .capture_quantifiers = array_new(),
+    .predicate_values = symbol_table_new(),
+    .predicate_steps = array_new(),
+    .patterns = array_new(),
+    .step_offsets = array_new(),
+    .string_buffer = array_new(),
+    .negated_fields = array_new(),
+    .repeat_symbols_with_rootless_patterns = array_new(),
+    .wildcard_root_pattern_count = 0,
+    .language = ts_language_copy(language),
+  };
+
+  if (!ts_query__deserialize_contents(self, &reader)) {
+    ts_query_delete(self);
+    return NULL;
+  }
+
+  *error = TSSnapshotErrorNone;
+  return self;
+}
+
 uint32_t ts_query_pattern_count(const TSQuery *self) {
   return self->patterns.size;
 }
@@ -2949,6 +3322,10 @@ uint32_t ts_query_string_count(const TSQuery *self) {
   return self->predicate_values.slices.size;
 }
 
+const TSLanguage *ts_query_language(const TSQuery *self) {
+  return self->language;
+}
+
 const char *ts_query_capture_name_for_id(
   const TSQuery *self,
   uint32_t index,
@@ -2999,6 +3376,13 @@ uint32_t ts_query_end_byte_for_pattern(
   return array_get(&self->patterns, pattern_index)->end_byte;
 }
 
+uint32_t ts_query_start_row_for_pattern(
+  const TSQuery *self,
+  uint32_t pattern_index
+) {
+  return array_get(&self->patterns, pattern_index)->start_row;
+}
+
 bool ts_query_is_pattern_rooted(
   const TSQuery *self,
   uint32_t pattern_index
@@ -3023,6 +3407,142 @@ bool ts_query_is_pattern_non_local(
   }
 }
 
+static bool ts_query__names_eq(
+  const SymbolTable *self,
+  uint16_t id,
+  const SymbolTable *other,
+  uint16_t other_id
+) {
+  uint32_t length, other_length;
+  const char *name = symbol_table_name_for_id(self, id, &length);
+  const char *other_name = symbol_table_name_for_id(other, other_id, &other_length);
+  return length == other_length && !memcmp(name, other_name, length);
+}
+
+// Compare two lists of negated fields, which are terminated by zeros.
+static bool ts_query__negated_fields_eq(
+  const TSQuery *self,
+  uint16_t id,
+  const TSQuery *other,
+  uint16_t other_id
+) {
+  if (!id || !other_id) return id == other_id;
+  for (;;) {
+    if (id >= self->negated_fields.size || other_id >= other->negated_fields.size) return false;
+    TSFieldId field_id = *array_get(&self->negated_fields, id++);
+    TSFieldId other_field_id = *array_get(&other->negated_fields, other_id++);
+    if (field_id != other_field_id) return false;
+    if (!field_id) return true;
+  }
+}
+
+bool ts_query_is_equivalent(const TSQuery *self, const TSQuery *other) {
+  if (
+    self->steps.size != other->steps.size ||
+    self->patterns.size != other->patterns.size ||
+    self->pattern_map.size != other->pattern_map.size ||
+    self->predicate_steps.size != other->predicate_steps.size ||
+    self->captures.slices.size != other->captures.slices.size ||
+    self->repeat_symbols_with_rootless_patterns.size != other->repeat_symbols_with_rootless_patterns.size ||
+    self->wildcard_root_pattern_count != other->wildcard_root_pattern_count
+  ) return false;
+
+  // Captures and strings are compared by their names, since they are numbered
+  // in the order in which they appear in the query's source.
+  for (unsigned i = 0; i < self->steps.size; i++) {
+    const QueryStep *step = array_get(&self->steps, i);
+    const QueryStep *other_step = array_get(&other->steps, i);
+    if (
+      step->symbol != other_step->symbol ||
+      step->supertype_symbol != other_step->supertype_symbol ||
+      step->field != other_step->field ||
+      step->depth != other_step->depth ||
+      step->alternative_index != other_step->alternative_index ||
+      step->is_named != other_step->is_named ||
+      step->is_immediate != other_step->is_immediate ||
+      step->is_last_child != other_step->is_last_child ||
+      step->is_pass_through != other_step->is_pass_through ||
+      step->is_dead_end != other_step->is_dead_end ||
+      step->alternative_is_immediate != other_step->alternative_is_immediate ||
+      step->contains_captures != other_step->contains_captures ||
+      step->root_pattern_guaranteed != other_step->root_pattern_guaranteed ||
+      step->parent_pattern_guaranteed != other_step->parent_pattern_guaranteed ||
+      step->is_missing != other_step->is_missing ||
+      !ts_query__negated_fields_eq(
+        self, step->negated_field_list_id,
+        other, other_step->negated_field_list_id
+      )
+    ) return false;
+    for (unsigned j = 0; j < MAX_STEP_CAPTURE_COUNT; j++) {
+      uint16_t capture_id = step->capture_ids[j];
+      uint16_t other_capture_id = other_step->capture_ids[j];
+      if (capture_id == NONE || other_capture_id == NONE) {
+        if (capture_id != other_capture_id) return false;
+      } else if (!ts_query__names_eq(&self->captures, capture_id, &other->captures, other_capture_id)) {
+        return false;
+      }
+    }
+  }
+
+  for (unsigned i = 0; i < self->pattern_map.size; i++) {
+    const PatternEntry *entry = array_get(&self->pattern_map, i);
+    const PatternEntry *other_entry = array_get(&other->pattern_map, i);
+    if (
+      entry->step_index != other_entry->step_index ||
+      entry->pattern_index != other_entry->pattern_index ||
+      entry->is_rooted != other_entry->is_rooted
+    ) return false;
+  }
+
+  for (unsigned i = 0; i < self->repeat_symbols_with_rootless_patterns.size; i++) {
+    if (
+      *array_get(&self->repeat_symbols_with_rootless_patterns, i) !=
+      *array_get(&other->repeat_symbols_with_rootless_patterns, i)
+    ) return false;
+  }
+
+  for (unsigned i = 0; i < self->predicate_steps.size; i++) {
+    const TSQueryPredicateStep *step = array_get(&self->predicate_steps, i);
+    const TSQueryPredicateStep *other_step = array_get(&other->predicate_steps, i);
+    if (step->type != other_step->type) return false;
+    if (
+      (step->type == TSQueryPredicateStepTypeCapture &&
+        !ts_query__names_eq(&self->captures, step->value_id, &other->captures, other_step->value_id)) ||
+      (step->type == TSQueryPredicateStepTypeString &&
+        !ts_query__names_eq(&self->predicate_values, step->value_id, &other->predicate_values, other_step->value_id))
+    ) return false;
+  }
+
+  for (unsigned i = 0; i < self->patterns.size; i++) {
+    const QueryPattern *pattern = array_get(&self->patterns, i);
+    const QueryPattern *other_pattern = array_get(&other->patterns, i);
+    if (
+      pattern->steps.offset != other_pattern->steps.offset ||
+      pattern->steps.length != other_pattern->steps.length ||
+      pattern->predicate_steps.offset != other_pattern->predicate_steps.offset ||
+      pattern->predicate_steps.length != other_pattern->predicate_steps.length ||
+      pattern->is_non_local != other_pattern->is_non_local
+    ) return false;
+
+    // Both queries have the same number of captures, so every capture must
+    // have a counterpart with the same name and quantifier.
+    const CaptureQuantifiers *quantifiers = array_get(&self->capture_quantifiers, i);
+    const CaptureQuantifiers *other_quantifiers = array_get(&other->capture_quantifiers, i);
+    for (uint16_t id = 0; id < self->captures.slices.size; id++) {
+      uint32_t length;
+      const char *name = symbol_table_name_for_id(&self->captures, id, &length);
+      int other_id = symbol_table_id_for_name(&other->captures, name, length);
+      if (other_id < 0) return false;
+      if (
+        capture_quantifier_for_id(quantifiers, id) !=
+        capture_quantifier_for_id(other_quantifiers, (uint16_t)other_id)
+      ) return false;
+    }
+  }
+
+  return true;
+}
+
 bool ts_query_is_pattern_guaranteed_at_step(
   const TSQuery *self,
   uint32_t byte_offset
@@ -0,0 +1,117 @@
+#ifndef TREE_SITTER_SNAPSHOT_H_
+#define TREE_SITTER_SNAPSHOT_H_
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#include <stdbool.h>
+#include <stdint.h>
+#include <string.h>
+#include "./array.h"
+#include "./length.h"
+
+// Helpers for reading and writing the binary snapshot formats used by
+// `ts_tree_serialize` and `ts_tree_deserialize`.
+//
+// All integers are written in little-endian byte order, so that snapshots
+// can be exchanged between hosts with different endianness.
+
+typedef Array(uint8_t) SnapshotWriter;
+
+typedef struct {
+  const uint8_t *data;
+  uint32_t length;
+  uint32_t offset;
+  bool failed;
+} SnapshotReader;
+
+static inline void snapshot_write_u8(SnapshotWriter *self, uint8_t value) {
+  array_push(self, value);
+}
+
+static inline void snapshot_write_u16(SnapshotWriter *self, uint16_t value) {
+  snapshot_write_u8(self, (uint8_t)value);
+  snapshot_write_u8(self, (uint8_t)(value >> 8));
+}
+
+static inline void snapshot_write_u32(SnapshotWriter *self, uint32_t value) {
+  snapshot_write_u16(self, (uint16_t)value);
+  snapshot_write_u16(self, (uint16_t)(value >> 16));
+}
+
+static inline void snapshot_write_bytes(SnapshotWriter *self, const void *data, uint32_t length) {
+  array_extend(self, length, (const uint8_t *)data);
+}
+
+static inline void snapshot_write_length(SnapshotWriter *self, Length value) {
+  snapshot_write_u32(self, value.bytes);
+  snapshot_write_u32(self, value.extent.row);
+  snapshot_write_u32(self, value.extent.column);
+}
+
+static inline void snapshot_write_point(SnapshotWriter *self, TSPoint value) {
+  snapshot_write_u32(self, value.row);
+  snapshot_write_u32(self, value.column);
+}
+
+static inline SnapshotReader snapshot_reader_new(const uint8_t *data, uint32_t length) {
+  return (SnapshotReader) {data, length, 0, false};
+}
+
+static inline uint32_t snapshot_remaining(const SnapshotReader *self) {
+  return self->length - self->offset;
+}
+
+// Consume `count` bytes, returning a pointer to them, or `NULL` if the
+// snapshot is truncated. Once a read has failed, all subsequent reads fail.
+static inline const uint8_t *snapshot_read_bytes(SnapshotReader *self, uint32_t count) {
+  if (self->failed || snapshot_remaining(self) < count) {
+    self->failed = true;
+    return NULL;
+  }
+  const uint8_t *result = &self->data[self->offset];
+  self->offset += count;
+  return result;
+}
+
+static inline uint8_t snapshot_read_u8(SnapshotReader *self) {
+  const uint8_t *bytes = snapshot_read_bytes(self, 1);
+  return bytes ? bytes[0] : 0;
+}
+
+static inline uint16_t snapshot_read_u16(SnapshotReader *self) {
+  const uint8_t *bytes = snapshot_read_bytes(self, 2);
+  return bytes ? (uint16_t)(bytes[0] | (bytes[1] << 8)) : 0;
+}
+
+static inline uint32_t snapshot_read_u32(SnapshotReader *self) {
+  const uint8_t *bytes = snapshot_read_bytes(self, 4);
+  if (!bytes) return 0;
+  return
+    (uint32_t)bytes[0] |
+    ((uint32_t)bytes[1] << 8) |
+    ((uint32_t)bytes[2] << 16) |
+    ((uint32_t)bytes[3] << 24);
+}
+
+static inline Length snapshot_read_length(SnapshotReader *self) {
+  Length result;
+  result.bytes = snapshot_read_u32(self);
+  result.extent.row = snapshot_read_u32(self);
+  result.extent.column = snapshot_read_u32(self);
+  return result;
+}
+
+static inline TSPoint snapshot_read_point(SnapshotReader *self) {
+  TSPoint result;
+  result.row = snapshot_read_u32(self);
+  result.column = snapshot_read_u32(self);
+  return result;
+}
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif  // TREE_SITTER_SNAPSHOT_H_
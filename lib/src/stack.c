@@ -777,6 +777,10 @@ void ts_stack_clear(Stack *self) {
   }));
 }
 
+void ts_stack_set_start_state(Stack *self, TSStateId state) {
+  self->base_node->state = state;
+}
+
 bool ts_stack_print_dot_graph(Stack *self, const TSLanguage *language, FILE *f) {
   array_reserve(&self->iterators, 32);
   if (!f) f = stderr;
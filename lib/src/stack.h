@@ -124,6 +124,10 @@ void ts_stack_remove_version(Stack *self, StackVersion version);
 
 void ts_stack_clear(Stack *self);
 
+// Set the state of the stack's base node, in which parsing begins. This
+// should only be called when the stack has just been cleared.
+void ts_stack_set_start_state(Stack *self, TSStateId state);
+
 bool ts_stack_print_dot_graph(Stack *self, const TSLanguage *language, FILE *f);
 
 #ifdef __cplusplus
@@ -1032,3 +1032,486 @@ bool ts_subtree_external_scanner_state_eq(Subtree self, Subtree other) {
     state_other->length
   );
 }
+
+// Snapshots
+
+#define SUBTREE_RECORD_INLINE 0
+#define SUBTREE_RECORD_HEAP 1
+
+typedef struct {
+  MutableSubtree tree;
+  uint32_t next_child_index;
+} SubtreeDecodeEntry;
+
+static void ts_subtree__serialize_node(Subtree self, SnapshotWriter *writer) {
+  if (self.data.is_inline) {
+    snapshot_write_u8(writer, SUBTREE_RECORD_INLINE);
+    snapshot_write_u8(writer, self.data.symbol);
+    snapshot_write_u16(writer, self.data.parse_state);
+    snapshot_write_u8(writer, (uint8_t)(
+      self.data.visible << 0 |
+      self.data.named << 1 |
+      self.data.extra << 2 |
+      self.data.has_changes << 3 |
+      self.data.is_missing << 4 |
+      self.data.is_keyword << 5
+    ));
+    snapshot_write_u8(writer, self.data.padding_bytes);
+    snapshot_write_u8(writer, self.data.padding_rows);
+    snapshot_write_u8(writer, self.data.padding_columns);
+    snapshot_write_u8(writer, self.data.size_bytes);
+    snapshot_write_u8(writer, self.data.lookahead_bytes);
+    return;
+  }
+
+  const SubtreeHeapData *data = self.ptr;
+  snapshot_write_u8(writer, SUBTREE_RECORD_HEAP);
+  snapshot_write_u16(writer, data->symbol);
+  snapshot_write_u16(writer, data->parse_state);
+  snapshot_write_u16(writer, (uint16_t)(
+    data->visible << 0 |
+    data->named << 1 |
+    data->extra << 2 |
+    data->fragile_left << 3 |
+    data->fragile_right << 4 |
+    data->has_changes << 5 |
+    data->has_external_tokens << 6 |
+    data->has_external_scanner_state_change << 7 |
+    data->depends_on_column << 8 |
+    data->is_missing << 9 |
+    data->is_keyword << 10
+  ));
+  snapshot_write_length(writer, data->padding);
+  snapshot_write_length(writer, data->size);
+  snapshot_write_u32(writer, data->lookahead_bytes);
+  snapshot_write_u32(writer, data->error_cost);
+  snapshot_write_u32(writer, data->child_count);
+
+  if (data->child_count > 0) {
+    snapshot_write_u32(writer, data->visible_child_count);
+    snapshot_write_u32(writer, data->named_child_count);
+    snapshot_write_u32(writer, data->visible_descendant_count);
+    snapshot_write_u32(writer, (uint32_t)data->dynamic_precedence);
+    snapshot_write_u16(writer, data->repeat_depth);
+    snapshot_write_u16(writer, data->production_id);
+    snapshot_write_u16(writer, data->first_leaf.symbol);
+    snapshot_write_u16(writer, data->first_leaf.parse_state);
+  } else if (data->has_external_tokens) {
+    const ExternalScannerState *state = &data->external_scanner_state;
+    snapshot_write_u32(writer, state->length);
+    snapshot_write_bytes(writer, ts_external_scanner_state_data(state), state->length);
+  } else if (data->symbol == ts_builtin_sym_error) {
+    snapshot_write_u32(writer, (uint32_t)data->lookahead_char);
+  }
+}
+
+// Get the number of bytes of heap memory that are used by a single subtree,
+// not including its children.
+static inline size_t ts_subtree__heap_size(Subtree self) {
+  if (self.data.is_inline) return 0;
+  size_t result = ts_subtree_alloc_size(self.ptr->child_count);
+  if (self.ptr->child_count == 0 && self.ptr->has_external_tokens) {
+    const ExternalScannerState *state = &self.ptr->external_scanner_state;
+    if (state->length > sizeof(state->short_data)) result += state->length;
+  }
+  return result;
+}
+
+// Get the number of bytes of heap memory that are used by the given subtree
+// and all of its descendants.
+size_t ts_subtree_memory_usage(Subtree self) {
+  size_t result = 0;
+  SubtreeArray stack = array_new();
+  array_push(&stack, self);
+  while (stack.size > 0) {
+    Subtree tree = array_pop(&stack);
+    if (tree.data.is_inline) continue;
+    result += ts_subtree__heap_size(tree);
+    const Subtree *children = ts_subtree_children(tree);
+    for (uint32_t i = 0; i < tree.ptr->child_count; i++) {
+      array_push(&stack, children[i]);
+    }
+  }
+  array_delete(&stack);
+  return result;
+}
+
+// Get the parse state in which the parser would resume after the given subtree,
+// if the parser was in the given state before it.
+//
+// When the state before the subtree is unknown, it is taken from the subtree
+// itself. Internal nodes store the state in which they were reduced, and
+// tokens store the state in which they were lexed, which is only the state in
+// which they were shifted if no reductions happened in between.
+static inline TSStateId ts_subtree__state_before(Subtree self, const TSLanguage *language) {
+  TSStateId state = ts_subtree_parse_state(self);
+  if (state == 0 || state == TS_TREE_STATE_NONE) return 0;
+  if (ts_subtree_child_count(self) > 0) return state;
+  return ts_language_next_state(language, state, ts_subtree_symbol(self)) ? state : 0;
+}
+
+// Get the parse state that the parser is in after the last token that ends at
+// or before the given byte offset, skipping extras.
+//
+// The state is computed by following the gotos and shifts of the parse table
+// for every subtree that precedes the offset, including the hidden ones, so
+// that it reflects the reductions that happened after each token. ERROR nodes
+// and tokens that were changed by an edit do not advance the state. After
+// them, the state is recovered from the next subtree that stores one. The walk
+// begins in the state in which the tree's parse began.
+TSStateId ts_subtree_parse_state_at(
+  Subtree self,
+  const TSLanguage *language,
+  TSStateId start_state,
+  uint32_t byte_offset
+) {
+  TSStateId state = start_state;
+  bool is_synchronized = true;
+  uint32_t position = 0;
+  SubtreeArray stack = array_new();
+  if (!ts_subtree_is_error(self)) {
+    // The root node is never reduced before the end of the input, so the
+    // walk starts with its children.
+    const Subtree *children = ts_subtree_children(self);
+    for (uint32_t i = ts_subtree_child_count(self); i > 0; i--) {
+      array_push(&stack, children[i - 1]);
+    }
+  }
+  while (stack.size > 0) {
+    Subtree tree = array_pop(&stack);
+    uint32_t start = position + ts_subtree_padding(tree).bytes;
+    uint32_t end = position + ts_subtree_total_bytes(tree);
+    uint32_t child_count = ts_subtree_child_count(tree);
+    if (start >= byte_offset) break;
+
+    if (ts_subtree_extra(tree)) {
+      position = end;
+      continue;
+    }
+    if (ts_subtree_is_error(tree)) {
+      is_synchronized = false;
+      position = end;
+      continue;
+    }
+
+    if (!is_synchronized) {
+      TSStateId state_before = ts_subtree__state_before(tree, language);
+      if (state_before) {
+        state = state_before;
+        is_synchronized = true;
+      } else if (child_count == 0) {
+        position = end;
+        continue;
+      }
+    }
+
+    if (is_synchronized && end <= byte_offset && !ts_subtree_has_changes(tree)) {
+      TSStateId next_state = ts_language_next_state(language, state, ts_subtree_symbol(tree));
+      if (next_state) {
+        state = next_state;
+      } else {
+        is_synchronized = false;
+      }
+      position = end;
+    } else if (child_count == 0) {
+      // The token either contains the offset, or was changed by an edit.
+      if (end > byte_offset) break;
+      is_synchronized = false;
+      position = end;
+    } else {
+      const Subtree *children = ts_subtree_children(tree);
+      for (uint32_t i = child_count; i > 0; i--) {
+        array_push(&stack, children[i - 1]);
+      }
+    }
+  }
+  array_delete(&stack);
+  return state;
+}
+
+static inline uint32_t ts_subtree__set_index(const SubtreeHeapData *ptr, uint32_t capacity) {
+  uint64_t hash = (uint64_t)(uintptr_t)ptr * 0x9E3779B97F4A7C15ull;
+  return (uint32_t)(hash >> 32) & (capacity - 1);
+}
+
+// Get the number of bytes of heap memory that the given subtree shares with
+// another subtree, such as the edited tree that was reused when parsing it.
+//
+// The heap-allocated subtrees of the other tree are stored in an open-addressing
+// hash set. The given subtree is then walked from the top, and every subtree
+// that is found in the set is counted along with all of its descendants.
+size_t ts_subtree_shared_memory_usage(Subtree self, Subtree other, uint32_t *node_count) {
+  SubtreeArray stack = array_new();
+  SubtreeArray other_subtrees = array_new();
+  array_push(&stack, other);
+  while (stack.size > 0) {
+    Subtree tree = array_pop(&stack);
+    if (tree.data.is_inline) continue;
+    array_push(&other_subtrees, tree);
+    const Subtree *children = ts_subtree_children(tree);
+    for (uint32_t i = 0; i < tree.ptr->child_count; i++) {
+      array_push(&stack, children[i]);
+    }
+  }
+
+  uint32_t capacity = 16;
+  while (capacity < other_subtrees.size * 2) capacity *= 2;
+  const SubtreeHeapData **set = ts_calloc(capacity, sizeof(SubtreeHeapData *));
+  for (uint32_t i = 0; i < other_subtrees.size; i++) {
+    const SubtreeHeapData *ptr = other_subtrees.contents[i].ptr;
+    uint32_t index = ts_subtree__set_index(ptr, capacity);
+    while (set[index] && set[index] != ptr) index = (index + 1) & (capacity - 1);
+    set[index] = ptr;
+  }
+  array_delete(&other_subtrees);
+
+  size_t result = 0;
+  uint32_t shared_node_count = 0;
+  array_push(&stack, self);
+  while (stack.size > 0) {
+    Subtree tree = array_pop(&stack);
+    if (tree.data.is_inline) continue;
+
+    uint32_t index = ts_subtree__set_index(tree.ptr, capacity);
+    while (set[index] && set[index] != tree.ptr) index = (index + 1) & (capacity - 1);
+    if (set[index]) {
+      result += ts_subtree_memory_usage(tree);
+      shared_node_count += ts_subtree_visible_descendant_count(tree);
+      if (ts_subtree_visible(tree)) shared_node_count++;
+      continue;
+    }
+
+    const Subtree *children = ts_subtree_children(tree);
+    for (uint32_t i = 0; i < tree.ptr->child_count; i++) {
+      array_push(&stack, children[i]);
+    }
+  }
+
+  array_delete(&stack);
+  ts_free(set);
+  if (node_count) *node_count = shared_node_count;
+  return result;
+}
+
+// Append a binary representation of the given subtree to the writer.
+//
+// Nodes are written in pre-order, each followed by its children. The
+// traversal uses an explicit stack so that deeply nested trees cannot
+// overflow the call stack.
+void ts_subtree_serialize(Subtree self, SnapshotWriter *writer) {
+  SubtreeArray stack = array_new();
+  array_push(&stack, self);
+  while (stack.size > 0) {
+    Subtree tree = array_pop(&stack);
+    ts_subtree__serialize_node(tree, writer);
+    uint32_t child_count = ts_subtree_child_count(tree);
+    if (child_count > 0) {
+      const Subtree *children = ts_subtree_children(tree);
+      for (uint32_t i = child_count; i > 0; i--) {
+        array_push(&stack, children[i - 1]);
+      }
+    }
+  }
+  array_delete(&stack);
+}
+
+static inline bool ts_subtree__valid_symbol(TSSymbol symbol, const TSLanguage *language) {
+  return
+    symbol < ts_language_symbol_count(language) ||
+    symbol == ts_builtin_sym_error ||
+    symbol == ts_builtin_sym_error_repeat;
+}
+
+static inline bool ts_subtree__valid_parse_state(TSStateId state, const TSLanguage *language) {
+  return state < language->state_count || state == TS_TREE_STATE_NONE;
+}
+
+static Subtree ts_subtree__deserialize_node(SnapshotReader *reader, const TSLanguage *language) {
+  uint8_t kind = snapshot_read_u8(reader);
+  if (reader->failed) return NULL_SUBTREE;
+
+  if (kind == SUBTREE_RECORD_INLINE) {
+    uint8_t symbol = snapshot_read_u8(reader);
+    uint16_t parse_state = snapshot_read_u16(reader);
+    uint8_t flags = snapshot_read_u8(reader);
+    uint8_t padding_bytes = snapshot_read_u8(reader);
+    uint8_t padding_rows = snapshot_read_u8(reader);
+    uint8_t padding_columns = snapshot_read_u8(reader);
+    uint8_t size_bytes = snapshot_read_u8(reader);
+    uint8_t lookahead_bytes = snapshot_read_u8(reader);
+    if (
+      reader->failed ||
+      !ts_subtree__valid_symbol(symbol, language) ||
+      !ts_subtree__valid_parse_state(parse_state, language) ||
+      padding_rows >= 16 ||
+      lookahead_bytes >= 16
+    ) {
+      reader->failed = true;
+      return NULL_SUBTREE;
+    }
+    return (Subtree) {{
+      .parse_state = parse_state,
+      .symbol = symbol,
+      .padding_bytes = padding_bytes,
+      .padding_rows = padding_rows,
+      .padding_columns = padding_columns,
+      .size_bytes = size_bytes,
+      .lookahead_bytes = lookahead_bytes,
+      .visible = flags & (1 << 0),
+      .named = flags & (1 << 1),
+      .extra = flags & (1 << 2),
+      .has_changes = flags & (1 << 3),
+      .is_missing = flags & (1 << 4),
+      .is_keyword = flags & (1 << 5),
+      .is_inline = true,
+    }};
+  }
+
+  if (kind != SUBTREE_RECORD_HEAP) {
+    reader->failed = true;
+    return NULL_SUBTREE;
+  }
+
+  TSSymbol symbol = snapshot_read_u16(reader);
+  TSStateId parse_state = snapshot_read_u16(reader);
+  uint16_t flags = snapshot_read_u16(reader);
+  Length padding = snapshot_read_length(reader);
+  Length size = snapshot_read_length(reader);
+  uint32_t lookahead_bytes = snapshot_read_u32(reader);
+  uint32_t error_cost = snapshot_read_u32(reader);
+  uint32_t child_count = snapshot_read_u32(reader);
+  if (
+    reader->failed ||
+    !ts_subtree__valid_symbol(symbol, language) ||
+    !ts_subtree__valid_parse_state(parse_state, language) ||
+    // Every child occupies at least one byte of the snapshot, so this
+    // bounds the allocation below by the size of the input.
+    child_count > snapshot_remaining(reader) ||
+    child_count > (SIZE_MAX - sizeof(SubtreeHeapData)) / sizeof(Subtree)
+  ) {
+    reader->failed = true;
+    return NULL_SUBTREE;
+  }
+
+  SubtreeHeapData *data;
+  if (child_count > 0) {
+    // Fill the children with empty inline subtrees, so that the node can
+    // be released safely if a later record turns out to be invalid.
+    Subtree *children = ts_malloc(ts_subtree_alloc_size(child_count));
+    for (uint32_t i = 0; i < child_count; i++) {
+      children[i] = (Subtree) {.data = {.is_inline = true}};
+    }
+    data = (SubtreeHeapData *)&children[child_count];
+  } else {
+    data = ts_malloc(sizeof(SubtreeHeapData));
+  }
+
+  *data = (SubtreeHeapData) {
+    .ref_count = 1,
+    .padding = padding,
+    .size = size,
+    .lookahead_bytes = lookahead_bytes,
+    .error_cost = error_cost,
+    .child_count = child_count,
+    .symbol = symbol,
+    .parse_state = parse_state,
+    .visible = flags & (1 << 0),
+    .named = flags & (1 << 1),
+    .extra = flags & (1 << 2),
+    .fragile_left = flags & (1 << 3),
+    .fragile_right = flags & (1 << 4),
+    .has_changes = flags & (1 << 5),
+    .has_external_tokens = flags & (1 << 6),
+    .has_external_scanner_state_change = flags & (1 << 7),
+    .depends_on_column = flags & (1 << 8),
+    .is_missing = flags & (1 << 9),
+    .is_keyword = flags & (1 << 10),
+    {{.first_leaf = {.symbol = 0, .parse_state = 0}}}
+  };
+
+  if (child_count > 0) {
+    data->visible_child_count = snapshot_read_u32(reader);
+    data->named_child_count = snapshot_read_u32(reader);
+    data->visible_descendant_count = snapshot_read_u32(reader);
+    data->dynamic_precedence = (int32_t)snapshot_read_u32(reader);
+    data->repeat_depth = snapshot_read_u16(reader);
+    data->production_id = snapshot_read_u16(reader);
+    data->first_leaf.symbol = snapshot_read_u16(reader);
+    data->first_leaf.parse_state = snapshot_read_u16(reader);
+    if (
+      data->production_id >= language->production_id_count ||
+      !ts_subtree__valid_symbol(data->first_leaf.symbol, language) ||
+      !ts_subtree__valid_parse_state(data->first_leaf.parse_state, language)
+    ) {
+      reader->failed = true;
+    }
+  } else if (data->has_external_tokens) {
+    // Leave the state empty until its bytes have been read, so that it can
+    // always be deleted.
+    ts_external_scanner_state_init(&data->external_scanner_state, NULL, 0);
+    uint32_t length = snapshot_read_u32(reader);
+    const uint8_t *bytes = snapshot_read_bytes(reader, length);
+    if (bytes) {
+      ts_external_scanner_state_init(&data->external_scanner_state, (const char *)bytes, length);
+    }
+  } else if (symbol == ts_builtin_sym_error) {
+    data->lookahead_char = (int32_t)snapshot_read_u32(reader);
+  }
+
+  return (Subtree) {.ptr = data};
+}
+
+// Check the properties of a parent node that can only be verified once
+// all of its children have been read.
+static bool ts_subtree__validate_children(Subtree self, const TSLanguage *language) {
+  if (self.ptr->production_id == 0) return true;
+  const Subtree *children = ts_subtree_children(self);
+  uint32_t structural_child_count = 0;
+  for (uint32_t i = 0; i < self.ptr->child_count; i++) {
+    if (!ts_subtree_extra(children[i])) structural_child_count++;
+  }
+  return structural_child_count <= language->max_alias_sequence_length;
+}
+
+// Read a subtree that was written by `ts_subtree_serialize`.
+//
+// Every symbol, parse state and production id is checked against the given
+// language. If the data is malformed, this returns `NULL_SUBTREE` and sets
+// the reader's `failed` flag.
+Subtree ts_subtree_deserialize(SnapshotReader *reader, const TSLanguage *language) {
+  SubtreePool pool = ts_subtree_pool_new(0);
+  Array(SubtreeDecodeEntry) stack = array_new();
+
+  Subtree root = ts_subtree__deserialize_node(reader, language);
+  if (!reader->failed && ts_subtree_child_count(root) > 0) {
+    array_push(&stack, ((SubtreeDecodeEntry) {ts_subtree_to_mut_unsafe(root), 0}));
+  }
+
+  while (!reader->failed && stack.size > 0) {
+    SubtreeDecodeEntry *entry = array_back(&stack);
+    if (entry->next_child_index == entry->tree.ptr->child_count) {
+      if (!ts_subtree__validate_children(ts_subtree_from_mut(entry->tree), language)) {
+        reader->failed = true;
+        break;
+      }
+      array_pop(&stack);
+      continue;
+    }
+
+    Subtree child = ts_subtree__deserialize_node(reader, language);
+    if (child.ptr == NULL) break;
+    ts_subtree_children(entry->tree)[entry->next_child_index++] = child;
+    if (reader->failed) break;
+    if (ts_subtree_child_count(child) > 0) {
+      array_push(&stack, ((SubtreeDecodeEntry) {ts_subtree_to_mut_unsafe(child), 0}));
+    }
+  }
+
+  array_delete(&stack);
+  if (reader->failed) {
+    if (root.ptr != NULL) ts_subtree_release(&pool, root);
+    root = NULL_SUBTREE;
+  }
+  ts_subtree_pool_delete(&pool);
+  return root;
+}
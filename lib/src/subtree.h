@@ -14,6 +14,7 @@ extern "C" {
 #include "./host.h"
 #include "tree_sitter/api.h"
 #include "./parser.h"
+#include "./snapshot.h"
 
 #define TS_TREE_STATE_NONE USHRT_MAX
 #define NULL_SUBTREE ((Subtree) {.ptr = NULL})
@@ -228,6 +229,11 @@ void ts_subtree_print_dot_graph(Subtree self, const TSLanguage *language, FILE *
 Subtree ts_subtree_last_external_token(Subtree tree);
 const ExternalScannerState *ts_subtree_external_scanner_state(Subtree self);
 bool ts_subtree_external_scanner_state_eq(Subtree self, Subtree other);
+size_t ts_subtree_memory_usage(Subtree self);
+size_t ts_subtree_shared_memory_usage(Subtree self, Subtree other, uint32_t *node_count);
+TSStateId ts_subtree_parse_state_at(Subtree self, const TSLanguage *language, TSStateId start_state, uint32_t byte_offset);
+void ts_subtree_serialize(Subtree self, SnapshotWriter *writer);
+Subtree ts_subtree_deserialize(SnapshotReader *reader, const TSLanguage *language);
 
 #define SUBTREE_GET(self, name) ((self).data.is_inline ? (self).data.name : (self).ptr->name)
 
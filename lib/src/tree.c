@@ -1,27 +1,40 @@
+#include <string.h>
 #include "tree_sitter/api.h"
 #include "./array.h"
+#include "./atomic.h"
 #include "./get_changed_ranges.h"
+#include "./language.h"
 #include "./length.h"
 #include "./subtree.h"
 #include "./tree_cursor.h"
 #include "./tree.h"
 
+static volatile uint32_t ts_tree_next_id = 0;
+
 TSTree *ts_tree_new(
-  Subtree root, const TSLanguage *language,
+  Subtree root, const TSLanguage *language, TSStateId start_state,
   const TSRange *included_ranges, unsigned included_range_count
 ) {
   TSTree *result = ts_malloc(sizeof(TSTree));
   result->root = root;
   result->language = ts_language_copy(language);
+  result->start_state = start_state;
   result->included_ranges = ts_calloc(included_range_count, sizeof(TSRange));
   memcpy(result->included_ranges, included_ranges, included_range_count * sizeof(TSRange));
   result->included_range_count = included_range_count;
+  result->id = atomic_inc(&ts_tree_next_id);
   return result;
 }
 
 TSTree *ts_tree_copy(const TSTree *self) {
   ts_subtree_retain(self->root);
-  return ts_tree_new(self->root, self->language, self->included_ranges, self->included_range_count);
+  return ts_tree_new(
+    self->root,
+    self->language,
+    self->start_state,
+    self->included_ranges,
+    self->included_range_count
+  );
 }
 
 void ts_tree_delete(TSTree *self) {
@@ -35,6 +48,10 @@ void ts_tree_delete(TSTree *self) {
   ts_free(self);
 }
 
+uint32_t ts_tree_id(const TSTree *self) {
+  return self->id;
+}
+
 TSNode ts_tree_root_node(const TSTree *self) {
   return ts_node_new(self, &self->root, ts_subtree_padding(self->root), 0);
 }
@@ -124,6 +141,137 @@ TSRange *ts_tree_get_changed_ranges(const TSTree *old_tree, const TSTree *new_tr
   return result;
 }
 
+size_t ts_tree_memory_usage(const TSTree *self) {
+  return
+    sizeof(TSTree) +
+    self->included_range_count * sizeof(TSRange) +
+    ts_subtree_memory_usage(self->root);
+}
+
+size_t ts_tree_shared_memory_usage(const TSTree *self, const TSTree *other, uint32_t *node_count) {
+  return ts_subtree_shared_memory_usage(self->root, other->root, node_count);
+}
+
+TSStateId ts_tree_parse_state_at(const TSTree *self, uint32_t byte_offset) {
+  return ts_subtree_parse_state_at(self->root, self->language, self->start_state, byte_offset);
+}
+
+TSStateId ts_tree_start_state(const TSTree *self) {
+  return self->start_state;
+}
+
+#define TREE_SNAPSHOT_MAGIC "TSTR"
+#define TREE_SNAPSHOT_VERSION 2
+
+char *ts_tree_serialize(const TSTree *self, uint32_t *length) {
+  SnapshotWriter writer = array_new();
+  const char *name = ts_language_name(self->language);
+  uint32_t name_length = name ? (uint32_t)strlen(name) : 0;
+
+  snapshot_write_bytes(&writer, TREE_SNAPSHOT_MAGIC, 4);
+  snapshot_write_u32(&writer, TREE_SNAPSHOT_VERSION);
+  snapshot_write_u32(&writer, ts_language_abi_version(self->language));
+  snapshot_write_u32(&writer, name_length);
+  snapshot_write_bytes(&writer, name, name_length);
+  snapshot_write_u32(&writer, ts_language_symbol_count(self->language));
+  snapshot_write_u32(&writer, ts_language_state_count(self->language));
+  snapshot_write_u32(&writer, self->start_state);
+
+  snapshot_write_u32(&writer, self->included_range_count);
+  for (unsigned i = 0; i < self->included_range_count; i++) {
+    const TSRange *range = &self->included_ranges[i];
+    snapshot_write_point(&writer, range->start_point);
+    snapshot_write_point(&writer, range->end_point);
+    snapshot_write_u32(&writer, range->start_byte);
+    snapshot_write_u32(&writer, range->end_byte);
+  }
+
+  ts_subtree_serialize(self->root, &writer);
+  *length = writer.size;
+  return (char *)writer.contents;
+}
+
+TSTree *ts_tree_deserialize(
+  const TSLanguage *language,
+  const char *data,
+  uint32_t length,
+  TSSnapshotError *error
+) {
+  SnapshotReader reader = snapshot_reader_new((const uint8_t *)data, length);
+  *error = TSSnapshotErrorFormat;
+
+  const uint8_t *magic = snapshot_read_bytes(&reader, 4);
+  if (!magic || memcmp(magic, TREE_SNAPSHOT_MAGIC, 4) != 0) return NULL;
+
+  uint32_t version = snapshot_read_u32(&reader);
+  if (reader.failed) return NULL;
+  if (version != TREE_SNAPSHOT_VERSION) {
+    *error = TSSnapshotErrorVersion;
+    return NULL;
+  }
+
+  uint32_t abi_version = snapshot_read_u32(&reader);
+  uint32_t name_length = snapshot_read_u32(&reader);
+  const uint8_t *name = snapshot_read_bytes(&reader, name_length);
+  uint32_t symbol_count = snapshot_read_u32(&reader);
+  uint32_t state_count = snapshot_read_u32(&reader);
+  uint32_t start_state = snapshot_read_u32(&reader);
+  if (reader.failed) return NULL;
+
+  const char *language_name = ts_language_name(language);
+  if (
+    (name_length > 0 && language_name && (
+      strlen(language_name) != name_length ||
+      memcmp(language_name, name, name_length) != 0
+    )) ||
+    symbol_count != ts_language_symbol_count(language) ||
+    state_count != ts_language_state_count(language) ||
+    start_state == 0 ||
+    start_state >= state_count
+  ) {
+    *error = TSSnapshotErrorLanguage;
+    return NULL;
+  }
+  if (abi_version != ts_language_abi_version(language)) {
+    *error = TSSnapshotErrorLanguageVersion;
+    return NULL;
+  }
+
+  // Each range occupies 24 bytes.
+  uint32_t included_range_count = snapshot_read_u32(&reader);
+  if (reader.failed || included_range_count > snapshot_remaining(&reader) / 24) return NULL;
+  TSRange *included_ranges = ts_calloc(included_range_count, sizeof(TSRange));
+  for (unsigned i = 0; i < included_range_count; i++) {
+    TSRange *range = &included_ranges[i];
+    range->start_point = snapshot_read_point(&reader);
+    range->end_point = snapshot_read_point(&reader);
+    range->start_byte = snapshot_read_u32(&reader);
+    range->end_byte = snapshot_read_u32(&reader);
+  }
+
+  Subtree root = ts_subtree_deserialize(&reader, language);
+  if (reader.failed || snapshot_remaining(&reader) > 0) {
+    if (root.ptr) {
+      SubtreePool pool = ts_subtree_pool_new(0);
+      ts_subtree_release(&pool, root);
+      ts_subtree_pool_delete(&pool);
+    }
+    ts_free(included_ranges);
+    return NULL;
+  }
+
+  TSTree *result = ts_tree_new(
+    root,
+    language,
+    (TSStateId)start_state,
+    included_ranges,
+    included_range_count
+  );
+  ts_free(included_ranges);
+  *error = TSSnapshotErrorNone;
+  return result;
+}
+
 #ifdef _WIN32
 
 #include <io.h>
@@ -19,9 +19,14 @@ struct TSTree {
   const TSLanguage *language;
   TSRange *included_ranges;
   unsigned included_range_count;
+  uint32_t id;
+  TSStateId start_state;
 };
 
-TSTree *ts_tree_new(Subtree root, const TSLanguage *language, const TSRange *included_ranges, unsigned included_range_count);
+TSTree *ts_tree_new(
+  Subtree root, const TSLanguage *language, TSStateId start_state,
+  const TSRange *included_ranges, unsigned included_range_count
+);
 TSNode ts_node_new(const TSTree *tree, const Subtree *subtree, Length position, TSSymbol alias);
 
 #ifdef __cplusplus
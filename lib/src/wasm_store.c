@@ -161,6 +161,9 @@ typedef struct {
   int32_t supertype_map_slices;
   int32_t supertype_map_entries;
   TSLanguageMetadata metadata;
+  uint64_t fingerprint;
+  uint32_t start_state_count;
+  int32_t start_states;
 } LanguageInWasmMemory;
 
 // LexerInWasmMemory - The memory layout of a `TSLexer` when compiled to wasm32.
@@ -1224,6 +1227,10 @@ const TSLanguage *ts_wasm_store_load_language(
     wasm_language.abi_version > LANGUAGE_VERSION_WITH_RESERVED_WORDS &&
     wasm_language.supertype_count > 0;
 
+  bool has_start_states =
+    wasm_language.abi_version >= LANGUAGE_VERSION_WITH_START_STATES &&
+    wasm_language.start_state_count > 0;
+
   int32_t addresses[] = {
     wasm_language.parse_table,
     wasm_language.small_parse_table,
@@ -1253,6 +1260,7 @@ const TSLanguage *ts_wasm_store_load_language(
     wasm_language.external_token_count > 0 ? wasm_language.external_scanner.scan : 0,
     wasm_language.external_token_count > 0 ? wasm_language.external_scanner.serialize : 0,
     wasm_language.external_token_count > 0 ? wasm_language.external_scanner.deserialize : 0,
+    has_start_states ? wasm_language.start_states : 0,
     language_address,
     self->current_memory_offset,
   };
@@ -1276,6 +1284,9 @@ const TSLanguage *ts_wasm_store_load_language(
     .max_alias_sequence_length = wasm_language.max_alias_sequence_length,
     .keyword_capture_token = wasm_language.keyword_capture_token,
     .metadata = wasm_language.metadata,
+    .fingerprint = wasm_language.abi_version >= LANGUAGE_VERSION_WITH_FINGERPRINT
+      ? wasm_language.fingerprint
+      : 0,
     .parse_table = copy(
       &memory[wasm_language.parse_table],
       wasm_language.large_state_count * wasm_language.symbol_count * sizeof(uint16_t)
@@ -1418,6 +1429,14 @@ const TSLanguage *ts_wasm_store_load_language(
     language->max_reserved_word_set_size = wasm_language.max_reserved_word_set_size;
   }
 
+  if (has_start_states) {
+    language->start_state_count = wasm_language.start_state_count;
+    language->start_states = copy(
+      &memory[wasm_language.start_states],
+      wasm_language.start_state_count * sizeof(TSStartState)
+    );
+  }
+
   if (language->external_token_count > 0) {
     language->external_scanner.symbol_map = copy(
       &memory[wasm_language.external_scanner.symbol_map],
@@ -1819,6 +1838,7 @@ void ts_wasm_language_release(const TSLanguage *self) {
     ts_free((void *)self->public_symbol_map);
     ts_free((void *)self->small_parse_table);
     ts_free((void *)self->small_parse_table_map);
+    ts_free((void *)self->start_states);
     ts_free((void *)self->symbol_metadata);
     ts_free((void *)self->symbol_names);
     ts_free((void *)self);